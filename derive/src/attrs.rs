@@ -0,0 +1,84 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `#[param(...)]` attributes parsing.
+
+use syn::spanned::Spanned as _;
+
+/// Parsed `#[param(...)]` attributes.
+pub(crate) struct Attrs(Vec<(String, syn::LitStr)>);
+
+impl Attrs {
+    /// Parses `#[param(key = "value", ...)]` attributes, allowing only the
+    /// given `keys`, each one at most once.
+    pub(crate) fn parse(
+        attrs: &[syn::Attribute],
+        keys: &[&str],
+    ) -> syn::Result<Self> {
+        let mut parsed = Vec::<(String, syn::LitStr)>::new();
+        for attr in attrs.iter().filter(|a| a.path.is_ident("param")) {
+            let meta_list = match attr.parse_meta()? {
+                syn::Meta::List(l) => l,
+                meta @ (syn::Meta::Path(_) | syn::Meta::NameValue(_)) => {
+                    return Err(syn::Error::new(
+                        meta.span(),
+                        "expected `#[param(key = \"value\")]`",
+                    ));
+                }
+            };
+            for nested in meta_list.nested {
+                let (key, lit) = match &nested {
+                    syn::NestedMeta::Meta(syn::Meta::NameValue(
+                        syn::MetaNameValue {
+                            path,
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        },
+                    )) => {
+                        (path.get_ident().map(ToString::to_string), lit.clone())
+                    }
+                    syn::NestedMeta::Meta(_) | syn::NestedMeta::Lit(_) => {
+                        return Err(syn::Error::new(
+                            nested.span(),
+                            "expected `key = \"value\"`",
+                        ));
+                    }
+                };
+                let key = match key {
+                    Some(k) if keys.contains(&k.as_str()) => k,
+                    _ => {
+                        return Err(syn::Error::new(
+                            nested.span(),
+                            format!("expected one of: {}", keys.join(", ")),
+                        ));
+                    }
+                };
+                if parsed.iter().any(|(k, _)| *k == key) {
+                    return Err(syn::Error::new(
+                        nested.span(),
+                        format!("duplicate `{}` argument", key),
+                    ));
+                }
+                parsed.push((key, lit));
+            }
+        }
+        Ok(Self(parsed))
+    }
+
+    /// Returns the value of the given `key`, if any.
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        self.lit(key).map(syn::LitStr::value)
+    }
+
+    /// Returns the literal of the given `key`, if any.
+    pub(crate) fn lit(&self, key: &str) -> Option<&syn::LitStr> {
+        self.0.iter().find_map(|(k, v)| (k == key).then(|| v))
+    }
+}
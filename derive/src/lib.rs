@@ -0,0 +1,144 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Derive macros for [`cucumber-expressions`] crate.
+//!
+//! Not intended to be used directly, enable `derive` Cargo feature of
+//! [`cucumber-expressions`] instead.
+//!
+//! [`cucumber-expressions`]: https://docs.rs/cucumber-expressions
+
+#![deny(
+    macro_use_extern_crate,
+    nonstandard_style,
+    rust_2018_idioms,
+    rustdoc::broken_intra_doc_links,
+    rustdoc::private_intra_doc_links,
+    trivial_casts,
+    trivial_numeric_casts
+)]
+#![forbid(non_ascii_idents, unsafe_code)]
+#![warn(
+    clippy::as_conversions,
+    clippy::branches_sharing_code,
+    clippy::clone_on_ref_ptr,
+    clippy::create_dir,
+    clippy::dbg_macro,
+    clippy::debug_assert_with_mut_call,
+    clippy::decimal_literal_representation,
+    clippy::else_if_without_else,
+    clippy::empty_line_after_outer_attr,
+    clippy::exit,
+    clippy::expect_used,
+    clippy::fallible_impl_from,
+    clippy::filetype_is_file,
+    clippy::float_cmp_const,
+    clippy::fn_to_numeric_cast,
+    clippy::get_unwrap,
+    clippy::if_then_some_else_none,
+    clippy::imprecise_flops,
+    clippy::let_underscore_must_use,
+    clippy::lossy_float_literal,
+    clippy::map_err_ignore,
+    clippy::mem_forget,
+    clippy::missing_const_for_fn,
+    clippy::missing_docs_in_private_items,
+    clippy::multiple_inherent_impl,
+    clippy::mutex_integer,
+    clippy::nonstandard_macro_braces,
+    clippy::option_if_let_else,
+    clippy::panic_in_result_fn,
+    clippy::pedantic,
+    clippy::print_stderr,
+    clippy::print_stdout,
+    clippy::rc_buffer,
+    clippy::rc_mutex,
+    clippy::rest_pat_in_fully_bound_structs,
+    clippy::shadow_unrelated,
+    clippy::str_to_string,
+    clippy::string_add,
+    clippy::string_lit_as_bytes,
+    clippy::suboptimal_flops,
+    clippy::suspicious_operation_groupings,
+    clippy::todo,
+    clippy::trivial_regex,
+    clippy::unimplemented,
+    clippy::unnecessary_self_imports,
+    clippy::unneeded_field_pattern,
+    clippy::unwrap_in_result,
+    clippy::unwrap_used,
+    clippy::use_debug,
+    clippy::use_self,
+    clippy::useless_let_if_seq,
+    clippy::verbose_file_reads,
+    clippy::wildcard_enum_match_arm,
+    future_incompatible,
+    meta_variable_misuse,
+    missing_copy_implementations,
+    missing_debug_implementations,
+    missing_docs,
+    noop_method_call,
+    semicolon_in_expressions_from_macros,
+    unreachable_pub,
+    unused_crate_dependencies,
+    unused_extern_crates,
+    unused_import_braces,
+    unused_labels,
+    unused_lifetimes,
+    unused_qualifications,
+    unused_results,
+    variant_size_differences
+)]
+
+mod attrs;
+mod parameter;
+mod parameters;
+
+use proc_macro::TokenStream;
+
+/// Derives a `TypedParameter` implementation for a fieldless enum, along with
+/// a [`FromStr`] one, matching each variant by its lowercased name with words
+/// separated by spaces (like `dark blue` for `DarkBlue`).
+///
+/// # Attributes
+///
+/// - `#[param(name = "...")]` on the enum: name of the parameter (the enum's
+///   name lowercased, by default).
+/// - `#[param(regex = "...")]` on the enum: regex pattern of the parameter
+///   (the alternation of the variants' values, by default).
+/// - `#[param(value = "...")]` on a variant: text the variant is matched by.
+///
+/// [`FromStr`]: std::str::FromStr
+#[proc_macro_derive(Parameter, attributes(param))]
+pub fn parameter(input: TokenStream) -> TokenStream {
+    parameter::derive(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives a `ParametersProvider` implementation for a reference to a struct,
+/// providing a custom parameter for each of its named fields.
+///
+/// The provided parameters are also available as the `PARAMETERS` associated
+/// constant of the struct. Their names and regex patterns are validated at
+/// compile time.
+///
+/// # Attributes
+///
+/// - `#[param(regex = "...")]` on a field (required): regex pattern of the
+///   parameter.
+/// - `#[param(name = "...")]` on a field: name of the parameter (the field's
+///   name, by default).
+#[proc_macro_derive(Parameters, attributes(param))]
+pub fn parameters(input: TokenStream) -> TokenStream {
+    parameters::derive(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
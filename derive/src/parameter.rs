@@ -0,0 +1,222 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `#[derive(Parameter)]` macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned as _;
+
+use crate::attrs::Attrs;
+
+/// Expands `#[derive(Parameter)]` macro.
+///
+/// # Errors
+///
+/// If the input isn't a fieldless enum, or its `#[param(...)]` attributes are
+/// malformed.
+pub(crate) fn derive(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let data = match &input.data {
+        syn::Data::Enum(data) => data,
+        syn::Data::Struct(_) | syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "only enums are supported",
+            ));
+        }
+    };
+
+    let attrs = Attrs::parse(&input.attrs, &["name", "regex"])?;
+    let name = attrs
+        .get("name")
+        .unwrap_or_else(|| input.ident.to_string().to_lowercase());
+
+    let mut variants = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new(
+                variant.fields.span(),
+                "only fieldless variants are supported",
+            ));
+        }
+        let value = Attrs::parse(&variant.attrs, &["value"])?
+            .get("value")
+            .unwrap_or_else(|| words(&variant.ident.to_string()));
+        variants.push((&variant.ident, value));
+    }
+    if variants.is_empty() {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "at least one variant is required",
+        ));
+    }
+
+    let regex = attrs.get("regex").unwrap_or_else(|| {
+        let mut values = variants.iter().map(|(_, v)| v).collect::<Vec<_>>();
+        // Longer values go first, so they aren't shadowed by their prefixes.
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        values
+            .iter()
+            .map(|v| escape(v))
+            .collect::<Vec<_>>()
+            .join("|")
+    });
+
+    let ty = &input.ident;
+    let (impl_gens, ty_gens, where_clause) = input.generics.split_for_impl();
+    let arms = variants.iter().map(|(ident, value)| {
+        quote! { #value => ::std::result::Result::Ok(Self::#ident), }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_gens ::std::str::FromStr for #ty #ty_gens #where_clause {
+            type Err = ::cucumber_expressions::expand::registry
+                ::UnknownVariantError;
+
+            fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                match s {
+                    #( #arms )*
+                    _ => ::std::result::Result::Err(
+                        ::cucumber_expressions::expand::registry
+                            ::UnknownVariantError {
+                                parameter: #name,
+                                value: ::std::borrow::ToOwned::to_owned(s),
+                            },
+                    ),
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_gens ::cucumber_expressions::expand::registry::TypedParameter
+         for #ty #ty_gens #where_clause
+        {
+            const NAME: &'static str = #name;
+            const REGEXP: &'static str = #regex;
+        }
+    })
+}
+
+/// Splits the given `CamelCase` identifier into lowercased words separated by
+/// spaces.
+fn words(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    for (i, c) in ident.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            out.push(' ');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+/// Escapes the given `text` to be matched literally by a regex.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod spec {
+    use quote::quote;
+
+    use super::{derive, escape, words};
+
+    #[test]
+    fn derives_enum() {
+        let input = quote! {
+            #[param(name = "colour")]
+            enum Color {
+                Red,
+                DarkBlue,
+                #[param(value = "light-green")]
+                Lime,
+            }
+        };
+        let output = quote! {
+            #[automatically_derived]
+            impl ::std::str::FromStr for Color {
+                type Err = ::cucumber_expressions::expand::registry
+                    ::UnknownVariantError;
+
+                fn from_str(s: &str) -> ::std::result::Result<Self, Self::Err> {
+                    match s {
+                        "red" => ::std::result::Result::Ok(Self::Red),
+                        "dark blue" => ::std::result::Result
+                            ::Ok(Self::DarkBlue),
+                        "light-green" => ::std::result::Result::Ok(Self::Lime),
+                        _ => ::std::result::Result::Err(
+                            ::cucumber_expressions::expand::registry
+                                ::UnknownVariantError {
+                                    parameter: "colour",
+                                    value: ::std::borrow::ToOwned::to_owned(s),
+                                },
+                        ),
+                    }
+                }
+            }
+
+            #[automatically_derived]
+            impl ::cucumber_expressions::expand::registry::TypedParameter
+             for Color
+            {
+                const NAME: &'static str = "colour";
+                const REGEXP: &'static str = "light\\-green|dark blue|red";
+            }
+        };
+
+        assert_eq!(derive(input).unwrap().to_string(), output.to_string());
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for (input, err) in [
+            (quote! { struct Color; }, "only enums are supported"),
+            (quote! { enum Color {} }, "at least one variant is required"),
+            (
+                quote! { enum Color { Red(u8) } },
+                "only fieldless variants are supported",
+            ),
+            (
+                quote! { #[param(id = "c")] enum Color { Red } },
+                "expected one of: name, regex",
+            ),
+            (
+                quote! { #[param(name = "a", name = "b")] enum Color { Red } },
+                "duplicate `name` argument",
+            ),
+            (
+                quote! { enum Color { #[param(value)] Red } },
+                "expected `key = \"value\"`",
+            ),
+        ] {
+            assert_eq!(derive(input).unwrap_err().to_string(), err);
+        }
+    }
+
+    #[test]
+    fn splits_words() {
+        assert_eq!(words("Red"), "red");
+        assert_eq!(words("DarkBlue"), "dark blue");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape("a.b (c)"), "a\\.b \\(c\\)");
+    }
+}
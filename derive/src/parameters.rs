@@ -0,0 +1,202 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! `#[derive(Parameters)]` macro implementation.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::attrs::Attrs;
+
+/// Characters a parameter name may not contain, as they can't be referenced
+/// from an expression.
+const ILLEGAL_NAME_CHARS: &[char] = &['{', '}', '(', ')', '\\', '/'];
+
+/// Expands `#[derive(Parameters)]` macro.
+///
+/// # Errors
+///
+/// If the input isn't a non-generic struct with named fields, its
+/// `#[param(...)]` attributes are malformed, or define invalid parameters.
+pub(crate) fn derive(input: TokenStream) -> syn::Result<TokenStream> {
+    let input = syn::parse2::<syn::DeriveInput>(input)?;
+    let fields = match &input.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        syn::Data::Struct(_) | syn::Data::Enum(_) | syn::Data::Union(_) => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "only structs with named fields are supported",
+            ));
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "generic structs are not supported",
+        ));
+    }
+
+    let mut params = Vec::<(String, String)>::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().ok_or_else(|| {
+            syn::Error::new(input.ident.span(), "unnamed field")
+        })?;
+        let attrs = Attrs::parse(&field.attrs, &["name", "regex"])?;
+
+        let name = attrs.get("name").unwrap_or_else(|| ident.to_string());
+        let name_span =
+            attrs.lit("name").map_or(ident.span(), syn::LitStr::span);
+        if name.is_empty() || name.contains(ILLEGAL_NAME_CHARS) {
+            return Err(syn::Error::new(
+                name_span,
+                "parameter name may not be empty or contain \
+                 `{`, `}`, `(`, `)`, `\\` or `/`",
+            ));
+        }
+        if params.iter().any(|(n, _)| *n == name) {
+            return Err(syn::Error::new(
+                name_span,
+                format!("duplicate parameter `{}`", name),
+            ));
+        }
+
+        let regex = attrs.lit("regex").ok_or_else(|| {
+            syn::Error::new(ident.span(), "missing `#[param(regex = \"...\")]`")
+        })?;
+        if let Err(e) = regex_syntax::Parser::new().parse(&regex.value()) {
+            return Err(syn::Error::new(
+                regex.span(),
+                format!("invalid regex: {}", e),
+            ));
+        }
+
+        params.push((name, regex.value()));
+    }
+
+    let ty = &input.ident;
+    let pairs = params.iter().map(|(n, r)| quote! { (#n, #r) });
+    let slice = quote! { &'static [(&'static str, &'static str)] };
+    let provider =
+        quote! { ::cucumber_expressions::expand::ParametersProvider };
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #ty {
+            /// Names and regex patterns of the provided parameters.
+            pub const PARAMETERS: #slice = &[#( #pairs ),*];
+        }
+
+        #[automatically_derived]
+        impl<'__p, __I> #provider<__I> for &'__p #ty
+        where
+            #slice: #provider<__I>,
+        {
+            type Item = <#slice as #provider<__I>>::Item;
+            type Value = <#slice as #provider<__I>>::Value;
+
+            fn get(
+                &self,
+                input: &__I,
+            ) -> ::std::option::Option<Self::Value> {
+                #provider::get(&#ty::PARAMETERS, input)
+            }
+
+            fn names(&self) -> ::std::vec::Vec<::std::string::String> {
+                #provider::<__I>::names(&#ty::PARAMETERS)
+            }
+
+            fn fingerprint(&self) -> ::std::option::Option<u64> {
+                #provider::<__I>::fingerprint(&#ty::PARAMETERS)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod spec {
+    use quote::quote;
+
+    use super::derive;
+
+    #[test]
+    fn derives_struct() {
+        let input = quote! {
+            struct Params {
+                #[param(regex = "red|blue")]
+                color: (),
+                #[param(name = "animal", regex = "cat|dog")]
+                pet: (),
+            }
+        };
+        let output = derive(input).unwrap().to_string();
+
+        assert!(output.contains(
+            &quote! {
+                pub const PARAMETERS: &'static [(&'static str, &'static str)] =
+                    &[("color", "red|blue"), ("animal", "cat|dog")];
+            }
+            .to_string(),
+        ));
+        assert!(output.contains(
+            &quote! {
+                impl<'__p, __I>
+                    ::cucumber_expressions::expand::ParametersProvider<__I>
+                    for &'__p Params
+            }
+            .to_string(),
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        for (input, err) in [
+            (
+                quote! { struct Params(u8); },
+                "only structs with named fields are supported",
+            ),
+            (
+                quote! { struct Params<T> { t: T } },
+                "generic structs are not supported",
+            ),
+            (
+                quote! { struct Params { color: () } },
+                "missing `#[param(regex = \"...\")]`",
+            ),
+            (
+                quote! { struct Params { #[param(regex = "(a")] a: () } },
+                "invalid regex: regex parse error:\n    (a\n    ^\n\
+                 error: unclosed group",
+            ),
+            (
+                quote! {
+                    struct Params { #[param(name = "{a}", regex = "a")] a: () }
+                },
+                "parameter name may not be empty or contain \
+                 `{`, `}`, `(`, `)`, `\\` or `/`",
+            ),
+            (
+                quote! {
+                    struct Params {
+                        #[param(regex = "a")]
+                        a: (),
+                        #[param(name = "a", regex = "b")]
+                        b: (),
+                    }
+                },
+                "duplicate parameter `a`",
+            ),
+        ] {
+            assert_eq!(derive(input).unwrap_err().to_string(), err);
+        }
+    }
+}
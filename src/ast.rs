@@ -16,38 +16,272 @@
 //! [1]: https://github.com/cucumber/cucumber-expressions#readme
 //! [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
 
+use std::{
+    fmt::{self, Display},
+    ops::RangeFrom,
+};
+
 use derive_more::{AsRef, Deref, DerefMut};
-use nom::{error::ErrorKind, Err, InputLength};
+use nom::{
+    error::ErrorKind, Compare, Err, InputIter, InputLength, InputTake,
+    InputTakeAtPosition, Offset, Slice,
+};
 use nom_locate::LocatedSpan;
 
-use crate::parse;
+use crate::{parse, Diagnostic};
 
 /// [`str`] along with its location information in the original input.
 pub type Spanned<'s> = LocatedSpan<&'s str>;
 
+/// Input of the [Cucumber Expressions][0] parser, carrying its location
+/// information in the original input.
+///
+/// Automatically implemented for all the types implementing the required
+/// [`nom`] traits, so [`Spanned`] may be replaced by a custom type (like a
+/// [`LocatedSpan`] with some `extra` data, or a rope-based slice tracking
+/// [LSP] positions) to avoid converting the locations after parsing.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{Expression, SingleExpression};
+/// # use nom_locate::LocatedSpan;
+/// #
+/// let expr =
+///     Expression::parse_span(LocatedSpan::new_extra("{int} cucumbers", 42))
+///         .unwrap();
+///
+/// match &expr[0] {
+///     SingleExpression::Parameter(p) => assert_eq!(p.extra, 42),
+///     _ => unreachable!(),
+/// }
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [LSP]: https://microsoft.github.io/language-server-protocol
+pub trait Span:
+    Clone
+    + Display
+    + Offset
+    + InputLength
+    + InputTake
+    + InputTakeAtPosition<Item = char>
+    + Slice<RangeFrom<usize>>
+    + InputIter<Item = char>
+    + for<'s> Compare<&'s str>
+{
+}
+
+impl<T> Span for T where
+    T: Clone
+        + Display
+        + Offset
+        + InputLength
+        + InputTake
+        + InputTakeAtPosition<Item = char>
+        + Slice<RangeFrom<usize>>
+        + InputIter<Item = char>
+        + for<'s> Compare<&'s str>
+{
+}
+
 /// Top-level `expression` defined in the [grammar spec][0].
 ///
 /// See [`parse::expression()`] for the detailed grammar and examples.
 ///
 /// [0]: crate#grammar
 #[derive(AsRef, Clone, Debug, Deref, DerefMut, Eq, PartialEq)]
-pub struct Expression<Input>(pub Vec<SingleExpression<Input>>);
+pub struct Expression<Input>(
+    /// [`SingleExpression`]s of this [`Expression`].
+    #[as_ref]
+    #[deref]
+    #[deref_mut]
+    pub Vec<SingleExpression<Input>>,
+    /// Original `Input` this [`Expression`] was parsed from.
+    pub Input,
+);
 
 impl<'s> TryFrom<&'s str> for Expression<Spanned<'s>> {
     type Error = parse::Error<Spanned<'s>>;
 
     fn try_from(value: &'s str) -> Result<Self, Self::Error> {
-        parse::expression(Spanned::new(value))
+        Self::parse_span(Spanned::new(value))
+    }
+}
+
+impl<Input> Expression<Input> {
+    /// Returns the original `Input` this [`Expression`] was parsed from.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let expr = Expression::parse("I have {int} cucumbers").unwrap();
+    ///
+    /// assert_eq!(**expr.source(), "I have {int} cucumbers");
+    /// ```
+    #[must_use]
+    pub const fn source(&self) -> &Input {
+        &self.1
+    }
+}
+
+impl<S: Span> Expression<S> {
+    /// Parses the given [`Span`] as an [`Expression`].
+    ///
+    /// Use this method instead of [`Expression::parse()`] for parsing into a
+    /// custom [`Span`] type.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    pub fn parse_span(span: S) -> Result<Self, parse::Error<S>> {
+        Self::parse_span_with_syntax(span, parse::Syntax::default())
+    }
+
+    /// Parses the given [`Span`] as an [`Expression`] of the given
+    /// [`parse::Syntax`].
+    ///
+    /// See [`Expression::parse_with_syntax()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    pub fn parse_span_with_syntax(
+        span: S,
+        syntax: parse::Syntax,
+    ) -> Result<Self, parse::Error<S>> {
+        parse::expression_with(syntax)(span)
             .map_err(|e| match e {
                 Err::Error(e) | Err::Failure(e) => e,
                 Err::Incomplete(n) => parse::Error::Needed(n),
             })
             .and_then(|(rest, parsed)| {
-                rest.is_empty()
-                    .then(|| parsed)
-                    .ok_or(parse::Error::Other(rest, ErrorKind::Verify))
+                if rest.input_len() == 0 {
+                    Ok(parsed)
+                } else {
+                    Err(parse::Error::Other(rest, ErrorKind::Verify))
+                }
             })
     }
+
+    /// Parses the given [`Span`] as an [`Expression`] in a lenient mode.
+    ///
+    /// See [`Expression::parse_lenient()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    pub fn parse_span_lenient(
+        span: S,
+    ) -> Result<(Self, Vec<Diagnostic<S>>), parse::Error<S>> {
+        let mut expr = Self(Vec::new(), span.clone());
+        let mut warnings = Vec::new();
+
+        let mut rest = span;
+        while rest.input_len() > 0 {
+            let mut unmatched = match Self::unmatched(rest.clone())? {
+                Ok(parsed) => {
+                    expr.0.extend(parsed.0);
+                    break;
+                }
+                Err(c) => c,
+            };
+
+            // The unmatched character may be reported only after another one
+            // preceding it (like the nested `(` in `((a)`), so the earliest of
+            // them is treated as a text, while the rest is parsed once again.
+            let prefix = loop {
+                let at = rest.offset(&unmatched);
+                match Self::unmatched(rest.take(at))? {
+                    Ok(prefix) => break prefix,
+                    Err(c) => unmatched = c,
+                }
+            };
+            expr.0.extend(prefix.0);
+
+            let at = rest.offset(&unmatched);
+            expr.0.push(SingleExpression::Text(unmatched.clone()));
+            warnings.push(Diagnostic::UnmatchedReservedCharacter(unmatched));
+
+            rest = rest.slice((at + 1)..);
+        }
+
+        Ok((expr, warnings))
+    }
+
+    /// Parses the given [`Span`] as an [`Expression`], returning the unmatched
+    /// `{` or `(` character failing the parsing, if any.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Span`] fails to parse for any other reason.
+    fn unmatched(span: S) -> Result<Result<Self, S>, parse::Error<S>> {
+        match Self::parse_span(span) {
+            Ok(parsed) => Ok(Ok(parsed)),
+            Err(
+                parse::Error::UnfinishedParameter(c)
+                | parse::Error::UnfinishedOptional(c)
+                | parse::Error::UnescapedReservedCharacter(c),
+            ) if c
+                .iter_elements()
+                .next()
+                .map_or(false, |c| "{(".contains(c)) =>
+            {
+                Ok(Err(c))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns [`Diagnostic::AdjacentAnonymousParameters`] warnings for every
+    /// anonymous `{}` [`Parameter`] following another one with nothing but
+    /// whitespaces or [`Optional`]s between them.
+    ///
+    /// Such [`Parameter`]s split the matched text between them arbitrarily,
+    /// while their unbounded `.*` patterns make backtracking regex engines
+    /// try every split, so consider separating them with some text, or
+    /// bounding them with [`Options::safe_anonymous`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::{diagnostic::Severity, Expression};
+    /// #
+    /// let expr = Expression::parse("{} (and ){} and {}").unwrap();
+    /// let warnings = expr.adjacent_anonymous_parameters();
+    ///
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].severity(), Severity::Warning);
+    /// assert_eq!(warnings[0].line_column(), Some((1, 11)));
+    /// ```
+    ///
+    /// [`Options::safe_anonymous`]: crate::expand::Options::safe_anonymous
+    #[must_use]
+    pub fn adjacent_anonymous_parameters(&self) -> Vec<Diagnostic<S>> {
+        let mut follows_anonymous = false;
+        let mut warnings = Vec::new();
+        for e in &self.0 {
+            match e {
+                SingleExpression::Parameter(Parameter(p)) => {
+                    let anonymous = p.input_len() == 0;
+                    if anonymous && follows_anonymous {
+                        warnings.push(Diagnostic::AdjacentAnonymousParameters(
+                            p.clone(),
+                        ));
+                    }
+                    follows_anonymous = anonymous;
+                }
+                SingleExpression::Optional(_)
+                | SingleExpression::OptionalParameter(_)
+                | SingleExpression::Whitespaces(_) => {}
+                SingleExpression::Alternation(_)
+                | SingleExpression::Text(_) => follows_anonymous = false,
+            }
+        }
+        warnings
+    }
 }
 
 impl<'s> Expression<Spanned<'s>> {
@@ -61,6 +295,84 @@ impl<'s> Expression<Spanned<'s>> {
     ) -> Result<Self, parse::Error<Spanned<'s>>> {
         Self::try_from(input.as_ref())
     }
+
+    /// Parses the given `input` as an [`Expression`] of the given
+    /// [`parse::Syntax`].
+    ///
+    /// Characters of the disabled constructs are treated as a plain text, while
+    /// escaping them is still allowed.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    pub fn parse_with_syntax<I: AsRef<str> + ?Sized>(
+        input: &'s I,
+        syntax: parse::Syntax,
+    ) -> Result<Self, parse::Error<Spanned<'s>>> {
+        Self::parse_span_with_syntax(Spanned::new(input.as_ref()), syntax)
+    }
+
+    /// Parses the given `input` as an [`Expression`] in a lenient mode,
+    /// treating unmatched `{` and `(` as text, like older [Cucumber][0]
+    /// implementations did.
+    ///
+    /// Every such character is reported with a
+    /// [`Diagnostic::UnmatchedReservedCharacter`] warning.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::{
+    /// #     diagnostic::Severity, Expression, SingleExpression,
+    /// # };
+    /// #
+    /// let (expr, warnings) =
+    ///     Expression::parse_lenient("I have {int} cucumbers :(").unwrap();
+    ///
+    /// match expr.last().unwrap() {
+    ///     SingleExpression::Text(t) => assert_eq!(**t, "("),
+    ///     _ => unreachable!(),
+    /// }
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].severity(), Severity::Warning);
+    /// ```
+    ///
+    /// [0]: https://cucumber.io
+    pub fn parse_lenient<I: AsRef<str> + ?Sized>(
+        input: &'s I,
+    ) -> Result<(Self, Vec<Diagnostic<Spanned<'s>>>), parse::Error<Spanned<'s>>>
+    {
+        Self::parse_span_lenient(Spanned::new(input.as_ref()))
+    }
+
+    /// Validates the given `input` as an [`Expression`] syntax, without
+    /// expanding it into anything.
+    ///
+    /// # Errors
+    ///
+    /// With [`Diagnostic`]s describing the problems found in the `input`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::{Diagnostic, Expression};
+    /// #
+    /// assert!(Expression::validate("I have {int} cucumbers").is_ok());
+    ///
+    /// let diagnostics =
+    ///     Expression::validate("I have {int cucumbers").unwrap_err();
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(**diagnostics[0].span().unwrap(), "{");
+    /// ```
+    pub fn validate<I: AsRef<str> + ?Sized>(
+        input: &'s I,
+    ) -> Result<(), Vec<Diagnostic<Spanned<'s>>>> {
+        Self::parse(input).map(drop).map_err(|e| vec![e.into()])
+    }
 }
 
 /// `single-expression` defined in the [grammar spec][0], representing a single
@@ -81,6 +393,13 @@ pub enum SingleExpression<Input> {
     /// [0]: crate#grammar
     Optional(Optional<Input>),
 
+    /// [`optional`][0] expression containing a single [`Parameter`].
+    ///
+    /// Parsed only if [`parse::Syntax::optional_parameters`] is enabled.
+    ///
+    /// [0]: crate#grammar
+    OptionalParameter(OptionalParameter<Input>),
+
     /// [`parameter`][0] expression.
     ///
     /// [0]: crate#grammar
@@ -112,32 +431,6 @@ pub type SingleAlternation<Input> = Vec<Alternative<Input>>;
 #[derive(AsRef, Clone, Debug, Deref, DerefMut, Eq, PartialEq)]
 pub struct Alternation<Input>(pub Vec<SingleAlternation<Input>>);
 
-impl<Input: InputLength> Alternation<Input> {
-    /// Returns length of this [`Alternation`]'s span in the `Input`.
-    pub(crate) fn span_len(&self) -> usize {
-        self.0
-            .iter()
-            .flatten()
-            .map(|alt| match alt {
-                Alternative::Text(t) => t.input_len(),
-                Alternative::Optional(opt) => opt.input_len() + 2,
-            })
-            .sum::<usize>()
-            + self.len()
-            - 1
-    }
-
-    /// Indicates whether any of [`SingleAlternation`]s consists only from
-    /// [`Optional`]s.
-    pub(crate) fn contains_only_optional(&self) -> bool {
-        (**self).iter().any(|single_alt| {
-            single_alt
-                .iter()
-                .all(|alt| matches!(alt, Alternative::Optional(_)))
-        })
-    }
-}
-
 /// `alternative` defined in the [grammar spec][0].
 ///
 /// See [`parse::alternative()`] for the detailed grammar and examples.
@@ -163,6 +456,29 @@ pub enum Alternative<Input> {
 #[derive(AsRef, Clone, Copy, Debug, Deref, DerefMut, Eq, PartialEq)]
 pub struct Optional<Input>(pub Input);
 
+/// [`Optional`] containing a single [`Parameter`] surrounded by some text (like
+/// `( with {int} retries)`), allowing to omit the [`Parameter`] along with its
+/// text.
+///
+/// Parsed only if [`parse::Syntax::optional_parameters`] is enabled. Whenever
+/// it doesn't match, the capturing group of its [`Parameter`] doesn't
+/// participate in the match, so a [default value][1] may be used instead.
+///
+/// See [`parse::optional_parameter()`] for the detailed grammar and examples.
+///
+/// [1]: crate::expand::CompiledExpression::with_default()
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OptionalParameter<Input> {
+    /// Raw text preceding the [`Parameter`].
+    pub prefix: Input,
+
+    /// [`Parameter`] itself.
+    pub parameter: Parameter<Input>,
+
+    /// Raw text following the [`Parameter`].
+    pub suffix: Input,
+}
+
 /// `parameter` defined in the [grammar spec][0], allowing to match some special
 /// `Input` described by a [`Parameter`] name.
 ///
@@ -171,3 +487,851 @@ pub struct Optional<Input>(pub Input);
 /// [0]: crate#grammar
 #[derive(AsRef, Clone, Copy, Debug, Deref, DerefMut, Eq, PartialEq)]
 pub struct Parameter<Input>(pub Input);
+
+impl<Input: Display> Display for Expression<Input> {
+    /// Renders this [`Expression`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter().try_for_each(|e| write!(f, "{}", e))
+    }
+}
+
+impl<Input> SingleExpression<Input> {
+    /// Returns the [`Parameter`] of this [`SingleExpression`], if it's either
+    /// a [`Parameter`] or an [`OptionalParameter`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::{parse::Syntax, Expression};
+    /// #
+    /// let syntax = Syntax {
+    ///     optional_parameters: true,
+    ///     ..Syntax::default()
+    /// };
+    /// let input = "{int}( x{word})(s)";
+    /// let expr = Expression::parse_with_syntax(input, syntax).unwrap();
+    /// let names = expr
+    ///     .iter()
+    ///     .filter_map(|e| e.parameter())
+    ///     .map(|p| *p.fragment())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(names, ["int", "word"]);
+    /// ```
+    #[must_use]
+    pub const fn parameter(&self) -> Option<&Parameter<Input>> {
+        match self {
+            Self::Parameter(p)
+            | Self::OptionalParameter(OptionalParameter {
+                parameter: p, ..
+            }) => Some(p),
+            Self::Alternation(_)
+            | Self::Optional(_)
+            | Self::Text(_)
+            | Self::Whitespaces(_) => None,
+        }
+    }
+}
+
+impl<Input: Display> Display for SingleExpression<Input> {
+    /// Renders this [`SingleExpression`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Alternation(alt) => write!(f, "{}", alt),
+            Self::Optional(opt) => write!(f, "{}", opt),
+            Self::OptionalParameter(opt) => write!(f, "{}", opt),
+            Self::Parameter(par) => write!(f, "{}", par),
+            Self::Text(t) | Self::Whitespaces(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+impl<Input: Display> Display for Alternation<Input> {
+    /// Renders this [`Alternation`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, single_alt) in self.iter().enumerate() {
+            if n > 0 {
+                write!(f, "/")?;
+            }
+            single_alt.iter().try_for_each(|alt| write!(f, "{}", alt))?;
+        }
+        Ok(())
+    }
+}
+
+impl<Input: Display> Display for Alternative<Input> {
+    /// Renders this [`Alternative`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Optional(opt) => write!(f, "{}", opt),
+            Self::Text(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+impl<Input: Display> Display for Optional<Input> {
+    /// Renders this [`Optional`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({})", self.0)
+    }
+}
+
+impl<Input: Display> Display for OptionalParameter<Input> {
+    /// Renders this [`OptionalParameter`] back into its source,
+    /// byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}{}{})", self.prefix, self.parameter, self.suffix)
+    }
+}
+
+impl<Input: Display> Display for Parameter<Input> {
+    /// Renders this [`Parameter`] back into its source, byte-for-byte.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}}}", self.0)
+    }
+}
+
+impl<Input: Span> Optional<Input> {
+    /// Returns [`TextChars`] of this [`Optional`]'s text, with escape
+    /// sequences resolved.
+    pub fn chars(&self) -> TextChars<Input> {
+        TextChars::new(&self.0)
+    }
+}
+
+impl<Input: Span> OptionalParameter<Input> {
+    /// Returns [`TextChars`] of this [`OptionalParameter`]'s prefix text, with
+    /// escape sequences resolved.
+    pub fn prefix_chars(&self) -> TextChars<Input> {
+        TextChars::new(&self.prefix)
+    }
+
+    /// Returns [`TextChars`] of this [`OptionalParameter`]'s suffix text, with
+    /// escape sequences resolved.
+    pub fn suffix_chars(&self) -> TextChars<Input> {
+        TextChars::new(&self.suffix)
+    }
+}
+
+impl<Input: Span> Parameter<Input> {
+    /// Returns [`TextChars`] of this [`Parameter`]'s name, with escape
+    /// sequences resolved.
+    pub fn chars(&self) -> TextChars<Input> {
+        TextChars::new(&self.0)
+    }
+}
+
+/// Single character of a textual `Input` in an [`Expression`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextChar {
+    /// Character appeared in the source as is.
+    Literal(char),
+
+    /// Character appeared in the source escaped with `\`.
+    Escaped(char),
+}
+
+impl TextChar {
+    /// Returns the cooked [`char`] of this [`TextChar`], without `\`.
+    #[must_use]
+    pub const fn char(self) -> char {
+        match self {
+            Self::Literal(c) | Self::Escaped(c) => c,
+        }
+    }
+
+    /// Indicates whether this [`TextChar`] was escaped in the source.
+    #[must_use]
+    pub const fn is_escaped(self) -> bool {
+        matches!(self, Self::Escaped(_))
+    }
+}
+
+impl Display for TextChar {
+    /// Renders this [`TextChar`] back into its source.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(c) => write!(f, "{}", c),
+            Self::Escaped(c) => write!(f, "\\{}", c),
+        }
+    }
+}
+
+/// [`Iterator`] over [`TextChar`]s of a raw textual `Input` of an
+/// [`Expression`], providing its cooked view.
+///
+/// Raw view is the `Input` itself, as [AST] nodes store it verbatim.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{Expression, SingleExpression, TextChars};
+/// #
+/// let expr = Expression::parse(r"\(a\)").unwrap();
+/// let text = match &expr[0] {
+///     SingleExpression::Text(t) => t,
+///     _ => unreachable!(),
+/// };
+///
+/// assert_eq!(**text, r"\(a\)");
+/// assert_eq!(TextChars::new(text).cooked(), "(a)");
+/// assert_eq!(expr.to_string(), r"\(a\)");
+/// ```
+///
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub struct TextChars<Input: Span> {
+    /// Underlying [`char`]s of the raw `Input`.
+    iter: <Input as InputIter>::IterElem,
+}
+
+impl<Input: Span> fmt::Debug for TextChars<Input> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TextChars").finish_non_exhaustive()
+    }
+}
+
+impl<Input: Span> TextChars<Input> {
+    /// Creates new [`TextChars`] out of the given raw `Input`.
+    #[must_use]
+    pub fn new(input: &Input) -> Self {
+        Self {
+            iter: input.iter_elements(),
+        }
+    }
+
+    /// Collects the cooked view of the remaining [`TextChar`]s into a
+    /// [`String`].
+    #[must_use]
+    pub fn cooked(self) -> String {
+        self.map(TextChar::char).collect()
+    }
+}
+
+impl<Input: Span> Iterator for TextChars<Input> {
+    type Item = TextChar;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.iter.next()? {
+            '\\' => self.iter.next().map(TextChar::Escaped),
+            c => Some(TextChar::Literal(c)),
+        }
+    }
+}
+
+/// Kind of a step definition string, telling whether it should be treated as
+/// a [Cucumber Expression][0] or as a regular expression.
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExpressionKind {
+    /// [Cucumber Expression][0].
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    Cucumber,
+
+    /// Regular expression.
+    Regular,
+}
+
+impl ExpressionKind {
+    /// Detects the [`ExpressionKind`] of the given `input` using the heuristic
+    /// of the original [`ExpressionFactory`][1]:
+    /// - `input` starting with `^` or ending with `$` is a regular expression;
+    /// - `input` wrapped into `/` (like `/pattern/`) is a regular expression;
+    /// - anything else is a [Cucumber Expression][0].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::ExpressionKind;
+    /// #
+    /// assert_eq!(
+    ///     ExpressionKind::detect("I have {int} cucumbers"),
+    ///     ExpressionKind::Cucumber,
+    /// );
+    /// assert_eq!(
+    ///     ExpressionKind::detect(r"^I have (\d+) cucumbers$"),
+    ///     ExpressionKind::Regular,
+    /// );
+    /// assert_eq!(
+    ///     ExpressionKind::detect(r"/I have (\d+) cucumbers/"),
+    ///     ExpressionKind::Regular,
+    /// );
+    /// ```
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    /// [1]: https://github.com/cucumber/cucumber-expressions/tree/main/java
+    #[must_use]
+    pub fn detect<I: AsRef<str> + ?Sized>(input: &I) -> Self {
+        Self::detect_span(&input.as_ref())
+    }
+
+    /// Detects the [`ExpressionKind`] of the given [`Span`].
+    ///
+    /// See [`ExpressionKind::detect()`] for details.
+    #[must_use]
+    pub fn detect_span<S: Span>(span: &S) -> Self {
+        if is_anchored(span) || is_wrapped_into_slashes(span) {
+            Self::Regular
+        } else {
+            Self::Cucumber
+        }
+    }
+}
+
+/// Checks whether the given [`Span`] starts with `^` or ends with `$`.
+fn is_anchored<S: Span>(span: &S) -> bool {
+    span.iter_elements().next() == Some('^')
+        || span.iter_elements().last() == Some('$')
+}
+
+/// Checks whether the given [`Span`] is wrapped into `/`.
+fn is_wrapped_into_slashes<S: Span>(span: &S) -> bool {
+    span.input_len() > 1
+        && span.iter_elements().next() == Some('/')
+        && span.iter_elements().last() == Some('/')
+}
+
+/// Either a [Cucumber Expression][0] or a regular expression, as detected by
+/// [`ExpressionKind::detect()`].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AnyExpression<Input> {
+    /// Parsed [Cucumber Expression][0].
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    Cucumber(Expression<Input>),
+
+    /// Regular expression to be used verbatim.
+    ///
+    /// Doesn't contain the wrapping `/`, if there were any.
+    Regular(Input),
+}
+
+impl<'s> TryFrom<&'s str> for AnyExpression<Spanned<'s>> {
+    type Error = parse::Error<Spanned<'s>>;
+
+    fn try_from(value: &'s str) -> Result<Self, Self::Error> {
+        Self::parse_span(Spanned::new(value))
+    }
+}
+
+impl<S: Span> AnyExpression<S> {
+    /// Parses the given [`Span`] as an [`AnyExpression`].
+    ///
+    /// Use this method instead of [`AnyExpression::parse()`] for parsing into
+    /// a custom [`Span`] type.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    pub fn parse_span(span: S) -> Result<Self, parse::Error<S>> {
+        Self::parse_span_with_syntax(span, parse::Syntax::default())
+    }
+
+    /// Parses the given [`Span`] as an [`AnyExpression`], parsing a
+    /// [Cucumber Expression][0] with the given [`parse::Syntax`].
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    pub fn parse_span_with_syntax(
+        span: S,
+        syntax: parse::Syntax,
+    ) -> Result<Self, parse::Error<S>> {
+        match ExpressionKind::detect_span(&span) {
+            ExpressionKind::Cucumber => {
+                Expression::parse_span_with_syntax(span, syntax)
+                    .map(Self::Cucumber)
+            }
+            ExpressionKind::Regular => {
+                Ok(Self::Regular(if is_wrapped_into_slashes(&span) {
+                    let len = span.input_len();
+                    span.slice(1..).take(len - 2)
+                } else {
+                    span
+                }))
+            }
+        }
+    }
+}
+
+impl<'s> AnyExpression<Spanned<'s>> {
+    /// Parses the given `input` as an [`AnyExpression`].
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::AnyExpression;
+    /// #
+    /// match AnyExpression::parse(r"/I have (\d+) cucumbers/").unwrap() {
+    ///     AnyExpression::Regular(re) => {
+    ///         assert_eq!(*re, r"I have (\d+) cucumbers");
+    ///     }
+    ///     AnyExpression::Cucumber(_) => unreachable!(),
+    /// }
+    /// ```
+    pub fn parse<I: AsRef<str> + ?Sized>(
+        input: &'s I,
+    ) -> Result<Self, parse::Error<Spanned<'s>>> {
+        Self::try_from(input.as_ref())
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], parsing a
+    /// [Cucumber Expression][0] with the given [`parse::Syntax`].
+    ///
+    /// # Errors
+    ///
+    /// See [`parse::Error`] for details.
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    pub fn parse_with_syntax<I: AsRef<str> + ?Sized>(
+        input: &'s I,
+        syntax: parse::Syntax,
+    ) -> Result<Self, parse::Error<Spanned<'s>>> {
+        Self::parse_span_with_syntax(Spanned::new(input.as_ref()), syntax)
+    }
+}
+
+/// Renders the given [`SingleExpression`] into a textual representation of its
+/// kind and contents, to be asserted on in specs.
+#[cfg(test)]
+pub(crate) fn node<Input: Display>(e: &SingleExpression<Input>) -> String {
+    match e {
+        SingleExpression::Alternation(a) => format!("alt:{}", a.len()),
+        SingleExpression::Optional(o) => format!("opt:{}", **o),
+        SingleExpression::OptionalParameter(o) => {
+            format!("optpar:{}|{}|{}", o.prefix, *o.parameter, o.suffix)
+        }
+        SingleExpression::Parameter(p) => format!("par:{}", **p),
+        SingleExpression::Text(t) => format!("text:{}", t),
+        SingleExpression::Whitespaces(w) => format!("ws:{}", w),
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use crate::{
+        parse, AnyExpression, Diagnostic, Expression, ExpressionKind,
+        SingleExpression, TextChar, TextChars,
+    };
+
+    use super::node;
+
+    mod display {
+        use super::{Expression, SingleExpression, TextChar, TextChars};
+
+        #[test]
+        fn renders_source_verbatim() {
+            for input in [
+                "",
+                "I have {int} cucumber(s) in my belly/stomach",
+                r"escaped \(\{\/\\ and\ spaces",
+                "{with spaces} (opt)left/right(opt)  many   spaces",
+                "🦀/⚙️ {🦀}",
+            ] {
+                let expr = Expression::parse(input)
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+
+                assert_eq!(expr.to_string(), input);
+            }
+        }
+
+        #[test]
+        fn keeps_source() {
+            let expr = Expression::parse("a {int} (b)").unwrap();
+            assert_eq!(**expr.source(), "a {int} (b)");
+
+            let (lenient, _) = Expression::parse_lenient("a(b {c").unwrap();
+            assert_eq!(**lenient.source(), "a(b {c");
+        }
+
+        #[test]
+        fn renders_lenient_source_verbatim() {
+            let (expr, _) = Expression::parse_lenient(r"a(b \( {c").unwrap();
+
+            assert_eq!(expr.to_string(), r"a(b \( {c");
+        }
+
+        #[test]
+        fn distinguishes_escaped_chars() {
+            let (expr, _) = Expression::parse_lenient(r"\((").unwrap();
+
+            let chars = expr
+                .iter()
+                .flat_map(|e| match e {
+                    SingleExpression::Text(t) => TextChars::new(t),
+                    SingleExpression::Alternation(_)
+                    | SingleExpression::Optional(_)
+                    | SingleExpression::OptionalParameter(_)
+                    | SingleExpression::Parameter(_)
+                    | SingleExpression::Whitespaces(_) => {
+                        panic!("wrong expression: {:?}", e)
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            assert_eq!(chars, [TextChar::Escaped('('), TextChar::Literal('(')]);
+        }
+    }
+
+    mod parse_lenient {
+        use super::{node, parse, Diagnostic, Expression};
+
+        /// Parses the given `input` leniently, returning textual representation
+        /// of the parsed [`SingleExpression`]s and offsets of the warnings.
+        fn lenient(input: &str) -> (Vec<String>, Vec<usize>) {
+            let (expr, warnings) = Expression::parse_lenient(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            let nodes = expr.iter().map(node).collect();
+            let warnings = warnings
+                .iter()
+                .map(|w| match w {
+                    Diagnostic::UnmatchedReservedCharacter(c) => {
+                        assert!("{(".contains(**c), "wrong char: {}", c);
+                        c.location_offset()
+                    }
+                    d @ (Diagnostic::Parsing(_)
+                    | Diagnostic::UnknownParameter(_)
+                    | Diagnostic::AdjacentAnonymousParameters(_)) => {
+                        panic!("wrong diagnostic: {}", d)
+                    }
+                })
+                .collect();
+
+            (nodes, warnings)
+        }
+
+        #[test]
+        fn same_as_strict() {
+            assert_eq!(
+                lenient("{int} (opt) a/b"),
+                (
+                    vec![
+                        "par:int".into(),
+                        "ws: ".into(),
+                        "opt:opt".into(),
+                        "ws: ".into(),
+                        "alt:2".into(),
+                    ],
+                    vec![],
+                ),
+            );
+        }
+
+        #[test]
+        fn unfinished_optional() {
+            assert_eq!(
+                lenient("a(b"),
+                (
+                    vec!["text:a".into(), "text:(".into(), "text:b".into()],
+                    vec![1],
+                ),
+            );
+        }
+
+        #[test]
+        fn unfinished_parameter() {
+            assert_eq!(
+                lenient("{int} {b"),
+                (
+                    vec![
+                        "par:int".into(),
+                        "ws: ".into(),
+                        "text:{".into(),
+                        "text:b".into(),
+                    ],
+                    vec![6],
+                ),
+            );
+        }
+
+        #[test]
+        fn nested_unmatched() {
+            assert_eq!(
+                lenient("{a(b}"),
+                (
+                    vec![
+                        "text:{".into(),
+                        "text:a".into(),
+                        "text:(".into(),
+                        "text:b}".into(),
+                    ],
+                    vec![0, 2],
+                ),
+            );
+        }
+
+        #[test]
+        fn recovers_nested_optional() {
+            assert_eq!(
+                lenient("x ((a)"),
+                (
+                    vec![
+                        "text:x".into(),
+                        "ws: ".into(),
+                        "text:(".into(),
+                        "opt:a".into(),
+                    ],
+                    vec![2],
+                ),
+            );
+        }
+
+        #[test]
+        fn trailing_unfinished_optional() {
+            assert_eq!(
+                lenient("a(b)("),
+                (
+                    vec!["text:a".into(), "opt:b".into(), "text:(".into()],
+                    vec![4],
+                ),
+            );
+        }
+
+        #[test]
+        fn fails_on_other_errors() {
+            let err = Expression::parse_lenient("a () b").unwrap_err();
+
+            assert!(
+                matches!(err, parse::Error::EmptyOptional(e) if *e == "()"),
+                "wrong error: {}",
+                err,
+            );
+        }
+    }
+
+    mod adjacent_anonymous_parameters {
+        use super::Expression;
+
+        #[test]
+        fn reports_only_adjacent() {
+            for (input, expected) in [
+                ("{}{}", &[3][..]),
+                ("{} {} (x){}", &[4, 10]),
+                ("{} and {}", &[]),
+                ("{} a/b {}", &[]),
+                ("{int} {}", &[]),
+                ("{}{int}{}", &[]),
+            ] {
+                let offsets = Expression::parse(input)
+                    .unwrap()
+                    .adjacent_anonymous_parameters()
+                    .into_iter()
+                    .map(|d| d.span().unwrap().location_offset())
+                    .collect::<Vec<_>>();
+
+                assert_eq!(offsets, expected, "on input: {}", input);
+            }
+        }
+    }
+
+    mod validate {
+        use super::{parse, Diagnostic, Expression};
+
+        #[test]
+        fn accepts_valid() {
+            for input in [
+                "",
+                "I have {int} cucumber(s) in my belly/stomach",
+                r"escaped \(\{\/\ and\ spaces",
+                "{custom} {}",
+            ] {
+                assert_eq!(
+                    Expression::validate(input),
+                    Ok(()),
+                    "on input: {}",
+                    input,
+                );
+            }
+        }
+
+        #[test]
+        fn reports_parsing_error() {
+            for (input, offset) in [
+                ("I have {int cucumbers", 7),
+                ("a (b", 2),
+                ("{a(b)}", 2),
+                ("a / b", 2),
+            ] {
+                let diagnostics = Expression::validate(input).unwrap_err();
+
+                assert_eq!(diagnostics.len(), 1, "on input: {}", input);
+                match &diagnostics[0] {
+                    d @ Diagnostic::Parsing(_) => assert_eq!(
+                        d.span().map(|s| s.location_offset()),
+                        Some(offset),
+                        "on input: {}",
+                        input,
+                    ),
+                    d @ (Diagnostic::UnknownParameter(_)
+                    | Diagnostic::UnmatchedReservedCharacter(_)
+                    | Diagnostic::AdjacentAnonymousParameters(_)) => {
+                        panic!("wrong diagnostic: {}", d)
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn same_as_parse() {
+            let input = "(a(b))";
+
+            assert_eq!(
+                Expression::validate(input).unwrap_err(),
+                vec![Diagnostic::Parsing(
+                    Expression::parse(input).unwrap_err()
+                )],
+            );
+            assert!(matches!(
+                Expression::validate(input).unwrap_err()[0],
+                Diagnostic::Parsing(parse::Error::NestedOptional(_)),
+            ));
+        }
+    }
+
+    mod expression_kind {
+        use super::ExpressionKind;
+
+        #[test]
+        fn detects_regular() {
+            for input in [
+                r"^I have (\d+) cucumbers",
+                r"I have (\d+) cucumbers$",
+                "^$",
+                r"/I have (\d+) cucumbers/",
+                "//",
+            ] {
+                assert_eq!(
+                    ExpressionKind::detect(input),
+                    ExpressionKind::Regular,
+                    "on input: {}",
+                    input,
+                );
+            }
+        }
+
+        #[test]
+        fn detects_cucumber() {
+            for input in [
+                "",
+                "/",
+                "I have {int} cucumber(s) in my belly/stomach",
+                "/I have {int} cucumbers",
+                "I have {int} cucumbers/",
+                "a ^ b $ c",
+            ] {
+                assert_eq!(
+                    ExpressionKind::detect(input),
+                    ExpressionKind::Cucumber,
+                    "on input: {}",
+                    input,
+                );
+            }
+        }
+    }
+
+    mod parse_span {
+        use nom_locate::LocatedSpan;
+
+        use super::{
+            AnyExpression, Expression, ExpressionKind, SingleExpression,
+        };
+
+        #[test]
+        fn keeps_custom_span() {
+            let expr = Expression::parse_span(LocatedSpan::new_extra(
+                "a {int} (b)",
+                "step.rs",
+            ))
+            .unwrap();
+
+            assert_eq!(expr.len(), 5);
+            for e in expr.iter() {
+                match e {
+                    SingleExpression::Parameter(p) => {
+                        assert_eq!(*p.fragment(), "int");
+                        assert_eq!(p.location_offset(), 3);
+                        assert_eq!(p.extra, "step.rs");
+                    }
+                    SingleExpression::Optional(o) => {
+                        assert_eq!(*o.fragment(), "b");
+                        assert_eq!(o.extra, "step.rs");
+                    }
+                    SingleExpression::Alternation(_)
+                    | SingleExpression::OptionalParameter(_)
+                    | SingleExpression::Text(_)
+                    | SingleExpression::Whitespaces(_) => {}
+                }
+            }
+        }
+
+        #[test]
+        fn same_as_parse() {
+            for input in ["", "a/b(c) {int}", "{a(b)}", "(a) b/c/d\\/ {}"] {
+                assert_eq!(
+                    Expression::parse_span(LocatedSpan::new_extra(input, ()))
+                        .map(|e| e.to_string())
+                        .map_err(|e| e.to_string()),
+                    Expression::parse(input)
+                        .map(|e| e.to_string())
+                        .map_err(|e| e.to_string()),
+                    "on input: {}",
+                    input,
+                );
+            }
+        }
+
+        #[test]
+        fn detects_kind() {
+            for (input, expected) in [
+                ("{int}", ExpressionKind::Cucumber),
+                ("/", ExpressionKind::Cucumber),
+                ("^a$", ExpressionKind::Regular),
+                ("/a/", ExpressionKind::Regular),
+            ] {
+                assert_eq!(
+                    ExpressionKind::detect_span(&LocatedSpan::new_extra(
+                        input, 1,
+                    )),
+                    expected,
+                    "on input: {}",
+                    input,
+                );
+            }
+        }
+
+        #[test]
+        fn any_expression() {
+            match AnyExpression::parse_span(LocatedSpan::new_extra("/a(b)/", 7))
+                .unwrap()
+            {
+                AnyExpression::Regular(re) => {
+                    assert_eq!(*re, "a(b)");
+                    assert_eq!(re.location_offset(), 1);
+                    assert_eq!(re.extra, 7);
+                }
+                e @ AnyExpression::Cucumber(_) => {
+                    panic!("wrong expression: {:?}", e)
+                }
+            }
+
+            match AnyExpression::parse_span(LocatedSpan::new_extra("{int}", 7))
+                .unwrap()
+            {
+                AnyExpression::Cucumber(e) => assert_eq!(e.len(), 1),
+                e @ AnyExpression::Regular(_) => {
+                    panic!("wrong expression: {:?}", e)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Catalog`] of step definitions, parsed out of a single text.
+
+use derive_more::{AsRef, Deref, Display, Error};
+use nom::{InputLength, InputTake, Slice};
+
+use crate::{parse, AnyExpression, Spanned};
+
+/// Layout of a text containing a [`Catalog`] of step definitions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Layout {
+    /// Single step definition per line.
+    ///
+    /// Blank lines and lines starting with `#` are skipped.
+    Lines,
+
+    /// Comma-separated values, having step definitions in the given `column`
+    /// (0-based).
+    ///
+    /// Values may be wrapped into `"`, while `""` inside them is reported with
+    /// an [`Error::EscapedQuote`], as step definitions are parsed right out of
+    /// the text, without unescaping. Blank lines are skipped.
+    Csv {
+        /// Index of the column containing step definitions.
+        column: usize,
+
+        /// Indicator whether the first line is a header to be skipped.
+        header: bool,
+    },
+}
+
+/// Step definitions parsed out of a single text, in the order they appear in
+/// it.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::catalog::{Catalog, Layout};
+/// #
+/// let catalog = Catalog::parse(
+///     "# steps\n\
+///      I have {int} cucumbers\n\
+///      /^I eat (\\d+)$/\n\
+///      I have {int cucumbers\n",
+///     Layout::Lines,
+/// );
+///
+/// assert_eq!(catalog.len(), 3);
+/// assert_eq!(
+///     catalog.errors().map(|(line, _)| line).collect::<Vec<_>>(),
+///     [4],
+/// );
+/// ```
+#[derive(AsRef, Clone, Debug, Deref, Eq, PartialEq)]
+pub struct Catalog<'s>(pub Vec<Entry<'s>>);
+
+/// Single step definition of a [`Catalog`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry<'s> {
+    /// 1-based number of the line this [`Entry`] is located on.
+    pub line: u32,
+
+    /// Raw step definition.
+    pub input: Spanned<'s>,
+
+    /// Result of parsing the [`Entry::input`].
+    pub result: Result<AnyExpression<Spanned<'s>>, Error<'s>>,
+}
+
+/// Possible errors of a [`Catalog`] [`Entry`].
+#[derive(Clone, Debug, Display, Error, Eq, PartialEq)]
+pub enum Error<'s> {
+    /// Step definition failed to parse.
+    #[display(fmt = "{}", _0)]
+    Parsing(#[error(not(source))] parse::Error<Spanned<'s>>),
+
+    /// [`Layout::Csv`] line doesn't have the required column.
+    #[display(fmt = "{}\nThe line does not have the column {}.", _0, _1)]
+    MissingColumn(
+        #[error(not(source))] Spanned<'s>,
+        #[error(not(source))] usize,
+    ),
+
+    /// [`Layout::Csv`] field contains an escaped `""` quote.
+    #[display(
+        fmt = "{}\nEscaped quotes are not supported in step definitions.",
+        _0
+    )]
+    EscapedQuote(#[error(not(source))] Spanned<'s>),
+}
+
+impl<'s> Catalog<'s> {
+    /// Parses all the step definitions in the given `text` of the given
+    /// [`Layout`].
+    ///
+    /// Never fails as a whole, reporting an [`Error`] per [`Entry`] instead.
+    ///
+    /// [`Error`]: enum@Error
+    #[must_use]
+    pub fn parse<I: AsRef<str> + ?Sized>(text: &'s I, layout: Layout) -> Self {
+        let mut entries = Vec::new();
+        let mut skip_header =
+            matches!(layout, Layout::Csv { header: true, .. });
+
+        let mut rest = Spanned::new(text.as_ref());
+        while rest.input_len() > 0 {
+            let len = rest.find('\n').unwrap_or_else(|| rest.input_len());
+            let (after, line) = rest.take_split(len);
+            rest = if after.input_len() > 0 {
+                after.slice(1..)
+            } else {
+                after
+            };
+
+            let line = if line.ends_with('\r') {
+                line.take(line.input_len() - 1)
+            } else {
+                line
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let input = match layout {
+                Layout::Lines => {
+                    if line.starts_with('#') {
+                        continue;
+                    }
+                    Ok(line)
+                }
+                Layout::Csv { column, .. } => {
+                    if skip_header {
+                        skip_header = false;
+                        continue;
+                    }
+                    csv_field(line, column)
+                }
+            };
+
+            entries.push(Entry {
+                line: line.location_line(),
+                input: input.clone().unwrap_or(line),
+                result: input.and_then(|i| {
+                    AnyExpression::parse_span(i).map_err(Error::Parsing)
+                }),
+            });
+        }
+
+        Self(entries)
+    }
+
+    /// Returns all the [`Error`]s of this [`Catalog`] along with the numbers of
+    /// lines they've occurred on.
+    ///
+    /// [`Error`]: enum@Error
+    pub fn errors(&self) -> impl Iterator<Item = (u32, &Error<'s>)> {
+        self.iter()
+            .filter_map(|e| e.result.as_ref().err().map(|err| (e.line, err)))
+    }
+
+    /// Indicates whether all the [`Entry`]s of this [`Catalog`] are parsed
+    /// successfully.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+}
+
+/// Returns the `column` (0-based) field of the given CSV `line`, without the
+/// wrapping `"`, if any.
+///
+/// # Errors
+///
+/// If the `line` doesn't have the `column`, or the field contains an escaped
+/// `""` quote.
+fn csv_field(
+    line: Spanned<'_>,
+    column: usize,
+) -> Result<Spanned<'_>, Error<'_>> {
+    let mut rest = line;
+    for n in 0.. {
+        let (field, end, escaped) = if rest.starts_with('"') {
+            let mut chars = rest.char_indices().skip(1).peekable();
+            let (mut closing, mut escaped) = (rest.input_len(), false);
+            while let Some((i, c)) = chars.next() {
+                if c == '"' {
+                    if chars.next_if(|&(_, next)| next == '"').is_none() {
+                        closing = i;
+                        break;
+                    }
+                    escaped = true;
+                }
+            }
+            let field = rest.slice(1..closing);
+            let end = rest.slice(closing..).find(',').map(|i| closing + i);
+            (field, end, escaped)
+        } else {
+            let end = rest.find(',');
+            (
+                rest.take(end.unwrap_or_else(|| rest.input_len())),
+                end,
+                false,
+            )
+        };
+
+        if n == column {
+            return if escaped {
+                Err(Error::EscapedQuote(field))
+            } else {
+                Ok(field)
+            };
+        }
+        rest = match end {
+            Some(end) => rest.slice((end + 1)..),
+            None => break,
+        };
+    }
+    Err(Error::MissingColumn(line, column))
+}
+
+#[cfg(test)]
+mod spec {
+    use super::{Catalog, Error, Layout};
+
+    /// Parses the given `text` into a [`Catalog`], returning numbers of lines
+    /// along with either raw parsed inputs or errors.
+    fn parse(text: &str, layout: Layout) -> Vec<(u32, Result<&str, String>)> {
+        Catalog::parse(text, layout)
+            .iter()
+            .map(|e| {
+                (
+                    e.line,
+                    e.result.as_ref().map(|_| *e.input.fragment()).map_err(
+                        |err| match err {
+                            Error::Parsing(_) => "parsing".to_owned(),
+                            Error::MissingColumn(_, c) => {
+                                format!("column {}", c)
+                            }
+                            Error::EscapedQuote(_) => "quote".to_owned(),
+                        },
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            parse(
+                "# comment\r\n\
+                 I have {int} cucumbers\r\n\
+                 \n\
+                 ^I eat (\\d+)$\n\
+                 {unfinished\n\
+                 (opt)/(opt)",
+                Layout::Lines,
+            ),
+            [
+                (2, Ok("I have {int} cucumbers")),
+                (4, Ok("^I eat (\\d+)$")),
+                (5, Err("parsing".to_owned())),
+                (6, Err("parsing".to_owned())),
+            ],
+        );
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            parse(
+                "name,step\n\
+                 eat,\"I eat {int}, then (some) more\"\n\
+                 no-step\n\
+                 have,I have {int} cucumbers,extra\n\
+                 quoted,\"say \"\"{word}\"\"\"\n\
+                 broken,{int",
+                Layout::Csv {
+                    column: 1,
+                    header: true,
+                },
+            ),
+            [
+                (2, Ok("I eat {int}, then (some) more")),
+                (3, Err("column 1".to_owned())),
+                (4, Ok("I have {int} cucumbers")),
+                (5, Err("quote".to_owned())),
+                (6, Err("parsing".to_owned())),
+            ],
+        );
+    }
+
+    #[test]
+    fn csv_escaped_quote() {
+        let catalog = Catalog::parse(
+            "\"say \"\"hi\"\" {int}\",\"say \"\"bye\"\"\"\n\
+             \"say {int}\",\"\"\"\"",
+            Layout::Csv {
+                column: 0,
+                header: false,
+            },
+        );
+
+        let errors = catalog.errors().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            (1, Error::EscapedQuote(field)) => {
+                assert_eq!(*field.fragment(), "say \"\"hi\"\" {int}");
+                assert_eq!(field.location_offset(), 1);
+            }
+            e => panic!("wrong err: {:?}", e),
+        }
+        assert_eq!(*catalog[1].input.fragment(), "say {int}");
+        assert!(catalog[1].result.is_ok());
+        assert_eq!(
+            catalog[0].result.as_ref().unwrap_err().to_string(),
+            "say \"\"hi\"\" {int}\n\
+             Escaped quotes are not supported in step definitions.",
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert!(parse("", Layout::Lines).is_empty());
+        assert!(Catalog::parse("\n\n", Layout::Lines).is_valid());
+    }
+}
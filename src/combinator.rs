@@ -47,6 +47,7 @@ where
 ///    non-`escapable` `Input` or end of line.
 ///
 /// [`escaped()`]: nom::bytes::complete::escaped()
+#[allow(clippy::multiple_bound_locations)] // lints of newer toolchains
 pub(crate) fn escaped0<'a, Input: 'a, Error, F, G, O1, O2>(
     mut normal: F,
     control_char: char,
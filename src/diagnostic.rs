@@ -0,0 +1,150 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Diagnostic`]s reported while validating [Cucumber Expressions][0].
+//!
+//! [0]: https://github.com/cucumber/cucumber-expressions#readme
+
+use std::fmt::Display;
+
+use derive_more::{Display, From};
+
+use crate::{parse, Spanned};
+
+/// Problem found while validating a [Cucumber Expression][0].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Display, Eq, From, PartialEq)]
+pub enum Diagnostic<Input>
+where
+    Input: Display,
+{
+    /// Parsing error.
+    #[display(fmt = "{}", _0)]
+    Parsing(parse::Error<Input>),
+
+    /// Unknown [`Parameter`] is used.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[display(fmt = "Parameter '{}' not found.", _0)]
+    #[from(ignore)]
+    UnknownParameter(Input),
+
+    /// Unmatched reserved character treated as text in a lenient mode.
+    ///
+    /// See [`Expression::parse_lenient()`] for details.
+    ///
+    /// [`Expression::parse_lenient()`]: crate::Expression::parse_lenient()
+    #[display(
+        fmt = "{}\n\
+               The unmatched '{}' is treated as text.\n\
+               You can use '\\' to escape it.",
+        _0,
+        _0
+    )]
+    #[from(ignore)]
+    UnmatchedReservedCharacter(Input),
+
+    /// Anonymous [`Parameter`] following another one with nothing but
+    /// whitespaces or [`Optional`]s between them.
+    ///
+    /// See [`Expression::adjacent_anonymous_parameters()`][0] for details.
+    ///
+    /// [0]: crate::Expression::adjacent_anonymous_parameters()
+    /// [`Optional`]: crate::Optional
+    /// [`Parameter`]: crate::Parameter
+    #[display(
+        fmt = "Anonymous parameter follows another one with nothing but \
+               whitespaces or optionals between them, so the text captured by \
+               each of them is ambiguous."
+    )]
+    #[from(ignore)]
+    AdjacentAnonymousParameters(Input),
+}
+
+/// Severity of a [`Diagnostic`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    /// [`Diagnostic`] doesn't prevent the [Cucumber Expression][0] from being
+    /// used, but is worth fixing.
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    Warning,
+
+    /// [`Diagnostic`] makes the [Cucumber Expression][0] unusable.
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    Error,
+}
+
+impl<Input: Display> Diagnostic<Input> {
+    /// Returns [`Severity`] of this [`Diagnostic`].
+    #[must_use]
+    pub const fn severity(&self) -> Severity {
+        match self {
+            Self::Parsing(_) | Self::UnknownParameter(_) => Severity::Error,
+            Self::UnmatchedReservedCharacter(_)
+            | Self::AdjacentAnonymousParameters(_) => Severity::Warning,
+        }
+    }
+
+    /// Returns the `Input` span this [`Diagnostic`] points to, if any.
+    #[must_use]
+    pub const fn span(&self) -> Option<&Input> {
+        use parse::Error as E;
+
+        match self {
+            Self::Parsing(
+                E::NestedParameter(i)
+                | E::OptionalInParameter(i)
+                | E::UnfinishedParameter(i)
+                | E::NestedOptional(i)
+                | E::ParameterInOptional(i)
+                | E::EmptyOptional(i)
+                | E::AlternationInOptional(i)
+                | E::UnfinishedOptional(i)
+                | E::EmptyAlternation(i)
+                | E::OnlyOptionalInAlternation(i)
+                | E::UnescapedReservedCharacter(i)
+                | E::EscapedNonReservedCharacter(i)
+                | E::EscapedEndOfLine(i)
+                | E::UnexpectedNewline(i)
+                | E::Other(i, _),
+            )
+            | Self::UnknownParameter(i)
+            | Self::UnmatchedReservedCharacter(i)
+            | Self::AdjacentAnonymousParameters(i) => Some(i),
+            Self::Parsing(E::Needed(_)) => None,
+        }
+    }
+}
+
+impl Diagnostic<Spanned<'_>> {
+    /// Returns 1-based line and column numbers in the original input this
+    /// [`Diagnostic`] points to, if any.
+    ///
+    /// Columns are counted in UTF-8 characters.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let diagnostics =
+    ///     Expression::validate("I have {int}\ncucumbers {in").unwrap_err();
+    ///
+    /// assert_eq!(diagnostics[0].line_column(), Some((2, 11)));
+    /// ```
+    #[must_use]
+    pub fn line_column(&self) -> Option<(u32, usize)> {
+        self.span()
+            .map(|span| (span.location_line(), span.get_utf8_column()))
+    }
+}
@@ -0,0 +1,499 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Event-based (pull) [Cucumber Expressions][0] parser, not allocating any
+//! [AST].
+//!
+//! [0]: https://github.com/cucumber/cucumber-expressions#readme
+//! [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+
+use std::{fmt::Display, iter};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{map, verify},
+    error::ErrorKind,
+    Err, IResult,
+};
+
+use crate::{
+    ast::{Alternative, Optional, OptionalParameter, Parameter, Span, Spanned},
+    parse::{self, Syntax},
+};
+
+/// Event emitted by the [`Events`] parser.
+///
+/// Every [`Event`] carries the `Input` span it's been emitted for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Event<Input> {
+    /// Raw text outside of any construct, or inside an [`Alternation`].
+    ///
+    /// [`Alternation`]: crate::Alternation
+    Text(Input),
+
+    /// Whitespaces outside of any construct.
+    Whitespaces(Input),
+
+    /// Opening `{` of a [`Parameter`].
+    ParameterStart(Input),
+
+    /// Raw name of a [`Parameter`].
+    ParameterName(Input),
+
+    /// Closing `}` of a [`Parameter`].
+    ParameterEnd(Input),
+
+    /// Opening `(` of an [`Optional`].
+    OptionalStart(Input),
+
+    /// Raw text of an [`Optional`].
+    OptionalText(Input),
+
+    /// Closing `)` of an [`Optional`].
+    OptionalEnd(Input),
+
+    /// Start of an [`Alternation`], carrying its whole span.
+    ///
+    /// [`Alternation`]: crate::Alternation
+    AlternationStart(Input),
+
+    /// `/` separating [`SingleAlternation`]s.
+    ///
+    /// [`SingleAlternation`]: crate::SingleAlternation
+    AlternationSeparator(Input),
+
+    /// End of an [`Alternation`], carrying an empty span right after it.
+    ///
+    /// [`Alternation`]: crate::Alternation
+    AlternationEnd(Input),
+}
+
+/// [`Iterator`] of [`Event`]s produced while parsing a
+/// [Cucumber Expression][0] in a single linear scan.
+///
+/// Doesn't allocate: consumers that don't need the whole [`Expression`]
+/// (highlighters, counters, streaming validators) may use it instead of
+/// [`Expression::parse()`].
+///
+/// Stops after the first [`parse::Error`].
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::event::{Event, Events};
+/// #
+/// let params = Events::new("I have {int} cucumber(s) in my {word}")
+///     .filter(|ev| matches!(ev, Ok(Event::ParameterName(_))))
+///     .count();
+///
+/// assert_eq!(params, 2);
+/// ```
+///
+/// [`Expression`]: crate::Expression
+/// [`Expression::parse()`]: crate::Expression::parse()
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug)]
+pub struct Events<Input> {
+    /// [`Syntax`] to parse the `Input` with.
+    syntax: Syntax,
+
+    /// Remaining `Input` to be parsed.
+    rest: Input,
+
+    /// Remaining `Input` of the currently parsed [`Alternation`], if any.
+    ///
+    /// [`Alternation`]: crate::Alternation
+    alternation: Option<Input>,
+
+    /// [`Event`]s already parsed, but not emitted yet.
+    pending: [Option<Event<Input>>; 6],
+
+    /// Indicator whether parsing has finished or failed.
+    done: bool,
+}
+
+impl<'s> Events<Spanned<'s>> {
+    /// Creates new [`Events`] of the given `input`.
+    #[must_use]
+    pub fn new<I: AsRef<str> + ?Sized>(input: &'s I) -> Self {
+        Self::from_span(Spanned::new(input.as_ref()), Syntax::default())
+    }
+}
+
+impl<Input: Span> Events<Input> {
+    /// Creates new [`Events`] of the given [`Span`] and [`Syntax`].
+    #[must_use]
+    pub const fn from_span(span: Input, syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            rest: span,
+            alternation: None,
+            pending: [None, None, None, None, None, None],
+            done: false,
+        }
+    }
+
+    /// Emits [`Event`]s of the given [`Optional`] and returns the first one.
+    fn optional(
+        &mut self,
+        input: &Input,
+        opt: &Optional<Input>,
+    ) -> Event<Input> {
+        let len = opt.input_len();
+        self.pending = [
+            Some(Event::OptionalText(opt.0.clone())),
+            Some(Event::OptionalEnd(input.slice((len + 1)..).take(1))),
+            None,
+            None,
+            None,
+            None,
+        ];
+        Event::OptionalStart(input.take(1))
+    }
+
+    /// Emits [`Event`]s of the given [`OptionalParameter`] and returns the
+    /// first one.
+    ///
+    /// Its empty prefix and suffix texts aren't emitted.
+    fn optional_parameter(
+        &mut self,
+        input: &Input,
+        opt: &OptionalParameter<Input>,
+    ) -> Event<Input> {
+        let text = |t: &Input| {
+            (t.input_len() > 0).then(|| Event::OptionalText(t.clone()))
+        };
+        let start = opt.prefix.input_len() + 1;
+        let end = start + opt.parameter.input_len() + 1;
+        let len = end + opt.suffix.input_len() + 1;
+        self.pending = [
+            text(&opt.prefix),
+            Some(Event::ParameterStart(input.slice(start..).take(1))),
+            Some(Event::ParameterName(opt.parameter.0.clone())),
+            Some(Event::ParameterEnd(input.slice(end..).take(1))),
+            text(&opt.suffix),
+            Some(Event::OptionalEnd(input.slice(len..).take(1))),
+        ];
+        Event::OptionalStart(input.take(1))
+    }
+
+    /// Emits [`Event`]s of the given [`Parameter`] and returns the first one.
+    fn parameter(
+        &mut self,
+        input: &Input,
+        par: &Parameter<Input>,
+    ) -> Event<Input> {
+        let len = par.input_len();
+        self.pending = [
+            Some(Event::ParameterName(par.0.clone())),
+            Some(Event::ParameterEnd(input.slice((len + 1)..).take(1))),
+            None,
+            None,
+            None,
+            None,
+        ];
+        Event::ParameterStart(input.take(1))
+    }
+
+    /// Parses the next [`Event`] inside the current [`Alternation`].
+    ///
+    /// [`Alternation`]: crate::Alternation
+    fn next_in_alternation(
+        &mut self,
+        rest: Input,
+    ) -> Result<Event<Input>, parse::Error<Input>> {
+        if rest.input_len() == 0 {
+            self.alternation = None;
+            return Ok(Event::AlternationEnd(rest));
+        }
+        if let Ok((after, slash)) =
+            tag::<_, _, parse::Error<Input>>("/")(rest.clone())
+        {
+            self.alternation = Some(after);
+            return Ok(Event::AlternationSeparator(slash));
+        }
+
+        let (after, alt) = parse::alternative_with(self.syntax)(rest.clone())
+            .map_err(into_error)?;
+        self.alternation = Some(after);
+        Ok(match alt {
+            Alternative::Optional(opt) => self.optional(&rest, &opt),
+            Alternative::Text(t) => Event::Text(t),
+        })
+    }
+
+    /// Parses the next top-level [`Event`].
+    fn next_top_level(&mut self) -> Result<Event<Input>, parse::Error<Input>> {
+        let input = self.rest.clone();
+        let (rest, token) =
+            token(self.syntax)(input.clone()).map_err(|e| match e {
+                Err::Error(_) => {
+                    parse::Error::Other(input.clone(), ErrorKind::Verify)
+                }
+                Err::Failure(e) => e,
+                Err::Incomplete(n) => parse::Error::Needed(n),
+            })?;
+        self.rest = rest;
+
+        Ok(match token {
+            Token::Alternation => {
+                let span = input.take(input.offset(&self.rest));
+                self.alternation = Some(span.clone());
+                Event::AlternationStart(span)
+            }
+            Token::Optional(opt) => self.optional(&input, &opt),
+            Token::OptionalParameter(opt) => {
+                self.optional_parameter(&input, &opt)
+            }
+            Token::Parameter(par) => self.parameter(&input, &par),
+            Token::Text(t) => Event::Text(t),
+            Token::Whitespaces(w) => Event::Whitespaces(w),
+        })
+    }
+}
+
+impl<Input: Span> Iterator for Events<Input> {
+    type Item = Result<Event<Input>, parse::Error<Input>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(ev) = self.pending.iter_mut().find_map(Option::take) {
+            return Some(Ok(ev));
+        }
+        if self.done {
+            return None;
+        }
+
+        let res = if let Some(rest) = self.alternation.take() {
+            self.next_in_alternation(rest)
+        } else if self.rest.input_len() > 0 {
+            self.next_top_level()
+        } else {
+            self.done = true;
+            return None;
+        };
+        if res.is_err() {
+            self.done = true;
+        }
+        Some(res)
+    }
+}
+
+impl<Input: Span> iter::FusedIterator for Events<Input> {}
+
+/// Top-level construct of a [Cucumber Expression][0].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+enum Token<Input> {
+    /// [`Alternation`], whose span is to be re-scanned.
+    ///
+    /// [`Alternation`]: crate::Alternation
+    Alternation,
+
+    /// [`Optional`].
+    Optional(Optional<Input>),
+
+    /// [`OptionalParameter`].
+    OptionalParameter(OptionalParameter<Input>),
+
+    /// [`Parameter`].
+    Parameter(Parameter<Input>),
+
+    /// Text without whitespaces.
+    Text(Input),
+
+    /// Whitespaces.
+    Whitespaces(Input),
+}
+
+/// Parses a single top-level [`Token`], mirroring the
+/// [`parse::single_expression_with()`], but without allocating.
+fn token<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Token<Input>, parse::Error<Input>> {
+    alt((
+        map(
+            parse::optional_parameter_with(syntax),
+            Token::OptionalParameter,
+        ),
+        map(
+            parse::alternation_fold(syntax, || (), |(), _, _| ()),
+            |()| Token::Alternation,
+        ),
+        map(parse::optional_with(syntax), Token::Optional),
+        map(parse::parameter_with(syntax), Token::Parameter),
+        map(
+            verify(parse::text_without_whitespace(syntax), |s: &Input| {
+                s.input_len() > 0
+            }),
+            Token::Text,
+        ),
+        map(take_while1(|c| c == ' '), Token::Whitespaces),
+        parse::forbidden_newline(syntax),
+    ))
+}
+
+/// Converts the given [`Err`] into a [`parse::Error`].
+fn into_error<Input: Display>(
+    err: Err<parse::Error<Input>>,
+) -> parse::Error<Input> {
+    match err {
+        Err::Error(e) | Err::Failure(e) => e,
+        Err::Incomplete(n) => parse::Error::Needed(n),
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use crate::{parse::Syntax, Expression, Spanned};
+
+    use super::{Event, Events};
+
+    /// Collects textual representation of all the [`Event`]s of the given
+    /// `input`.
+    fn events(input: &str) -> Vec<String> {
+        events_with(input, Syntax::default())
+    }
+
+    /// Collects textual representation of all the [`Event`]s of the given
+    /// `input` parsed with the given [`Syntax`].
+    fn events_with(input: &str, syntax: Syntax) -> Vec<String> {
+        Events::from_span(Spanned::new(input), syntax)
+            .map(|ev| match ev {
+                Ok(Event::Text(i)) => format!("text:{}", i),
+                Ok(Event::Whitespaces(i)) => format!("ws:{}", i),
+                Ok(Event::ParameterStart(i)) => format!("par-start:{}", i),
+                Ok(Event::ParameterName(i)) => format!("par-name:{}", i),
+                Ok(Event::ParameterEnd(i)) => format!("par-end:{}", i),
+                Ok(Event::OptionalStart(i)) => format!("opt-start:{}", i),
+                Ok(Event::OptionalText(i)) => format!("opt-text:{}", i),
+                Ok(Event::OptionalEnd(i)) => format!("opt-end:{}", i),
+                Ok(Event::AlternationStart(i)) => format!("alt-start:{}", i),
+                Ok(Event::AlternationSeparator(i)) => format!("alt-sep:{}", i),
+                Ok(Event::AlternationEnd(i)) => {
+                    format!("alt-end:{}", i.location_offset())
+                }
+                Err(e) => format!("err:{}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn emits_all_constructs() {
+        assert_eq!(
+            events("I have {int} cucumber(s) in/on my belly(s)/stomach"),
+            [
+                "text:I",
+                "ws: ",
+                "text:have",
+                "ws: ",
+                "par-start:{",
+                "par-name:int",
+                "par-end:}",
+                "ws: ",
+                "text:cucumber",
+                "opt-start:(",
+                "opt-text:s",
+                "opt-end:)",
+                "ws: ",
+                "alt-start:in/on",
+                "text:in",
+                "alt-sep:/",
+                "text:on",
+                "alt-end:30",
+                "ws: ",
+                "text:my",
+                "ws: ",
+                "alt-start:belly(s)/stomach",
+                "text:belly",
+                "opt-start:(",
+                "opt-text:s",
+                "opt-end:)",
+                "alt-sep:/",
+                "text:stomach",
+                "alt-end:50",
+            ],
+        );
+    }
+
+    #[test]
+    fn emits_optional_parameters() {
+        let syntax = Syntax {
+            optional_parameters: true,
+            ..Syntax::default()
+        };
+
+        assert_eq!(
+            events_with("a( with {int} retries)({word}) (b)", syntax),
+            [
+                "text:a",
+                "opt-start:(",
+                "opt-text: with ",
+                "par-start:{",
+                "par-name:int",
+                "par-end:}",
+                "opt-text: retries",
+                "opt-end:)",
+                "opt-start:(",
+                "par-start:{",
+                "par-name:word",
+                "par-end:}",
+                "opt-end:)",
+                "ws: ",
+                "opt-start:(",
+                "opt-text:b",
+                "opt-end:)",
+            ],
+        );
+
+        let offsets = Events::from_span(Spanned::new("a( x{int}y)"), syntax)
+            .map(|ev| match ev.unwrap() {
+                Event::Text(i)
+                | Event::Whitespaces(i)
+                | Event::ParameterStart(i)
+                | Event::ParameterName(i)
+                | Event::ParameterEnd(i)
+                | Event::OptionalStart(i)
+                | Event::OptionalText(i)
+                | Event::OptionalEnd(i)
+                | Event::AlternationStart(i)
+                | Event::AlternationSeparator(i)
+                | Event::AlternationEnd(i) => i.location_offset(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, [0, 1, 2, 4, 5, 8, 9, 10]);
+    }
+
+    #[test]
+    fn emits_nothing_on_empty() {
+        assert!(events("").is_empty());
+    }
+
+    #[test]
+    fn stops_on_error() {
+        let evs = events("a {b c");
+
+        assert_eq!(evs.len(), 3);
+        assert_eq!(&evs[..2], ["text:a", "ws: "]);
+        assert!(evs[2].starts_with("err:"), "no error: {}", evs[2]);
+    }
+
+    #[test]
+    fn errors_same_as_expression() {
+        for input in ["a/", "(a)/(b)", "{a(b)}", "(a", "\\", "a\\b"] {
+            let expected = Expression::parse(input).unwrap_err();
+            let actual = Events::new(input)
+                .find_map(Result::err)
+                .unwrap_or_else(|| panic!("no error for: {}", input));
+
+            assert_eq!(actual, expected, "input: {}", input);
+        }
+    }
+}
@@ -0,0 +1,602 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Expansion into boxed [`Iterator`]s.
+
+use std::{fmt, iter, vec};
+
+use nom::{AsChar, InputIter};
+
+use crate::{
+    Alternation, Alternative, AnyExpression, Expression, Optional,
+    OptionalParameter, Parameter, SingleExpression,
+};
+
+use super::{
+    builtin_parameter, expression_prefix_iter, expression_suffix_iter,
+    group_names, Dialect, EscapeForRegex, Options, ParametersProvider,
+    SkipLast, UnknownParameterError, WithCustomParameters,
+};
+
+/// Boxed [`Iterator`] performing an expansion of a [Cucumber Expressions][0]
+/// [AST] element into a [`Regex`].
+///
+/// [`Regex`]: regex::Regex
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub type BoxedRegexCharIter<'a, Input> =
+    Box<dyn Iterator<Item = Result<char, UnknownParameterError<Input>>> + 'a>;
+
+/// Boxed expansion of a [`Parameter`] into a capturing group, named with the
+/// given name, if any.
+type BoxedParameterExpansion<'a, Input> = Box<
+    dyn FnMut(Parameter<Input>, Option<&str>) -> BoxedRegexCharIter<'a, Input>
+        + 'a,
+>;
+
+/// Expansion of a [Cucumber Expressions][0] [AST] element into a [`Regex`] by
+/// producing a [`BoxedRegexCharIter`].
+///
+/// Unlike [`IntoRegexCharIter`], every [AST] node is expanded into its own
+/// boxed [`Iterator`], and [`ParametersProvider`]s are type-erased. This costs
+/// a heap allocation per node, but avoids deeply nested [`Iterator`] types
+/// being monomorphized for every `Input` and [`ParametersProvider`] pair.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::IntoBoxedRegexCharIter as _, Expression,
+/// # };
+/// #
+/// let expr = Expression::parse("I have {int} cucumber(s)").unwrap();
+/// let re = expr
+///     .into_regex_char_iter_boxed()
+///     .collect::<Result<String, _>>()
+///     .unwrap();
+///
+/// assert_eq!(re, "^I have ((?:-?\\d+)|(?:\\d+)) cucumber(?:s)?$");
+/// ```
+///
+/// [`IntoRegexCharIter`]: super::IntoRegexCharIter
+/// [`Regex`]: regex::Regex
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub trait IntoBoxedRegexCharIter<Input: fmt::Display> {
+    /// Consumes this [AST] element returning a [`BoxedRegexCharIter`] over
+    /// [`char`]s transformable into a [`Regex`].
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_char_iter_boxed<'a>(self) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: Sized + 'a,
+    {
+        self.into_regex_char_iter_boxed_with(Options::default())
+    }
+
+    /// Consumes this [AST] element returning a [`BoxedRegexCharIter`] over
+    /// [`char`]s transformable into a [`Regex`], expanded with the given
+    /// [`Options`].
+    ///
+    /// [`Options`] related to a whole [`Expression`] (like anchors) are ignored
+    /// when expanding its inner [AST] nodes.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a;
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for Expression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        expression(
+            self,
+            Box::new(move |p, name| builtin_parameter_named(p, name, options)),
+            options,
+        )
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for AnyExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        match self {
+            Self::Cucumber(expr) => {
+                expr.into_regex_char_iter_boxed_with(options)
+            }
+            Self::Regular(re) => regular_expression(&re, options),
+        }
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for SingleExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        match self {
+            Self::Alternation(alt) => {
+                alt.into_regex_char_iter_boxed_with(options)
+            }
+            Self::Optional(opt) => opt.into_regex_char_iter_boxed_with(options),
+            Self::OptionalParameter(OptionalParameter {
+                prefix,
+                parameter,
+                suffix,
+            }) => {
+                let par = parameter.into_regex_char_iter_boxed_with(options);
+                optional_parameter(&prefix, par, &suffix, options)
+            }
+            Self::Parameter(p) => p.into_regex_char_iter_boxed_with(options),
+            Self::Whitespaces(t) => options.whitespaces().map_or_else(
+                || escaped(&t, options.dialect),
+                |re| Box::new(re.chars().map(Ok)),
+            ),
+            Self::Text(t) => escaped(&t, options.dialect),
+        }
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for Alternation<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        let alternatives = self.0.into_iter().flat_map(move |alt| {
+            alt.into_iter()
+                .flat_map(move |a| a.into_regex_char_iter_boxed_with(options))
+                .chain(iter::once(Ok('|')))
+        });
+
+        Box::new(
+            options
+                .alternation_start()
+                .chars()
+                .map(Ok)
+                .chain(SkipLast::new(alternatives))
+                .chain(iter::once(Ok(')'))),
+        )
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for Alternative<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        match self {
+            Self::Optional(opt) => opt.into_regex_char_iter_boxed_with(options),
+            Self::Text(text) => escaped(&text, options.dialect),
+        }
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for Optional<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        Box::new(
+            "(?:"
+                .chars()
+                .chain(EscapeForRegex::for_dialect(
+                    self.0.iter_elements().map(AsChar::as_char),
+                    options.dialect,
+                ))
+                .chain(options.optional_end().chars())
+                .map(Ok),
+        )
+    }
+}
+
+impl<Input> IntoBoxedRegexCharIter<Input> for Parameter<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        builtin_parameter_named(self, None, options)
+    }
+}
+
+impl<Input, Pars> IntoBoxedRegexCharIter<Input>
+    for WithCustomParameters<Expression<Input>, Pars>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: ParametersProvider<Input>,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        let parameters = self.parameters;
+        expression(
+            self.element,
+            Box::new(move |p, name| {
+                custom_parameter(p, &parameters, name, options)
+            }),
+            options,
+        )
+    }
+}
+
+impl<Input, Pars> IntoBoxedRegexCharIter<Input>
+    for WithCustomParameters<AnyExpression<Input>, Pars>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: ParametersProvider<Input>,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        match self.element {
+            AnyExpression::Cucumber(element) => WithCustomParameters {
+                element,
+                parameters: self.parameters,
+            }
+            .into_regex_char_iter_boxed_with(options),
+            AnyExpression::Regular(re) => regular_expression(&re, options),
+        }
+    }
+}
+
+impl<Input, Pars> IntoBoxedRegexCharIter<Input>
+    for WithCustomParameters<Parameter<Input>, Pars>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: ParametersProvider<Input>,
+{
+    fn into_regex_char_iter_boxed_with<'a>(
+        self,
+        options: Options,
+    ) -> BoxedRegexCharIter<'a, Input>
+    where
+        Self: 'a,
+    {
+        options.debug_assert_iterable();
+
+        custom_parameter(self.element, &self.parameters, None, options)
+    }
+}
+
+/// Expands the given [`Expression`] into a [`BoxedRegexCharIter`] with the
+/// given [`Options`], using the provided `parameter` expansion for its
+/// [`Parameter`]s.
+///
+/// Doesn't depend on a [`ParametersProvider`] type, so is monomorphized only
+/// once per `Input`.
+fn expression<'a, Input>(
+    expr: Expression<Input>,
+    mut parameter: BoxedParameterExpansion<'a, Input>,
+    options: Options,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: Clone + fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+{
+    let mut names = group_names(&expr, options).into_iter();
+
+    Box::new(
+        expression_prefix_iter(options)
+            .chain(expr.0.into_iter().flat_map(move |e| match e {
+                SingleExpression::Parameter(p) => {
+                    parameter(p, names.next().as_deref())
+                }
+                SingleExpression::OptionalParameter(OptionalParameter {
+                    prefix,
+                    parameter: p,
+                    suffix,
+                }) => {
+                    let par = parameter(p, names.next().as_deref());
+                    optional_parameter(&prefix, par, &suffix, options)
+                }
+                e @ (SingleExpression::Alternation(_)
+                | SingleExpression::Optional(_)
+                | SingleExpression::Text(_)
+                | SingleExpression::Whitespaces(_)) => {
+                    e.into_regex_char_iter_boxed_with(options)
+                }
+            }))
+            .chain(expression_suffix_iter(options)),
+    )
+}
+
+/// Expands an [`OptionalParameter`] with the given `prefix` and `suffix` into
+/// a [`BoxedRegexCharIter`], wrapping its already expanded [`Parameter`].
+fn optional_parameter<'a, Input>(
+    prefix: &Input,
+    parameter: BoxedRegexCharIter<'a, Input>,
+    suffix: &Input,
+    options: Options,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+{
+    Box::new(
+        "(?:"
+            .chars()
+            .map(Ok)
+            .chain(escaped(prefix, options.dialect))
+            .chain(parameter)
+            .chain(escaped(suffix, options.dialect))
+            .chain(options.optional_end().chars().map(Ok)),
+    )
+}
+
+/// Expands the given [`Parameter`] into a [`BoxedRegexCharIter`] as a capturing
+/// group with the given [`Options`], named with the given `name`, if any,
+/// considering the custom `parameters` in addition to [default ones][1].
+///
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+fn custom_parameter<'a, Input, Pars>(
+    parameter: Parameter<Input>,
+    parameters: &Pars,
+    name: Option<&str>,
+    options: Options,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+    Pars: ParametersProvider<Input> + 'a,
+{
+    parameters.get(&parameter).map_or_else(
+        || -> BoxedRegexCharIter<'a, Input> {
+            if builtin_parameter(&parameter.0, options).is_some() {
+                builtin_parameter_named(parameter, name, options)
+            } else {
+                Box::new(iter::once(Err(UnknownParameterError::new(
+                    parameter.0,
+                    parameters.names(),
+                ))))
+            }
+        },
+        |v| -> BoxedRegexCharIter<'a, Input> {
+            Box::new(
+                group_start(name, options.dialect)
+                    .chain(v.iter_elements().map(AsChar::as_char))
+                    .chain(iter::once(')'))
+                    .map(Ok),
+            )
+        },
+    )
+}
+
+/// Expands the given built-in [`Parameter`] into a [`BoxedRegexCharIter`] as a
+/// capturing group with the given [`Options`], named with the given `name`, if
+/// any.
+fn builtin_parameter_named<'a, Input>(
+    parameter: Parameter<Input>,
+    name: Option<&str>,
+    options: Options,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+{
+    builtin_parameter(&parameter.0, options).map_or_else(
+        || -> BoxedRegexCharIter<'a, Input> {
+            Box::new(iter::once(Err(UnknownParameterError::new(
+                parameter.0,
+                Vec::new(),
+            ))))
+        },
+        |re| {
+            // All the built-in `Parameter`s are capturing groups already.
+            let re = re.strip_prefix('(').unwrap_or(re);
+            Box::new(
+                group_start(name, options.dialect).chain(re.chars()).map(Ok),
+            )
+        },
+    )
+}
+
+/// Returns a start of a capturing group of the given [`Dialect`], named with
+/// the given `name`, if any.
+fn group_start(name: Option<&str>, dialect: Dialect) -> vec::IntoIter<char> {
+    name.map_or_else(
+        || "(".to_owned(),
+        |n| format!("{}{}>", dialect.named_group_start(), n),
+    )
+    .chars()
+    .collect::<Vec<_>>()
+    .into_iter()
+}
+
+/// Expands the given verbatim regular expression `re` into a
+/// [`BoxedRegexCharIter`], wrapping it according to the given [`Options`].
+fn regular_expression<'a, Input>(
+    re: &Input,
+    options: Options,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+{
+    Box::new(
+        options
+            .prefix()
+            .chars()
+            .chain("(?:".chars())
+            .chain(re.iter_elements().map(AsChar::as_char))
+            .chain(")".chars())
+            .chain(options.suffix().chars())
+            .map(Ok),
+    )
+}
+
+/// Expands the given `text` into a [`BoxedRegexCharIter`], escaping it with
+/// [`EscapeForRegex`] for the given [`Dialect`].
+fn escaped<'a, Input>(
+    text: &Input,
+    dialect: Dialect,
+) -> BoxedRegexCharIter<'a, Input>
+where
+    Input: fmt::Display + InputIter + 'a,
+    <Input as InputIter>::Item: AsChar,
+{
+    Box::new(
+        EscapeForRegex::for_dialect(
+            text.iter_elements().map(AsChar::as_char),
+            dialect,
+        )
+        .map(Ok),
+    )
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use crate::{
+        expand::{IntoRegexCharIter as _, WithCustomParameters},
+        AnyExpression, Expression,
+    };
+
+    use super::IntoBoxedRegexCharIter as _;
+
+    #[test]
+    fn same_as_unboxed() {
+        for input in [
+            "",
+            "a/b(c) {int} {float}",
+            "(a) b/c/d\\/ {} {word}",
+            "\\(a\\) [b]. ^$ \\\\ \\{",
+            "/^a(b)$/",
+        ] {
+            let expr = AnyExpression::parse(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(
+                expr.clone()
+                    .into_regex_char_iter_boxed()
+                    .collect::<Result<String, _>>()
+                    .unwrap_or_else(|e| panic!("failed: {}", e)),
+                expr.into_regex_string()
+                    .unwrap_or_else(|e| panic!("failed: {}", e)),
+                "on input: {}",
+                input,
+            );
+        }
+    }
+
+    #[test]
+    fn custom_parameters() {
+        let pars = HashMap::from([("custom", "custom"), ("int", "\\d")]);
+        let expr = Expression::parse("{custom} a/b {int} {word}").unwrap();
+
+        let re = WithCustomParameters {
+            element: expr,
+            parameters: &pars,
+        }
+        .into_regex_char_iter_boxed()
+        .collect::<Result<String, _>>()
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(re, "^(custom) (?:a|b) (\\d) ([^\\s]+)$");
+    }
+
+    #[test]
+    fn unknown_parameter() {
+        let err = Expression::parse("a {custom}")
+            .unwrap()
+            .into_regex_char_iter_boxed()
+            .collect::<Result<String, _>>()
+            .unwrap_err();
+
+        assert_eq!(*err.not_found, "custom");
+    }
+}
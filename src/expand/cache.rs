@@ -0,0 +1,264 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Thread-local [LRU] cache of [`Regex`]es expanded from [`Expression`]s.
+//!
+//! Disabled by default. Once enabled for the current thread with
+//! [`set_capacity()`],
+//! [`Expression::regex()`], [`Expression::regex_with_options()`],
+//! [`Expression::regex_with_parameters()`] and
+//! [`Expression::regex_with_parameters_and_options()`] return a cached
+//! [`Regex`] for the same `input` and [`Options`], skipping both parsing and
+//! [`Regex`] compilation.
+//!
+//! Custom parameters are considered only if their
+//! [`ParametersProvider::fingerprint()`] is [`Some`], otherwise the [`Regex`]
+//! is neither cached, nor looked up.
+//!
+//! Every thread has its own cache, which should be enabled separately.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use cucumber_expressions::{expand::cache, Expression};
+//! #
+//! cache::set_capacity(100);
+//!
+//! let re = Expression::regex("I have {int} cucumbers").unwrap();
+//! let cached = Expression::regex("I have {int} cucumbers").unwrap();
+//!
+//! assert_eq!(re.as_str(), cached.as_str());
+//! assert!(cache::len() > 0);
+//!
+//! cache::set_capacity(0);
+//! assert_eq!(cache::len(), 0);
+//! ```
+//!
+//! [LRU]: https://en.wikipedia.org/wiki/Cache_replacement_policies#LRU
+
+use std::{cell::RefCell, collections::HashMap};
+
+use regex::Regex;
+
+#[cfg(doc)]
+use crate::{expand::ParametersProvider, Expression};
+
+use super::Options;
+
+thread_local! {
+    /// [`Cache`] of the current thread, if enabled.
+    static CACHE: RefCell<Option<Cache>> = RefCell::new(None);
+}
+
+/// Sets the maximum number of [`Regex`]es kept in the cache of the current
+/// thread, evicting the least recently used ones, if there are more.
+///
+/// Zero capacity disables the cache and clears it.
+pub fn set_capacity(capacity: usize) {
+    with_cache(|cache| {
+        if capacity == 0 {
+            *cache = None;
+        } else {
+            let cache = cache.get_or_insert_with(Cache::default);
+            cache.capacity = capacity;
+            cache.shrink();
+        }
+    });
+}
+
+/// Returns the maximum number of [`Regex`]es kept in the cache of the current
+/// thread.
+///
+/// Zero means the cache is disabled.
+#[must_use]
+pub fn capacity() -> usize {
+    with_cache(|cache| cache.as_ref().map_or(0, |c| c.capacity))
+}
+
+/// Returns the number of [`Regex`]es currently kept in the cache of the
+/// current thread.
+#[must_use]
+pub fn len() -> usize {
+    with_cache(|cache| cache.as_ref().map_or(0, |c| c.entries.len()))
+}
+
+/// Removes all the [`Regex`]es from the cache of the current thread, keeping
+/// it enabled.
+pub fn clear() {
+    with_cache(|cache| {
+        if let Some(c) = cache {
+            c.entries.clear();
+        }
+    });
+}
+
+/// Key of a [`Regex`] in the [`Cache`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(super) struct Key {
+    /// Source of the expanded [`Expression`].
+    pub(super) input: String,
+
+    /// [`Options`] the [`Expression`] is expanded with.
+    pub(super) options: Options,
+
+    /// [`ParametersProvider::fingerprint()`] of the custom parameters the
+    /// [`Expression`] is expanded with, if any.
+    pub(super) parameters: Option<u64>,
+}
+
+/// Returns the [`Regex`] cached for the given [`Key`], or caches the one
+/// returned by the given `compile` function.
+///
+/// Neither looks up, nor caches anything, if the [`Key`] is [`None`].
+///
+/// # Errors
+///
+/// If the `compile` function errors.
+pub(super) fn get_or_compile<E>(
+    key: Option<Key>,
+    compile: impl FnOnce() -> Result<Regex, E>,
+) -> Result<Regex, E> {
+    let key = match key {
+        Some(k) => k,
+        None => return compile(),
+    };
+    if let Some(re) = with_cache(|c| c.as_mut().and_then(|c| c.get(&key))) {
+        return Ok(re);
+    }
+
+    let re = compile()?;
+    with_cache(|cache| {
+        if let Some(c) = cache {
+            c.insert(key, re.clone());
+        }
+    });
+    Ok(re)
+}
+
+/// Calls the given function with the [`Cache`] of the current thread.
+fn with_cache<R>(f: impl FnOnce(&mut Option<Cache>) -> R) -> R {
+    CACHE.with(|cache| f(&mut cache.borrow_mut()))
+}
+
+/// [LRU] cache of [`Regex`]es.
+///
+/// [LRU]: https://en.wikipedia.org/wiki/Cache_replacement_policies#LRU
+#[derive(Debug, Default)]
+struct Cache {
+    /// Maximum number of cached [`Regex`]es.
+    capacity: usize,
+
+    /// Cached [`Regex`]es along with the [`Cache::tick`] of their last use.
+    entries: HashMap<Key, (Regex, u64)>,
+
+    /// Counter of [`Cache`] uses, ordering its [`Cache::entries`] by recency.
+    tick: u64,
+}
+
+impl Cache {
+    /// Returns the [`Regex`] cached for the given [`Key`], if any, marking it
+    /// as the most recently used one.
+    fn get(&mut self, key: &Key) -> Option<Regex> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(re, used)| {
+            *used = tick;
+            re.clone()
+        })
+    }
+
+    /// Caches the given [`Regex`] for the given [`Key`], evicting the least
+    /// recently used [`Regex`], if the [`Cache::capacity`] is exceeded.
+    fn insert(&mut self, key: Key, re: Regex) {
+        self.tick += 1;
+        drop(self.entries.insert(key, (re, self.tick)));
+        self.shrink();
+    }
+
+    /// Evicts the least recently used [`Regex`]es until the
+    /// [`Cache::capacity`] is satisfied.
+    fn shrink(&mut self) {
+        while self.entries.len() > self.capacity {
+            let lru = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone());
+            if let Some(k) = lru {
+                drop(self.entries.remove(&k));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use regex::Regex;
+
+    use super::{Cache, Key, Options};
+
+    fn key(input: &str) -> Key {
+        Key {
+            input: input.into(),
+            options: Options::default(),
+            parameters: None,
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = Cache {
+            capacity: 2,
+            ..Cache::default()
+        };
+        cache.insert(key("a"), Regex::new("a+").unwrap());
+        cache.insert(key("b"), Regex::new("b+").unwrap());
+        assert!(cache.get(&key("a")).is_some());
+
+        cache.insert(key("c"), Regex::new("c+").unwrap());
+
+        assert_eq!(cache.entries.len(), 2);
+        assert!(cache.get(&key("b")).is_none());
+        assert_eq!(cache.get(&key("a")).unwrap().as_str(), "a+");
+        assert_eq!(cache.get(&key("c")).unwrap().as_str(), "c+");
+
+        cache.capacity = 1;
+        cache.shrink();
+
+        assert_eq!(cache.entries.len(), 1);
+        assert!(cache.get(&key("c")).is_some());
+    }
+
+    #[test]
+    fn distinguishes_options_and_parameters() {
+        let mut cache = Cache {
+            capacity: 10,
+            ..Cache::default()
+        };
+        cache.insert(key("a"), Regex::new("a+").unwrap());
+
+        assert!(cache
+            .get(&Key {
+                options: Options {
+                    case_insensitive: true,
+                    ..Options::default()
+                },
+                ..key("a")
+            })
+            .is_none());
+        assert!(cache
+            .get(&Key {
+                parameters: Some(1),
+                ..key("a")
+            })
+            .is_none());
+        assert!(cache.get(&key("a")).is_some());
+    }
+}
@@ -0,0 +1,752 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`ExpressionCompiler`] builder compiling [`Expression`]s with the configured
+//! custom parameters and [`Options`].
+
+use std::{collections::HashMap, ops::Range};
+
+use regex::{Captures, Regex};
+
+use crate::{Expression, Spanned};
+
+use super::{
+    parameters::capture_groups_parents, Dialect, Error, ExtractError,
+    FromArguments, Options, ParameterCaptures, ParameterRef,
+    ParametersProvider,
+};
+
+/// Builder of [`Regex`]es compiled from [Cucumber Expressions][0], holding
+/// custom `Parameters` and [`Options`] to compile them with.
+///
+/// Configured once, may be used to [compile][1] any number of
+/// [`Expression`]s, without re-plumbing the same `Parameters` and [`Options`]
+/// through every call. Without custom `Parameters`, only the
+/// [default ones][2] are considered.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// #
+/// # use cucumber_expressions::expand::ExpressionCompiler;
+/// #
+/// let parameters = HashMap::from([("color", "red|blue")]);
+/// let compiler = ExpressionCompiler::new()
+///     .parameters(&parameters)
+///     .case_insensitive(true)
+///     .named_groups(true);
+///
+/// let compiled = compiler.compile("{color} {int} cucumber(s)").unwrap();
+/// let caps = compiled.regex.captures("RED 2 Cucumbers").unwrap();
+///
+/// assert_eq!(&caps["color"], "RED");
+/// assert_eq!(&caps["int"], "2");
+/// assert_eq!(compiled.parameters[1].name, "int");
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [1]: ExpressionCompiler::compile()
+/// [2]: https://github.com/cucumber/cucumber-expressions#parameter-types
+#[derive(Clone, Copy, Debug)]
+pub struct ExpressionCompiler<
+    Parameters = &'static HashMap<&'static str, &'static str>,
+> {
+    /// Custom `Parameters` (in addition to [default ones][1]) to compile
+    /// [`Expression`]s with, if any.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    parameters: Option<Parameters>,
+
+    /// [`Options`] to compile [`Expression`]s with.
+    options: Options,
+
+    /// Indicator whether the thread-local [`cache`] should be used.
+    ///
+    /// [`cache`]: super::cache
+    cached: bool,
+}
+
+impl Default for ExpressionCompiler {
+    fn default() -> Self {
+        Self {
+            parameters: None,
+            options: Options::default(),
+            cached: true,
+        }
+    }
+}
+
+impl ExpressionCompiler {
+    /// Creates a new [`ExpressionCompiler`] without custom `Parameters`, with
+    /// the default [`Options`] and the [`cache`] used.
+    ///
+    /// [`cache`]: super::cache
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Parameters> ExpressionCompiler<Parameters> {
+    /// Sets custom `parameters` (in addition to [default ones][1]) to compile
+    /// [`Expression`]s with, replacing the previously set ones.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub fn parameters<P>(self, parameters: P) -> ExpressionCompiler<P> {
+        ExpressionCompiler {
+            parameters: Some(parameters),
+            options: self.options,
+            cached: self.cached,
+        }
+    }
+
+    /// Sets all the [`Options`] to compile [`Expression`]s with at once.
+    #[must_use]
+    pub const fn options(mut self, options: Options) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets [`Options::anchor_start`] and [`Options::anchor_end`].
+    #[must_use]
+    pub const fn anchored(mut self, anchored: bool) -> Self {
+        self.options.anchor_start = anchored;
+        self.options.anchor_end = anchored;
+        self
+    }
+
+    /// Sets [`Options::case_insensitive`].
+    #[must_use]
+    pub const fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.options.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Sets [`Options::dialect`].
+    ///
+    /// Note, that [`Regex`] compiles [`Dialect::Rust`] patterns only, so other
+    /// [`Dialect`]s make sense as long as they're compatible with it.
+    #[must_use]
+    pub const fn dialect(mut self, dialect: Dialect) -> Self {
+        self.options.dialect = dialect;
+        self
+    }
+
+    /// Sets [`Options::named_groups`].
+    #[must_use]
+    pub const fn named_groups(mut self, named_groups: bool) -> Self {
+        self.options.named_groups = named_groups;
+        self
+    }
+
+    /// Sets whether the thread-local [`cache`] should be used (if enabled) for
+    /// compiled [`Regex`]es.
+    ///
+    /// [`cache`]: super::cache
+    #[must_use]
+    pub const fn cached(mut self, cached: bool) -> Self {
+        self.cached = cached;
+        self
+    }
+
+    /// Returns the [`Options`] [`Expression`]s are compiled with.
+    #[must_use]
+    pub const fn get_options(&self) -> Options {
+        self.options
+    }
+
+    /// Parses the given `input` as an [`Expression`], and compiles it into a
+    /// [`CompiledExpression`] with the configured custom `Parameters` and
+    /// [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    pub fn compile<'s>(
+        &self,
+        input: &'s str,
+    ) -> Result<CompiledExpression<'s>, Error<Spanned<'s>>>
+    where
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        self.parameters.as_ref().map_or_else(
+            || self.compile_with(input, &&HashMap::<&str, &str>::new()),
+            |pars| self.compile_with(input, pars),
+        )
+    }
+
+    /// Compiles the given `input` into a [`CompiledExpression`] with the given
+    /// `parameters`.
+    fn compile_with<'s, P>(
+        &self,
+        input: &'s str,
+        parameters: &P,
+    ) -> Result<CompiledExpression<'s>, Error<Spanned<'s>>>
+    where
+        P: ParametersProvider<Spanned<'s>>,
+    {
+        let regex = Expression::regex_with_borrowed_parameters(
+            input,
+            parameters,
+            self.options,
+            self.cached,
+        )?;
+        let expression =
+            Expression::parse_with_syntax(input, self.options.syntax())?;
+        let parameters = expression
+            .capture_map_with_borrowed_parameters(parameters, self.options)?;
+        Ok(CompiledExpression {
+            expression,
+            regex,
+            parameters,
+            defaults: HashMap::new(),
+        })
+    }
+}
+
+/// [`Expression`] compiled by an [`ExpressionCompiler`].
+#[derive(Clone, Debug)]
+pub struct CompiledExpression<'s> {
+    /// Parsed [`Expression`].
+    pub expression: Expression<Spanned<'s>>,
+
+    /// [`Regex`] the [`Expression`] is expanded and compiled into.
+    pub regex: Regex,
+
+    /// [`ParameterRef`]s describing which [`Parameter`] owns each capturing
+    /// group of the [`Regex`].
+    ///
+    /// See [`Expression::capture_map()`] for more details.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub parameters: Vec<ParameterRef<'s>>,
+
+    /// Default texts of the [`Parameter`]s by their indices, substituted
+    /// instead of their missing matches.
+    ///
+    /// See [`CompiledExpression::with_default()`] for more details.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub defaults: HashMap<usize, String>,
+}
+
+impl<'s> CompiledExpression<'s> {
+    /// Sets the default text of the [`Parameter`] with the given `index`
+    /// (starting from `0` for the first [`Parameter`] of the [`Expression`]),
+    /// substituted instead of its missing match, so a [`Parameter`] of an
+    /// [`OptionalParameter`] (like `( with {int} retries)`) still has a value
+    /// once its [`OptionalParameter`] doesn't match.
+    ///
+    /// The default text is set for this very occurrence of the [`Parameter`]
+    /// only, so other [`Parameter`]s of the same [`ParameterType`] aren't
+    /// affected. It's substituted by [`CompiledExpression::captures()`] (as
+    /// the whole match only, without any nested capturing groups) and by
+    /// [`CompiledExpression::extract()`], while
+    /// [`CompiledExpression::match_against()`] reports the missing match as
+    /// is.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::expand::{
+    /// #     ExpressionCompiler, Options, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let registry = ParameterTypeRegistry::new();
+    /// let compiled = ExpressionCompiler::new()
+    ///     .parameters(&registry)
+    ///     .options(Options {
+    ///         optional_parameters: true,
+    ///         ..Options::default()
+    ///     })
+    ///     .compile("I connect( with {int} retries)( in {int} seconds)")
+    ///     .unwrap()
+    ///     .with_default(0, "3");
+    ///
+    /// let captures = compiled.captures("I connect with 5 retries").unwrap();
+    /// let retries = registry.transform(&captures[0]).unwrap();
+    /// assert_eq!(retries.downcast_ref::<i32>(), Some(&5));
+    ///
+    /// let captures = compiled.captures("I connect").unwrap();
+    /// let retries = registry.transform(&captures[0]).unwrap();
+    /// assert_eq!(retries.downcast_ref::<i32>(), Some(&3));
+    /// assert_eq!(captures[1].groups, [None]);
+    ///
+    /// let args = compiled.extract::<(i32, i32)>("I connect in 9 seconds");
+    /// assert_eq!(args.unwrap(), (3, 9));
+    /// assert!(compiled.extract::<(i32, i32)>("I connect").is_err());
+    /// # }
+    /// ```
+    ///
+    /// [`OptionalParameter`]: crate::OptionalParameter
+    /// [`Parameter`]: crate::Parameter
+    /// [`ParameterType`]: super::ParameterType
+    #[must_use]
+    pub fn with_default(
+        mut self,
+        index: usize,
+        default: impl Into<String>,
+    ) -> Self {
+        drop(self.defaults.insert(index, default.into()));
+        self
+    }
+
+    /// Matches the given `text` against the [`Regex`] of this
+    /// [`CompiledExpression`], returning [`ParameterCaptures`] of each of its
+    /// [`Parameter`]s in order, if it matches.
+    ///
+    /// [`Parameter`]s with nested capturing groups (like the ones of
+    /// [grouped][1] [`ParameterType`]s) capture all of them. Missing matches
+    /// of the [`Parameter`]s having a [default text][2] capture it instead.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::expand::ExpressionCompiler;
+    /// #
+    /// let pars = HashMap::from([("pair", "(\\d+),(\\d+)")]);
+    /// let compiled = ExpressionCompiler::new()
+    ///     .parameters(&pars)
+    ///     .compile("{word} has {pair}")
+    ///     .unwrap();
+    ///
+    /// let captures = compiled.captures("Bob has 1,2").unwrap();
+    /// assert_eq!(captures.len(), 2);
+    /// assert_eq!(captures[0].parameter.name, "word");
+    /// assert_eq!(captures[0].groups, [Some("Bob")]);
+    /// assert_eq!(captures[1].groups, [Some("1,2"), Some("1"), Some("2")]);
+    ///
+    /// assert!(compiled.captures("Bob has").is_none());
+    /// ```
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [`ParameterType`]: super::ParameterType
+    /// [1]: super::ParameterType::grouped()
+    /// [2]: CompiledExpression::with_default()
+    #[must_use]
+    pub fn captures<'t>(
+        &'t self,
+        text: &'t str,
+    ) -> Option<Vec<ParameterCaptures<'s, 't>>> {
+        let caps = self.regex.captures(text)?;
+        let mut captures = Vec::<ParameterCaptures<'s, 't>>::new();
+        for (i, p) in self.parameters.iter().enumerate() {
+            let group = caps.get(i + 1).map(|m| m.as_str());
+            match captures.last_mut() {
+                Some(last) if last.parameter == *p => last.groups.push(group),
+                _ => captures.push(ParameterCaptures {
+                    parameter: p.clone(),
+                    groups: vec![group],
+                }),
+            }
+        }
+        for (i, c) in captures.iter_mut().enumerate() {
+            if let (Some(None), Some(default)) =
+                (c.groups.first(), self.defaults.get(&i))
+            {
+                c.groups = vec![Some(default.as_str())];
+            }
+        }
+        Some(captures)
+    }
+
+    /// Matches the given `text` against the [`Regex`] of this
+    /// [`CompiledExpression`], returning an [`Argument`] of each of its
+    /// [`Parameter`]s in order, if it matches, mirroring
+    /// `CucumberExpression.match()` of the [reference implementations][0].
+    ///
+    /// Unlike [`CompiledExpression::captures()`], the nested capturing groups
+    /// of a [`Parameter`] (like the ones of [grouped][1] [`ParameterType`]s)
+    /// form a tree of [`Group`]s, according to their nesting in the
+    /// [`Regex`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::expand::ExpressionCompiler;
+    /// #
+    /// let pars = HashMap::from([("range", "((\\d+)-(\\d+))|(\\d+)")]);
+    /// let compiled = ExpressionCompiler::new()
+    ///     .parameters(&pars)
+    ///     .compile("{word} takes {range} days")
+    ///     .unwrap();
+    ///
+    /// let args = compiled.match_against("Bob takes 3-5 days").unwrap();
+    /// assert_eq!(args[0].parameter.name, "word");
+    /// assert_eq!(args[0].value(), Some("Bob"));
+    /// assert_eq!(args[0].group.span, Some(0..3));
+    ///
+    /// let range = &args[1].group;
+    /// assert_eq!(range.value, Some("3-5"));
+    /// assert_eq!(range.children.len(), 2);
+    /// assert_eq!(range.children[0].children[1].value, Some("5"));
+    /// assert_eq!(range.children[1].value, None);
+    ///
+    /// assert!(compiled.match_against("Bob takes days").is_none());
+    /// ```
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [`ParameterType`]: super::ParameterType
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    /// [1]: super::ParameterType::grouped()
+    #[must_use]
+    pub fn match_against<'t>(
+        &self,
+        text: &'t str,
+    ) -> Option<Vec<Argument<'s, 't>>> {
+        let caps = self.regex.captures(text)?;
+        let parents = capture_groups_parents(self.regex.as_str());
+        Some(
+            self.parameters
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| parents.get(*i).map_or(true, Option::is_none))
+                .map(|(i, p)| Argument {
+                    parameter: p.clone(),
+                    group: Group::new(&caps, &parents, i),
+                })
+                .collect(),
+        )
+    }
+
+    /// Matches the given `text` against the [`Regex`] of this
+    /// [`CompiledExpression`], and extracts a tuple of [`FromStr`] values
+    /// from the texts matched by its [`Parameter`]s, in order.
+    ///
+    /// The texts matched by `{string}` [`Parameter`]s are [unquoted][1] before
+    /// being parsed. Missing matches of the [`Parameter`]s having a
+    /// [default text][2] are parsed from it instead.
+    ///
+    /// # Errors
+    ///
+    /// If the `text` doesn't match, the number of the tuple elements differs
+    /// from the number of the [`Parameter`]s, or any of the matched texts is
+    /// missing or fails to be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::ExpressionCompiler;
+    /// #
+    /// let compiled = ExpressionCompiler::new()
+    ///     .compile("{int} cucumbers weigh {float} in {word}")
+    ///     .unwrap();
+    ///
+    /// let (count, weight, unit) = compiled
+    ///     .extract::<(i32, f64, String)>("5 cucumbers weigh 1.5 in kg")
+    ///     .unwrap();
+    /// assert_eq!((count, weight, unit.as_str()), (5, 1.5, "kg"));
+    ///
+    /// let err = compiled
+    ///     .extract::<(u8, f64, String)>("500 cucumbers weigh 1.5 in kg")
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Argument 0 of parameter 'int' failed to parse '500': number too \
+    ///      large to fit in target type",
+    /// );
+    /// ```
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    /// [`Parameter`]: crate::Parameter
+    /// [1]: super::unquote_string()
+    /// [2]: CompiledExpression::with_default()
+    pub fn extract<T: FromArguments>(
+        &self,
+        text: &str,
+    ) -> Result<T, ExtractError> {
+        let mut arguments =
+            self.match_against(text).ok_or(ExtractError::NoMatch)?;
+        for (i, arg) in arguments.iter_mut().enumerate() {
+            if let (None, Some(default)) =
+                (arg.group.value, self.defaults.get(&i))
+            {
+                arg.group.value = Some(default.as_str());
+            }
+        }
+        T::from_arguments(&arguments)
+    }
+}
+
+/// Argument of a [`Parameter`] matched by a [`CompiledExpression`], returned
+/// by [`CompiledExpression::match_against()`].
+///
+/// Mirrors the `Argument` of the [reference implementations][0].
+///
+/// [`Parameter`]: crate::Parameter
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Argument<'s, 't> {
+    /// Reference to the matched [`Parameter`], whose name is the one of its
+    /// [`ParameterType`] (empty for the anonymous [`Parameter`] `{}`).
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [`ParameterType`]: super::ParameterType
+    pub parameter: ParameterRef<'s>,
+
+    /// Capturing [`Group`] around the matched [`Parameter`], along with the
+    /// nested ones.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub group: Group<'t>,
+}
+
+impl<'t> Argument<'_, 't> {
+    /// Returns the text matched by the [`Parameter`] of this [`Argument`], if
+    /// it participates in the match.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[must_use]
+    pub const fn value(&self) -> Option<&'t str> {
+        self.group.value
+    }
+}
+
+/// Capturing group of a [`Regex`] match, along with the nested ones.
+///
+/// Mirrors the `Group` of the [reference implementations][0].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Group<'t> {
+    /// Matched text, or [`None`] if this [`Group`] doesn't participate in the
+    /// match (like the one inside an unmatched [`Optional`]).
+    ///
+    /// [`Optional`]: crate::Optional
+    pub value: Option<&'t str>,
+
+    /// Byte range of the matched text in the whole matched text, or [`None`]
+    /// if this [`Group`] doesn't participate in the match.
+    pub span: Option<Range<usize>>,
+
+    /// Capturing [`Group`]s nested directly into this one, in order.
+    pub children: Vec<Self>,
+}
+
+impl<'t> Group<'t> {
+    /// Builds a [`Group`] of the capturing group with the given `index`
+    /// (starting from `0` for the first capturing group), with the nested
+    /// ones according to the given `parents` of all the capturing groups.
+    fn new(
+        caps: &Captures<'t>,
+        parents: &[Option<usize>],
+        index: usize,
+    ) -> Self {
+        let m = caps.get(index + 1);
+        Self {
+            value: m.map(|m| m.as_str()),
+            span: m.map(|m| m.range()),
+            children: parents
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| **p == Some(index))
+                .map(|(i, _)| Self::new(caps, parents, i))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use crate::expand::{cache, Dialect, Error, Options};
+
+    use super::{ExpressionCompiler, Group};
+
+    #[test]
+    fn compiles_with_options() {
+        let pars = HashMap::from([("pair", "(\\d+),(\\d+)")]);
+        let compiler = ExpressionCompiler::new()
+            .parameters(&pars)
+            .anchored(false)
+            .case_insensitive(true)
+            .named_groups(true)
+            .dialect(Dialect::Rust);
+
+        let expr = compiler
+            .compile("{pair} Cucumber(s)")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(
+            expr.regex.as_str(),
+            "(?i)(?P<pair>(\\d+),(\\d+)) Cucumber(?:s)?",
+        );
+        assert_eq!(expr.expression.source().fragment(), &"{pair} Cucumber(s)",);
+        assert_eq!(
+            expr.parameters.iter().map(|p| p.name).collect::<Vec<_>>(),
+            ["pair"; 3],
+        );
+        assert!(expr.regex.is_match("x 1,2 cucumbers"));
+        assert_eq!(
+            compiler.get_options(),
+            Options {
+                anchor_start: false,
+                anchor_end: false,
+                case_insensitive: true,
+                named_groups: true,
+                ..Options::default()
+            },
+        );
+    }
+
+    #[test]
+    fn compiles_without_parameters() {
+        let compiled = ExpressionCompiler::new()
+            .compile("{int} cucumbers")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(
+            compiled.regex.as_str(),
+            "^((?:-?\\d+)|(?:\\d+)) cucumbers$",
+        );
+        assert_eq!(compiled.parameters.len(), 1);
+
+        assert!(matches!(
+            ExpressionCompiler::new().compile("{unknown}"),
+            Err(Error::Expansion(_)),
+        ));
+    }
+
+    #[test]
+    fn captures_parameters() {
+        let pars = HashMap::from([("pair", "(\\d+)(?:,(\\d+))?")]);
+        let compiled = ExpressionCompiler::new()
+            .parameters(&pars)
+            .compile("{pair} and {int} or {pair}")
+            .unwrap();
+
+        let captures = compiled.captures("1,2 and 5 or 3").unwrap();
+        assert_eq!(captures.len(), 3);
+        assert_eq!(captures[0].parameter.span, 0..6);
+        assert_eq!(captures[0].groups, [Some("1,2"), Some("1"), Some("2")]);
+        assert_eq!(captures[1].parameter.name, "int");
+        assert_eq!(captures[1].groups, [Some("5")]);
+        assert_eq!(captures[2].parameter.span, 20..26);
+        assert_eq!(captures[2].groups, [Some("3"), Some("3"), None]);
+
+        let non_capturing = ExpressionCompiler::new()
+            .parameters(&pars)
+            .options(Options {
+                non_capturing_parameters: true,
+                ..Options::default()
+            })
+            .compile("{pair}")
+            .unwrap();
+        assert_eq!(
+            non_capturing.captures("1,2").unwrap()[0].groups,
+            [Some("1,2")],
+        );
+    }
+
+    #[test]
+    fn captures_defaults() {
+        let pars = HashMap::from([("pair", "(\\d+),(\\d+)")]);
+        let compiled = ExpressionCompiler::new()
+            .parameters(&pars)
+            .options(Options {
+                optional_parameters: true,
+                ..Options::default()
+            })
+            .compile("a( {pair})( {int})( {int})")
+            .unwrap()
+            .with_default(0, "0,0")
+            .with_default(1, "3");
+
+        let captures = compiled.captures("a 1,2 5 7").unwrap();
+        assert_eq!(captures[0].groups, [Some("1,2"), Some("1"), Some("2")]);
+        assert_eq!(captures[1].groups, [Some("5")]);
+        assert_eq!(captures[2].groups, [Some("7")]);
+
+        let captures = compiled.captures("a").unwrap();
+        assert_eq!(captures[0].groups, [Some("0,0")]);
+        assert_eq!(captures[1].groups, [Some("3")]);
+        assert_eq!(captures[2].groups, [None]);
+
+        let args = compiled.match_against("a").unwrap();
+        assert_eq!(args[1].value(), None);
+    }
+
+    #[test]
+    fn matches_arguments() {
+        let pars = HashMap::from([("nested", "(a(b)?)(c)")]);
+        let compiled = ExpressionCompiler::new()
+            .parameters(&pars)
+            .options(Options {
+                optional_parameters: true,
+                named_groups: true,
+                ..Options::default()
+            })
+            .compile("{} and( {int}) {nested}")
+            .unwrap();
+
+        let args = compiled.match_against("x and 5 ac").unwrap();
+        assert_eq!(args.len(), 3);
+        assert_eq!(args[0].parameter.name, "");
+        assert_eq!(args[0].parameter.span, 0..2);
+        assert_eq!(args[0].value(), Some("x"));
+        assert_eq!(args[1].group.span, Some(6..7));
+        assert_eq!(
+            args[2].group,
+            Group {
+                value: Some("ac"),
+                span: Some(8..10),
+                children: vec![
+                    Group {
+                        value: Some("a"),
+                        span: Some(8..9),
+                        children: vec![Group {
+                            value: None,
+                            span: None,
+                            children: vec![],
+                        }],
+                    },
+                    Group {
+                        value: Some("c"),
+                        span: Some(9..10),
+                        children: vec![],
+                    },
+                ],
+            },
+        );
+
+        let without_int = compiled.match_against("x and abc").unwrap();
+        assert_eq!(without_int[1].value(), None);
+        assert_eq!(
+            without_int[2].group.children[0].children[0].value,
+            Some("b"),
+        );
+
+        assert!(compiled.match_against("x and 5").is_none());
+    }
+
+    #[test]
+    fn uses_cache_if_cached() {
+        cache::set_capacity(10);
+
+        let uncached = ExpressionCompiler::new().cached(false);
+        drop(uncached.compile("{word}").unwrap());
+        assert_eq!(cache::len(), 0);
+
+        drop(ExpressionCompiler::new().compile("{word}").unwrap());
+        assert_eq!(cache::len(), 1);
+
+        cache::set_capacity(0);
+    }
+}
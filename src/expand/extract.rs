@@ -0,0 +1,314 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Extraction of typed values from the [`Argument`]s of a
+//! [`CompiledExpression`] match via [`FromStr`].
+//!
+//! [`CompiledExpression`]: super::CompiledExpression
+
+use std::{borrow::Cow, error::Error as StdError, str::FromStr, sync::Arc};
+
+use derive_more::{Display, Error};
+
+use super::{registry::BoxError, unquote_string, Argument};
+
+/// Type extracted from the [`Argument`]s of a [`CompiledExpression`] match
+/// via [`CompiledExpression::extract()`].
+///
+/// Implemented for tuples (up to 12 elements) of [`FromStr`] types, each
+/// parsed from the text matched by the corresponding [`Parameter`].
+///
+/// [`CompiledExpression`]: super::CompiledExpression
+/// [`CompiledExpression::extract()`]: super::CompiledExpression::extract()
+/// [`Parameter`]: crate::Parameter
+pub trait FromArguments: Sized {
+    /// Number of the [`Argument`]s this type is extracted from.
+    const LEN: usize;
+
+    /// Extracts this type from the given [`Argument`]s.
+    ///
+    /// # Errors
+    ///
+    /// If the number of the [`Argument`]s differs from [`FromArguments::LEN`],
+    /// or any of them fails to be extracted.
+    fn from_arguments(
+        arguments: &[Argument<'_, '_>],
+    ) -> Result<Self, ExtractError>;
+}
+
+/// Implements [`FromArguments`] for a tuple of the given types with the given
+/// indices.
+macro_rules! impl_from_arguments {
+    ($len:literal: $($ty:ident $i:tt),*) => {
+        impl<$($ty),*> FromArguments for ($($ty,)*)
+        where
+            $($ty: FromStr, $ty::Err: Into<BoxError>,)*
+        {
+            const LEN: usize = $len;
+
+            fn from_arguments(
+                arguments: &[Argument<'_, '_>],
+            ) -> Result<Self, ExtractError> {
+                if arguments.len() != Self::LEN {
+                    return Err(ExtractError::ArgumentsCount {
+                        expected: Self::LEN,
+                        actual: arguments.len(),
+                    });
+                }
+                Ok(($(parse::<$ty>(arguments, $i)?,)*))
+            }
+        }
+    };
+}
+
+impl_from_arguments!(0:);
+impl_from_arguments!(1: A 0);
+impl_from_arguments!(2: A 0, B 1);
+impl_from_arguments!(3: A 0, B 1, C 2);
+impl_from_arguments!(4: A 0, B 1, C 2, D 3);
+impl_from_arguments!(5: A 0, B 1, C 2, D 3, E 4);
+impl_from_arguments!(6: A 0, B 1, C 2, D 3, E 4, F 5);
+impl_from_arguments!(7: A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_from_arguments!(8: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_from_arguments!(9: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_from_arguments!(10: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_from_arguments!(
+    11: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10
+);
+impl_from_arguments!(
+    12: A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11
+);
+
+/// Parses the text matched by the [`Argument`] with the given `index` via
+/// [`FromStr`].
+///
+/// The text matched by a `{string}` [`Parameter`] is [unquoted][1] first, the
+/// same way its [built-in transformer][2] does.
+///
+/// # Errors
+///
+/// If there is no such [`Argument`], it doesn't participate in the match, or
+/// fails to be parsed.
+///
+/// [`Parameter`]: crate::Parameter
+/// [1]: super::unquote_string()
+/// [2]: super::ParameterTypeRegistry
+fn parse<T>(
+    arguments: &[Argument<'_, '_>],
+    index: usize,
+) -> Result<T, ExtractError>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    let arg = arguments.get(index).ok_or(ExtractError::ArgumentsCount {
+        expected: index + 1,
+        actual: arguments.len(),
+    })?;
+    let matched = arg.value().ok_or_else(|| ExtractError::Missing {
+        index,
+        parameter: arg.parameter.name.to_owned(),
+    })?;
+    let matched = match arg.parameter.name {
+        "string" => unquote_string(matched).unwrap_or(Cow::Borrowed(matched)),
+        _ => Cow::Borrowed(matched),
+    };
+    matched.parse().map_err(|e: T::Err| ExtractError::Parse {
+        index,
+        parameter: arg.parameter.name.to_owned(),
+        matched: matched.into_owned(),
+        reason: Arc::from(e.into()),
+    })
+}
+
+/// Error of [extracting][1] typed values from a [`CompiledExpression`] match.
+///
+/// [`CompiledExpression`]: super::CompiledExpression
+/// [1]: super::CompiledExpression::extract()
+#[derive(Clone, Debug, Display, Error)]
+pub enum ExtractError {
+    /// Text doesn't match the [`CompiledExpression`].
+    ///
+    /// [`CompiledExpression`]: super::CompiledExpression
+    #[display(fmt = "Text doesn't match the expression.")]
+    NoMatch,
+
+    /// Number of the extracted values differs from the number of the
+    /// [`Parameter`]s.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[display(
+        fmt = "Expected {} arguments, but the expression has {} parameters.",
+        expected,
+        actual
+    )]
+    ArgumentsCount {
+        /// Number of the extracted values.
+        expected: usize,
+
+        /// Number of the [`Parameter`]s.
+        ///
+        /// [`Parameter`]: crate::Parameter
+        actual: usize,
+    },
+
+    /// [`Parameter`] doesn't participate in the match (like the one inside an
+    /// unmatched [`OptionalParameter`]).
+    ///
+    /// [`OptionalParameter`]: crate::OptionalParameter
+    /// [`Parameter`]: crate::Parameter
+    #[display(
+        fmt = "Argument {} of parameter '{}' is missing in the match.",
+        index,
+        parameter
+    )]
+    Missing {
+        /// Index of the [`Argument`].
+        index: usize,
+
+        /// Name of the [`Parameter`].
+        ///
+        /// [`Parameter`]: crate::Parameter
+        parameter: String,
+    },
+
+    /// Text matched by a [`Parameter`] fails to be parsed via [`FromStr`].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[display(
+        fmt = "Argument {} of parameter '{}' failed to parse '{}': {}",
+        index,
+        parameter,
+        matched,
+        reason
+    )]
+    Parse {
+        /// Index of the [`Argument`].
+        index: usize,
+
+        /// Name of the [`Parameter`].
+        ///
+        /// [`Parameter`]: crate::Parameter
+        parameter: String,
+
+        /// Text matched by the [`Parameter`] (unquoted for a `{string}` one).
+        ///
+        /// [`Parameter`]: crate::Parameter
+        matched: String,
+
+        /// Error returned by [`FromStr`].
+        #[error(not(source))]
+        reason: Arc<dyn StdError + Send + Sync>,
+    },
+}
+
+#[cfg(test)]
+mod spec {
+    use crate::expand::{ExpressionCompiler, Options};
+
+    use super::ExtractError;
+
+    #[test]
+    fn extracts_tuples() {
+        let compiled = ExpressionCompiler::new()
+            .compile("{int} cukes weigh {float} in {string} by {word}")
+            .unwrap();
+        let text = "5 cukes weigh 1.5 in 'belly' by Bob";
+
+        assert_eq!(
+            compiled
+                .extract::<(i32, f64, String, String)>(text)
+                .unwrap(),
+            (5, 1.5, "belly".to_owned(), "Bob".to_owned()),
+        );
+        assert_eq!(
+            compiled
+                .extract::<(u8, f32, char, String)>(text)
+                .unwrap_err()
+                .to_string(),
+            "Argument 2 of parameter 'string' failed to parse 'belly': too \
+             many characters in string",
+        );
+        assert!(matches!(
+            compiled.extract::<(i32, f64)>(text),
+            Err(ExtractError::ArgumentsCount {
+                expected: 2,
+                actual: 4,
+            }),
+        ));
+        assert!(matches!(
+            compiled.extract::<(i32, f64, String, String)>("5 cukes"),
+            Err(ExtractError::NoMatch),
+        ));
+
+        let none = ExpressionCompiler::new().compile("cukes").unwrap();
+        none.extract::<()>("cukes").unwrap();
+    }
+
+    #[test]
+    fn reports_missing() {
+        let compiled = ExpressionCompiler::new()
+            .options(Options {
+                optional_parameters: true,
+                ..Options::default()
+            })
+            .compile("{word}( with {int})")
+            .unwrap();
+
+        assert_eq!(
+            compiled.extract::<(String, u8)>("Bob with 3").unwrap(),
+            ("Bob".to_owned(), 3),
+        );
+        assert_eq!(
+            compiled
+                .extract::<(String, u8)>("Bob")
+                .unwrap_err()
+                .to_string(),
+            "Argument 1 of parameter 'int' is missing in the match.",
+        );
+        assert!(matches!(
+            compiled.extract::<(String, u8)>("Bob with 300"),
+            Err(ExtractError::Parse { index: 1, .. }),
+        ));
+    }
+
+    #[test]
+    fn extracts_defaults() {
+        let compiled = ExpressionCompiler::new()
+            .options(Options {
+                optional_parameters: true,
+                ..Options::default()
+            })
+            .compile("{word}( with {int})( and {int})")
+            .unwrap()
+            .with_default(1, "3");
+
+        assert_eq!(
+            compiled
+                .extract::<(String, u8, u8)>("Bob with 5 and 7")
+                .unwrap(),
+            ("Bob".to_owned(), 5, 7),
+        );
+        assert_eq!(
+            compiled.extract::<(String, u8, u8)>("Bob and 7").unwrap(),
+            ("Bob".to_owned(), 3, 7),
+        );
+        assert!(matches!(
+            compiled.extract::<(String, u8, u8)>("Bob with 5"),
+            Err(ExtractError::Missing { index: 2, .. }),
+        ));
+
+        let invalid = compiled.with_default(2, "x");
+        assert!(matches!(
+            invalid.extract::<(String, u8, u8)>("Bob"),
+            Err(ExtractError::Parse { index: 2, .. }),
+        ));
+    }
+}
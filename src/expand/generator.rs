@@ -0,0 +1,306 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generation of [Cucumber Expressions][0] from step texts, mirroring the
+//! `CucumberExpressionGenerator` of the [reference implementations][0].
+//!
+//! [0]: https://github.com/cucumber/cucumber-expressions#readme
+
+use std::{cmp::Reverse, collections::HashMap};
+
+use regex::Regex;
+
+use super::registry::AnyParameterType;
+
+/// Maximum number of [`GeneratedExpression`]s produced for a single text, as
+/// their number grows exponentially with ambiguous [`Parameter`]s.
+///
+/// [`Parameter`]: crate::Parameter
+const MAX_EXPRESSIONS: usize = 256;
+
+/// [Cucumber Expression][0] generated from a step text, to be suggested in a
+/// step definition snippet.
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GeneratedExpression {
+    /// Source of the generated [Cucumber Expression][0].
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    pub source: String,
+
+    /// Names of the [`ParameterType`]s of the [`Parameter`]s, in order.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [`ParameterType`]: super::ParameterType
+    pub parameter_types: Vec<String>,
+
+    /// Unique names of the [`Parameter`]s to be used as arguments of a step
+    /// definition, in order (like `int`, `int2`, `string`).
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub parameter_names: Vec<String>,
+}
+
+/// Generates [`GeneratedExpression`]s for the given `text` with the given
+/// snippet [`ParameterType`]s, in order of their preference.
+///
+/// Follows the algorithm of the [reference implementations][0]: the leftmost
+/// longest matches of the [`ParameterType`]s on word boundaries become
+/// [`Parameter`]s, while several [`ParameterType`]s matching the same text
+/// produce an expression for each combination of them, in the order of the
+/// given `types`.
+///
+/// [`Parameter`]: crate::Parameter
+/// [`ParameterType`]: super::ParameterType
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+pub(super) fn generate<'r>(
+    types: impl Iterator<Item = &'r AnyParameterType>,
+    text: &str,
+) -> Vec<GeneratedExpression> {
+    let matchers = types
+        .filter_map(|t| {
+            Regex::new(&format!("({})", t.pattern()))
+                .ok()
+                .map(|re| (t, re))
+        })
+        .collect::<Vec<_>>();
+
+    let mut template = Vec::<&str>::new();
+    let mut combinations = Vec::<Vec<&AnyParameterType>>::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let found = matchers
+            .iter()
+            .filter_map(|(t, re)| find(re, text, pos).map(|m| (*t, m)))
+            .collect::<Vec<_>>();
+        let best = match found
+            .iter()
+            .map(|(_, m)| *m)
+            .min_by_key(|&(s, e)| (s, Reverse(e - s)))
+        {
+            Some(best) => best,
+            None => break,
+        };
+
+        let alternatives = found
+            .into_iter()
+            .filter(|(_, m)| *m == best)
+            .map(|(t, _)| t)
+            .collect::<Vec<_>>();
+
+        template.push(&text[pos..best.0]);
+        combinations.push(alternatives);
+        pos = best.1;
+    }
+    let tail = text.get(pos..).unwrap_or_default();
+
+    let mut expressions = Vec::new();
+    let _ = combine(&combinations, &mut Vec::new(), &mut |chosen| {
+        expressions.push(expression(&template, tail, chosen));
+        expressions.len() < MAX_EXPRESSIONS
+    });
+    expressions
+}
+
+/// Finds the leftmost match of the given [`Regex`] in the `text`, starting
+/// at the `from` position and spanning whole words, if any.
+fn find(re: &Regex, text: &str, from: usize) -> Option<(usize, usize)> {
+    let is_boundary =
+        |c: Option<char>| c.map_or(true, |c| !c.is_alphanumeric());
+
+    let mut pos = from;
+    while pos < text.len() {
+        let m = re.find_at(text, pos)?;
+        if !m.as_str().is_empty()
+            && is_boundary(text[..m.start()].chars().next_back())
+            && is_boundary(text[m.end()..].chars().next())
+        {
+            return Some((m.start(), m.end()));
+        }
+        pos = m.start() + text[m.start()..].chars().next()?.len_utf8();
+    }
+    None
+}
+
+/// Calls the given function `f` with every combination of the
+/// `combinations`' items, in order, until it returns `false`.
+///
+/// Returns `false` if stopped.
+fn combine<'r>(
+    combinations: &[Vec<&'r AnyParameterType>],
+    chosen: &mut Vec<&'r AnyParameterType>,
+    f: &mut impl FnMut(&[&'r AnyParameterType]) -> bool,
+) -> bool {
+    let (first, rest) = match combinations.split_first() {
+        Some(split) => split,
+        None => return f(chosen),
+    };
+    for t in first {
+        chosen.push(t);
+        let proceed = combine(rest, chosen, f);
+        let _ = chosen.pop();
+        if !proceed {
+            return false;
+        }
+    }
+    true
+}
+
+/// Creates a [`GeneratedExpression`] from the given `template` texts preceding
+/// the `chosen` [`ParameterType`]s, followed by the `tail` text.
+///
+/// [`ParameterType`]: super::ParameterType
+fn expression(
+    template: &[&str],
+    tail: &str,
+    chosen: &[&AnyParameterType],
+) -> GeneratedExpression {
+    let mut source = String::new();
+    let mut usages = HashMap::<&str, usize>::new();
+    let mut parameter_names = Vec::with_capacity(chosen.len());
+    for (text, t) in template.iter().zip(chosen) {
+        source.push_str(&escape(text));
+        source.push('{');
+        source.push_str(t.name());
+        source.push('}');
+
+        let usage = usages.entry(t.name()).or_default();
+        *usage += 1;
+        parameter_names.push(if *usage == 1 {
+            t.name().to_owned()
+        } else {
+            format!("{}{}", t.name(), usage)
+        });
+    }
+    source.push_str(&escape(tail));
+
+    GeneratedExpression {
+        source,
+        parameter_types: chosen.iter().map(|t| t.name().to_owned()).collect(),
+        parameter_names,
+    }
+}
+
+/// Escapes the given `text` to be matched literally by a
+/// [Cucumber Expression][0].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '(' | '{' | '/') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod spec {
+    use crate::{
+        expand::{ParameterType, ParameterTypeRegistry},
+        Expression,
+    };
+
+    use super::{escape, GeneratedExpression};
+
+    /// Generates expressions for the given `text` and checks that they match
+    /// it back, returning their sources.
+    fn sources(registry: &ParameterTypeRegistry, text: &str) -> Vec<String> {
+        registry
+            .generate_expressions(text)
+            .into_iter()
+            .map(|e| {
+                let re = Expression::regex_with_parameters(&e.source, registry)
+                    .unwrap_or_else(|err| panic!("{}: {}", e.source, err));
+                assert!(re.is_match(text), "{} doesn't match", e.source);
+                e.source
+            })
+            .collect()
+    }
+
+    #[test]
+    fn generates_builtins() {
+        let registry = ParameterTypeRegistry::new();
+
+        assert_eq!(
+            sources(&registry, "I have 3 cukes and 1.5 \"big\" ones"),
+            [
+                "I have {int} cukes and {float} {string} ones",
+                "I have {float} cukes and {float} {string} ones",
+            ],
+        );
+        assert_eq!(
+            registry.generate_expressions("I have 3 and 4 cukes")[0],
+            GeneratedExpression {
+                source: "I have {int} and {int} cukes".to_owned(),
+                parameter_types: vec!["int".to_owned(), "int".to_owned()],
+                parameter_names: vec!["int".to_owned(), "int2".to_owned()],
+            },
+        );
+        assert_eq!(
+            sources(&registry, "a3 (or {b}) / c\\d"),
+            ["a3 \\(or \\{b}) \\/ c\\\\d"],
+        );
+        assert_eq!(sources(&registry, ""), [""]);
+    }
+
+    #[test]
+    fn honors_snippet_preferences() {
+        let mut registry = ParameterTypeRegistry::new();
+        registry
+            .define(ParameterType::untyped("color", "red|blue"))
+            .unwrap();
+        registry
+            .define(
+                ParameterType::untyped("shade", "red|blue").preferential(true),
+            )
+            .unwrap();
+        registry
+            .define(
+                ParameterType::untyped("hidden", "cukes")
+                    .use_for_snippets(false),
+            )
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("size", "small|big"))
+            .unwrap();
+
+        assert_eq!(
+            sources(&registry, "a red big cukes"),
+            ["a {shade} {size} cukes", "a {color} {size} cukes"],
+        );
+        assert_eq!(sources(&registry, "reddish"), ["reddish"]);
+    }
+
+    #[test]
+    fn limits_combinations() {
+        let mut registry = ParameterTypeRegistry::new();
+        for name in ["a", "b", "c", "d"] {
+            registry
+                .define(
+                    ParameterType::untyped(name, "x").preferential(name == "a"),
+                )
+                .unwrap();
+        }
+
+        let expressions = registry.generate_expressions("x x x x x");
+        assert_eq!(expressions.len(), 256);
+        assert_eq!(expressions[0].source, "{a} {a} {a} {a} {a}");
+        assert_eq!(expressions[1].source, "{a} {a} {a} {a} {b}");
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape("a(b){c}/d\\e"), "a\\(b)\\{c}\\/d\\\\e");
+    }
+}
@@ -0,0 +1,589 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Expansion of [`Expression`]s directly into a [`Hir`], without writing and
+//! reparsing a [`Regex`] pattern.
+//!
+//! [`Regex`]: regex::Regex
+
+use std::collections::HashMap;
+
+use nom::{AsChar, InputIter};
+use regex_syntax::{
+    hir::{self, Anchor, GroupKind, Hir, HirKind, RepetitionKind},
+    ParserBuilder,
+};
+
+use crate::{
+    Alternative, Expression, OptionalParameter, Parameter, SingleExpression,
+    Span, TextChars,
+};
+
+use super::{
+    builtin_or_quantified_parameter, builtin_parameter, group_names, Dialect,
+    Error, InvalidParameterError, Options, ParametersProvider,
+    ShadowedParameterError, UnknownParameterError, Whitespaces,
+};
+
+#[allow(clippy::multiple_inherent_impl)] // because of `into-regex` feature
+impl<Input: Span> Expression<Input> {
+    /// Expands this [`Expression`] directly into a [`Hir`] of the [`Regex`]
+    /// [`Expression::regex()`] compiles.
+    ///
+    /// See [`Expression::hir_with_parameters_and_options()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let hir = Expression::parse("I have {int} cucumber(s)")
+    ///     .unwrap()
+    ///     .hir()
+    ///     .unwrap();
+    ///
+    /// assert!(hir.is_anchored_start());
+    /// assert!(hir.is_anchored_end());
+    /// assert!(!hir.is_match_empty());
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    /// [`Regex`]: regex::Regex
+    pub fn hir(&self) -> Result<Hir, Error<Input>> {
+        self.hir_with_parameters_and_options(
+            &HashMap::<&str, &str>::new(),
+            Options::default(),
+        )
+    }
+
+    /// Expands this [`Expression`] directly into a [`Hir`] with the given
+    /// [`Options`], considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// Texts are turned into [`Hir`] literals as is, so never need escaping,
+    /// while patterns of [`Parameter`]s are parsed on their own, with their
+    /// capturing groups renumbered to match the [`Regex`] expanded from a
+    /// string. The [`Hir`] may be analyzed or post-processed, and then rendered
+    /// back into a pattern with its [`Display`] implementation.
+    ///
+    /// The [`Hir`] is expanded for [`Dialect::Rust`] regardless of the
+    /// [`Options::dialect`], while [`Options::optimize`] and
+    /// [`Options::atomic_groups`] are ignored.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details. A [`Parameter`] pattern failing to
+    /// parse is reported with an [`Error::InvalidParameter`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::{expand::Options, Expression};
+    /// #
+    /// let parameters = HashMap::from([("color", "red|green|blue")]);
+    /// let hir = Expression::parse("a {color} (*)")
+    ///     .unwrap()
+    ///     .hir_with_parameters_and_options(
+    ///         &parameters,
+    ///         Options {
+    ///             named_groups: true,
+    ///             ..Options::default()
+    ///         },
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     hir.to_string(),
+    ///     "\\Aa (?P<color>red|green|blue) (?:\\*)?\\z",
+    /// );
+    /// ```
+    ///
+    /// [`Display`]: std::fmt::Display
+    /// [`Error`]: enum@Error
+    /// [`Regex`]: regex::Regex
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    pub fn hir_with_parameters_and_options<Parameters>(
+        &self,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<Hir, Error<Input>>
+    where
+        Parameters: ParametersProvider<Input>,
+    {
+        let options = Options {
+            dialect: Dialect::Rust,
+            ..options
+        };
+        let mut expansion = Expansion {
+            options,
+            next_index: 1,
+        };
+        let mut names = group_names(self, options).into_iter();
+
+        let mut hirs = Vec::with_capacity(self.0.len() + 4);
+        if options.anchor_start {
+            hirs.push(Hir::anchor(Anchor::StartText));
+        }
+        hirs.push(expansion.padding());
+        for e in &self.0 {
+            hirs.push(match e {
+                SingleExpression::Parameter(p) => {
+                    expansion.parameter(p, &parameters, names.next())?
+                }
+                SingleExpression::Alternation(alt) => group(Hir::alternation(
+                    alt.iter()
+                        .map(|single| {
+                            Hir::concat(
+                                single
+                                    .iter()
+                                    .map(|a| match a {
+                                        Alternative::Text(t) => {
+                                            expansion.text(TextChars::new(t))
+                                        }
+                                        Alternative::Optional(opt) => {
+                                            expansion.optional(opt.chars())
+                                        }
+                                    })
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                )),
+                SingleExpression::Optional(opt) => {
+                    expansion.optional(opt.chars())
+                }
+                SingleExpression::OptionalParameter(opt) => expansion
+                    .optional_parameter(opt, &parameters, names.next())?,
+                SingleExpression::Whitespaces(t) => match options.whitespaces {
+                    Whitespaces::Literal => expansion.text(TextChars::new(t)),
+                    Whitespaces::Flexible | Whitespaces::FlexiblePadded => {
+                        whitespaces(1)
+                    }
+                },
+                SingleExpression::Text(t) => expansion.text(TextChars::new(t)),
+            });
+        }
+        hirs.push(expansion.padding());
+        if options.anchor_end {
+            hirs.push(Hir::anchor(Anchor::EndText));
+        }
+        Ok(Hir::concat(hirs))
+    }
+}
+
+/// State of expanding a single [`Expression`] into a [`Hir`].
+#[derive(Clone, Copy, Debug)]
+struct Expansion {
+    /// [`Options`] to expand with.
+    options: Options,
+
+    /// Index of the next capturing group.
+    next_index: u32,
+}
+
+impl Expansion {
+    /// Returns a [`Hir`] matching the given `text` literally.
+    fn text<Input: Span>(self, text: TextChars<Input>) -> Hir {
+        self.literal(&text.cooked())
+    }
+
+    /// Returns a [`Hir`] matching the given `text` optionally.
+    fn optional<Input: Span>(self, text: TextChars<Input>) -> Hir {
+        Hir::repetition(hir::Repetition {
+            kind: RepetitionKind::ZeroOrOne,
+            greedy: true,
+            hir: Box::new(group(self.literal(&text.cooked()))),
+        })
+    }
+
+    /// Returns a [`Hir`] of the given [`OptionalParameter`], capturing its
+    /// [`Parameter`] into a group named with the given `name`, if any.
+    fn optional_parameter<Input, Pars>(
+        &mut self,
+        opt: &OptionalParameter<Input>,
+        parameters: &Pars,
+        name: Option<String>,
+    ) -> Result<Hir, Error<Input>>
+    where
+        Input: Span,
+        Pars: ParametersProvider<Input>,
+    {
+        let prefix = self.text(opt.prefix_chars());
+        let parameter = self.parameter(&opt.parameter, parameters, name)?;
+        let suffix = self.text(opt.suffix_chars());
+        Ok(Hir::repetition(hir::Repetition {
+            kind: RepetitionKind::ZeroOrOne,
+            greedy: true,
+            hir: Box::new(group(Hir::concat(vec![prefix, parameter, suffix]))),
+        }))
+    }
+
+    /// Returns a [`Hir`] matching the given cooked `text` literally, ignoring
+    /// its case if [`Options::case_insensitive`] is enabled.
+    fn literal(self, text: &str) -> Hir {
+        Hir::concat(
+            text.chars()
+                .map(|c| {
+                    if !self.options.case_insensitive {
+                        return Hir::literal(hir::Literal::Unicode(c));
+                    }
+                    let mut class =
+                        hir::ClassUnicode::new([hir::ClassUnicodeRange::new(
+                            c, c,
+                        )]);
+                    class.case_fold_simple();
+                    if class.ranges().len() == 1
+                        && class.ranges()[0].start() == class.ranges()[0].end()
+                    {
+                        Hir::literal(hir::Literal::Unicode(c))
+                    } else {
+                        Hir::class(hir::Class::Unicode(class))
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns a [`Hir`] of the [`Options::padding()`].
+    fn padding(self) -> Hir {
+        match self.options.whitespaces {
+            Whitespaces::FlexiblePadded => whitespaces(0),
+            Whitespaces::Literal | Whitespaces::Flexible => Hir::empty(),
+        }
+    }
+
+    /// Returns a [`Hir`] of the given [`Parameter`] as a capturing group,
+    /// named with the given `name`, if any.
+    fn parameter<Input, Pars>(
+        &mut self,
+        parameter: &Parameter<Input>,
+        parameters: &Pars,
+        name: Option<String>,
+    ) -> Result<Hir, Error<Input>>
+    where
+        Input: Span,
+        Pars: ParametersProvider<Input>,
+    {
+        let value = match parameters.get(parameter) {
+            Some(v) => v,
+            None => return self.builtin_parameter(parameter, parameters, name),
+        };
+        if self.options.strict_parameters
+            && builtin_parameter(&parameter.0, self.options).is_some()
+        {
+            return Err(ShadowedParameterError {
+                shadowed: parameter.0.clone(),
+            }
+            .into());
+        }
+
+        let fragment = value
+            .iter_elements()
+            .map(AsChar::as_char)
+            .collect::<String>();
+        let parsed = self.parse_parameter(parameter, &fragment)?;
+        let index = self.next_index;
+        self.next_index += 1;
+        Ok(Hir::group(hir::Group {
+            kind: capture(index, name),
+            hir: Box::new(self.renumber(parsed, &mut None)),
+        }))
+    }
+
+    /// Returns a [`Hir`] of the given built-in [`Parameter`] as a capturing
+    /// group, named with the given `name`, if any.
+    fn builtin_parameter<Input, Pars>(
+        &mut self,
+        parameter: &Parameter<Input>,
+        parameters: &Pars,
+        mut name: Option<String>,
+    ) -> Result<Hir, Error<Input>>
+    where
+        Input: Span,
+        Pars: ParametersProvider<Input>,
+    {
+        let re = builtin_or_quantified_parameter(&parameter.0, self.options)
+            .ok_or_else(|| {
+                UnknownParameterError::new(
+                    parameter.0.clone(),
+                    parameters.names(),
+                )
+            })?;
+        let parsed = self.parse_parameter(parameter, &re)?;
+        // All the built-in `Parameter`s are capturing groups already.
+        Ok(self.renumber(parsed, &mut name))
+    }
+
+    /// Parses the given `pattern` of the [`Parameter`] into a [`Hir`].
+    fn parse_parameter<Input: Span>(
+        self,
+        parameter: &Parameter<Input>,
+        pattern: &str,
+    ) -> Result<Hir, Error<Input>> {
+        ParserBuilder::new()
+            .case_insensitive(self.options.case_insensitive)
+            .build()
+            .parse(pattern)
+            .map_err(|e| {
+                InvalidParameterError {
+                    parameter: parameter.0.clone(),
+                    fragment: pattern.to_owned(),
+                    reason: e.to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// Renumbers capturing groups of the given parsed [`Hir`] to follow the
+    /// already expanded ones, naming the first of them with the given `name`,
+    /// if any.
+    ///
+    /// Converts the capturing groups into non-capturing ones, if
+    /// [`Options::non_capturing_parameters`] are enabled, except the first one
+    /// named with the `name`.
+    fn renumber(&mut self, hir: Hir, name: &mut Option<String>) -> Hir {
+        match hir.into_kind() {
+            HirKind::Group(hir::Group {
+                kind: GroupKind::NonCapturing,
+                hir,
+            }) => Hir::group(hir::Group {
+                kind: GroupKind::NonCapturing,
+                hir: Box::new(self.renumber(*hir, name)),
+            }),
+            HirKind::Group(group) => {
+                let name = match (name.take(), group.kind) {
+                    (Some(n), _) => Some(n),
+                    (None, _) if self.options.non_capturing_parameters => {
+                        return Hir::group(hir::Group {
+                            kind: GroupKind::NonCapturing,
+                            hir: Box::new(self.renumber(*group.hir, &mut None)),
+                        });
+                    }
+                    (None, GroupKind::CaptureName { name, .. }) => Some(name),
+                    (None, _) => None,
+                };
+                let index = self.next_index;
+                self.next_index += 1;
+                Hir::group(hir::Group {
+                    kind: capture(index, name),
+                    hir: Box::new(self.renumber(*group.hir, &mut None)),
+                })
+            }
+            HirKind::Repetition(rep) => Hir::repetition(hir::Repetition {
+                hir: Box::new(self.renumber(*rep.hir, name)),
+                ..rep
+            }),
+            HirKind::Concat(subs) => Hir::concat(
+                subs.into_iter().map(|h| self.renumber(h, name)).collect(),
+            ),
+            HirKind::Alternation(subs) => Hir::alternation(
+                subs.into_iter().map(|h| self.renumber(h, name)).collect(),
+            ),
+            HirKind::Empty => Hir::empty(),
+            HirKind::Literal(lit) => Hir::literal(lit),
+            HirKind::Class(class) => Hir::class(class),
+            HirKind::Anchor(anchor) => Hir::anchor(anchor),
+            HirKind::WordBoundary(boundary) => Hir::word_boundary(boundary),
+        }
+    }
+}
+
+/// Wraps the given [`Hir`] into a non-capturing group, so it's rendered as a
+/// single unit when concatenated or repeated.
+fn group(hir: Hir) -> Hir {
+    Hir::group(hir::Group {
+        kind: GroupKind::NonCapturing,
+        hir: Box::new(hir),
+    })
+}
+
+/// Returns a [`GroupKind`] of a capturing group with the given `index`, named
+/// with the given `name`, if any.
+fn capture(index: u32, name: Option<String>) -> GroupKind {
+    name.map_or(GroupKind::CaptureIndex(index), |name| {
+        GroupKind::CaptureName { name, index }
+    })
+}
+
+/// Returns a [`Hir`] of at least `min` whitespaces, as `\\s` matches.
+fn whitespaces(min: u32) -> Hir {
+    let class = hir::ClassUnicode::new(
+        [
+            ('\t', '\r'),
+            (' ', ' '),
+            ('\u{85}', '\u{85}'),
+            ('\u{a0}', '\u{a0}'),
+            ('\u{1680}', '\u{1680}'),
+            ('\u{2000}', '\u{200a}'),
+            ('\u{2028}', '\u{2029}'),
+            ('\u{202f}', '\u{202f}'),
+            ('\u{205f}', '\u{205f}'),
+            ('\u{3000}', '\u{3000}'),
+        ]
+        .into_iter()
+        .map(|(start, end)| hir::ClassUnicodeRange::new(start, end)),
+    );
+    Hir::repetition(hir::Repetition {
+        kind: RepetitionKind::Range(hir::RepetitionRange::AtLeast(min)),
+        greedy: true,
+        hir: Box::new(Hir::class(hir::Class::Unicode(class))),
+    })
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use regex::Regex;
+
+    use crate::{
+        expand::{Error, Options, Whitespaces},
+        Expression,
+    };
+
+    #[test]
+    fn same_as_regex() {
+        let pars = HashMap::from([
+            ("pair", "(\\d+),(?P<second>\\d+)"),
+            ("color", "red|green"),
+        ]);
+        for (expr, options, texts) in [
+            (
+                "I have {int} cucumber(s) in my belly/stomach",
+                Options::default(),
+                &[
+                    "I have 1 cucumber in my belly",
+                    "I have -5 cucumbers in my stomach",
+                    "I have 1 cucumbers in my",
+                ][..],
+            ),
+            (
+                "{pair} and {color} {ordinal} {}",
+                Options::default(),
+                &["1,2 and red 3rd x", "1,2 and blue 3rd x"],
+            ),
+            (
+                "{pair} and {color} {ordinal}",
+                Options {
+                    named_groups: true,
+                    non_capturing_parameters: true,
+                    ..Options::default()
+                },
+                &["1,2 and red 3rd", "1,2 and red 3"],
+            ),
+            (
+                "Straße {word} $1.5 [x]\\(y\\) a|b",
+                Options {
+                    case_insensitive: true,
+                    ..Options::default()
+                },
+                &["STRASSE A $1.5 [X](Y) A|B", "straße a $1.5 [x](y) a|b"],
+            ),
+            (
+                "a  b {int}",
+                Options {
+                    whitespaces: Whitespaces::FlexiblePadded,
+                    anchor_end: false,
+                    ..Options::default()
+                },
+                &[" a\tb\u{a0} 1 ", "a b", "ab 1"],
+            ),
+            (
+                "{word:2,\"+\"} or {int+}",
+                Options {
+                    quantified_parameters: true,
+                    ..Options::default()
+                },
+                &["a+b or 1 2 3", "a b or 1"],
+            ),
+        ] {
+            let hir = Expression::parse(expr)
+                .unwrap()
+                .hir_with_parameters_and_options(&pars, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let from_hir = Regex::new(&hir.to_string()).unwrap();
+            let re = Expression::regex_with_parameters_and_options(
+                expr, &pars, options,
+            )
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(
+                from_hir.capture_names().collect::<Vec<_>>(),
+                re.capture_names().collect::<Vec<_>>(),
+                "on expression: {}",
+                expr,
+            );
+            for text in texts {
+                let captures = |regex: &Regex| {
+                    regex.captures(text).map(|caps| {
+                        caps.iter()
+                            .map(|m| m.map(|m| m.as_str().to_owned()))
+                            .collect::<Vec<_>>()
+                    })
+                };
+
+                assert_eq!(
+                    captures(&from_hir),
+                    captures(&re),
+                    "on expression `{}` and input: {}",
+                    expr,
+                    text,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let pars = HashMap::from([("bad", "(a"), ("int", "\\d+")]);
+        let strict = Options {
+            strict_parameters: true,
+            ..Options::default()
+        };
+
+        for (expr, options, expected) in [
+            (
+                "{bad}",
+                Options::default(),
+                "Parameter 'bad' has invalid regex",
+            ),
+            (
+                "{unknown}",
+                Options::default(),
+                "Parameter 'unknown' not found",
+            ),
+            ("{int}", strict, "Parameter 'int' shadows"),
+        ] {
+            let err = Expression::parse(expr)
+                .unwrap()
+                .hir_with_parameters_and_options(&pars, options)
+                .unwrap_err();
+
+            assert!(
+                matches!(
+                    err,
+                    Error::InvalidParameter(_)
+                        | Error::Expansion(_)
+                        | Error::Shadowing(_),
+                ),
+                "wrong err: {}",
+                err,
+            );
+            assert!(err.to_string().contains(expected), "wrong err: {}", err);
+        }
+    }
+}
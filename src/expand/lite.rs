@@ -0,0 +1,170 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Expansion into [`regex_lite::Regex`].
+
+use std::collections::HashMap;
+
+use crate::{Expression, Spanned};
+
+use super::{
+    parameters::validate_parameters, Error, ExpandInto as _, Options,
+    ParametersProvider,
+};
+
+#[allow(clippy::multiple_inherent_impl)] // because of `into-regex-lite` feature
+impl<'s> Expression<Spanned<'s>> {
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`regex_lite::Regex`].
+    ///
+    /// [`regex_lite`] character classes (like `\s` or `\d`) match ASCII only.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let re = Expression::regex_lite("I have {float} cucumbers").unwrap();
+    ///
+    /// assert!(re.is_match("I have -.5 cucumbers"));
+    /// assert!(!re.is_match("I have . cucumbers"));
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    pub fn regex_lite<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<regex_lite::Regex, Error<Spanned<'s>>> {
+        Expression::regex_lite_with_parameters_and_options(
+            input,
+            &HashMap::<&str, &str>::new(),
+            Options::default(),
+        )
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`regex_lite::Regex`] with the given
+    /// [`Options`], considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// See [`Expression::regex_lite()`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn regex_lite_with_parameters_and_options<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<regex_lite::Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse_with_syntax(input, options.syntax())?;
+        validate_parameters(&expr, &parameters, |re| {
+            regex_lite::Regex::new(re).map(drop)
+        })?;
+        let mut re = String::new();
+        expr.with_parameters(parameters)
+            .expand_into_with(&mut re, options)?;
+        regex_lite::Regex::new(&re).map_err(Error::RegexLite)
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use crate::{
+        expand::{Error, Options},
+        Expression,
+    };
+
+    #[test]
+    fn builtins() {
+        let re = Expression::regex_lite("{int} {float} {word} {string} {}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures("-1 +2.5E-3 word 'str' any thing").unwrap();
+
+        assert_eq!(&caps[1], "-1");
+        assert_eq!(&caps[2], "+2.5E-3");
+        assert_eq!(&caps[3], "word");
+        assert_eq!(&caps[4], "'str'");
+        assert_eq!(&caps[5], "any thing");
+    }
+
+    #[test]
+    fn float() {
+        let re = Expression::regex_lite("{float}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        for matching in
+            ["1", "-1.5", ".5", "+10", "1E10", "1.5E-3", "1.5e3", "2e+1"]
+        {
+            assert!(re.is_match(matching), "on input: {}", matching);
+        }
+        for not_matching in ["", ".", "1.", "-", "E10", "e10", "1.5e"] {
+            assert!(!re.is_match(not_matching), "on input: {}", not_matching);
+        }
+    }
+
+    #[test]
+    fn custom_parameters_and_options() {
+        let pars = HashMap::from([("color", "red|blue"), ("float", "\\d")]);
+        let re = Expression::regex_lite_with_parameters_and_options(
+            "{color} {float}",
+            &pars,
+            Options {
+                case_insensitive: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(re.as_str(), "(?i)^(red|blue) (\\d)$");
+        assert!(re.is_match("BLUE 1"));
+    }
+
+    #[test]
+    fn errors() {
+        let pars = HashMap::from([("bad", "(")]);
+        let unbalanced = HashMap::from([("bad", "a)(b")]);
+
+        assert!(matches!(
+            Expression::regex_lite("{unknown}").unwrap_err(),
+            Error::Expansion(_),
+        ));
+        assert!(matches!(
+            Expression::regex_lite_with_parameters_and_options(
+                "{bad}",
+                &pars,
+                Options::default(),
+            )
+            .unwrap_err(),
+            Error::InvalidParameter(_),
+        ));
+        assert!(matches!(
+            Expression::regex_lite_with_parameters_and_options(
+                "{bad}",
+                &unbalanced,
+                Options::default(),
+            )
+            .unwrap_err(),
+            Error::InvalidParameter(_),
+        ));
+    }
+}
@@ -17,24 +17,73 @@
 //! [1]: https://git.io/J159T
 //! [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
 
+mod boxed;
+#[cfg(feature = "into-regex")]
+pub mod cache;
+#[cfg(feature = "into-regex")]
+mod compiler;
+#[cfg(feature = "into-regex")]
+mod extract;
+#[cfg(feature = "into-regex")]
+mod generator;
+#[cfg(feature = "into-regex")]
+mod hir;
+#[cfg(feature = "into-regex-lite")]
+mod lite;
+mod optimize;
+#[cfg(feature = "packs")]
+pub mod packs;
 pub mod parameters;
+#[cfg(feature = "into-regex")]
+mod prefix;
+mod quantified;
+#[cfg(feature = "into-regex")]
+pub mod redos;
+pub mod registry;
+#[cfg(feature = "hot-reload")]
+pub mod reload;
 
-use std::{fmt, iter, str, vec};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt, iter,
+    ops::Range,
+    slice, str, vec,
+};
 
 use derive_more::{Display, Error, From};
 use either::Either;
 use nom::{AsChar, InputIter};
-use regex::Regex;
+#[cfg(feature = "into-regex")]
+use regex::{bytes, Regex, RegexBuilder};
 
 use crate::{
-    parse, Alternation, Alternative, Expression, Optional, Parameter,
-    SingleAlternation, SingleExpression, Spanned,
+    parse, Alternation, Alternative, AnyExpression, Diagnostic, Expression,
+    Optional, OptionalParameter, Parameter, SingleAlternation,
+    SingleExpression, Spanned,
 };
 
-pub use self::parameters::{
-    Provider as ParametersProvider, WithCustom as WithCustomParameters,
+pub use self::{
+    boxed::{BoxedRegexCharIter, IntoBoxedRegexCharIter},
+    parameters::{
+        Provider as ParametersProvider, WithCustom as WithCustomParameters,
+    },
+    quantified::{QuantifiedParameter, Repetition},
+    registry::{
+        ParameterType, Registry as ParameterTypeRegistry, TypedParameter,
+    },
+};
+#[cfg(feature = "into-regex")]
+pub use self::{
+    compiler::{Argument, CompiledExpression, ExpressionCompiler, Group},
+    extract::{ExtractError, FromArguments},
+    generator::GeneratedExpression,
 };
 
+use self::parameters::capture_groups_len;
+#[cfg(feature = "into-regex")]
+use self::parameters::{expand_expression_into, validate_parameters};
+
 #[allow(clippy::multiple_inherent_impl)] // because of `into-regex` feature
 impl<'s> Expression<Spanned<'s>> {
     /// Parses the given `input` as an [`Expression`], and immediately expands
@@ -50,13 +99,35 @@ impl<'s> Expression<Spanned<'s>> {
     /// | --------------- | ---------------------------------------------- |
     /// | `{int}`         | Matches integers                               |
     /// | `{float}`       | Matches floats                                 |
+    /// | `{boolean}`     | Matches `true` or `false`                      |
+    /// | `{ordinal}`     | Matches ordinals (`1st`), capturing the number |
     /// | `{word}`        | Matches words without whitespace               |
     /// | `{string}`      | Matches single-quoted or double-quoted strings |
     /// | `{}` anonymous  | Matches anything (`/.*/`)                      |
     ///
+    /// Use [`unquote_string()`] to get a `{string}` content without quotes.
+    ///
+    /// For compatibility with [`cucumber-jvm`][2], `{byte}`, `{short}`,
+    /// `{long}` and `{biginteger}` are matched the same way as `{int}`, while
+    /// `{double}` and `{bigdecimal}` are matched the same way as `{float}`.
+    ///
+    /// With `extra-params` feature enabled, the following parameter types are
+    /// built-in too:
+    ///
+    /// | Parameter Type  | Description                                    |
+    /// | --------------- | ---------------------------------------------- |
+    /// | `{uuid}`        | Matches hyphenated UUIDs of any case           |
+    /// | `{date}`        | Matches ISO 8601 dates (`2021-12-31`)          |
+    /// | `{datetime}`    | Matches ISO 8601 times (`2021-12-31T23:59Z`)   |
+    /// | `{email}`       | Matches e-mail addresses                       |
+    /// | `{url}`         | Matches URLs with a scheme (`https://a.b/c`)   |
+    ///
     /// To expand an [`Expression`] with custom parameter types in addition to
     /// the built-in ones, use [`Expression::regex_with_parameters()`].
     ///
+    /// To skip parsing and compilation when expanding the same `input`
+    /// repeatedly, enable the [`cache`].
+    ///
     /// # Errors
     ///
     /// See [`Error`] for more details.
@@ -64,18 +135,111 @@ impl<'s> Expression<Spanned<'s>> {
     /// [`Error`]: enum@Error
     /// [0]: https://github.com/cucumber/cucumber-expressions#readme
     /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: https://github.com/cucumber/cucumber-jvm
+    #[cfg(feature = "into-regex")]
     pub fn regex<Input: AsRef<str> + ?Sized>(
         input: &'s Input,
     ) -> Result<Regex, Error<Spanned<'s>>> {
-        let re_str = Expression::parse(input)?
-            .into_regex_char_iter()
-            .collect::<Result<String, _>>()?;
-        Regex::new(&re_str).map_err(Into::into)
+        Expression::regex_with_options(input, Options::default())
     }
 
     /// Parses the given `input` as an [`Expression`], and immediately expands
-    /// it into the appropriate [`Regex`], considering the custom defined
-    /// `parameters` in addition to [default ones][1].
+    /// it into the appropriate [`Regex`] with the given [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_options<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+        options: Options,
+    ) -> Result<Regex, Error<Spanned<'s>>> {
+        let key = (cache::capacity() > 0).then(|| cache::Key {
+            input: input.as_ref().to_owned(),
+            options,
+            parameters: None,
+        });
+        cache::get_or_compile(key, || {
+            let mut re = String::new();
+            Expression::parse_with_syntax(input, options.syntax())?
+                .expand_into_with(&mut re, options)?;
+            Regex::new(&re).map_err(Into::into)
+        })
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`Regex`] with the given [`Options`],
+    /// considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_parameters_and_options<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        Self::regex_with_borrowed_parameters(input, &parameters, options, true)
+    }
+
+    /// Same as [`Expression::regex_with_parameters_and_options()`], but
+    /// borrows the `parameters`, so they may be shared between many
+    /// expansions without being cloned.
+    ///
+    /// Doesn't use the [`cache`] unless `cached` is `true`.
+    #[cfg(feature = "into-regex")]
+    fn regex_with_borrowed_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: &Parameters,
+        options: Options,
+        cached: bool,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let key = (cached && cache::capacity() > 0)
+            .then(|| parameters.fingerprint())
+            .flatten()
+            .map(|fingerprint| cache::Key {
+                input: input.as_ref().to_owned(),
+                options,
+                parameters: Some(fingerprint),
+            });
+        cache::get_or_compile(key, || {
+            let expr = Expression::parse_with_syntax(input, options.syntax())?;
+            validate_parameters(&expr, parameters, |re| {
+                Regex::new(re).map(drop)
+            })?;
+            let mut re = String::new();
+            expand_expression_into(&expr, parameters, &mut re, options)?;
+            Regex::new(&re).map_err(Into::into)
+        })
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`Regex`] with the given [`Options`],
+    /// considering the custom defined `parameters` in addition to
+    /// [default ones][1], and compiles it with a [`RegexBuilder`] set up by
+    /// the provided `configure` function.
+    ///
+    /// Allows bounding memory of the compiled [`Regex`] (via
+    /// [`RegexBuilder::size_limit()`] and [`RegexBuilder::dfa_size_limit()`])
+    /// for untrusted `input`s and `parameters`. Every custom `parameter` is
+    /// checked on its own with the configured [`RegexBuilder`] too. The
+    /// [`cache`] is neither looked up, nor populated.
     ///
     /// # Errors
     ///
@@ -86,528 +250,5111 @@ impl<'s> Expression<Spanned<'s>> {
     /// ```rust
     /// # use std::collections::HashMap;
     /// #
-    /// # use cucumber_expressions::Expression;
+    /// # use cucumber_expressions::{
+    /// #     expand::{Error, Options},
+    /// #     Expression,
+    /// # };
     /// #
-    /// let parameters = HashMap::from([("color", "[Rr]ed|[Gg]reen|[Bb]lue")]);
-    /// let re = Expression::regex_with_parameters(
-    ///     "{word} has {color} eyes",
+    /// let parameters = HashMap::<&str, &str>::new();
+    /// let re = Expression::regex_with_builder(
+    ///     "I have {int} cucumbers",
     ///     &parameters,
+    ///     Options::default(),
+    ///     |b| b.size_limit(1 << 20).unicode(false),
     /// )
     /// .unwrap();
+    /// assert!(re.is_match("I have 42 cucumbers"));
     ///
-    /// assert_eq!(
-    ///     re.as_str(),
-    ///     "^([^\\s]+) has ([Rr]ed|[Gg]reen|[Bb]lue) eyes$",
+    /// let too_big = Expression::regex_with_builder(
+    ///     "{string} {string} {string}",
+    ///     &parameters,
+    ///     Options::default(),
+    ///     |b| b.size_limit(100),
     /// );
+    /// assert!(matches!(too_big.unwrap_err(), Error::Regex(_)));
     /// ```
     ///
     /// [`Error`]: enum@Error
     /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
-    pub fn regex_with_parameters<Input, Parameters>(
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_builder<Input, Parameters, F>(
         input: &'s Input,
         parameters: Parameters,
+        options: Options,
+        configure: F,
     ) -> Result<Regex, Error<Spanned<'s>>>
     where
         Input: AsRef<str> + ?Sized,
-        Parameters: Clone + ParametersProvider<Spanned<'s>>,
-        Parameters::Value: InputIter,
-        <Parameters::Value as InputIter>::Item: AsChar,
+        Parameters: ParametersProvider<Spanned<'s>>,
+        F: Fn(&mut RegexBuilder) -> &mut RegexBuilder,
     {
-        let re_str = Expression::parse(input)?
-            .with_parameters(parameters)
-            .into_regex_char_iter()
-            .collect::<Result<String, _>>()?;
-        Regex::new(&re_str).map_err(Into::into)
+        let build = |re: &str| configure(&mut RegexBuilder::new(re)).build();
+
+        let expr = Expression::parse_with_syntax(input, options.syntax())?;
+        validate_parameters(&expr, &parameters, |re| build(re).map(drop))?;
+        let mut re = String::new();
+        expr.with_parameters(parameters)
+            .expand_into_with(&mut re, options)?;
+        build(&re).map_err(Into::into)
     }
 
-    /// Creates a parser, parsing [`Expression`]s and immediately expanding them
-    /// into appropriate [`Regex`]es, considering the custom defined
-    /// `parameters` in addition to [default ones][1].
+    /// Parses all the given `inputs` as [`Expression`]s, and immediately
+    /// expands them into the appropriate [`Regex`]es, considering the custom
+    /// defined `parameters` in addition to [default ones][1].
+    ///
+    /// Returns a result for every input, in the same order. With `parallel`
+    /// feature enabled, the `inputs` are expanded and compiled in parallel via
+    /// [`rayon`], which speeds up compilation of thousands of [`Regex`]es
+    /// considerably.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let parameters = HashMap::from([("color", "red|blue")]);
+    /// let res = Expression::regex_batch(
+    ///     &["I have {int} cucumbers", "{color} eyes", "{unknown}"],
+    ///     &parameters,
+    /// );
+    ///
+    /// assert_eq!(res.len(), 3);
+    /// assert!(res[0].as_ref().unwrap().is_match("I have 2 cucumbers"));
+    /// assert!(res[1].as_ref().unwrap().is_match("red eyes"));
+    /// assert!(res[2].is_err());
+    /// ```
     ///
     /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
-    pub fn with_parameters<P: ParametersProvider<Spanned<'s>>>(
-        self,
-        parameters: P,
-    ) -> WithCustomParameters<Self, P> {
-        WithCustomParameters {
-            element: self,
-            parameters,
-        }
-    }
-}
-
-/// Possible errors while parsing `Input` representing a
-/// [Cucumber Expression][0] and expanding it into a [`Regex`].
-///
-/// [0]: https://github.com/cucumber/cucumber-expressions#readme
-#[derive(Clone, Debug, Display, Error, From)]
-pub enum Error<Input>
-where
-    Input: fmt::Display,
-{
-    /// Parsing error.
-    #[display(fmt = "Parsing failed: {}", _0)]
-    Parsing(parse::Error<Input>),
-
-    /// Expansion error.
-    #[display(fmt = "Regex expansion failed: {}", _0)]
-    Expansion(UnknownParameterError<Input>),
-
-    /// [`Regex`] creation error.
-    #[display(fmt = "Regex creation failed: {}", _0)]
-    Regex(regex::Error),
-}
+    /// [`rayon`]: https://docs.rs/rayon
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    #[cfg(feature = "into-regex")]
+    pub fn regex_batch<Input, Parameters>(
+        inputs: &[&'s Input],
+        parameters: Parameters,
+    ) -> Vec<Result<Regex, Error<Spanned<'s>>>>
+    where
+        Input: AsRef<str> + Sync + ?Sized,
+        Parameters: Sync + ParametersProvider<Spanned<'s>>,
+    {
+        let regex = |input: &&'s Input| {
+            Self::regex_with_borrowed_parameters(
+                *input,
+                &parameters,
+                Options::default(),
+                true,
+            )
+        };
 
-/// Error of an unknown [`Parameter`] being used in an [`Expression`].
-#[derive(Clone, Copy, Debug, Display, Error)]
-#[display(fmt = "Parameter '{}' not found.", not_found)]
-pub struct UnknownParameterError<Input>
-where
-    Input: fmt::Display,
-{
-    /// [`Parameter`] not found.
-    pub not_found: Input,
-}
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::iter::{
+                IntoParallelRefIterator as _, ParallelIterator as _,
+            };
 
-/// Expansion of a [Cucumber Expressions][0] [AST] element into a [`Regex`] by
-/// producing a [`char`]s [`Iterator`] following original [production rules][1].
-///
-/// [0]: https://github.com/cucumber/cucumber-expressions#readme
-/// [1]: https://git.io/J159T
-/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
-pub trait IntoRegexCharIter<Input: fmt::Display> {
-    /// Type of an [`Iterator`] performing the expansion.
-    type Iter: Iterator<Item = Result<char, UnknownParameterError<Input>>>;
+            inputs.par_iter().map(regex).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            inputs.iter().map(regex).collect()
+        }
+    }
 
-    /// Consumes this [AST] element returning an [`Iterator`] over [`char`]s
-    /// transformable into a [`Regex`].
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`bytes::Regex`], matching raw bytes without
+    /// requiring them to be valid UTF-8.
     ///
-    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
-    fn into_regex_char_iter(self) -> Self::Iter;
-}
-
-impl<Input> IntoRegexCharIter<Input> for Expression<Input>
-where
-    Input: Clone + fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = ExpressionIter<Input>;
-
-    fn into_regex_char_iter(self) -> Self::Iter {
-        let into_regex_char_iter: fn(_) -> _ =
-            IntoRegexCharIter::into_regex_char_iter;
-
-        iter::once(Ok('^'))
-            .chain(self.0.into_iter().flat_map(into_regex_char_iter))
-            .chain(iter::once(Ok('$')))
+    /// The expanded [`bytes::Regex`] is compiled with Unicode support
+    /// disabled, so `.` and character classes (like `\s` or `\d`) match single
+    /// bytes and ASCII only, while non-ASCII text of the `input` is still
+    /// matched as its UTF-8 encoding.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let re = Expression::bytes_regex("I have {word}").unwrap();
+    ///
+    /// assert!(re.is_match(b"I have caf\xe9"));
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    #[cfg(feature = "into-regex")]
+    pub fn bytes_regex<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<bytes::Regex, Error<Spanned<'s>>> {
+        Expression::bytes_regex_with_parameters_and_options(
+            input,
+            &HashMap::<&str, &str>::new(),
+            Options::default(),
+        )
     }
-}
-
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for an [`Expression`].
-type ExpressionIter<Input> = iter::Chain<
-    iter::Chain<
-        iter::Once<Result<char, UnknownParameterError<Input>>>,
-        iter::FlatMap<
-            vec::IntoIter<SingleExpression<Input>>,
-            <SingleExpression<Input> as IntoRegexCharIter<Input>>::Iter,
-            fn(
-                SingleExpression<Input>,
-            )
-                -> <SingleExpression<Input> as IntoRegexCharIter<Input>>::Iter,
-        >,
-    >,
-    iter::Once<Result<char, UnknownParameterError<Input>>>,
->;
-
-impl<Input> IntoRegexCharIter<Input> for SingleExpression<Input>
-where
-    Input: Clone + fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = SingleExpressionIter<Input>;
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        use Either::{Left, Right};
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`bytes::Regex`] with the given [`Options`],
+    /// considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// See [`Expression::bytes_regex()`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-regex")]
+    pub fn bytes_regex_with_parameters_and_options<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<bytes::Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse_with_syntax(input, options.syntax())?;
+        validate_parameters(&expr, &parameters, |re| {
+            bytes::RegexBuilder::new(re)
+                .unicode(false)
+                .build()
+                .map(drop)
+        })?;
+        let mut re = String::new();
+        expr.with_parameters(parameters)
+            .expand_into_with(&mut re, options)?;
+        bytes::RegexBuilder::new(&re)
+            .unicode(false)
+            .build()
+            .map_err(Into::into)
+    }
 
-        let ok: fn(_) -> _ = Ok;
-        let as_char: fn(_) -> _ = AsChar::as_char;
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`fancy_regex::Regex`], considering the custom
+    /// defined `parameters` in addition to [default ones][1].
+    ///
+    /// Unlike [`Regex`], [`fancy_regex::Regex`] supports look-around and
+    /// backreferences, so custom `parameters` may use them.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let parameters = HashMap::from([("twice", "(\\w)\\2")]);
+    /// let re = Expression::fancy_regex_with_parameters("{twice}", &parameters)
+    ///     .unwrap();
+    ///
+    /// assert!(re.is_match("aa").unwrap());
+    /// assert!(!re.is_match("ab").unwrap());
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-fancy-regex")]
+    pub fn fancy_regex_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<fancy_regex::Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        Expression::fancy_regex_with_parameters_and_options(
+            input,
+            parameters,
+            Options::default(),
+        )
+    }
 
-        match self {
-            Self::Alternation(alt) => Left(alt.into_regex_char_iter()),
-            Self::Optional(opt) => Right(Left(opt.into_regex_char_iter())),
-            Self::Parameter(p) => Right(Right(Left(p.into_regex_char_iter()))),
-            Self::Text(t) | Self::Whitespaces(t) => Right(Right(Right(
-                EscapeForRegex::new(t.iter_elements().map(as_char)).map(ok),
-            ))),
-        }
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`fancy_regex::Regex`] with the given
+    /// [`Options`], considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// See [`Expression::fancy_regex_with_parameters()`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-fancy-regex")]
+    pub fn fancy_regex_with_parameters_and_options<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<fancy_regex::Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse_with_syntax(input, options.syntax())?;
+        validate_parameters(&expr, &parameters, |re| {
+            match fancy_regex::Regex::new(re) {
+                // Backreferences may refer to groups outside the fragment.
+                Ok(_)
+                | Err(fancy_regex::Error::CompileError(
+                    fancy_regex::CompileError::InvalidBackref,
+                )) => Ok(()),
+                Err(e) => Err(Box::new(e)),
+            }
+        })?;
+        let mut re = String::new();
+        expr.with_parameters(parameters)
+            .expand_into_with(&mut re, options)?;
+        fancy_regex::Regex::new(&re).map_err(|e| Error::FancyRegex(e.into()))
     }
-}
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for a [`SingleExpression`].
-type SingleExpressionIter<Input> = Either<
-    <Alternation<Input> as IntoRegexCharIter<Input>>::Iter,
-    Either<
-        <Optional<Input> as IntoRegexCharIter<Input>>::Iter,
-        Either<
-            <Parameter<Input> as IntoRegexCharIter<Input>>::Iter,
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`Regex`], along with [`ParameterRef`]s
+    /// describing which [`Parameter`] owns each of its capturing groups.
+    ///
+    /// The `n`th [`ParameterRef`] corresponds to the capturing group with the
+    /// `n + 1` index (as the `0` one is the whole match).
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let (re, params) =
+    ///     Expression::regex_with_capture_map("{word} has {int}").unwrap();
+    /// let caps = re.captures("Bob has 2").unwrap();
+    ///
+    /// assert_eq!(params[1].name, "int");
+    /// assert_eq!(params[1].span, 11..16);
+    /// assert_eq!(&caps[2], "2");
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_capture_map<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<(Regex, Vec<ParameterRef<'s>>), Error<Spanned<'s>>> {
+        let expr = Expression::parse(input)?;
+        let mut re = String::new();
+        expr.expand_into(&mut re)?;
+        let re = Regex::new(&re)?;
+        Ok((re, expr.capture_map()?))
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`Regex`], considering the custom defined
+    /// `parameters` in addition to [default ones][1], along with
+    /// [`ParameterRef`]s describing which [`Parameter`] owns each of its
+    /// capturing groups.
+    ///
+    /// Custom `parameters` with nested capturing groups own all of them.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let parameters = HashMap::from([("pair", "(\\d+),(\\d+)")]);
+    /// let (re, params) = Expression::regex_with_parameters_and_capture_map(
+    ///     "{pair} and {word}",
+    ///     &parameters,
+    /// )
+    /// .unwrap();
+    /// let caps = re.captures("1,2 and three").unwrap();
+    ///
+    /// assert_eq!(params.len(), 4);
+    /// assert_eq!(params[2].name, "pair");
+    /// assert_eq!(params[3].name, "word");
+    /// assert_eq!(&caps[4], "three");
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_parameters_and_capture_map<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<(Regex, Vec<ParameterRef<'s>>), Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse(input)?;
+        validate_parameters(&expr, &parameters, |re| Regex::new(re).map(drop))?;
+        let with_pars = WithCustomParameters {
+            element: &expr,
+            parameters,
+        };
+        let mut re = String::new();
+        with_pars.expand_into(&mut re)?;
+        let re = Regex::new(&re)?;
+
+        let map = expr.capture_map_with_parameters_and_options(
+            with_pars.parameters,
+            Options::default(),
+        )?;
+        Ok((re, map))
+    }
+
+    /// Returns [`ParameterRef`]s describing which [`Parameter`] owns each
+    /// capturing group of the [`Regex`] this [`Expression`] expands into,
+    /// without expanding or compiling it.
+    ///
+    /// The `n`th [`ParameterRef`] corresponds to the capturing group with the
+    /// `n + 1` index (as the `0` one is the whole match).
+    ///
+    /// # Errors
+    ///
+    /// With [`Error::Expansion`] if this [`Expression`] contains an unknown
+    /// [`Parameter`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let expr = Expression::parse("{word} has {int} cucumber(s)").unwrap();
+    /// let params = expr.capture_map().unwrap();
+    ///
+    /// assert_eq!(expr.capture_count().unwrap(), 2);
+    /// assert_eq!(params[1].name, "int");
+    /// assert_eq!(params[1].span, 11..16);
+    /// ```
+    pub fn capture_map(
+        &self,
+    ) -> Result<Vec<ParameterRef<'s>>, Error<Spanned<'s>>> {
+        self.capture_map_with_parameters_and_options(
+            &HashMap::<&str, &str>::new(),
+            Options::default(),
+        )
+    }
+
+    /// Returns [`ParameterRef`]s describing which [`Parameter`] owns each
+    /// capturing group of the [`Regex`] this [`Expression`] expands into with
+    /// the given [`Options`], considering the custom defined `parameters` in
+    /// addition to [default ones][1], without expanding or compiling it.
+    ///
+    /// Capturing groups of custom `parameters` are counted by scanning their
+    /// patterns, so each of them owns all its nested capturing groups (unless
+    /// [`Options::non_capturing_parameters`] are enabled), exactly as
+    /// [`Expression::regex_with_parameters_and_capture_map()`] reports.
+    ///
+    /// # Errors
+    ///
+    /// With [`Error::Expansion`] if this [`Expression`] contains an unknown
+    /// [`Parameter`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::{expand::Options, Expression};
+    /// #
+    /// let parameters = HashMap::from([("pair", "(\\d+),(\\d+)")]);
+    /// let expr = Expression::parse("{pair} and {word}").unwrap();
+    /// let params = expr
+    ///     .capture_map_with_parameters_and_options(
+    ///         &parameters,
+    ///         Options::default(),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// assert_eq!(params.len(), 4);
+    /// assert_eq!(params[2].name, "pair");
+    /// assert_eq!(params[3].name, "word");
+    /// ```
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    pub fn capture_map_with_parameters_and_options<Parameters>(
+        &self,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<Vec<ParameterRef<'s>>, Error<Spanned<'s>>>
+    where
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        self.capture_map_with_borrowed_parameters(&parameters, options)
+    }
+
+    /// Same as [`Expression::capture_map_with_parameters_and_options()`], but
+    /// borrows the `parameters`.
+    fn capture_map_with_borrowed_parameters<Parameters>(
+        &self,
+        parameters: &Parameters,
+        options: Options,
+    ) -> Result<Vec<ParameterRef<'s>>, Error<Spanned<'s>>>
+    where
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        capture_map(self, |p| match parameters.get(p) {
+            Some(_) if options.non_capturing_parameters => Ok(1),
+            Some(v) => Ok(1 + capture_groups_len(
+                v.iter_elements().map(AsChar::as_char),
+            )),
+            None if builtin_or_quantified_parameter(p, options).is_some() => {
+                Ok(1)
+            }
+            None => {
+                Err(UnknownParameterError::new(*p, parameters.names()).into())
+            }
+        })
+    }
+
+    /// Returns the number of capturing groups (except the `0` one, being the
+    /// whole match) of the [`Regex`] this [`Expression`] expands into, without
+    /// expanding or compiling it.
+    ///
+    /// See [`Expression::capture_map()`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// With [`Error::Expansion`] if this [`Expression`] contains an unknown
+    /// [`Parameter`].
+    pub fn capture_count(&self) -> Result<usize, Error<Spanned<'s>>> {
+        self.capture_map().map(|map| map.len())
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into a [`Regex`] pattern, without compiling it.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let re = Expression::regex_string("I have {word}").unwrap();
+    ///
+    /// assert_eq!(re, "^I have ([^\\s]+)$");
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    pub fn regex_string<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<String, Error<Spanned<'s>>> {
+        let mut re = String::new();
+        Expression::parse(input)?.expand_into(&mut re)?;
+        Ok(re)
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into the appropriate [`Regex`], considering the custom defined
+    /// `parameters` in addition to [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let parameters = HashMap::from([("color", "[Rr]ed|[Gg]reen|[Bb]lue")]);
+    /// let re = Expression::regex_with_parameters(
+    ///     "{word} has {color} eyes",
+    ///     &parameters,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     re.as_str(),
+    ///     "^([^\\s]+) has ([Rr]ed|[Gg]reen|[Bb]lue) eyes$",
+    /// );
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+        Parameters::Value: InputIter,
+        <Parameters::Value as InputIter>::Item: AsChar,
+    {
+        Expression::regex_with_parameters_and_options(
+            input,
+            parameters,
+            Options::default(),
+        )
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into a [`Regex`] pattern, without compiling it, considering the
+    /// custom defined `parameters` in addition to [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn regex_string_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<String, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+        Parameters::Value: InputIter,
+        <Parameters::Value as InputIter>::Item: AsChar,
+    {
+        let mut re = String::new();
+        Expression::parse(input)?
+            .with_parameters(parameters)
+            .expand_into(&mut re)?;
+        Ok(re)
+    }
+
+    /// Validates the given `input` as an [`Expression`], additionally checking
+    /// that all its [`Parameter`]s are either [built-in][1] or provided by the
+    /// custom defined `parameters`.
+    ///
+    /// Doesn't expand the [`Expression`] into a [`Regex`], so is much cheaper
+    /// than [`Expression::regex_with_parameters()`].
+    ///
+    /// # Errors
+    ///
+    /// With [`Diagnostic`]s describing all the problems found in the `input`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::{Diagnostic, Expression};
+    /// #
+    /// let parameters = HashMap::from([("color", "[Rr]ed|[Gg]reen|[Bb]lue")]);
+    /// let diagnostics = Expression::validate_with_parameters(
+    ///     "{colour} {word} {size}",
+    ///     &parameters,
+    /// )
+    /// .unwrap_err();
+    ///
+    /// assert_eq!(diagnostics.len(), 2);
+    /// assert_eq!(diagnostics[0].to_string(), "Parameter 'colour' not found.");
+    /// assert_eq!(diagnostics[1].to_string(), "Parameter 'size' not found.");
+    /// ```
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    pub fn validate_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<(), Vec<Diagnostic<Spanned<'s>>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse(input).map_err(|e| vec![e.into()])?;
+
+        let unknown = expr
+            .iter()
+            .filter_map(SingleExpression::parameter)
+            .filter(|Parameter(p)| {
+                builtin_parameter(p, Options::default()).is_none()
+                    && parameters.get(p).is_none()
+            })
+            .map(|Parameter(p)| Diagnostic::UnknownParameter(*p))
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(unknown)
+        }
+    }
+
+    /// Creates a parser, parsing [`Expression`]s and immediately expanding them
+    /// into appropriate [`Regex`]es, considering the custom defined
+    /// `parameters` in addition to [default ones][1].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn with_parameters<P: ParametersProvider<Spanned<'s>>>(
+        self,
+        parameters: P,
+    ) -> WithCustomParameters<Self, P> {
+        WithCustomParameters {
+            element: self,
+            parameters,
+        }
+    }
+}
+
+#[allow(clippy::multiple_inherent_impl)] // because of `into-regex` feature
+impl<'s> AnyExpression<Spanned<'s>> {
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into the appropriate [`Regex`].
+    ///
+    /// [`AnyExpression::Regular`] is used verbatim, being only anchored at its
+    /// start and end. It's anchored even if it has its own anchors already
+    /// (like `^\d+$` expanded into `^(?:^\d+$)$`), as they may belong to its
+    /// top-level alternatives only (like in `^a|b$`), while the redundant
+    /// ones don't change what it matches.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::AnyExpression;
+    /// #
+    /// let re = AnyExpression::regex(r"/I have (\d+|no) cucumbers/").unwrap();
+    ///
+    /// assert_eq!(re.as_str(), r"^(?:I have (\d+|no) cucumbers)$");
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    #[cfg(feature = "into-regex")]
+    pub fn regex<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<Regex, Error<Spanned<'s>>> {
+        let re_str = AnyExpression::regex_string(input)?;
+        Regex::new(&re_str).map_err(Into::into)
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into the appropriate [`Regex`] with the given [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_options<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+        options: Options,
+    ) -> Result<Regex, Error<Spanned<'s>>> {
+        let mut re = String::new();
+        AnyExpression::parse_with_syntax(input, options.syntax())?
+            .expand_into_with(&mut re, options)?;
+        Regex::new(&re).map_err(Into::into)
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into the appropriate [`Regex`] with the given [`Options`],
+    /// considering the custom defined `parameters` in addition to
+    /// [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_parameters_and_options<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+        options: Options,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = AnyExpression::parse_with_syntax(input, options.syntax())?;
+        if let AnyExpression::Cucumber(e) = &expr {
+            validate_parameters(e, &parameters, |re| Regex::new(re).map(drop))?;
+        }
+        let mut re = String::new();
+        expr.with_parameters(parameters)
+            .expand_into_with(&mut re, options)?;
+        Regex::new(&re).map_err(Into::into)
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into a [`Regex`] pattern, without compiling it.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    pub fn regex_string<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<String, Error<Spanned<'s>>> {
+        let mut re = String::new();
+        AnyExpression::parse(input)?.expand_into(&mut re)?;
+        Ok(re)
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into the appropriate [`Regex`], considering the custom
+    /// defined `parameters` in addition to [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[cfg(feature = "into-regex")]
+    pub fn regex_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+        Parameters::Value: InputIter,
+        <Parameters::Value as InputIter>::Item: AsChar,
+    {
+        let expr = AnyExpression::parse(input)?;
+        if let AnyExpression::Cucumber(e) = &expr {
+            validate_parameters(e, &parameters, |re| Regex::new(re).map(drop))?;
+        }
+        let mut re = String::new();
+        expr.with_parameters(parameters).expand_into(&mut re)?;
+        Regex::new(&re).map_err(Into::into)
+    }
+
+    /// Parses the given `input` as an [`AnyExpression`], and immediately
+    /// expands it into a [`Regex`] pattern, without compiling it, considering
+    /// the custom defined `parameters` in addition to [default ones][1].
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn regex_string_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<String, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+        Parameters::Value: InputIter,
+        <Parameters::Value as InputIter>::Item: AsChar,
+    {
+        let mut re = String::new();
+        AnyExpression::parse(input)?
+            .with_parameters(parameters)
+            .expand_into(&mut re)?;
+        Ok(re)
+    }
+
+    /// Creates a parser, parsing [`AnyExpression`]s and immediately expanding
+    /// them into appropriate [`Regex`]es, considering the custom defined
+    /// `parameters` in addition to [default ones][1].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn with_parameters<P: ParametersProvider<Spanned<'s>>>(
+        self,
+        parameters: P,
+    ) -> WithCustomParameters<Self, P> {
+        WithCustomParameters {
+            element: self,
+            parameters,
+        }
+    }
+}
+
+/// Possible errors while parsing `Input` representing a
+/// [Cucumber Expression][0] and expanding it into a [`Regex`].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Debug, Display, Error, From)]
+pub enum Error<Input>
+where
+    Input: fmt::Display,
+{
+    /// Parsing error.
+    #[display(fmt = "Parsing failed: {}", _0)]
+    Parsing(parse::Error<Input>),
+
+    /// Expansion error.
+    #[display(fmt = "Regex expansion failed: {}", _0)]
+    Expansion(UnknownParameterError<Input>),
+
+    /// Custom [`Parameter`] shadowing a built-in one, while
+    /// [`Options::strict_parameters`] is enabled.
+    #[display(fmt = "Regex expansion failed: {}", _0)]
+    Shadowing(ShadowedParameterError<Input>),
+
+    /// Custom [`Parameter`] provided with an invalid [`Regex`] fragment.
+    #[display(fmt = "Regex creation failed: {}", _0)]
+    InvalidParameter(InvalidParameterError<Input>),
+
+    /// [`Regex`] creation error.
+    #[cfg(feature = "into-regex")]
+    #[display(fmt = "Regex creation failed: {}", _0)]
+    Regex(regex::Error),
+
+    /// [`regex_lite::Regex`] creation error.
+    #[cfg(feature = "into-regex-lite")]
+    #[display(fmt = "Regex creation failed: {}", _0)]
+    RegexLite(regex_lite::Error),
+
+    /// Error of writing an expanded [`Regex`] via [`ExpandInto`].
+    #[display(fmt = "Regex writing failed: {}", _0)]
+    Formatting(fmt::Error),
+
+    /// [`fancy_regex::Regex`] creation error.
+    ///
+    /// Shared, as [`fancy_regex::Error`] is quite large and isn't [`Clone`].
+    #[cfg(feature = "into-fancy-regex")]
+    #[display(fmt = "Fancy regex creation failed: {}", _0)]
+    FancyRegex(std::sync::Arc<fancy_regex::Error>),
+}
+
+/// Error of an unknown [`Parameter`] being used in an [`Expression`].
+#[derive(Clone, Debug, Error)]
+pub struct UnknownParameterError<Input>
+where
+    Input: fmt::Display,
+{
+    /// [`Parameter`] not found.
+    pub not_found: Input,
+
+    /// Names of all the known [`Parameter`]s: [built-in][1] ones followed by
+    /// the ones returned by [`ParametersProvider::names()`].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub known: Vec<String>,
+
+    /// Name of the [`known`] [`Parameter`] closest to the [`not_found`] one by
+    /// edit distance, if it's close enough to be a typo.
+    ///
+    /// [`known`]: Self::known
+    /// [`not_found`]: Self::not_found
+    pub suggestion: Option<String>,
+}
+
+impl<Input> UnknownParameterError<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    /// Creates a new [`UnknownParameterError`] for the `not_found`
+    /// [`Parameter`], suggesting the closest one among the built-in and the
+    /// given `custom` [`Parameter`]s.
+    fn new(not_found: Input, mut custom: Vec<String>) -> Self {
+        custom.sort_unstable();
+        let mut known = builtin_parameter_names()
+            .map(ToOwned::to_owned)
+            .collect::<Vec<_>>();
+        for name in custom {
+            if !known.contains(&name) {
+                known.push(name);
+            }
+        }
+
+        let name = not_found
+            .iter_elements()
+            .map(AsChar::as_char)
+            .collect::<String>();
+        let max_distance = (name.chars().count() / 3).max(1);
+        let suggestion = known
+            .iter()
+            .map(|k| (edit_distance(&name, k), k))
+            .filter(|(d, _)| *d <= max_distance)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, k)| k.clone());
+
+        Self {
+            not_found,
+            known,
+            suggestion,
+        }
+    }
+}
+
+impl<Input: fmt::Display> fmt::Display for UnknownParameterError<Input> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parameter '{}' not found", self.not_found)?;
+        if let Some(s) = &self.suggestion {
+            write!(f, ", did you mean '{}'?", s)
+        } else {
+            f.write_str(".")
+        }
+    }
+}
+
+/// Error of a custom [`Parameter`] having the same name as a built-in one,
+/// while [`Options::strict_parameters`] is enabled.
+#[derive(Clone, Copy, Debug, Display, Error)]
+#[display(fmt = "Parameter '{}' shadows a built-in one.", shadowed)]
+pub struct ShadowedParameterError<Input>
+where
+    Input: fmt::Display,
+{
+    /// Custom [`Parameter`] shadowing a built-in one.
+    pub shadowed: Input,
+}
+
+/// Error of a custom [`Parameter`] being provided with a [`Regex`] fragment,
+/// which fails to compile on its own.
+#[derive(Clone, Debug, Display, Error)]
+#[display(
+    fmt = "Parameter '{}' has invalid regex '{}': {}",
+    parameter,
+    fragment,
+    reason
+)]
+pub struct InvalidParameterError<Input>
+where
+    Input: fmt::Display,
+{
+    /// Custom [`Parameter`] provided with an invalid [`Regex`] fragment.
+    pub parameter: Input,
+
+    /// Invalid [`Regex`] fragment.
+    pub fragment: String,
+
+    /// Description of why the [`Regex`] fragment is invalid, as reported by
+    /// a regex engine.
+    pub reason: String,
+}
+
+/// Reference to a [`Parameter`] owning a capturing group of an expanded
+/// [`Regex`].
+///
+/// See [`Expression::capture_map()`] for more details.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ParameterRef<'s> {
+    /// Name of the referenced [`Parameter`].
+    pub name: &'s str,
+
+    /// Byte range of the referenced [`Parameter`] (including its curly braces)
+    /// in the parsed input.
+    pub span: Range<usize>,
+}
+
+/// Capturing groups owned by a [`Parameter`] in a match of an expanded
+/// [`Regex`].
+///
+/// See [`CompiledExpression::captures()`] for more details.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ParameterCaptures<'s, 't> {
+    /// Reference to the [`Parameter`] owning the capturing groups.
+    pub parameter: ParameterRef<'s>,
+
+    /// Matched values of the capturing groups: the one around the
+    /// [`Parameter`] first, followed by the nested ones (if any).
+    ///
+    /// [`None`] for the groups not participating in the match (like the ones
+    /// inside an unmatched [`Optional`]).
+    pub groups: Vec<Option<&'t str>>,
+}
+
+/// Builds [`ParameterRef`]s for all the capturing groups of the given expanded
+/// [`Expression`], where each of its [`Parameter`]s owns the number of groups
+/// returned by the provided `groups` function.
+///
+/// # Errors
+///
+/// If the provided `groups` function errors.
+fn capture_map<'s, F, E>(
+    expr: &Expression<Spanned<'s>>,
+    mut groups: F,
+) -> Result<Vec<ParameterRef<'s>>, E>
+where
+    F: FnMut(&Spanned<'s>) -> Result<usize, E>,
+{
+    let mut map = Vec::new();
+    for e in expr.iter() {
+        if let Some(Parameter(p)) = e.parameter() {
+            let start = p.location_offset() - 1;
+            let param = ParameterRef {
+                name: p.fragment(),
+                span: start..(p.location_offset() + p.fragment().len() + 1),
+            };
+            let len = groups(p)?;
+            map.extend(iter::repeat(param).take(len));
+        }
+    }
+    Ok(map)
+}
+
+/// Options of expanding a [Cucumber Expression][0] into a [`Regex`].
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{ExpandInto as _, Options},
+/// #     Expression,
+/// # };
+/// #
+/// let mut re = String::new();
+/// Expression::parse("I have {word}")
+///     .unwrap()
+///     .expand_into_with(
+///         &mut re,
+///         Options {
+///             anchor_start: false,
+///             ..Options::default()
+///         },
+///     )
+///     .unwrap();
+///
+/// assert_eq!(re, "I have ([^\\s]+)$");
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[allow(clippy::struct_excessive_bools)] // independent flags
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Options {
+    /// Indicator whether the expanded [`Regex`] should be anchored at the
+    /// start of a matched text with `^`.
+    ///
+    /// Disable to embed the expanded [`Regex`] into a larger one, or to match
+    /// it as a substring.
+    pub anchor_start: bool,
+
+    /// Indicator whether the expanded [`Regex`] should be anchored at the end
+    /// of a matched text with `$`.
+    ///
+    /// Disable to embed the expanded [`Regex`] into a larger one, or to match
+    /// it as a substring.
+    pub anchor_end: bool,
+
+    /// Indicator whether the expanded [`Regex`] should match case-insensitively
+    /// by being prefixed with `(?i)`.
+    pub case_insensitive: bool,
+
+    /// Expansion of whitespaces in a [Cucumber Expression][0].
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    pub whitespaces: Whitespaces,
+
+    /// Indicator whether [`Parameter`]s should be expanded into named
+    /// capturing groups (`(?P<name>...)`), derived from their names.
+    ///
+    /// Names are sanitized to contain only ASCII alphanumerics and
+    /// underscores. [`Parameter`]s sharing the same name are numbered in order
+    /// of appearance (`int_1`, `int_2`), while unique ones keep their name as
+    /// is. An anonymous `{}` [`Parameter`] is named `anonymous`.
+    ///
+    /// Naming requires looking at a whole [`Expression`], so it's applied by
+    /// [`ExpandInto`] (and so [`Expression::regex_with_options()`]) and
+    /// [`IntoBoxedRegexCharIter`] only, while [`IntoRegexCharIter`] ignores
+    /// it.
+    pub named_groups: bool,
+
+    /// [`Dialect`] of a regex engine to expand for.
+    pub dialect: Dialect,
+
+    /// Indicator whether the expanded [`Regex`] should be simplified:
+    /// - single-[`char`] [`Optional`]s are expanded without a group (`s?`
+    ///   instead of `(?:s)?`);
+    /// - textual parts of every [`Alternation`] branch are merged, and their
+    ///   common prefix is hoisted out of the group (`(?:apple|apricot)`
+    ///   becomes `ap(?:ple|ricot)`), with duplicated branches removed.
+    ///
+    /// The simplified [`Regex`] matches exactly the same texts, but doesn't
+    /// mirror the [`Expression`] structure anymore.
+    ///
+    /// Applied by [`ExpandInto`] (and so [`Expression::regex_with_options()`])
+    /// only, so [`IntoRegexCharIter`] and [`IntoBoxedRegexCharIter`] panic in
+    /// debug builds once it's enabled, instead of ignoring it silently.
+    pub optimize: bool,
+
+    /// Indicator whether an anonymous `{}` [`Parameter`] should be expanded
+    /// into a lazy `(.*?)` capturing group instead of a greedy `(.*)` one.
+    ///
+    /// Matters when an [`Expression`] contains several anonymous
+    /// [`Parameter`]s: matching `a and b and c` against `{} and {}`, the
+    /// greedy one captures `a and b` and `c`, while the lazy one captures `a`
+    /// and `b and c`. Note, that the last lazy [`Parameter`] matches an empty
+    /// string, unless followed by some text or [`Options::anchor_end`].
+    ///
+    /// To make only some anonymous [`Parameter`]s lazy, use a custom
+    /// [`Parameter`] with `.*?` pattern instead.
+    pub lazy_anonymous: bool,
+
+    /// Indicator whether an anonymous `{}` [`Parameter`] should be expanded
+    /// into a bounded [`SAFE_ANONYMOUS_PATTERN`] (`([^\n]{0,256})`), instead
+    /// of an unbounded `(.*)` one.
+    ///
+    /// Bounds the backtracking of [`Dialect`]s other than [`Dialect::Rust`]
+    /// and [`Dialect::Re2`] when several anonymous [`Parameter`]s and
+    /// [`Optional`]s interact, and never matches newlines, even with an `s`
+    /// flag enabled. Texts longer than 256 characters aren't matched, so use a
+    /// custom [`Parameter`] with the desired bound for them. Combines with
+    /// [`Options::lazy_anonymous`].
+    ///
+    /// See [`Expression::adjacent_anonymous_parameters()`] for detecting
+    /// ambiguous anonymous [`Parameter`]s.
+    pub safe_anonymous: bool,
+
+    /// Indicator whether [`Alternation`]s should be expanded into atomic
+    /// groups (`(?>a|b)`), and [`Optional`]s should be matched possessively
+    /// (`(?:s)?+`), preventing catastrophic backtracking on adversarial
+    /// texts.
+    ///
+    /// Once an atomic group or a possessive [`Optional`] matches, it's never
+    /// backtracked into, so some texts may stop matching (like `ab` matched
+    /// against `a(b)b`).
+    ///
+    /// Applied for [`Dialect::Pcre`] only, as other [`Dialect`]s either don't
+    /// support atomic groups, or don't backtrack at all.
+    pub atomic_groups: bool,
+
+    /// [`NumberFormat`] of numbers matched by `{int}` and `{float}`
+    /// [`Parameter`]s.
+    pub number_format: NumberFormat,
+
+    /// Indicator whether a `{boolean}` [`Parameter`] should match `yes`, `no`,
+    /// `on` and `off` in addition to `true` and `false`.
+    pub extended_booleans: bool,
+
+    /// [`Word`]s matched by a `{word}` [`Parameter`].
+    pub word: Word,
+
+    /// Indicator whether a `{string}` [`Parameter`] should match strings in
+    /// typographic quotes (`“…”` and `‘…’`) too, as produced by word
+    /// processors.
+    pub smart_quotes: bool,
+
+    /// Indicator whether a custom [`Parameter`] with the same name as a
+    /// built-in one (like `{word}` or `{string}`) should fail the expansion
+    /// with an [`Error::Shadowing`], instead of silently taking precedence
+    /// over the built-in one.
+    ///
+    /// Custom [`Parameter`]s are looked up by name only, so just the ones used
+    /// in an [`Expression`] are checked. Applied by [`ExpandInto`] (and so
+    /// [`Expression::regex_with_parameters_and_options()`]) only, so
+    /// [`IntoRegexCharIter`] and [`IntoBoxedRegexCharIter`] panic in debug
+    /// builds once it's enabled, instead of ignoring it silently.
+    pub strict_parameters: bool,
+
+    /// Indicator whether capturing groups (including named ones) inside
+    /// custom [`Parameter`]s should be expanded into non-capturing ones, so
+    /// every [`Parameter`] owns exactly one capturing group of the expanded
+    /// [`Regex`], and group indices of the following [`Parameter`]s don't
+    /// shift.
+    ///
+    /// Custom [`Parameter`]s are always wrapped into a capturing group, so
+    /// alternations (like `red|green|blue`) don't leak into the surrounding
+    /// [`Regex`] regardless of this option. Note, that backreferences to the
+    /// converted groups stop working.
+    ///
+    /// Applied by [`ExpandInto`] (and so
+    /// [`Expression::regex_with_parameters_and_options()`]) only, so
+    /// [`IntoRegexCharIter`] and [`IntoBoxedRegexCharIter`] panic in debug
+    /// builds once it's enabled, instead of ignoring it silently.
+    pub non_capturing_parameters: bool,
+
+    /// Indicator whether [`Parameter`]s named like `{int:3}`, `{word+}` or
+    /// `{int:3,","}` should be expanded into a built-in [`Parameter`] repeated
+    /// the given number of times with a separator, as described by
+    /// [`QuantifiedParameter`].
+    ///
+    /// All the repeated values are captured by a single capturing group,
+    /// which may be split into them with [`QuantifiedParameter::split()`].
+    /// Custom [`Parameter`]s with the same name take precedence.
+    ///
+    /// Applied by [`ExpandInto`] (and so [`Expression::regex_with_options()`])
+    /// only, so [`IntoRegexCharIter`] and [`IntoBoxedRegexCharIter`] panic in
+    /// debug builds once it's enabled, instead of ignoring it silently.
+    pub quantified_parameters: bool,
+
+    /// Indicator whether an [`Expression`] should be parsed with
+    /// [`parse::Syntax::optional_parameters`], allowing an [`Optional`] to
+    /// contain a single [`Parameter`] (like `( with {int} retries)`).
+    ///
+    /// Whenever such [`OptionalParameter`] doesn't match, its capturing group
+    /// doesn't participate in the match, so a [default text][1] of its
+    /// [`Parameter`] may be used instead.
+    ///
+    /// Applied by the functions parsing an [`Expression`] along with
+    /// [`Options`] (like [`Expression::regex_with_options()`] or
+    /// [`ExpressionCompiler::compile()`]), while an already parsed
+    /// [`Expression`] is expanded as is.
+    ///
+    /// [1]: CompiledExpression::with_default()
+    pub optional_parameters: bool,
+}
+
+/// Expansion of whitespaces in a [Cucumber Expression][0] into a [`Regex`].
+///
+/// Doesn't affect [`AnyExpression::Regular`]s, as they are used verbatim.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{ExpandInto as _, Options, Whitespaces},
+/// #     Expression,
+/// # };
+/// #
+/// let mut re = String::new();
+/// Expression::parse("I have {word}")
+///     .unwrap()
+///     .expand_into_with(
+///         &mut re,
+///         Options {
+///             whitespaces: Whitespaces::FlexiblePadded,
+///             ..Options::default()
+///         },
+///     )
+///     .unwrap();
+///
+/// assert_eq!(re, "^\\s*I\\s+have\\s+([^\\s]+)\\s*$");
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Whitespaces {
+    /// Whitespaces are matched literally.
+    Literal,
+
+    /// Every sequence of whitespaces matches one or more whitespaces of any
+    /// kind (`\s+`).
+    ///
+    /// Escaped whitespaces (`\ `) are still matched literally.
+    Flexible,
+
+    /// Same as [`Whitespaces::Flexible`], but additionally allows any leading
+    /// and trailing whitespaces (`\s*`).
+    FlexiblePadded,
+}
+
+/// Dialect of a regex engine to expand a [Cucumber Expression][0] for.
+///
+/// Adjusts escaping, syntax of named capturing groups and inline flags, while
+/// patterns of built-in [`Parameter`]s use only constructs supported by all the
+/// engines.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{Dialect, IntoRegexCharIter as _},
+/// #     Expression,
+/// # };
+/// #
+/// let re = Expression::parse("{int} a\\/b")
+///     .unwrap()
+///     .into_regex_string_for(Dialect::EcmaScript)
+///     .unwrap();
+///
+/// assert_eq!(re, "^((?:-?\\d+)|(?:\\d+)) a\\/b$");
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Dialect {
+    /// [`regex`] and [`regex_lite`] crates.
+    ///
+    /// [`regex_lite`]: https://docs.rs/regex-lite
+    Rust,
+
+    /// [PCRE] and compatible engines.
+    ///
+    /// [PCRE]: https://www.pcre.org
+    Pcre,
+
+    /// [ECMAScript] (JavaScript) `RegExp`, constructed from a [`String`]
+    /// pattern or a literal.
+    ///
+    /// Has no inline flags, so [`Options::case_insensitive`] isn't applied,
+    /// and the `i` flag should be passed to `RegExp` instead (see
+    /// [`ExpandInto::expand_into_pattern_with()`]).
+    ///
+    /// Patterns of custom [`Parameter`]s are used verbatim, so the
+    /// [case-insensitive][1] [`ParameterType`]s, expanded into `(?i:…)`
+    /// groups, produce patterns rejected by `RegExp`, and shouldn't be used
+    /// with this [`Dialect`].
+    ///
+    /// [ECMAScript]: https://tc39.es/ecma262
+    /// [1]: ParameterType::case_insensitive()
+    EcmaScript,
+
+    /// [RE2] and compatible engines (like Go's `regexp`).
+    ///
+    /// [RE2]: https://github.com/google/re2/wiki/Syntax
+    Re2,
+}
+
+impl Dialect {
+    /// Returns [`char`]s escaped with `\` in a matched text for this
+    /// [`Dialect`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::Dialect;
+    /// #
+    /// assert!(!Dialect::Rust.escaped_chars().contains('/'));
+    /// assert!(Dialect::EcmaScript.escaped_chars().contains('/'));
+    /// ```
+    #[must_use]
+    pub const fn escaped_chars(self) -> &'static str {
+        match self {
+            Self::Rust | Self::Pcre | Self::Re2 => "^$[]()\\{}.|?*+",
+            // `/` is escaped to allow using the pattern in a literal too.
+            Self::EcmaScript => "^$[]()\\{}.|?*+/",
+        }
+    }
+
+    /// Returns a start of a named capturing group, preceding its name.
+    const fn named_group_start(self) -> &'static str {
+        match self {
+            Self::Rust | Self::Pcre | Self::Re2 => "(?P<",
+            Self::EcmaScript => "(?<",
+        }
+    }
+
+    /// Indicates whether inline flags (like `(?i)`) are supported.
+    const fn supports_inline_flags(self) -> bool {
+        match self {
+            Self::Rust | Self::Pcre | Self::Re2 => true,
+            Self::EcmaScript => false,
+        }
+    }
+
+    /// Indicates whether atomic groups (`(?>...)`) and possessive quantifiers
+    /// (`?+`) are supported.
+    const fn supports_atomic_groups(self) -> bool {
+        match self {
+            Self::Pcre => true,
+            Self::Rust | Self::EcmaScript | Self::Re2 => false,
+        }
+    }
+}
+
+/// Format of numbers matched by `{int}` and `{float}` [`Parameter`]s, mirroring
+/// locale-aware parameter types of [`cucumber-jvm`][1].
+///
+/// Numbers without thousands separators (like `1000`) are matched by any
+/// [`NumberFormat`], while a decimal separator is always required to be the
+/// one of the [`NumberFormat`].
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{ExpandInto as _, NumberFormat, Options},
+/// #     Expression,
+/// # };
+/// #
+/// let mut re = String::new();
+/// Expression::parse("{int}")
+///     .unwrap()
+///     .expand_into_with(
+///         &mut re,
+///         Options {
+///             number_format: NumberFormat::DotGroupingDecimalComma,
+///             ..Options::default()
+///         },
+///     )
+///     .unwrap();
+///
+/// assert_eq!(re, "^(-?(?:\\d{1,3}(?:\\.\\d{3})+|\\d+))$");
+/// ```
+///
+/// [1]: https://github.com/cucumber/cucumber-jvm
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NumberFormat {
+    /// No thousands separators and `.` decimal separator (like `1000.5`).
+    Plain,
+
+    /// `,` thousands separators and `.` decimal separator (like `1,000.5`), as
+    /// in English locales.
+    CommaGrouping,
+
+    /// `.` thousands separators and `,` decimal separator (like `1.000,5`), as
+    /// in German locales.
+    DotGroupingDecimalComma,
+
+    /// Space thousands separators (including no-break ones) and `,` decimal
+    /// separator (like `1 000,5`), as in French locales.
+    SpaceGroupingDecimalComma,
+}
+
+impl NumberFormat {
+    /// Returns the [`NumberFormat`] used in the given [BCP 47] locale (like
+    /// `en-US` or `de`), if known.
+    ///
+    /// Only the language subtag is considered, so regional variations (like
+    /// `de-CH` using `'` thousands separators) aren't detected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::NumberFormat;
+    /// #
+    /// assert_eq!(
+    ///     NumberFormat::from_locale("en-US"),
+    ///     Some(NumberFormat::CommaGrouping),
+    /// );
+    /// assert_eq!(
+    ///     NumberFormat::from_locale("fr_FR"),
+    ///     Some(NumberFormat::SpaceGroupingDecimalComma),
+    /// );
+    /// assert_eq!(NumberFormat::from_locale("tlh"), None);
+    /// ```
+    ///
+    /// [BCP 47]: https://www.rfc-editor.org/info/bcp47
+    #[must_use]
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let lang = locale.split(&['-', '_'][..]).next()?.to_ascii_lowercase();
+        Some(match lang.as_str() {
+            "en" | "ja" | "zh" | "ko" | "he" | "th" | "hi" | "ms" | "fil"
+            | "ga" => Self::CommaGrouping,
+            "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el"
+            | "ro" | "hr" | "sl" | "sr" | "vi" => Self::DotGroupingDecimalComma,
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "nb" | "no" | "nn"
+            | "fi" | "uk" | "hu" | "bg" | "lt" | "lv" | "et" => {
+                Self::SpaceGroupingDecimalComma
+            }
+            _ => return None,
+        })
+    }
+
+    /// Normalizes the given number formatted in this [`NumberFormat`] into the
+    /// [`Plain`] one, so it can be parsed via [`str::parse()`].
+    ///
+    /// [`Plain`]: NumberFormat::Plain
+    pub(super) fn normalize(self, number: &str) -> Cow<'_, str> {
+        let (grouping, decimal_comma): (&[char], _) = match self {
+            Self::Plain => return Cow::Borrowed(number),
+            Self::CommaGrouping => (&[','], false),
+            Self::DotGroupingDecimalComma => (&['.'], true),
+            Self::SpaceGroupingDecimalComma => {
+                (&[' ', '\u{a0}', '\u{202f}'], true)
+            }
+        };
+        Cow::Owned(
+            number
+                .chars()
+                .filter(|c| !grouping.contains(c))
+                .map(|c| if decimal_comma && c == ',' { '.' } else { c })
+                .collect(),
+        )
+    }
+
+    /// Returns a [`Regex`] pattern of an `{int}` [`Parameter`].
+    const fn int(self) -> &'static str {
+        match self {
+            Self::Plain => INT_PATTERN,
+            Self::CommaGrouping => r"(-?(?:\d{1,3}(?:,\d{3})+|\d+))",
+            Self::DotGroupingDecimalComma => r"(-?(?:\d{1,3}(?:\.\d{3})+|\d+))",
+            Self::SpaceGroupingDecimalComma => {
+                "(-?(?:\\d{1,3}(?:[ \u{a0}\u{202f}]\\d{3})+|\\d+))"
+            }
+        }
+    }
+
+    /// Returns a [`Regex`] pattern of a `{float}` [`Parameter`].
+    const fn float(self) -> &'static str {
+        match self {
+            Self::Plain => FLOAT_PATTERN,
+            Self::CommaGrouping => concat!(
+                r"([-+]?(?:(?:\d{1,3}(?:,\d{3})+|\d*)\.\d+",
+                r"|\d{1,3}(?:,\d{3})+|\d+)(?:[Ee][+-]?\d+)?)",
+            ),
+            Self::DotGroupingDecimalComma => concat!(
+                r"([-+]?(?:(?:\d{1,3}(?:\.\d{3})+|\d*),\d+",
+                r"|\d{1,3}(?:\.\d{3})+|\d+)(?:[Ee][+-]?\d+)?)",
+            ),
+            Self::SpaceGroupingDecimalComma => concat!(
+                "([-+]?(?:(?:\\d{1,3}(?:[ \u{a0}\u{202f}]\\d{3})+|\\d*),\\d+",
+                "|\\d{1,3}(?:[ \u{a0}\u{202f}]\\d{3})+|\\d+)",
+                "(?:[Ee][+-]?\\d+)?)",
+            ),
+        }
+    }
+}
+
+/// Characters of a word matched by a `{word}` [`Parameter`].
+///
+/// To match words of any other characters, define a custom `word`
+/// [`Parameter`] (see [`Expression::with_parameters()`]).
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{ExpandInto as _, Options, Word},
+/// #     Expression,
+/// # };
+/// #
+/// let mut re = String::new();
+/// Expression::parse("I pick {word}")
+///     .unwrap()
+///     .expand_into_with(
+///         &mut re,
+///         Options {
+///             word: Word::WordChars,
+///             ..Options::default()
+///         },
+///     )
+///     .unwrap();
+///
+/// assert_eq!(re, "^I pick ([\\w-]+)$");
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Word {
+    /// Any non-whitespace characters (`[^\s]+`), including punctuation.
+    NonWhitespace,
+
+    /// Any non-whitespace characters, except `.`, `,`, `;`, `:`, `!` and `?`
+    /// punctuation (`[^\s.,;:!?]+`), so a word stops before them.
+    NonPunctuation,
+
+    /// Letters, digits, `_` and `-` only (`[\w-]+`).
+    WordChars,
+}
+
+impl Word {
+    /// Returns a [`Regex`] pattern of a `{word}` [`Parameter`].
+    const fn pattern(self) -> &'static str {
+        match self {
+            Self::NonWhitespace => WORD_PATTERN,
+            Self::NonPunctuation => r"([^\s.,;:!?]+)",
+            Self::WordChars => r"([\w-]+)",
+        }
+    }
+}
+
+/// [`Regex`] pattern along with [`RegexFlags`] to be passed separately to a
+/// regex engine, instead of being inlined into the pattern.
+///
+/// Useful for engines accepting flags as a separate argument, like
+/// JavaScript's `new RegExp(pattern, flags)` or Python's
+/// `re.compile(pattern, flags)`.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{
+/// #     expand::{Dialect, ExpandInto as _, Options},
+/// #     Expression,
+/// # };
+/// #
+/// let re = Expression::parse("I have {int}")
+///     .unwrap()
+///     .expand_into_pattern_with(Options {
+///         case_insensitive: true,
+///         dialect: Dialect::EcmaScript,
+///         ..Options::default()
+///     })
+///     .unwrap();
+///
+/// assert_eq!(re.pattern, "^I have ((?:-?\\d+)|(?:\\d+))$");
+/// assert_eq!(re.flags.to_string(), "i");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RegexPattern {
+    /// [`Regex`] pattern without any inline flags.
+    pub pattern: String,
+
+    /// Flags to compile the [`RegexPattern::pattern`] with.
+    pub flags: RegexFlags,
+}
+
+/// Flags of a [`RegexPattern`].
+///
+/// [`Display`]s as a string of [ECMAScript] flags (like `i`).
+///
+/// [`Display`]: fmt::Display
+/// [ECMAScript]: https://tc39.es/ecma262
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RegexFlags {
+    /// Indicator whether a [`RegexPattern`] should match case-insensitively.
+    pub case_insensitive: bool,
+}
+
+impl fmt::Display for RegexFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.case_insensitive {
+            f.write_str("i")?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            anchor_start: true,
+            anchor_end: true,
+            case_insensitive: false,
+            whitespaces: Whitespaces::Literal,
+            named_groups: false,
+            dialect: Dialect::Rust,
+            optimize: false,
+            lazy_anonymous: false,
+            safe_anonymous: false,
+            atomic_groups: false,
+            number_format: NumberFormat::Plain,
+            extended_booleans: false,
+            word: Word::NonWhitespace,
+            smart_quotes: false,
+            strict_parameters: false,
+            non_capturing_parameters: false,
+            quantified_parameters: false,
+            optional_parameters: false,
+        }
+    }
+}
+
+impl Options {
+    /// Asserts the [`Options`] applied by [`ExpandInto`] only to be disabled,
+    /// so [`IntoRegexCharIter`] and [`IntoBoxedRegexCharIter`] don't ignore
+    /// them silently.
+    fn debug_assert_iterable(self) {
+        debug_assert!(
+            !self.optimize
+                && !self.strict_parameters
+                && !self.non_capturing_parameters
+                && !self.quantified_parameters,
+            "`Options::optimize`, `Options::strict_parameters`, \
+             `Options::non_capturing_parameters` and \
+             `Options::quantified_parameters` are supported by `ExpandInto` \
+             only, not by expanding into a `char`s `Iterator`",
+        );
+    }
+
+    /// Returns the [`parse::Syntax`] to parse an [`Expression`] with.
+    const fn syntax(self) -> parse::Syntax {
+        parse::Syntax {
+            optional_parameters: self.optional_parameters,
+            ..parse::Syntax::CUCUMBER
+        }
+    }
+
+    /// Returns a [`Regex`] pattern preceding the expanded [`Expression`].
+    #[must_use]
+    pub const fn prefix(self) -> &'static str {
+        let case_insensitive =
+            self.case_insensitive && self.dialect.supports_inline_flags();
+        match (case_insensitive, self.anchor_start) {
+            (true, true) => "(?i)^",
+            (true, false) => "(?i)",
+            (false, true) => "^",
+            (false, false) => "",
+        }
+    }
+
+    /// Returns a [`Regex`] pattern following the expanded [`Expression`].
+    #[must_use]
+    pub const fn suffix(self) -> &'static str {
+        if self.anchor_end {
+            "$"
+        } else {
+            ""
+        }
+    }
+
+    /// Returns a [`Regex`] pattern padding the expanded [`Expression`] inside
+    /// its [`Options::prefix()`] and [`Options::suffix()`].
+    const fn padding(self) -> &'static str {
+        match self.whitespaces {
+            Whitespaces::FlexiblePadded => r"\s*",
+            Whitespaces::Literal | Whitespaces::Flexible => "",
+        }
+    }
+
+    /// Indicates whether atomic groups should be used.
+    const fn atomic(self) -> bool {
+        self.atomic_groups && self.dialect.supports_atomic_groups()
+    }
+
+    /// Returns a start of a group an [`Alternation`] is expanded into.
+    const fn alternation_start(self) -> &'static str {
+        if self.atomic() {
+            "(?>"
+        } else {
+            "(?:"
+        }
+    }
+
+    /// Returns a quantifier an [`Optional`] is expanded with.
+    const fn optional_quantifier(self) -> &'static str {
+        if self.atomic() {
+            "?+"
+        } else {
+            "?"
+        }
+    }
+
+    /// Returns an end of a group an [`Optional`] is expanded into.
+    const fn optional_end(self) -> &'static str {
+        if self.atomic() {
+            ")?+"
+        } else {
+            ")?"
+        }
+    }
+
+    /// Returns a [`Regex`] pattern [`SingleExpression::Whitespaces`] are
+    /// expanded into, unless they should be matched literally.
+    const fn whitespaces(self) -> Option<&'static str> {
+        match self.whitespaces {
+            Whitespaces::Literal => None,
+            Whitespaces::Flexible | Whitespaces::FlexiblePadded => Some(r"\s+"),
+        }
+    }
+}
+
+/// [`Iterator`] over an [`Options::prefix()`] followed by an
+/// [`Options::padding()`] of an [`Expression`].
+type ExpressionPrefixIter<Input> = iter::Map<
+    iter::Chain<str::Chars<'static>, str::Chars<'static>>,
+    MapOkChar<Input>,
+>;
+
+/// [`Iterator`] over an [`Options::padding()`] followed by an
+/// [`Options::suffix()`] of an [`Expression`].
+type ExpressionSuffixIter<Input> = ExpressionPrefixIter<Input>;
+
+/// Creates a new [`ExpressionPrefixIter`] for the given [`Options`].
+fn expression_prefix_iter<Input>(
+    options: Options,
+) -> ExpressionPrefixIter<Input>
+where
+    Input: fmt::Display,
+{
+    options
+        .prefix()
+        .chars()
+        .chain(options.padding().chars())
+        .map(Ok)
+}
+
+/// Creates a new [`ExpressionSuffixIter`] for the given [`Options`].
+fn expression_suffix_iter<Input>(
+    options: Options,
+) -> ExpressionSuffixIter<Input>
+where
+    Input: fmt::Display,
+{
+    options
+        .padding()
+        .chars()
+        .chain(options.suffix().chars())
+        .map(Ok)
+}
+
+/// Returns a lower bound of the [`Regex`] pattern length wrapping an expanded
+/// [`Expression`] with the given [`Options`].
+const fn expression_affixes_len(options: Options) -> usize {
+    options.prefix().len()
+        + 2 * options.padding().len()
+        + options.suffix().len()
+}
+
+/// Expansion of a [Cucumber Expressions][0] [AST] element into a [`Regex`] by
+/// producing a [`char`]s [`Iterator`] following original [production rules][1].
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [1]: https://git.io/J159T
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub trait IntoRegexCharIter<Input: fmt::Display> {
+    /// Type of an [`Iterator`] performing the expansion.
+    type Iter: Iterator<Item = Result<char, UnknownParameterError<Input>>>;
+
+    /// Consumes this [AST] element returning an [`Iterator`] over [`char`]s
+    /// transformable into a [`Regex`].
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_char_iter(self) -> Self::Iter
+    where
+        Self: Sized,
+    {
+        self.into_regex_char_iter_with(Options::default())
+    }
+
+    /// Consumes this [AST] element returning an [`Iterator`] over [`char`]s
+    /// transformable into a [`Regex`], expanded with the given [`Options`].
+    ///
+    /// [`Options`] related to a whole [`Expression`] (like anchors) are ignored
+    /// when expanding its inner [AST] nodes.
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter;
+
+    /// Consumes this [AST] element expanding it into a [`String`] pattern,
+    /// without compiling it into a [`Regex`].
+    ///
+    /// Useful for feeding the pattern into a different regex engine.
+    ///
+    /// # Errors
+    ///
+    /// If this [AST] element contains an unknown [`Parameter`].
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_string(self) -> Result<String, UnknownParameterError<Input>>
+    where
+        Self: Sized,
+    {
+        self.into_regex_string_for(Dialect::Rust)
+    }
+
+    /// Consumes this [AST] element expanding it into a [`String`] pattern for
+    /// the given target [`Dialect`].
+    ///
+    /// # Errors
+    ///
+    /// If this [AST] element contains an unknown [`Parameter`].
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn into_regex_string_for(
+        self,
+        dialect: Dialect,
+    ) -> Result<String, UnknownParameterError<Input>>
+    where
+        Self: Sized,
+    {
+        let iter = self.into_regex_char_iter_with(Options {
+            dialect,
+            ..Options::default()
+        });
+        let mut re = String::with_capacity(iter.size_hint().0);
+        for c in iter {
+            re.push(c?);
+        }
+        Ok(re)
+    }
+}
+
+/// Expansion of a [Cucumber Expressions][0] [AST] element into a [`Regex`] by
+/// writing it directly into a [`fmt::Write`]r, following original
+/// [production rules][1].
+///
+/// Unlike [`IntoRegexCharIter`], doesn't consume the [AST] element and avoids
+/// going through a [`char`]-by-[`char`] [`Iterator`], so is cheaper when
+/// expanding lots of [`Expression`]s.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{expand::ExpandInto as _, Expression};
+/// #
+/// let expr = Expression::parse("I have {int} cucumber(s)").unwrap();
+///
+/// let mut re = String::new();
+/// expr.expand_into(&mut re).unwrap();
+///
+/// assert_eq!(re, "^I have ((?:-?\\d+)|(?:\\d+)) cucumber(?:s)?$");
+/// ```
+///
+/// [0]: https://github.com/cucumber/cucumber-expressions#readme
+/// [1]: https://git.io/J159T
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub trait ExpandInto<Input: fmt::Display> {
+    /// Writes this [AST] element expanded into a [`Regex`] pattern to the
+    /// given `w`riter.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Expansion`] if this [AST] element contains an unknown
+    ///   [`Parameter`];
+    /// - [`Error::Shadowing`] if a custom [`Parameter`] shadows a built-in
+    ///   one, while [`Options::strict_parameters`] is enabled;
+    /// - [`Error::Formatting`] if the `w`riter fails.
+    ///
+    /// On error, the `w`riter may contain a partially expanded pattern.
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn expand_into<W>(&self, w: &mut W) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        self.expand_into_with(w, Options::default())
+    }
+
+    /// Writes this [AST] element expanded into a [`Regex`] pattern with the
+    /// given [`Options`] to the given `w`riter.
+    ///
+    /// [`Options`] related to a whole [`Expression`] (like anchors) are ignored
+    /// when expanding its inner [AST] nodes.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ExpandInto::expand_into()`] does.
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized;
+
+    /// Expands this [AST] element into a [`RegexPattern`] with the given
+    /// [`Options`], returning flags (like [`Options::case_insensitive`])
+    /// separately instead of inlining them into the pattern.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ExpandInto::expand_into()`] does.
+    ///
+    /// [AST]: https://github.com/cucumber/cucumber-expressions#readme
+    fn expand_into_pattern_with(
+        &self,
+        options: Options,
+    ) -> Result<RegexPattern, Error<Input>> {
+        let mut pattern = String::new();
+        self.expand_into_with(
+            &mut pattern,
+            Options {
+                case_insensitive: false,
+                ..options
+            },
+        )?;
+        Ok(RegexPattern {
+            pattern,
+            flags: RegexFlags {
+                case_insensitive: options.case_insensitive,
+            },
+        })
+    }
+}
+
+impl<Input, T> ExpandInto<Input> for &T
+where
+    Input: fmt::Display,
+    T: ExpandInto<Input> + ?Sized,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        (**self).expand_into_with(w, options)
+    }
+}
+
+/// Object-safe companion of [`ExpandInto`], writing an [AST] element expanded
+/// into a [`Regex`] pattern to a `dyn `[`fmt::Write`]r.
+///
+/// Implemented for every [`ExpandInto`] implementor, so allows storing
+/// heterogeneous [AST] elements (like [`Expression`]s along with
+/// [`Parameter`]s with custom `Parameters`) as `Box<dyn WriteRegex<_>>`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// #
+/// # use cucumber_expressions::{
+/// #     expand::{WithCustomParameters, WriteRegex},
+/// #     AnyExpression, Expression, Spanned,
+/// # };
+/// #
+/// let pars = HashMap::from([("color", "red|blue")]);
+/// let elements: Vec<Box<dyn WriteRegex<Spanned<'_>>>> = vec![
+///     Box::new(Expression::parse("{int} cucumbers").unwrap()),
+///     Box::new(AnyExpression::parse("/^\\d+$/").unwrap()),
+///     Box::new(WithCustomParameters {
+///         element: Expression::parse("{color}").unwrap(),
+///         parameters: &pars,
+///     }),
+/// ];
+///
+/// let mut re = String::new();
+/// for e in &elements {
+///     e.write_regex(&mut re).unwrap();
+///     re.push('\n');
+/// }
+///
+/// // Own anchors of a regular expression are kept along with the added ones.
+/// assert_eq!(
+///     re,
+///     "^((?:-?\\d+)|(?:\\d+)) cucumbers$\n\
+///      ^(?:^\\d+$)$\n\
+///      ^(red|blue)$\n",
+/// );
+/// ```
+///
+/// [`Regex`]: regex::Regex
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+pub trait WriteRegex<Input: fmt::Display> {
+    /// Writes this [AST] element expanded into a [`Regex`] pattern to the
+    /// given `w`riter.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ExpandInto::expand_into()`] does.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+    fn write_regex(&self, w: &mut dyn fmt::Write) -> Result<(), Error<Input>> {
+        self.write_regex_with(w, Options::default())
+    }
+
+    /// Writes this [AST] element expanded into a [`Regex`] pattern with the
+    /// given [`Options`] to the given `w`riter.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ExpandInto::expand_into()`] does.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+    fn write_regex_with(
+        &self,
+        w: &mut dyn fmt::Write,
+        options: Options,
+    ) -> Result<(), Error<Input>>;
+}
+
+impl<Input, T> WriteRegex<Input> for T
+where
+    Input: fmt::Display,
+    T: ExpandInto<Input> + ?Sized,
+{
+    fn write_regex_with(
+        &self,
+        w: &mut dyn fmt::Write,
+        options: Options,
+    ) -> Result<(), Error<Input>> {
+        self.expand_into_with(w, options)
+    }
+}
+
+impl<Input> ExpandInto<Input> for Expression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        let mut names = group_names(self, options).into_iter();
+
+        w.write_str(options.prefix())?;
+        w.write_str(options.padding())?;
+        for e in &self.0 {
+            match e {
+                SingleExpression::Parameter(p) => {
+                    let name = names.next();
+                    expand_builtin_parameter_into(
+                        p,
+                        options,
+                        name.as_deref(),
+                        w,
+                    )?;
+                }
+                SingleExpression::OptionalParameter(opt) => {
+                    let name = names.next();
+                    expand_optional_parameter_into(opt, options, w, |p, w| {
+                        expand_builtin_parameter_into(
+                            p,
+                            options,
+                            name.as_deref(),
+                            w,
+                        )
+                    })?;
+                }
+                SingleExpression::Alternation(_)
+                | SingleExpression::Optional(_)
+                | SingleExpression::Text(_)
+                | SingleExpression::Whitespaces(_) => {
+                    e.expand_into_with(w, options)?;
+                }
+            }
+        }
+        w.write_str(options.padding())?;
+        Ok(w.write_str(options.suffix())?)
+    }
+}
+
+/// [`AnyExpression::Regular`] is wrapped into a non-capturing group and
+/// anchored even if it has its own anchors already, as they may belong to its
+/// top-level alternatives only (like in `^a|b$`).
+impl<Input> ExpandInto<Input> for AnyExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match self {
+            Self::Cucumber(expr) => expr.expand_into_with(w, options),
+            Self::Regular(re) => {
+                w.write_str(options.prefix())?;
+                w.write_str("(?:")?;
+                for c in re.iter_elements() {
+                    w.write_char(c.as_char())?;
+                }
+                w.write_char(')')?;
+                Ok(w.write_str(options.suffix())?)
+            }
+        }
+    }
+}
+
+impl<Input> ExpandInto<Input> for SingleExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match self {
+            Self::Alternation(alt) => alt.expand_into_with(w, options),
+            Self::Optional(opt) => opt.expand_into_with(w, options),
+            Self::OptionalParameter(opt) => opt.expand_into_with(w, options),
+            Self::Parameter(p) => p.expand_into_with(w, options),
+            Self::Whitespaces(t) => Ok(match options.whitespaces() {
+                Some(re) => w.write_str(re),
+                None => write_escaped_for_regex(w, t, options.dialect),
+            }?),
+            Self::Text(t) => {
+                Ok(write_escaped_for_regex(w, t, options.dialect)?)
+            }
+        }
+    }
+}
+
+impl<Input> ExpandInto<Input> for Alternation<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        if options.optimize {
+            if let Some(literals) = optimize::literals(self) {
+                return Ok(optimize::write_hoisted(w, &literals, options)?);
+            }
+        }
+
+        w.write_str(options.alternation_start())?;
+        for (n, alt) in self.0.iter().enumerate() {
+            if n > 0 {
+                w.write_char('|')?;
+            }
+            for a in alt {
+                a.expand_into_with(w, options)?;
+            }
+        }
+        Ok(w.write_char(')')?)
+    }
+}
+
+impl<Input> ExpandInto<Input> for Alternative<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match self {
+            Self::Optional(opt) => opt.expand_into_with(w, options),
+            Self::Text(text) => {
+                Ok(write_escaped_for_regex(w, text, options.dialect)?)
+            }
+        }
+    }
+}
+
+impl<Input> ExpandInto<Input> for Optional<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        if options.optimize {
+            if let Some(c) = optimize::single_char(self) {
+                let mut buf = [0; 4];
+                optimize::write_escaped(
+                    w,
+                    c.encode_utf8(&mut buf),
+                    options.dialect,
+                )?;
+                return Ok(w.write_str(options.optional_quantifier())?);
+            }
+        }
+
+        w.write_str("(?:")?;
+        write_escaped_for_regex(w, &self.0, options.dialect)?;
+        Ok(w.write_str(options.optional_end())?)
+    }
+}
+
+impl<Input> ExpandInto<Input> for OptionalParameter<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_optional_parameter_into(self, options, w, |p, w| {
+            p.expand_into_with(w, options)
+        })
+    }
+}
+
+impl<Input> ExpandInto<Input> for Parameter<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_builtin_parameter_into(self, options, None, w)
+    }
+}
+
+/// Writes the built-in [`Parameter`] into the provided [`fmt::Write`]r as a
+/// capturing group with the given [`Options`], named with the given `name`, if
+/// any.
+///
+/// # Errors
+///
+/// If the [`Parameter`] isn't a built-in one or writing fails.
+fn expand_builtin_parameter_into<Input, W>(
+    p: &Parameter<Input>,
+    options: Options,
+    name: Option<&str>,
+    w: &mut W,
+) -> Result<(), Error<Input>>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    W: fmt::Write + ?Sized,
+{
+    let re = builtin_or_quantified_parameter(&p.0, options)
+        .ok_or_else(|| UnknownParameterError::new(p.0.clone(), Vec::new()))?;
+    write_group_start(w, name, options.dialect)?;
+    // All the built-in `Parameter`s are capturing groups already.
+    Ok(w.write_str(re.strip_prefix('(').unwrap_or(&re))?)
+}
+
+/// Expands the given [`OptionalParameter`] into the provided
+/// [`fmt::Write`]r, expanding its [`Parameter`] with the given `parameter`
+/// function.
+///
+/// # Errors
+///
+/// If writing or the `parameter` function fails.
+fn expand_optional_parameter_into<Input, W, E>(
+    opt: &OptionalParameter<Input>,
+    options: Options,
+    w: &mut W,
+    parameter: impl FnOnce(&Parameter<Input>, &mut W) -> Result<(), E>,
+) -> Result<(), E>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    W: fmt::Write + ?Sized,
+    E: From<fmt::Error>,
+{
+    w.write_str("(?:")?;
+    write_escaped_for_regex(w, &opt.prefix, options.dialect)?;
+    parameter(&opt.parameter, w)?;
+    write_escaped_for_regex(w, &opt.suffix, options.dialect)?;
+    Ok(w.write_str(options.optional_end())?)
+}
+
+/// Writes a start of a capturing group of the given [`Dialect`] into the
+/// provided [`fmt::Write`]r, named with the given `name`, if any.
+///
+/// # Errors
+///
+/// If writing fails.
+fn write_group_start<W>(
+    w: &mut W,
+    name: Option<&str>,
+    dialect: Dialect,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    if let Some(n) = name {
+        w.write_str(dialect.named_group_start())?;
+        w.write_str(n)?;
+        w.write_char('>')
+    } else {
+        w.write_char('(')
+    }
+}
+
+/// Returns names of capturing groups for all the [`Parameter`]s of the given
+/// [`Expression`] in order of their appearance, if [`Options::named_groups`]
+/// are enabled, or nothing otherwise.
+///
+/// See [`Options::named_groups`] for the naming rules.
+fn group_names<Input>(expr: &Expression<Input>, options: Options) -> Vec<String>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    if !options.named_groups {
+        return Vec::new();
+    }
+
+    let bases = expr
+        .iter()
+        .filter_map(|e| e.parameter().map(|p| group_name(&p.0)))
+        .collect::<Vec<_>>();
+
+    let mut taken = bases.iter().cloned().collect::<HashSet<_>>();
+    let mut counters = HashMap::<&str, usize>::new();
+    bases
+        .iter()
+        .map(|base| {
+            if bases.iter().filter(|b| *b == base).count() == 1 {
+                return base.clone();
+            }
+            let counter = counters.entry(base).or_insert(0);
+            loop {
+                *counter += 1;
+                let name = format!("{}_{}", base, counter);
+                if taken.insert(name.clone()) {
+                    return name;
+                }
+            }
+        })
+        .collect()
+}
+
+/// Sanitizes the given [`Parameter`] `name` into a valid name of a [`Regex`]
+/// capturing group.
+fn group_name<Input>(name: &Input) -> String
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let mut sanitized = name
+        .iter_elements()
+        .map(AsChar::as_char)
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if sanitized.is_empty() {
+        sanitized.push_str("anonymous");
+    }
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+/// Writes the given `text` into the `w`riter, escaped for the given
+/// [`Dialect`] the same way as [`EscapeForRegex`] does.
+///
+/// The `text` is written in chunks of its [`fmt::Display`] implementation (so
+/// should render it verbatim), with runs of [`char`]s not requiring escaping
+/// written at once.
+fn write_escaped_for_regex<W, Input>(
+    w: &mut W,
+    text: &Input,
+    dialect: Dialect,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+    Input: fmt::Display,
+{
+    let mut escaping = EscapingWriter {
+        w,
+        escaped_chars: dialect.escaped_chars(),
+        backslash: false,
+    };
+    fmt::Write::write_fmt(&mut escaping, format_args!("{}", text))
+}
+
+/// [`fmt::Write`]r escaping everything written into it for a [`Regex`] the
+/// same way as [`EscapeForRegex`] does, before passing it to the inner one.
+struct EscapingWriter<'w, W: ?Sized> {
+    /// Inner [`fmt::Write`]r to pass the escaped text into.
+    w: &'w mut W,
+
+    /// [`char`]s to be escaped.
+    escaped_chars: &'static str,
+
+    /// Indicator whether the previous chunk ended with a `\\`, escaping the
+    /// first [`char`] of the next one.
+    backslash: bool,
+}
+
+impl<W: fmt::Write + ?Sized> fmt::Write for EscapingWriter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let escaped_chars = self.escaped_chars;
+        let should_be_escaped = |c| escaped_chars.contains(c);
+
+        let mut rest = s;
+        if self.backslash {
+            if let Some(c) = rest.chars().next() {
+                self.backslash = false;
+                if should_be_escaped(c) {
+                    self.w.write_char('\\')?;
+                    self.w.write_char(c)?;
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        while let Some(i) = rest.find(|c| c == '\\' || should_be_escaped(c)) {
+            self.w.write_str(&rest[..i])?;
+            let c = rest[i..].chars().next().unwrap_or_default();
+            rest = &rest[(i + c.len_utf8())..];
+            if c != '\\' {
+                self.w.write_char('\\')?;
+                self.w.write_char(c)?;
+                continue;
+            }
+            match rest.chars().next() {
+                Some(next) if should_be_escaped(next) => {
+                    self.w.write_char('\\')?;
+                    self.w.write_char(next)?;
+                    rest = &rest[next.len_utf8()..];
+                }
+                Some(_) => {}
+                None => self.backslash = true,
+            }
+        }
+        self.w.write_str(rest)
+    }
+}
+
+impl<Input> IntoRegexCharIter<Input> for Expression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = ExpressionIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let into_regex_char_iter: fn(_) -> _ =
+            |(e, opts): (SingleExpression<Input>, _)| {
+                e.into_regex_char_iter_with(opts)
+            };
+
+        let lower = expression_affixes_len(options) + self.min_regex_len();
+        SizeHinted::new(
+            expression_prefix_iter(options)
+                .chain(
+                    self.0
+                        .into_iter()
+                        .zip(iter::repeat(options))
+                        .flat_map(into_regex_char_iter),
+                )
+                .chain(expression_suffix_iter(options)),
+            lower,
+        )
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`Expression`].
+type ExpressionIter<Input> =
+    SizeHinted<
+        iter::Chain<
+            iter::Chain<
+                ExpressionPrefixIter<Input>,
+                iter::FlatMap<
+                    iter::Zip<
+                        vec::IntoIter<SingleExpression<Input>>,
+                        iter::Repeat<Options>,
+                    >,
+                    <SingleExpression<Input> as IntoRegexCharIter<Input>>::Iter,
+                    fn(
+                        (SingleExpression<Input>, Options),
+                    )
+                        -> <SingleExpression<Input> as IntoRegexCharIter<
+                        Input,
+                    >>::Iter,
+                >,
+            >,
+            ExpressionSuffixIter<Input>,
+        >,
+    >;
+
+impl<'e, Input> IntoRegexCharIter<Input> for &'e Expression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = ExpressionRefIter<'e, Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let into_regex_char_iter: fn(_) -> _ =
+            |(e, opts): (&'e SingleExpression<Input>, _)| {
+                e.into_regex_char_iter_with(opts)
+            };
+
+        SizeHinted::new(
+            expression_prefix_iter(options)
+                .chain(
+                    self.0
+                        .iter()
+                        .zip(iter::repeat(options))
+                        .flat_map(into_regex_char_iter),
+                )
+                .chain(expression_suffix_iter(options)),
+            expression_affixes_len(options) + self.min_regex_len(),
+        )
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a borrowed [`Expression`].
+type ExpressionRefIter<'e, Input> = SizeHinted<
+    iter::Chain<
+        iter::Chain<
+            ExpressionPrefixIter<Input>,
+            iter::FlatMap<
+                iter::Zip<
+                    slice::Iter<'e, SingleExpression<Input>>,
+                    iter::Repeat<Options>,
+                >,
+                <&'e SingleExpression<Input> as IntoRegexCharIter<Input>>::Iter,
+                fn(
+                    (&'e SingleExpression<Input>, Options),
+                )
+                    -> <&'e SingleExpression<Input> as IntoRegexCharIter<
+                    Input,
+                >>::Iter,
+            >,
+        >,
+        ExpressionSuffixIter<Input>,
+    >,
+>;
+
+impl<Input> IntoRegexCharIter<Input> for AnyExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = AnyExpressionIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self {
+            Self::Cucumber(expr) => {
+                Left(expr.into_regex_char_iter_with(options))
+            }
+            Self::Regular(re) => Right(regular_expression_iter(&re, options)),
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`AnyExpression`].
+type AnyExpressionIter<Input> =
+    Either<ExpressionIter<Input>, RegularExpressionIter<Input>>;
+
+impl<'e, Input> IntoRegexCharIter<Input> for &'e AnyExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = AnyExpressionRefIter<'e, Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self {
+            AnyExpression::Cucumber(expr) => {
+                Left(expr.into_regex_char_iter_with(options))
+            }
+            AnyExpression::Regular(re) => {
+                Right(regular_expression_iter(re, options))
+            }
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a borrowed [`AnyExpression`].
+type AnyExpressionRefIter<'e, Input> =
+    Either<ExpressionRefIter<'e, Input>, RegularExpressionIter<Input>>;
+
+/// [`Iterator`] over a verbatim regular expression wrapped into anchors.
+type RegularExpressionIter<Input> = SizeHinted<
+    iter::Map<
+        iter::Chain<
+            iter::Chain<
+                iter::Chain<
+                    iter::Chain<str::Chars<'static>, str::Chars<'static>>,
+                    iter::Map<
+                        <Input as InputIter>::IterElem,
+                        fn(<Input as InputIter>::Item) -> char,
+                    >,
+                >,
+                str::Chars<'static>,
+            >,
+            str::Chars<'static>,
+        >,
+        MapOkChar<Input>,
+    >,
+>;
+
+/// Creates a new [`RegularExpressionIter`] from the given `re`, wrapping it
+/// according to the given [`Options`].
+fn regular_expression_iter<Input>(
+    re: &Input,
+    options: Options,
+) -> RegularExpressionIter<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+
+    SizeHinted::new(
+        options
+            .prefix()
+            .chars()
+            .chain("(?:".chars())
+            .chain(re.iter_elements().map(as_char))
+            .chain(")".chars())
+            .chain(options.suffix().chars())
+            .map(Ok),
+        options.prefix().len()
+            + "(?:)".len()
+            + re.iter_elements().count()
+            + options.suffix().len(),
+    )
+}
+
+impl<Input> IntoRegexCharIter<Input> for SingleExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = SingleExpressionIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        let ok: fn(_) -> _ = Ok;
+        let as_char: fn(_) -> _ = AsChar::as_char;
+
+        let escaped = |t: Input| {
+            EscapeForRegex::for_dialect(
+                t.iter_elements().map(as_char),
+                options.dialect,
+            )
+            .map(ok)
+        };
+
+        match self {
+            Self::Alternation(alt) => {
+                Left(alt.into_regex_char_iter_with(options))
+            }
+            Self::Optional(opt) => {
+                Right(Left(opt.into_regex_char_iter_with(options)))
+            }
+            Self::Parameter(p) => {
+                Right(Right(Left(Left(p.into_regex_char_iter_with(options)))))
+            }
+            Self::OptionalParameter(opt) => Right(Right(Left(Right(
+                opt.into_regex_char_iter_with(options),
+            )))),
+            Self::Whitespaces(t) => {
+                Right(Right(Right(options.whitespaces().map_or_else(
+                    || Right(escaped(t)),
+                    |re| Left(re.chars().map(ok)),
+                ))))
+            }
+            Self::Text(t) => Right(Right(Right(Right(escaped(t))))),
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a [`SingleExpression`].
+type SingleExpressionIter<Input> = Either<
+    <Alternation<Input> as IntoRegexCharIter<Input>>::Iter,
+    Either<
+        <Optional<Input> as IntoRegexCharIter<Input>>::Iter,
+        Either<
+            Either<
+                <Parameter<Input> as IntoRegexCharIter<Input>>::Iter,
+                <OptionalParameter<Input> as IntoRegexCharIter<Input>>::Iter,
+            >,
+            Either<
+                iter::Map<str::Chars<'static>, MapOkChar<Input>>,
+                iter::Map<
+                    EscapeForRegex<
+                        iter::Map<
+                            <Input as InputIter>::IterElem,
+                            fn(<Input as InputIter>::Item) -> char,
+                        >,
+                    >,
+                    MapOkChar<Input>,
+                >,
+            >,
+        >,
+    >,
+>;
+
+impl<'e, Input> IntoRegexCharIter<Input> for &'e SingleExpression<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = SingleExpressionRefIter<'e, Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        if let SingleExpression::Alternation(alt) = self {
+            Left(alt.into_regex_char_iter_with(options))
+        } else {
+            Right(self.clone().into_regex_char_iter_with(options))
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a borrowed [`SingleExpression`].
+///
+/// Only [`Alternation`]s are expanded by reference, as all the other
+/// [`SingleExpression`]s are cheap to clone.
+type SingleExpressionRefIter<'e, Input> = Either<
+    <&'e Alternation<Input> as IntoRegexCharIter<Input>>::Iter,
+    SingleExpressionIter<Input>,
+>;
+
+impl<Input> IntoRegexCharIter<Input> for Alternation<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = AlternationIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let ok: fn(_) -> _ = Ok;
+        let single_alt: fn((SingleAlternation<Input>, _)) -> _ =
+            |(alt, opts)| {
+                let into_regex_char_iter: fn((Alternative<Input>, _)) -> _ =
+                    |(a, o)| a.into_regex_char_iter_with(o);
+
+                alt.into_iter()
+                    .zip(iter::repeat(opts))
+                    .flat_map(into_regex_char_iter)
+                    .chain(iter::once(Ok('|')))
+            };
+
+        options
+            .alternation_start()
+            .chars()
+            .map(ok)
+            .chain(SkipLast::new(
+                self.0
+                    .into_iter()
+                    .zip(iter::repeat(options))
+                    .flat_map(single_alt),
+            ))
+            .chain(iter::once(Ok(')')))
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`Alternation`].
+type AlternationIter<I> = iter::Chain<
+    iter::Chain<
+        iter::Map<str::Chars<'static>, MapOkChar<I>>,
+        SkipLast<
+            iter::FlatMap<
+                iter::Zip<
+                    vec::IntoIter<SingleAlternation<I>>,
+                    iter::Repeat<Options>,
+                >,
+                AlternationIterInner<I>,
+                fn((SingleAlternation<I>, Options)) -> AlternationIterInner<I>,
+            >,
+        >,
+    >,
+    iter::Once<Result<char, UnknownParameterError<I>>>,
+>;
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// Inner type of an [`AlternationIter`].
+type AlternationIterInner<I> = iter::Chain<
+    iter::FlatMap<
+        iter::Zip<vec::IntoIter<Alternative<I>>, iter::Repeat<Options>>,
+        <Alternative<I> as IntoRegexCharIter<I>>::Iter,
+        fn(
+            (Alternative<I>, Options),
+        ) -> <Alternative<I> as IntoRegexCharIter<I>>::Iter,
+    >,
+    iter::Once<Result<char, UnknownParameterError<I>>>,
+>;
+
+impl<'e, Input> IntoRegexCharIter<Input> for &'e Alternation<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = AlternationRefIter<'e, Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let ok: fn(_) -> _ = Ok;
+        let single_alt: fn((&'e SingleAlternation<Input>, _)) -> _ =
+            |(alt, opts)| {
+                let into_regex_char_iter: fn((Alternative<Input>, _)) -> _ =
+                    |(a, o)| a.into_regex_char_iter_with(o);
+
+                alt.iter()
+                    .cloned()
+                    .zip(iter::repeat(opts))
+                    .flat_map(into_regex_char_iter)
+                    .chain(iter::once(Ok('|')))
+            };
+
+        options
+            .alternation_start()
+            .chars()
+            .map(ok)
+            .chain(SkipLast::new(
+                self.0
+                    .iter()
+                    .zip(iter::repeat(options))
+                    .flat_map(single_alt),
+            ))
+            .chain(iter::once(Ok(')')))
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a borrowed [`Alternation`].
+type AlternationRefIter<'e, I> = iter::Chain<
+    iter::Chain<
+        iter::Map<str::Chars<'static>, MapOkChar<I>>,
+        SkipLast<
+            iter::FlatMap<
+                iter::Zip<
+                    slice::Iter<'e, SingleAlternation<I>>,
+                    iter::Repeat<Options>,
+                >,
+                AlternationRefIterInner<'e, I>,
+                fn(
+                    (&'e SingleAlternation<I>, Options),
+                ) -> AlternationRefIterInner<'e, I>,
+            >,
+        >,
+    >,
+    iter::Once<Result<char, UnknownParameterError<I>>>,
+>;
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// Inner type of an [`AlternationRefIter`].
+type AlternationRefIterInner<'e, I> = iter::Chain<
+    iter::FlatMap<
+        iter::Zip<
+            iter::Cloned<slice::Iter<'e, Alternative<I>>>,
+            iter::Repeat<Options>,
+        >,
+        <Alternative<I> as IntoRegexCharIter<I>>::Iter,
+        fn(
+            (Alternative<I>, Options),
+        ) -> <Alternative<I> as IntoRegexCharIter<I>>::Iter,
+    >,
+    iter::Once<Result<char, UnknownParameterError<I>>>,
+>;
+
+impl<Input> IntoRegexCharIter<Input> for Alternative<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = AlternativeIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+
+        match self {
+            Self::Optional(opt) => Left(opt.into_regex_char_iter_with(options)),
+            Self::Text(text) => Right(
+                EscapeForRegex::for_dialect(
+                    text.iter_elements().map(as_char),
+                    options.dialect,
+                )
+                .map(Ok),
+            ),
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`Alternative`].
+type AlternativeIter<Input> = Either<
+    <Optional<Input> as IntoRegexCharIter<Input>>::Iter,
+    iter::Map<
+        EscapeForRegex<
             iter::Map<
+                <Input as InputIter>::IterElem,
+                fn(<Input as InputIter>::Item) -> char,
+            >,
+        >,
+        MapOkChar<Input>,
+    >,
+>;
+
+impl<Input> IntoRegexCharIter<Input> for Optional<Input>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = OptionalIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+
+        "(?:"
+            .chars()
+            .chain(EscapeForRegex::for_dialect(
+                self.0.iter_elements().map(as_char),
+                options.dialect,
+            ))
+            .chain(options.optional_end().chars())
+            .map(Ok)
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`Optional`].
+type OptionalIter<Input> = iter::Map<
+    iter::Chain<
+        iter::Chain<
+            str::Chars<'static>,
+            EscapeForRegex<
+                iter::Map<
+                    <Input as InputIter>::IterElem,
+                    fn(<Input as InputIter>::Item) -> char,
+                >,
+            >,
+        >,
+        str::Chars<'static>,
+    >,
+    MapOkChar<Input>,
+>;
+
+impl<Input> IntoRegexCharIter<Input> for OptionalParameter<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = OptionalParameterIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        optional_parameter_iter(
+            &self.prefix,
+            self.parameter.into_regex_char_iter_with(options),
+            &self.suffix,
+            options,
+        )
+    }
+}
+
+/// Creates an [`OptionalParameterIter`] of an [`OptionalParameter`] with the
+/// given `prefix` and `suffix`, wrapping the already expanded `parameter`.
+fn optional_parameter_iter<Input, P>(
+    prefix: &Input,
+    parameter: P,
+    suffix: &Input,
+    options: Options,
+) -> OptionalParameterIter<Input, P>
+where
+    Input: fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    P: Iterator<Item = Result<char, UnknownParameterError<Input>>>,
+{
+    let ok: MapOkChar<Input> = Ok;
+    let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+
+    "(?:"
+        .chars()
+        .chain(EscapeForRegex::for_dialect(
+            prefix.iter_elements().map(as_char),
+            options.dialect,
+        ))
+        .map(ok)
+        .chain(parameter)
+        .chain(
+            EscapeForRegex::for_dialect(
+                suffix.iter_elements().map(as_char),
+                options.dialect,
+            )
+            .chain(options.optional_end().chars())
+            .map(ok),
+        )
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for an [`OptionalParameter`], with its
+/// [`Parameter`] expanded into the `P` [`Iterator`].
+type OptionalParameterIter<
+    Input,
+    P = <Parameter<Input> as IntoRegexCharIter<Input>>::Iter,
+> = iter::Chain<
+    iter::Chain<
+        iter::Map<
+            iter::Chain<
+                str::Chars<'static>,
                 EscapeForRegex<
                     iter::Map<
                         <Input as InputIter>::IterElem,
                         fn(<Input as InputIter>::Item) -> char,
                     >,
                 >,
-                MapOkChar<Input>,
             >,
+            MapOkChar<Input>,
+        >,
+        P,
+    >,
+    iter::Map<
+        iter::Chain<
+            EscapeForRegex<
+                iter::Map<
+                    <Input as InputIter>::IterElem,
+                    fn(<Input as InputIter>::Item) -> char,
+                >,
+            >,
+            str::Chars<'static>,
         >,
+        MapOkChar<Input>,
+    >,
+>;
+
+/// Function pointer describing [`Ok`].
+type MapOkChar<Input> = fn(char) -> Result<char, UnknownParameterError<Input>>;
+
+impl<Input> IntoRegexCharIter<Input> for Parameter<Input>
+where
+    Input: Clone + fmt::Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Iter = ParameterIter<Input>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        let ok: fn(_) -> _ = Ok;
+        builtin_parameter(&self.0, options).map_or_else(
+            || {
+                Right(iter::once(Err(UnknownParameterError::new(
+                    self.0,
+                    Vec::new(),
+                ))))
+            },
+            |re| Left(re.chars().map(ok)),
+        )
+    }
+}
+
+/// [`Regex`] pattern of an `{int}` [`Parameter`] with the default
+/// [`Options`].
+///
+/// Like all the built-in [`Parameter`] patterns, it's a single capturing group.
+pub const INT_PATTERN: &str = r"((?:-?\d+)|(?:\d+))";
+
+/// [`Regex`] pattern of a `{float}` [`Parameter`] with the default
+/// [`Options`].
+///
+/// Like all the built-in [`Parameter`] patterns, it contains no look-around, so
+/// is portable across all the [`Dialect`]s.
+pub const FLOAT_PATTERN: &str = r"([-+]?(?:\d*\.\d+|\d+)(?:[Ee][+-]?\d+)?)";
+
+/// [`Regex`] pattern of a `{word}` [`Parameter`] with the default
+/// [`Options`].
+pub const WORD_PATTERN: &str = r"([^\s]+)";
+
+/// [`Regex`] pattern of a `{string}` [`Parameter`] with the default
+/// [`Options`].
+///
+/// Captures the quotes too, use [`unquote_string()`] to strip them.
+pub const STRING_PATTERN: &str = concat!(
+    r#"("(?:[^"\\]*(?:\\.[^"\\]*)*)""#,
+    r#"|'(?:[^'\\]*(?:\\.[^'\\]*)*)')"#,
+);
+
+/// [`Regex`] pattern of an anonymous `{}` [`Parameter`] with the default
+/// [`Options`].
+pub const ANONYMOUS_PATTERN: &str = r"(.*)";
+
+/// [`Regex`] pattern of an anonymous `{}` [`Parameter`] with
+/// [`Options::safe_anonymous`] enabled.
+pub const SAFE_ANONYMOUS_PATTERN: &str = r"([^\n]{0,256})";
+
+/// Returns a [`Regex`] pattern of the built-in [`Parameter`] with the given
+/// `name` (empty for the anonymous one), as expanded with the given
+/// [`Options`], if any.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::expand::{
+/// #     builtin_parameter_pattern, Options, Word, WORD_PATTERN,
+/// # };
+/// #
+/// let opts = Options {
+///     word: Word::WordChars,
+///     ..Options::default()
+/// };
+///
+/// assert_eq!(
+///     builtin_parameter_pattern("word", Options::default()),
+///     Some(WORD_PATTERN),
+/// );
+/// assert_eq!(builtin_parameter_pattern("word", opts), Some("([\\w-]+)"));
+/// assert_eq!(builtin_parameter_pattern("color", opts), None);
+/// ```
+#[must_use]
+pub fn builtin_parameter_pattern(
+    name: &str,
+    options: Options,
+) -> Option<&'static str> {
+    builtin_parameter(&name, options)
+}
+
+/// Names of the built-in [`Parameter`]s, except the anonymous one.
+const BUILTIN_PARAMETERS: &[&str] = &[
+    "int",
+    "byte",
+    "short",
+    "long",
+    "biginteger",
+    "float",
+    "double",
+    "bigdecimal",
+    "boolean",
+    "ordinal",
+    "word",
+    "string",
+];
+
+/// Names of the extra built-in [`Parameter`]s, enabled by `extra-params`
+/// feature.
+const EXTRA_BUILTIN_PARAMETERS: &[&str] =
+    &["uuid", "date", "datetime", "email", "url"];
+
+/// Returns names of all the enabled built-in [`Parameter`]s, except the
+/// anonymous one.
+///
+/// Use [`builtin_parameter_pattern()`] to get their [`Regex`] patterns.
+pub fn builtin_parameter_names() -> impl Iterator<Item = &'static str> {
+    let extra: &[&str] = if cfg!(feature = "extra-params") {
+        EXTRA_BUILTIN_PARAMETERS
+    } else {
+        &[]
+    };
+    BUILTIN_PARAMETERS.iter().chain(extra).copied()
+}
+
+/// Returns the [edit distance][1] between the given strings, counted in
+/// [`char`]s, with a transposition of two adjacent [`char`]s counted as a
+/// single edit.
+///
+/// [1]: https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    // Rows of the distance matrix for the previous two and the current `char`s
+    // of `a`.
+    let mut before = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut curr = vec![0; b.len() + 1];
+    for i in 0..a.len() {
+        curr[0] = i + 1;
+        for j in 0..b.len() {
+            let substitution = prev[j] + usize::from(a[i] != b[j]);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                curr[j + 1] = curr[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        before.clone_from(&prev);
+        prev.clone_from(&curr);
+    }
+    prev[b.len()]
+}
+
+/// Returns a [`Regex`] pattern of the built-in [`Parameter`] with the given
+/// `name`, if any, according to the given [`Options`].
+fn builtin_parameter<Input>(
+    name: &Input,
+    options: Options,
+) -> Option<&'static str>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let eq =
+        |str: &str| name.iter_elements().map(AsChar::as_char).eq(str.chars());
+
+    if ["int", "byte", "short", "long", "biginteger"]
+        .into_iter()
+        .any(eq)
+    {
+        Some(options.number_format.int())
+    } else if ["float", "double", "bigdecimal"].into_iter().any(eq) {
+        Some(options.number_format.float())
+    } else if eq("boolean") {
+        Some(if options.extended_booleans {
+            "(true|false|yes|no|on|off)"
+        } else {
+            "(true|false)"
+        })
+    } else if eq("ordinal") {
+        Some(r"(\d+)(?:st|nd|rd|th)")
+    } else if eq("word") {
+        Some(options.word.pattern())
+    } else if eq("string") {
+        Some(if options.smart_quotes {
+            concat!(
+                r#"("(?:[^"\\]*(?:\\.[^"\\]*)*)""#,
+                r#"|'(?:[^'\\]*(?:\\.[^'\\]*)*)'"#,
+                r#"|“(?:[^”\\]*(?:\\.[^”\\]*)*)”"#,
+                r#"|‘(?:[^’\\]*(?:\\.[^’\\]*)*)’)"#,
+            )
+        } else {
+            STRING_PATTERN
+        })
+    } else if eq("") {
+        Some(match (options.safe_anonymous, options.lazy_anonymous) {
+            (true, true) => r"([^\n]{0,256}?)",
+            (true, false) => SAFE_ANONYMOUS_PATTERN,
+            (false, true) => r"(.*?)",
+            (false, false) => ANONYMOUS_PATTERN,
+        })
+    } else if cfg!(feature = "extra-params") {
+        extra_builtin_parameter(eq)
+    } else {
+        None
+    }
+}
+
+/// Returns a [`Regex`] pattern of the built-in [`Parameter`] with the given
+/// `name`, or of the [`QuantifiedParameter`] repeating it, if
+/// [`Options::quantified_parameters`] are enabled.
+fn builtin_or_quantified_parameter<Input>(
+    name: &Input,
+    options: Options,
+) -> Option<Cow<'static, str>>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    if let Some(re) = builtin_parameter(name, options) {
+        return Some(re.into());
+    }
+    if !options.quantified_parameters {
+        return None;
+    }
+    let raw = name
+        .iter_elements()
+        .map(AsChar::as_char)
+        .collect::<String>();
+    QuantifiedParameter::parse(&raw)?
+        .pattern(options)
+        .map(Cow::Owned)
+}
+
+/// Returns a [`Regex`] pattern of the extra built-in [`Parameter`], enabled by
+/// `extra-params` feature, satisfying the given `eq` predicate, if any.
+fn extra_builtin_parameter(eq: impl Fn(&str) -> bool) -> Option<&'static str> {
+    if eq("uuid") {
+        Some(concat!(
+            "([0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}",
+            "-[0-9a-fA-F]{12})",
+        ))
+    } else if eq("date") {
+        Some(r"(\d{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12]\d|3[01]))")
+    } else if eq("datetime") {
+        Some(concat!(
+            r"(\d{4}-(?:0[1-9]|1[0-2])-(?:0[1-9]|[12]\d|3[01])",
+            r"[Tt ](?:[01]\d|2[0-3]):[0-5]\d(?::[0-5]\d(?:\.\d+)?)?",
+            r"(?:[Zz]|[+-](?:[01]\d|2[0-3]):?[0-5]\d)?)",
+        ))
+    } else if eq("email") {
+        Some(concat!(
+            r"([A-Za-z0-9.!#$%&'*+=?^_`{|}~-]+",
+            r"@[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?",
+            r"(?:\.[A-Za-z0-9](?:[A-Za-z0-9-]{0,61}[A-Za-z0-9])?)+)",
+        ))
+    } else if eq("url") {
+        Some(r"([A-Za-z][A-Za-z0-9+.-]*:\/\/[^\s\/?#]+[^\s]*)")
+    } else {
+        None
+    }
+}
+
+/// Strips the surrounding quotes from a text `captured` by a `{string}`
+/// [`Parameter`], unescaping the escaped closing quotes inside it, the same way
+/// the [reference implementations][1] do.
+///
+/// Returns [`None`] if the `captured` text isn't a quoted string.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::expand::unquote_string;
+/// #
+/// assert_eq!(unquote_string(r#""a \"b\" c""#).unwrap(), r#"a "b" c"#);
+/// assert_eq!(unquote_string("'it\\'s'").unwrap(), "it's");
+/// assert_eq!(unquote_string("“smart”").unwrap(), "smart");
+/// assert_eq!(unquote_string("unquoted"), None);
+/// ```
+///
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+#[must_use]
+pub fn unquote_string(captured: &str) -> Option<Cow<'_, str>> {
+    let open = captured.chars().next()?;
+    let close = match open {
+        '"' => '"',
+        '\'' => '\'',
+        '“' => '”',
+        '‘' => '’',
+        _ => return None,
+    };
+    let inner = captured.strip_prefix(open)?.strip_suffix(close)?;
+
+    Some(if inner.contains('\\') {
+        let mut escaped = String::from('\\');
+        escaped.push(close);
+        Cow::Owned(inner.replace(&escaped, close.encode_utf8(&mut [0; 4])))
+    } else {
+        Cow::Borrowed(inner)
+    })
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for a [`Parameter`].
+type ParameterIter<Input> = Either<
+    iter::Map<
+        str::Chars<'static>,
+        fn(char) -> Result<char, UnknownParameterError<Input>>,
     >,
+    iter::Once<Result<char, UnknownParameterError<Input>>>,
 >;
 
-impl<Input> IntoRegexCharIter<Input> for Alternation<Input>
-where
-    Input: fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = AlternationIter<Input>;
+/// [`Iterator`] reporting a precomputed lower bound of its length, as
+/// [`iter::FlatMap`]s inside expansion [`Iterator`]s are unable to do so.
+#[derive(Clone, Debug)]
+pub struct SizeHinted<Iter> {
+    /// Inner [`Iterator`].
+    iter: Iter,
+
+    /// Lower bound of the number of the remaining [`Item`]s.
+    ///
+    /// [`Item`]: Iterator::Item
+    lower: usize,
+}
+
+impl<Iter> SizeHinted<Iter> {
+    /// Creates a new [`SizeHinted`] [`Iterator`], yielding at least `lower`
+    /// [`Item`]s.
+    ///
+    /// [`Item`]: Iterator::Item
+    pub const fn new(iter: Iter, lower: usize) -> Self {
+        Self { iter, lower }
+    }
+}
+
+impl<Iter: Iterator> Iterator for SizeHinted<Iter> {
+    type Item = Iter::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.iter.next();
+        self.lower = self.lower.saturating_sub(1);
+        next
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (lower.max(self.lower), upper)
+    }
+}
+
+/// Lower bound of a length (in [`char`]s) of a [`Regex`] pattern, an [AST]
+/// element is expanded into.
+///
+/// Doesn't include [`Options::prefix()`] and [`Options::suffix()`] of an
+/// [`Expression`]. Every [`Parameter`] is counted as a single [`char`], as it
+/// may be unknown or expanded with custom [`ParametersProvider`].
+///
+/// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+trait MinRegexLen {
+    /// Returns a lower bound of the [`Regex`] pattern length.
+    fn min_regex_len(&self) -> usize;
+}
+
+impl<Input> MinRegexLen for Expression<Input>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn min_regex_len(&self) -> usize {
+        self.iter().map(MinRegexLen::min_regex_len).sum()
+    }
+}
+
+impl<Input> MinRegexLen for SingleExpression<Input>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn min_regex_len(&self) -> usize {
+        match self {
+            Self::Alternation(alt) => alt.min_regex_len(),
+            Self::Optional(opt) => opt.min_regex_len(),
+            Self::OptionalParameter(opt) => {
+                "(?:)?".len()
+                    + min_escaped_len(&opt.prefix)
+                    + 1
+                    + min_escaped_len(&opt.suffix)
+            }
+            Self::Parameter(_) => 1,
+            Self::Text(t) => min_escaped_len(t),
+            Self::Whitespaces(t) => min_escaped_len(t).min(r"\s+".len()),
+        }
+    }
+}
+
+impl<Input> MinRegexLen for Alternation<Input>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn min_regex_len(&self) -> usize {
+        "(?:)".len()
+            + self.0.len().saturating_sub(1)
+            + self
+                .0
+                .iter()
+                .flatten()
+                .map(|alt| match alt {
+                    Alternative::Optional(opt) => opt.min_regex_len(),
+                    Alternative::Text(t) => min_escaped_len(t),
+                })
+                .sum::<usize>()
+    }
+}
+
+impl<Input> MinRegexLen for Optional<Input>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    fn min_regex_len(&self) -> usize {
+        "(?:)?".len() + min_escaped_len(&self.0)
+    }
+}
+
+/// Returns a lower bound of the `text` length after [`EscapeForRegex`].
+///
+/// Every [`char`] except `\` is guaranteed to be present in the output.
+fn min_escaped_len<Input>(text: &Input) -> usize
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    text.iter_elements()
+        .map(AsChar::as_char)
+        .filter(|&c| c != '\\')
+        .count()
+}
+
+/// [`Iterator`] for skipping a last [`Item`].
+///
+/// [`Item`]: Iterator::Item
+pub struct SkipLast<Iter: Iterator> {
+    /// Inner [`Iterator`] to skip the last [`Item`] from.
+    ///
+    /// [`Item`]: Iterator::Item
+    iter: iter::Peekable<Iter>,
+}
+
+impl<Iter> Clone for SkipLast<Iter>
+where
+    Iter: Clone + Iterator,
+    Iter::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<Iter> fmt::Debug for SkipLast<Iter>
+where
+    Iter: fmt::Debug + Iterator,
+    Iter::Item: fmt::Debug,
+{
+    #[allow(unused_qualifications)] // lints of newer toolchains
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkipLast")
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
+
+impl<Iter: Iterator> SkipLast<Iter> {
+    /// Creates a new [`SkipLast`] [`Iterator`].
+    pub fn new(iter: Iter) -> Self {
+        Self {
+            iter: iter.peekable(),
+        }
+    }
+}
+
+impl<Iter> Iterator for SkipLast<Iter>
+where
+    Iter: Iterator,
+{
+    type Item = Iter::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.iter.next();
+        (self.iter.peek().is_some()).then(|| next).flatten()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        (lower.saturating_sub(1), upper.map(|u| u.saturating_sub(1)))
+    }
+}
+
+/// [`Iterator`] for escaping `^`, `$`, `[`, `]`, `(`, `)`, `{`, `}`, `.`, `|`,
+/// `?`, `*`, `+` with `\`, and removing it for other [`char`]s.
+///
+/// Use [`EscapeForRegex::for_dialect()`] to escape additional [`char`]s
+/// required by a particular [`Dialect`] (like `/` for [`Dialect::EcmaScript`]),
+/// or [`EscapeForRegex::with_escaped_chars()`] to escape an arbitrary set of
+/// [`char`]s.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::expand::EscapeForRegex;
+/// #
+/// assert_eq!(
+///     EscapeForRegex::new("\\\\text\\ (\\)\\".chars()).collect::<String>(),
+///     "\\\\text \\(\\)",
+/// );
+/// assert_eq!(
+///     EscapeForRegex::with_escaped_chars("a-b (c)".chars(), "\\-")
+///         .collect::<String>(),
+///     "a\\-b (c)",
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct EscapeForRegex<Iter: Iterator> {
+    /// Inner [`Iterator`] for escaping.
+    iter: iter::Peekable<Iter>,
+
+    /// [`Item`] that was escaped.
+    ///
+    /// [`Item`]: Iterator::Item
+    was_escaped: Option<Iter::Item>,
+
+    /// [`char`]s to be escaped.
+    escaped: &'static str,
+}
+
+impl<Iter: Iterator> EscapeForRegex<Iter> {
+    /// Creates a new [`EscapeForRegex`] [`Iterator`].
+    pub fn new(iter: Iter) -> Self {
+        Self::for_dialect(iter, Dialect::Rust)
+    }
+
+    /// Creates a new [`EscapeForRegex`] [`Iterator`] escaping [`char`]s
+    /// special for the given [`Dialect`].
+    pub fn for_dialect(iter: Iter, dialect: Dialect) -> Self {
+        Self::with_escaped_chars(iter, dialect.escaped_chars())
+    }
+
+    /// Creates a new [`EscapeForRegex`] [`Iterator`] escaping the given
+    /// `escaped` [`char`]s only.
+    ///
+    /// `escaped` [`char`]s should contain `\` to keep it escaped, otherwise
+    /// it's removed as for any other [`char`].
+    pub fn with_escaped_chars(iter: Iter, escaped: &'static str) -> Self {
+        Self {
+            iter: iter.peekable(),
+            was_escaped: None,
+            escaped,
+        }
+    }
+}
+
+impl<Iter> Iterator for EscapeForRegex<Iter>
+where
+    Iter: Iterator<Item = char>,
+{
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let escaped = self.escaped;
+        let should_be_escaped = |c| escaped.contains(c);
+
+        if self.was_escaped.is_some() {
+            return self.was_escaped.take();
+        }
+
+        loop {
+            return match self.iter.next() {
+                Some('\\') => {
+                    let c = *self.iter.peek()?;
+                    if should_be_escaped(c) {
+                        self.was_escaped = self.iter.next();
+                        Some('\\')
+                    } else {
+                        continue;
+                    }
+                }
+                Some(c) if should_be_escaped(c) => {
+                    self.was_escaped = Some(c);
+                    Some('\\')
+                }
+                Some(c) => Some(c),
+                None => None,
+            };
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every `char` is either escaped (doubling it), or removed along with
+        // the preceding `\`.
+        let pending = usize::from(self.was_escaped.is_some());
+        let (lower, upper) = self.iter.size_hint();
+        (
+            lower / 2 + pending,
+            upper.and_then(|u| u.checked_mul(2)?.checked_add(pending)),
+        )
+    }
+}
+
+// All test examples from: <https://git.io/J159G>
+// Naming of test cases is preserved.
+#[cfg(all(test, feature = "into-regex"))]
+mod spec {
+    use std::{collections::HashMap, fmt};
+
+    use super::{
+        builtin_parameter_names, builtin_parameter_pattern, AnyExpression,
+        Diagnostic, Dialect, Error, EscapeForRegex, ExpandInto as _,
+        Expression, IntoBoxedRegexCharIter as _, IntoRegexCharIter as _,
+        NumberFormat, Options, QuantifiedParameter, RegexFlags, Spanned,
+        UnknownParameterError, Whitespaces, Word, WriteRegex,
+        ANONYMOUS_PATTERN, FLOAT_PATTERN, INT_PATTERN, STRING_PATTERN,
+        WORD_PATTERN,
+    };
+
+    #[test]
+    fn alternation_with_optional() {
+        let expr = Expression::regex("a/b(c)")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(?:a|b(?:c)?)$");
+    }
+
+    #[test]
+    fn alternation() {
+        let expr = Expression::regex("a/b c/d/e")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(?:a|b) (?:c|d|e)$");
+    }
+
+    #[test]
+    fn borrowed() {
+        for input in ["", "a/b(c) {int}", "(a) b/c/d\\/ {}", "/^a(b)$/"] {
+            let expr = AnyExpression::parse(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            let borrowed = (&expr)
+                .into_regex_string()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let owned = expr
+                .into_regex_string()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(borrowed, owned, "on input: {}", input);
+        }
+    }
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        let ok: fn(_) -> _ = Ok;
-        let single_alt: fn(SingleAlternation<Input>) -> _ = |alt| {
-            let into_regex_char_iter: fn(_) -> _ =
-                IntoRegexCharIter::into_regex_char_iter;
+    #[test]
+    fn empty() {
+        let expr =
+            Expression::regex("").unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^$");
+    }
+
+    #[test]
+    fn anchors_anchored_regular_expression() {
+        let re = AnyExpression::regex("/^a|b$/")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(re.as_str(), "^(?:^a|b$)$");
+        assert!(re.is_match("a"));
+        assert!(re.is_match("b"));
+        assert!(!re.is_match("ab"));
+        assert!(!re.is_match("xb"));
+    }
+
+    #[test]
+    fn expand_into() {
+        for input in [
+            "",
+            "a/b(c) {int} {float}",
+            "(a) b/c/d\\/ {} {word}",
+            "\\(a\\) [b]. ^$|?*+ \\\\ \\{",
+            "/^a(b)$/",
+        ] {
+            let expr = AnyExpression::parse(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            let mut written = String::new();
+            expr.expand_into(&mut written)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(
+                written,
+                expr.into_regex_string()
+                    .unwrap_or_else(|e| panic!("failed: {}", e)),
+                "on input: {}",
+                input,
+            );
+        }
+    }
+
+    #[test]
+    fn write_regex_dyn() {
+        let expr = Expression::parse("a/b {int} (c)").unwrap();
+        let elements: Vec<Box<dyn WriteRegex<Spanned<'_>>>> = vec![
+            Box::new(expr.clone()),
+            Box::new(expr.0[2].clone()),
+            Box::new(AnyExpression::parse("/^a$/").unwrap()),
+        ];
+
+        let written = elements
+            .iter()
+            .map(|e| {
+                let mut re = String::new();
+                e.write_regex(&mut re)
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+                re
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            written,
+            [
+                "^(?:a|b) ((?:-?\\d+)|(?:\\d+)) (?:c)?$",
+                "((?:-?\\d+)|(?:\\d+))",
+                "^(?:^a$)$",
+            ],
+        );
+    }
 
-            alt.into_iter()
-                .flat_map(into_regex_char_iter)
-                .chain(iter::once(Ok('|')))
+    #[test]
+    fn expand_into_failing_writer() {
+        /// [`fmt::Write`] failing on any write.
+        struct Failing;
+
+        impl fmt::Write for Failing {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let expr = Expression::parse("{int}").unwrap();
+
+        assert!(matches!(
+            expr.expand_into(&mut Failing).unwrap_err(),
+            Error::Formatting(fmt::Error),
+        ));
+    }
+
+    #[test]
+    fn escape_regex_characters() {
+        let expr = Expression::regex(r"^$[]\(\){}\\.|?*+")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), r"^\^\$\[\]\(\)(.*)\\\.\|\?\*\+$");
+    }
+
+    #[test]
+    fn options_anchors() {
+        for (anchor_start, anchor_end, expected_cucumber, expected_regular) in [
+            (true, true, "^a (?:b)?$", "^(?:a+)$"),
+            (false, true, "a (?:b)?$", "(?:a+)$"),
+            (true, false, "^a (?:b)?", "^(?:a+)"),
+            (false, false, "a (?:b)?", "(?:a+)"),
+        ] {
+            let options = Options {
+                anchor_start,
+                anchor_end,
+                ..Options::default()
+            };
+
+            for (input, expected) in
+                [("a (b)", expected_cucumber), ("/a+/", expected_regular)]
+            {
+                let expr = AnyExpression::parse(input).unwrap();
+
+                let mut written = String::new();
+                expr.expand_into_with(&mut written, options)
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+                assert_eq!(written, expected, "on input: {}", input);
+
+                let boxed = expr
+                    .clone()
+                    .into_regex_char_iter_boxed_with(options)
+                    .collect::<Result<String, _>>()
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+                assert_eq!(boxed, expected, "on input: {}", input);
+
+                let iter = expr.into_regex_char_iter_with(options);
+                assert!(iter.size_hint().0 <= expected.len());
+                let iterated = iter
+                    .collect::<Result<String, _>>()
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+                assert_eq!(iterated, expected, "on input: {}", input);
+
+                let re = AnyExpression::regex_with_options(input, options)
+                    .unwrap_or_else(|e| panic!("failed: {}", e));
+                assert_eq!(re.as_str(), expected, "on input: {}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn options_case_insensitive() {
+        let options = Options {
+            case_insensitive: true,
+            ..Options::default()
         };
 
-        "(?:"
-            .chars()
-            .map(ok)
-            .chain(SkipLast::new(self.0.into_iter().flat_map(single_alt)))
-            .chain(iter::once(Ok(')')))
+        for (input, expected) in [
+            ("I click {word}", "(?i)^I click ([^\\s]+)$"),
+            ("/I click OK/", "(?i)^(?:I click OK)$"),
+        ] {
+            let re = AnyExpression::regex_with_options(input, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(re.as_str(), expected, "on input: {}", input);
+            assert!(re.is_match("i CLICK ok"), "on input: {}", input);
+
+            let iterated = AnyExpression::parse(input)
+                .unwrap()
+                .into_regex_char_iter_with(options)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(iterated, expected, "on input: {}", input);
+        }
     }
-}
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for an [`Alternation`].
-type AlternationIter<I> = iter::Chain<
-    iter::Chain<
-        iter::Map<str::Chars<'static>, MapOkChar<I>>,
-        SkipLast<
-            iter::FlatMap<
-                vec::IntoIter<SingleAlternation<I>>,
-                AlternationIterInner<I>,
-                fn(SingleAlternation<I>) -> AlternationIterInner<I>,
-            >,
-        >,
-    >,
-    iter::Once<Result<char, UnknownParameterError<I>>>,
->;
+    #[test]
+    fn options_whitespaces() {
+        for (whitespaces, expected) in [
+            (Whitespaces::Literal, "^a  b (?:c)?$"),
+            (Whitespaces::Flexible, "^a\\s+b (?:c)?$"),
+            (Whitespaces::FlexiblePadded, "^\\s*a\\s+b (?:c)?\\s*$"),
+        ] {
+            let options = Options {
+                whitespaces,
+                ..Options::default()
+            };
+            let expr = Expression::parse("a  b\\ (c)").unwrap();
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// Inner type of an [`AlternationIter`].
-type AlternationIterInner<I> = iter::Chain<
-    iter::FlatMap<
-        vec::IntoIter<Alternative<I>>,
-        <Alternative<I> as IntoRegexCharIter<I>>::Iter,
-        fn(Alternative<I>) -> <Alternative<I> as IntoRegexCharIter<I>>::Iter,
-    >,
-    iter::Once<Result<char, UnknownParameterError<I>>>,
->;
+            let mut written = String::new();
+            expr.expand_into_with(&mut written, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(written, expected, "with {:?}", whitespaces);
 
-impl<Input> IntoRegexCharIter<Input> for Alternative<Input>
-where
-    Input: fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = AlternativeIter<Input>;
+            let boxed = expr
+                .clone()
+                .into_regex_char_iter_boxed_with(options)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(boxed, expected, "with {:?}", whitespaces);
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        use Either::{Left, Right};
+            let iter = (&expr).into_regex_char_iter_with(options);
+            assert!(iter.size_hint().0 <= expected.len());
+            let iterated = iter
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(iterated, expected, "with {:?}", whitespaces);
+        }
 
-        let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+        let re = Expression::regex_with_options(
+            "I have {int} cucumbers",
+            Options {
+                whitespaces: Whitespaces::Flexible,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert!(re.is_match("I have\t2   cucumbers"));
+        assert!(!re.is_match(" I have 2 cucumbers"));
+    }
 
-        match self {
-            Self::Optional(opt) => Left(opt.into_regex_char_iter()),
-            Self::Text(text) => Right(
-                EscapeForRegex::new(text.iter_elements().map(as_char)).map(Ok),
+    #[test]
+    fn options_named_groups() {
+        let options = Options {
+            named_groups: true,
+            ..Options::default()
+        };
+        let expected = "^(?P<int_1>(?:-?\\d+)|(?:\\d+)) (?P<word>[^\\s]+) \
+                        (?P<int_2>(?:-?\\d+)|(?:\\d+)) (?P<anonymous>.*) \
+                        (?:a|b)$";
+        let expr = Expression::parse("{int} {word} {int} {} a/b").unwrap();
+
+        let mut written = String::new();
+        expr.expand_into_with(&mut written, options)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(written, expected);
+
+        let boxed = expr
+            .into_regex_char_iter_boxed_with(options)
+            .collect::<Result<String, _>>()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(boxed, expected);
+
+        let re = Expression::regex_with_options("{int} {word} {int}", options)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures("1 a 3").unwrap();
+        assert_eq!(&caps["int_1"], "1");
+        assert_eq!(&caps["word"], "a");
+        assert_eq!(&caps["int_2"], "3");
+    }
+
+    #[test]
+    fn options_optimize() {
+        let options = Options {
+            optimize: true,
+            ..Options::default()
+        };
+        for (input, expected) in [
+            ("cucumber(s)", "^cucumbers?$"),
+            ("cucumber(\\()", "^cucumber\\(?$"),
+            ("cucumber(es)", "^cucumber(?:es)?$"),
+            ("apple/apricot", "^ap(?:ple|ricot)$"),
+            ("a.b/a.c/a.b", "^a\\.(?:b|c)$"),
+            ("a\\/b/a\\/c", "^a/(?:b|c)$"),
+            ("red/red", "^red$"),
+            ("ab/abc", "^ab(?:|c)$"),
+            ("x/y", "^(?:x|y)$"),
+            ("apple(s)/apricot", "^(?:apples?|apricot)$"),
+        ] {
+            let mut written = String::new();
+            Expression::parse(input)
+                .unwrap()
+                .expand_into_with(&mut written, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(written, expected, "on input: {}", input);
+
+            let re = Expression::regex_with_options(input, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let unoptimized = Expression::regex(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            for text in ["cucumber", "cucumbers", "apple", "apricot", "ab"] {
+                assert_eq!(
+                    re.is_match(text),
+                    unoptimized.is_match(text),
+                    "on input: {}, text: {}",
+                    input,
+                    text,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn options_lazy_anonymous() {
+        let lazy = Options {
+            lazy_anonymous: true,
+            ..Options::default()
+        };
+
+        let greedy_re = Expression::regex("{} and {}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        let greedy_caps = greedy_re.captures("a and b and c").unwrap();
+        assert_eq!(&greedy_caps[1], "a and b");
+        assert_eq!(&greedy_caps[2], "c");
+
+        let lazy_re = Expression::regex_with_options("{} and {}", lazy)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(lazy_re.as_str(), "^(.*?) and (.*?)$");
+        let lazy_caps = lazy_re.captures("a and b and c").unwrap();
+        assert_eq!(&lazy_caps[1], "a");
+        assert_eq!(&lazy_caps[2], "b and c");
+
+        let boxed = Expression::parse("{} {int}")
+            .unwrap()
+            .into_regex_char_iter_boxed_with(lazy)
+            .collect::<Result<String, _>>()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(boxed, "^(.*?) ((?:-?\\d+)|(?:\\d+))$");
+
+        let unanchored = Expression::regex_with_options(
+            "{}",
+            Options {
+                anchor_end: false,
+                ..lazy
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(&unanchored.captures("abc").unwrap()[1], "");
+    }
+
+    #[test]
+    fn options_safe_anonymous() {
+        let safe = Options {
+            safe_anonymous: true,
+            ..Options::default()
+        };
+
+        let re = Expression::regex_with_options("{} and {}", safe)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^([^\\n]{0,256}) and ([^\\n]{0,256})$");
+        let caps = re.captures("a and b and c").unwrap();
+        assert_eq!(&caps[1], "a and b");
+        assert_eq!(&caps[2], "c");
+        assert!(!re.is_match(&format!("{} and b", "a".repeat(257))));
+        assert!(!re.is_match("(?s)a\nb and c"));
+
+        let lazy = Expression::regex_with_options(
+            "{} and {}",
+            Options {
+                lazy_anonymous: true,
+                ..safe
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(&lazy.captures("a and b and c").unwrap()[1], "a");
+
+        let boxed = Expression::parse("{} {int}")
+            .unwrap()
+            .into_regex_char_iter_boxed_with(safe)
+            .collect::<Result<String, _>>()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(boxed, "^([^\\n]{0,256}) ((?:-?\\d+)|(?:\\d+))$");
+    }
+
+    #[test]
+    fn options_atomic_groups() {
+        let expr = Expression::parse("a(b)b x/y(z)").unwrap();
+        for (dialect, expected) in [
+            (Dialect::Pcre, "^a(?:b)?+b (?>x|y(?:z)?+)$"),
+            (Dialect::Rust, "^a(?:b)?b (?:x|y(?:z)?)$"),
+            (Dialect::EcmaScript, "^a(?:b)?b (?:x|y(?:z)?)$"),
+        ] {
+            let options = Options {
+                atomic_groups: true,
+                dialect,
+                ..Options::default()
+            };
+
+            let mut written = String::new();
+            expr.expand_into_with(&mut written, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(written, expected, "on dialect: {:?}", dialect);
+
+            let iterated = expr
+                .clone()
+                .into_regex_char_iter_with(options)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(iterated, expected, "on dialect: {:?}", dialect);
+
+            let boxed = expr
+                .clone()
+                .into_regex_char_iter_boxed_with(options)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(boxed, expected, "on dialect: {:?}", dialect);
+        }
+
+        let mut optimized = String::new();
+        Expression::parse("cucumber(s) apple/apricot")
+            .unwrap()
+            .expand_into_with(
+                &mut optimized,
+                Options {
+                    atomic_groups: true,
+                    optimize: true,
+                    dialect: Dialect::Pcre,
+                    ..Options::default()
+                },
+            )
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(optimized, "^cucumbers?+ ap(?>ple|ricot)$");
+    }
+
+    #[test]
+    fn pattern_with_flags() {
+        let pars = HashMap::from([("color", "red|blue")]);
+        let expr = Expression::parse("{color} \\/(a)").unwrap();
+
+        let insensitive = expr
+            .clone()
+            .with_parameters(&pars)
+            .expand_into_pattern_with(Options {
+                case_insensitive: true,
+                dialect: Dialect::EcmaScript,
+                ..Options::default()
+            })
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(insensitive.pattern, "^(red|blue) \\/(?:a)?$");
+        assert!(insensitive.flags.case_insensitive);
+        assert_eq!(insensitive.flags.to_string(), "i");
+
+        let unknown = expr
+            .expand_into_pattern_with(Options {
+                anchor_end: false,
+                ..Options::default()
+            })
+            .unwrap_err();
+        assert!(matches!(unknown, Error::Expansion(_)));
+
+        let any = AnyExpression::parse("/^[a-z]+$/")
+            .unwrap()
+            .expand_into_pattern_with(Options::default())
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(any.pattern, "^(?:^[a-z]+$)$");
+        assert_eq!(any.flags, RegexFlags::default());
+        assert_eq!(any.flags.to_string(), "");
+    }
+
+    #[test]
+    fn float_exponent() {
+        let re = Expression::regex("{float}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        for matching in ["1E10", "1e10", "1.5E-3", "1.5e-3", "-.5e+2"] {
+            assert!(re.is_match(matching), "on input: {}", matching);
+        }
+        for not_matching in ["e10", "1e", "1.5E", "1f10"] {
+            assert!(!re.is_match(not_matching), "on input: {}", not_matching);
+        }
+
+        #[cfg(feature = "into-fancy-regex")]
+        {
+            let pcre = Expression::fancy_regex_with_parameters_and_options(
+                "{float}",
+                &HashMap::<&str, &str>::new(),
+                Options {
+                    dialect: Dialect::Pcre,
+                    ..Options::default()
+                },
+            )
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+            for matching in ["1E10", "1e10", "1.5E-3", "1.5e-3"] {
+                assert!(
+                    pcre.is_match(matching).unwrap(),
+                    "on input: {}",
+                    matching,
+                );
+            }
+            for not_matching in ["e10", "1e", "1f10"] {
+                assert!(
+                    !pcre.is_match(not_matching).unwrap(),
+                    "on input: {}",
+                    not_matching,
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "extra-params")]
+    #[test]
+    fn extra_builtins() {
+        for (par, matching, not_matching) in [
+            (
+                "uuid",
+                &[
+                    "123e4567-e89b-12d3-a456-426614174000",
+                    "ABCDEF01-2345-6789-ABCD-EF0123456789",
+                ][..],
+                &[
+                    "123e4567e89b12d3a456426614174000",
+                    "123e4567-e89b-12d3-a456-42661417400g",
+                ][..],
+            ),
+            (
+                "date",
+                &["2021-12-31", "2000-01-01"],
+                &["2021-13-01", "2021-12-32", "21-12-31", "2021/12/31"],
+            ),
+            (
+                "datetime",
+                &[
+                    "2021-12-31T23:59",
+                    "2021-12-31 23:59:59",
+                    "2021-12-31T23:59:59.123Z",
+                    "2021-12-31T23:59:59+03:00",
+                    "2021-12-31t23:59:59-0130",
+                ],
+                &["2021-12-31", "2021-12-31T24:00", "2021-12-31T23:60"],
+            ),
+            (
+                "email",
+                &["user@example.com", "first.last+tag@sub.example.co"],
+                &["user@localhost", "@example.com", "user example.com"],
             ),
+            (
+                "url",
+                &["https://example.com", "http://a.b/c?d=e#f", "ftp://host"],
+                &["example.com", "https://", "https:// example.com"],
+            ),
+        ] {
+            let input = format!("{{{}}}", par);
+            let re = Expression::regex(&input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(re.captures_len(), 2, "on parameter: {}", par);
+            for m in matching {
+                assert!(re.is_match(m), "{} on matching: {}", par, m);
+            }
+            for m in not_matching {
+                assert!(!re.is_match(m), "{} on not matching: {}", par, m);
+            }
         }
+
+        let pars = HashMap::from([("date", "\\d+")]);
+        let custom = Expression::regex_with_parameters("{date}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(custom.as_str(), "^(\\d+)$");
     }
-}
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for an [`Alternative`].
-type AlternativeIter<Input> = Either<
-    <Optional<Input> as IntoRegexCharIter<Input>>::Iter,
-    iter::Map<
-        EscapeForRegex<
-            iter::Map<
-                <Input as InputIter>::IterElem,
-                fn(<Input as InputIter>::Item) -> char,
-            >,
-        >,
-        MapOkChar<Input>,
-    >,
->;
+    #[cfg(not(feature = "extra-params"))]
+    #[test]
+    fn extra_builtins_disabled() {
+        assert!(matches!(
+            Expression::regex("{uuid}").unwrap_err(),
+            Error::Expansion(_),
+        ));
+    }
 
-impl<Input> IntoRegexCharIter<Input> for Optional<Input>
-where
-    Input: fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = OptionalIter<Input>;
+    #[test]
+    fn boolean() {
+        let re = Expression::regex("it's {boolean}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^it's (true|false)$");
+        assert_eq!(&re.captures("it's true").unwrap()[1], "true");
+        assert_eq!(&re.captures("it's false").unwrap()[1], "false");
+        assert!(!re.is_match("it's yes"));
+        assert!(!re.is_match("it's truefalse"));
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        let as_char: fn(<Input as InputIter>::Item) -> char = AsChar::as_char;
+        let extended = Expression::regex_with_options(
+            "it's {boolean}",
+            Options {
+                extended_booleans: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        for b in ["true", "false", "yes", "no", "on", "off"] {
+            let text = format!("it's {}", b);
+            assert_eq!(&extended.captures(&text).unwrap()[1], b);
+        }
+        assert!(!extended.is_match("it's maybe"));
+    }
 
-        "(?:"
-            .chars()
-            .chain(EscapeForRegex::new(self.0.iter_elements().map(as_char)))
-            .chain(")?".chars())
-            .map(Ok)
+    #[test]
+    fn ordinal() {
+        let re = Expression::regex("I click the {ordinal} button")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.captures_len(), 2);
+        for (text, num) in [("1st", "1"), ("2nd", "2"), ("3rd", "3")] {
+            let step = format!("I click the {} button", text);
+            assert_eq!(&re.captures(&step).unwrap()[1], num);
+        }
+        let step = "I click the 111th button";
+        assert_eq!(&re.captures(step).unwrap()[1], "111");
+        assert!(!re.is_match("I click the 1 button"));
+        assert!(!re.is_match("I click the first button"));
+
+        let named = Expression::regex_with_options(
+            "{ordinal}",
+            Options {
+                named_groups: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(named.as_str(), "^(?P<ordinal>\\d+)(?:st|nd|rd|th)$");
+        assert_eq!(&named.captures("42nd").unwrap()["ordinal"], "42");
     }
-}
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for an [`Optional`].
-type OptionalIter<Input> = iter::Map<
-    iter::Chain<
-        iter::Chain<
-            str::Chars<'static>,
-            EscapeForRegex<
-                iter::Map<
-                    <Input as InputIter>::IterElem,
-                    fn(<Input as InputIter>::Item) -> char,
-                >,
-            >,
-        >,
-        str::Chars<'static>,
-    >,
-    MapOkChar<Input>,
->;
+    #[test]
+    fn options_word() {
+        for (word, matching, not_matching) in [
+            (
+                Word::NonWhitespace,
+                &["apple", "apple,", "don't", "a.b"][..],
+                &["two words", ""][..],
+            ),
+            (
+                Word::NonPunctuation,
+                &["apple", "don't", "well-known", "ünïcödé"],
+                &["apple,", "apple.", "a.b", "why?", "two words"],
+            ),
+            (
+                Word::WordChars,
+                &["apple", "well-known", "snake_case", "ünïcödé"],
+                &["apple,", "don't", "a.b", "two words"],
+            ),
+        ] {
+            let re = Expression::regex_with_options(
+                "{word}",
+                Options {
+                    word,
+                    ..Options::default()
+                },
+            )
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+            for m in matching {
+                assert!(re.is_match(m), "{:?} on matching: {}", word, m);
+            }
+            for m in not_matching {
+                assert!(!re.is_match(m), "{:?} on not matching: {}", word, m);
+            }
+        }
+
+        let re = Expression::regex_with_options(
+            "I pick {word}, then {word}.",
+            Options {
+                word: Word::NonPunctuation,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures("I pick apples, then pears.").unwrap();
+        assert_eq!(&caps[1], "apples");
+        assert_eq!(&caps[2], "pears");
+    }
+
+    #[test]
+    fn options_smart_quotes() {
+        let plain = Expression::regex("{string}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        let smart = Expression::regex_with_options(
+            "{string}",
+            Options {
+                smart_quotes: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(smart.captures_len(), 2);
+
+        for m in ["\"str\"", "'str'", "\"a \\\" b\""] {
+            assert!(plain.is_match(m), "plain on: {}", m);
+            assert!(smart.is_match(m), "smart on: {}", m);
+        }
+        for m in ["“str”", "‘str’", "“a ” b”", "‘it’s’"] {
+            assert!(!plain.is_match(m), "plain on: {}", m);
+        }
+        for m in ["“str”", "‘str’", "“a \\” b”"] {
+            assert!(smart.is_match(m), "smart on: {}", m);
+        }
+        for m in ["“str\"", "'str’", "“a ” b”", "”str“"] {
+            assert!(!smart.is_match(m), "smart on: {}", m);
+        }
+    }
 
-/// Function pointer describing [`Ok`].
-type MapOkChar<Input> = fn(char) -> Result<char, UnknownParameterError<Input>>;
+    #[test]
+    fn unquote_string() {
+        let re = Expression::regex_with_options(
+            "I say {string}",
+            Options {
+                smart_quotes: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
 
-impl<Input> IntoRegexCharIter<Input> for Parameter<Input>
-where
-    Input: Clone + fmt::Display + InputIter,
-    <Input as InputIter>::Item: AsChar,
-{
-    type Iter = ParameterIter<Input>;
+        for (step, content) in [
+            (r#"I say "hello""#, "hello"),
+            (r#"I say "a \"quoted\" word""#, r#"a "quoted" word"#),
+            (r#"I say 'it\'s "fine"'"#, r#"it's "fine""#),
+            (r#"I say "keep \n and \' as is""#, r"keep \n and \' as is"),
+            ("I say “smart \\” quotes”", "smart ” quotes"),
+            ("I say ‘single’", "single"),
+            (r#"I say """#, ""),
+        ] {
+            let caps = re.captures(step).unwrap();
+            assert_eq!(
+                super::unquote_string(&caps[1]).as_deref(),
+                Some(content),
+                "on step: {}",
+                step,
+            );
+        }
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        use Either::{Left, Right};
+        assert!(matches!(
+            super::unquote_string("\"plain\""),
+            Some(std::borrow::Cow::Borrowed("plain")),
+        ));
+    }
 
-        let eq = |i: &Input, str: &str| {
-            i.iter_elements().map(AsChar::as_char).eq(str.chars())
-        };
+    #[test]
+    fn numeric_builtins() {
+        let int = Expression::regex_string("{int}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        for name in ["byte", "short", "long", "biginteger"] {
+            let input = format!("{{{}}}", name);
+            let re = Expression::regex_string(&input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(re, int, "on parameter: {}", name);
+        }
 
-        if eq(&self.0, "int") {
-            Left(r#"((?:-?\d+)|(?:\d+))"#.chars().map(Ok))
-        } else if eq(&self.0, "float") {
-            Left(
-                r#"((?=.*\d.*)[-+]?\d*(?:\.(?=\d.*))?\d*(?:\d+[E][+-]?\d+)?)"#
-                    .chars()
-                    .map(Ok),
-            )
-        } else if eq(&self.0, "word") {
-            Left(r#"([^\s]+)"#.chars().map(Ok))
-        } else if eq(&self.0, "string") {
-            Left(
-                r#"("(?:[^"\\]*(?:\\.[^"\\]*)*)"|'(?:[^'\\]*(?:\\.[^'\\]*)*)')"#
-                    .chars()
-                    .map(Ok),
-            )
-        } else if eq(&self.0, "") {
-            Left(r#"(.*)"#.chars().map(Ok))
-        } else {
-            Right(iter::once(Err(UnknownParameterError { not_found: self.0 })))
+        let float = Expression::regex_string("{float}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        for name in ["double", "bigdecimal"] {
+            let input = format!("{{{}}}", name);
+            let re = Expression::regex_string(&input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(re, float, "on parameter: {}", name);
         }
-    }
-}
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for a [`Parameter`].
-type ParameterIter<Input> = Either<
-    iter::Map<
-        str::Chars<'static>,
-        fn(char) -> Result<char, UnknownParameterError<Input>>,
-    >,
-    iter::Once<Result<char, UnknownParameterError<Input>>>,
->;
+        let pars = HashMap::from([("long", "\\d{20}")]);
+        let custom = Expression::regex_with_parameters("{long}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(custom.as_str(), "^(\\d{20})$");
 
-/// [`Iterator`] for skipping a last [`Item`].
-///
-/// [`Item`]: Iterator::Item
-pub struct SkipLast<Iter: Iterator> {
-    /// Inner [`Iterator`] to skip the last [`Item`] from.
-    ///
-    /// [`Item`]: Iterator::Item
-    iter: iter::Peekable<Iter>,
-}
+        let none = HashMap::<&str, &str>::new();
+        assert!(
+            Expression::validate_with_parameters("{long} {double}", &none)
+                .is_ok()
+        );
+        assert!(
+            Expression::validate_with_parameters("{longer}", &none).is_err()
+        );
+    }
 
-impl<Iter> Clone for SkipLast<Iter>
-where
-    Iter: Clone + Iterator,
-    Iter::Item: Clone,
-{
-    fn clone(&self) -> Self {
-        Self {
-            iter: self.iter.clone(),
+    #[test]
+    fn number_format_from_locale() {
+        for (locale, expected) in [
+            ("en", Some(NumberFormat::CommaGrouping)),
+            ("en-GB", Some(NumberFormat::CommaGrouping)),
+            ("DE_at", Some(NumberFormat::DotGroupingDecimalComma)),
+            ("ru-RU", Some(NumberFormat::SpaceGroupingDecimalComma)),
+            ("", None),
+            ("xx-US", None),
+        ] {
+            assert_eq!(
+                NumberFormat::from_locale(locale),
+                expected,
+                "{}",
+                locale
+            );
         }
-    }
-}
 
-impl<Iter> fmt::Debug for SkipLast<Iter>
-where
-    Iter: fmt::Debug + Iterator,
-    Iter::Item: fmt::Debug,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SkipLast")
-            .field("iter", &self.iter)
-            .finish()
+        assert_eq!(NumberFormat::Plain.normalize("1,5"), "1,5");
+        assert_eq!(NumberFormat::CommaGrouping.normalize("1,000.5"), "1000.5");
+        assert_eq!(
+            NumberFormat::DotGroupingDecimalComma.normalize("-1.000,5"),
+            "-1000.5",
+        );
+        assert_eq!(
+            NumberFormat::SpaceGroupingDecimalComma.normalize("1\u{a0}000,5"),
+            "1000.5",
+        );
     }
-}
 
-impl<Iter: Iterator> SkipLast<Iter> {
-    /// Creates a new [`SkipLast`] [`Iterator`].
-    pub fn new(iter: Iter) -> Self {
-        Self {
-            iter: iter.peekable(),
+    #[test]
+    fn options_number_format() {
+        for (format, ints, not_ints, floats, not_floats) in [
+            (
+                NumberFormat::Plain,
+                &["1000", "-1"][..],
+                &["1,000", "1.000"][..],
+                &["3.14", "-.5", "1000.5", "1e3"][..],
+                &["3,14", "1,000.5"][..],
+            ),
+            (
+                NumberFormat::CommaGrouping,
+                &["1000", "-1,000", "12,345,678"],
+                &["1,00", ",100", "1.000"],
+                &["3.14", "1,000.5", ".5", "1,000", "1.5E3"],
+                &["3,14", "1,00.5", "1.000,5"],
+            ),
+            (
+                NumberFormat::DotGroupingDecimalComma,
+                &["1000", "-1.000", "12.345.678"],
+                &["1.00", "1,000"],
+                &["3,14", "1.000,5", ",5", "-1.000", "1,5e3"],
+                &["3.14", "1.00,5", "1,000.5"],
+            ),
+            (
+                NumberFormat::SpaceGroupingDecimalComma,
+                &["1000", "-1 000", "1\u{a0}000", "1\u{202f}000"],
+                &["1 00", "1.000"],
+                &["3,14", "1 000,5", "1\u{202f}000,5"],
+                &["3.14", "1 00,5"],
+            ),
+        ] {
+            let options = Options {
+                number_format: format,
+                ..Options::default()
+            };
+            let int = Expression::regex_with_options("{int}", options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let float = Expression::regex_with_options("{float}", options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            for i in ints {
+                assert!(int.is_match(i), "{:?} on int: {}", format, i);
+            }
+            for i in not_ints {
+                assert!(!int.is_match(i), "{:?} on not int: {}", format, i);
+            }
+            for f in floats {
+                assert!(float.is_match(f), "{:?} on float: {}", format, f);
+            }
+            for f in not_floats {
+                assert!(!float.is_match(f), "{:?} on float: {}", format, f);
+            }
         }
     }
-}
 
-impl<Iter> Iterator for SkipLast<Iter>
-where
-    Iter: Iterator,
-{
-    type Item = Iter::Item;
+    #[test]
+    fn builtin_patterns_without_look_around() {
+        for dialect in [
+            Dialect::Rust,
+            Dialect::Pcre,
+            Dialect::EcmaScript,
+            Dialect::Re2,
+        ] {
+            for number_format in [
+                NumberFormat::Plain,
+                NumberFormat::CommaGrouping,
+                NumberFormat::DotGroupingDecimalComma,
+                NumberFormat::SpaceGroupingDecimalComma,
+            ] {
+                let options = Options {
+                    dialect,
+                    number_format,
+                    ..Options::default()
+                };
+                for name in builtin_parameter_names().chain(Some("")) {
+                    let re = builtin_parameter_pattern(name, options).unwrap();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let next = self.iter.next();
-        (self.iter.peek().is_some()).then(|| next).flatten()
+                    assert!(
+                        ["(?=", "(?!", "(?<=", "(?<!"]
+                            .iter()
+                            .all(|look| !re.contains(look)),
+                        "`{}` on {:?}: {}",
+                        name,
+                        options,
+                        re,
+                    );
+                    assert!(
+                        regex::Regex::new(re).is_ok(),
+                        "`{}`: {}",
+                        name,
+                        re
+                    );
+                }
+            }
+        }
     }
-}
 
-/// [`Iterator`] for escaping `^`, `$`, `[`, `]`, `(`, `)`, `{`, `}`, `.`, `|`,
-/// `?`, `*`, `+` with `\`, and removing it for other [`char`]s.
-///
-/// # Example
-///
-/// ```rust
-/// # use cucumber_expressions::expand::EscapeForRegex;
-/// #
-/// assert_eq!(
-///     EscapeForRegex::new("\\\\text\\ (\\)\\".chars()).collect::<String>(),
-///     "\\\\text \\(\\)",
-/// );
-/// ```
-#[derive(Clone, Debug)]
-pub struct EscapeForRegex<Iter: Iterator> {
-    /// Inner [`Iterator`] for escaping.
-    iter: iter::Peekable<Iter>,
+    #[test]
+    fn escapes_in_chunks() {
+        for text in [
+            "plain text",
+            "a(b)c",
+            "\\(escaped\\) \\{x\\} \\/ \\\\",
+            "\\ space \\q trailing\\",
+            "🦀.🦀\\🦀",
+        ] {
+            for dialect in [Dialect::Rust, Dialect::EcmaScript] {
+                let expected =
+                    EscapeForRegex::for_dialect(text.chars(), dialect)
+                        .collect::<String>();
 
-    /// [`Item`] that was escaped.
-    ///
-    /// [`Item`]: Iterator::Item
-    was_escaped: Option<Iter::Item>,
-}
+                let mut whole = String::new();
+                super::write_escaped_for_regex(&mut whole, &text, dialect)
+                    .unwrap();
+                assert_eq!(whole, expected, "on input: {}", text);
 
-impl<Iter: Iterator> EscapeForRegex<Iter> {
-    /// Creates a new [`EscapeForRegex`] [`Iterator`].
-    pub fn new(iter: Iter) -> Self {
-        Self {
-            iter: iter.peekable(),
-            was_escaped: None,
+                for (at, _) in text.char_indices() {
+                    let mut chunked = String::new();
+                    let mut w = super::EscapingWriter {
+                        w: &mut chunked,
+                        escaped_chars: dialect.escaped_chars(),
+                        backslash: false,
+                    };
+                    fmt::Write::write_str(&mut w, &text[..at]).unwrap();
+                    fmt::Write::write_str(&mut w, &text[at..]).unwrap();
+
+                    assert_eq!(
+                        chunked, expected,
+                        "on input `{}` split at {}",
+                        text, at,
+                    );
+                }
+            }
         }
     }
-}
 
-impl<Iter> Iterator for EscapeForRegex<Iter>
-where
-    Iter: Iterator<Item = char>,
-{
-    type Item = char;
+    #[test]
+    fn options_dialect() {
+        let expr = Expression::parse("{float} a\\/b(c)").unwrap();
+        for (dialect, expected) in [
+            (
+                Dialect::Rust,
+                "(?i)^(?P<float>[-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) \
+                 a/b(?:c)?$",
+            ),
+            (
+                Dialect::Re2,
+                "(?i)^(?P<float>[-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) \
+                 a/b(?:c)?$",
+            ),
+            (
+                Dialect::Pcre,
+                "(?i)^(?P<float>[-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) \
+                 a/b(?:c)?$",
+            ),
+            (
+                Dialect::EcmaScript,
+                "^(?<float>[-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) \
+                 a\\/b(?:c)?$",
+            ),
+        ] {
+            let options = Options {
+                case_insensitive: true,
+                named_groups: true,
+                dialect,
+                ..Options::default()
+            };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let should_be_escaped = |c| "^$[]()\\{}.|?*+".contains(c);
+            let mut written = String::new();
+            expr.expand_into_with(&mut written, options)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(written, expected, "on dialect: {:?}", dialect);
 
-        if self.was_escaped.is_some() {
-            return self.was_escaped.take();
+            let boxed = expr
+                .clone()
+                .into_regex_char_iter_boxed_with(options)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(boxed, expected, "on dialect: {:?}", dialect);
         }
 
-        loop {
-            return match self.iter.next() {
-                Some('\\') => {
-                    let c = *self.iter.peek()?;
-                    if should_be_escaped(c) {
-                        self.was_escaped = self.iter.next();
-                        Some('\\')
-                    } else {
-                        continue;
-                    }
-                }
-                Some(c) if should_be_escaped(c) => {
-                    self.was_escaped = Some(c);
-                    Some('\\')
-                }
-                Some(c) => Some(c),
-                None => None,
-            };
+        assert_eq!(
+            expr.clone()
+                .into_regex_string_for(Dialect::EcmaScript)
+                .unwrap_or_else(|e| panic!("failed: {}", e)),
+            "^([-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) a\\/b(?:c)?$",
+        );
+        assert_eq!(
+            expr.into_regex_string()
+                .unwrap_or_else(|e| panic!("failed: {}", e)),
+            "^([-+]?(?:\\d*\\.\\d+|\\d+)(?:[Ee][+-]?\\d+)?) a/b(?:c)?$",
+        );
+
+        let re = Expression::regex("{float}")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        for matching in ["1", "-1.5", ".5", "+10", "1E10"] {
+            assert!(re.is_match(matching), "on input: {}", matching);
+        }
+        for not_matching in ["", ".", "1.", "-"] {
+            assert!(!re.is_match(not_matching), "on input: {}", not_matching);
         }
     }
-}
-
-// All test examples from: <https://git.io/J159G>
-// Naming of test cases is preserved.
-#[cfg(test)]
-mod spec {
-    use super::{Error, Expression, UnknownParameterError};
 
     #[test]
-    fn alternation_with_optional() {
-        let expr = Expression::regex("a/b(c)")
+    fn builtin_patterns() {
+        let re = Expression::regex("{int} {float} {word} {string} {}")
             .unwrap_or_else(|e| panic!("failed: {}", e));
 
-        assert_eq!(expr.as_str(), "^(?:a|b(?:c)?)$");
+        assert_eq!(
+            re.as_str(),
+            format!(
+                "^{} {} {} {} {}$",
+                INT_PATTERN,
+                FLOAT_PATTERN,
+                WORD_PATTERN,
+                STRING_PATTERN,
+                ANONYMOUS_PATTERN,
+            ),
+        );
+
+        for name in builtin_parameter_names() {
+            let pattern = builtin_parameter_pattern(name, Options::default())
+                .unwrap_or_else(|| panic!("no pattern for: {}", name));
+            let single = Expression::regex(&format!("{{{}}}", name))
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(
+                single.as_str(),
+                format!("^{}$", pattern),
+                "on: {}",
+                name
+            );
+        }
+        assert_eq!(
+            builtin_parameter_pattern("", Options::default()),
+            Some(ANONYMOUS_PATTERN),
+        );
+        assert_eq!(
+            builtin_parameter_pattern("custom", Options::default()),
+            None
+        );
     }
 
     #[test]
-    fn alternation() {
-        let expr = Expression::regex("a/b c/d/e")
+    fn quantified_parameters() {
+        let opts = Options {
+            quantified_parameters: true,
+            ..Options::default()
+        };
+        let re = Expression::regex_with_options(
+            r#"sum of {int:3,","} is {int} for {word+}"#,
+            opts,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures("sum of 1,-2,3 is 2 for me and you").unwrap();
+        let quantified = QuantifiedParameter::parse(r#"int:3,",""#).unwrap();
+
+        assert_eq!(re.captures_len(), 4);
+        assert_eq!(
+            quantified.split(&caps[1]).collect::<Vec<_>>(),
+            ["1", "-2", "3"],
+        );
+        assert_eq!(&caps[2], "2");
+        assert_eq!(&caps[3], "me and you");
+        assert!(!re.is_match("sum of 1,2 is 3 for me"));
+        assert!(!re.is_match("sum of 1,2,3,4 is 3 for me"));
+
+        let pars = HashMap::from([("int+", "\\d")]);
+        let custom = Expression::regex_with_parameters_and_options(
+            "{int+} {color+}",
+            &pars,
+            opts,
+        );
+
+        assert!(matches!(
+            custom.unwrap_err(),
+            Error::Expansion(e) if *e.not_found == "color+",
+        ));
+        assert!(matches!(
+            Expression::regex("{int+}").unwrap_err(),
+            Error::Expansion(_),
+        ));
+    }
+
+    #[test]
+    fn regex_batch() {
+        let pars = HashMap::from([("color", "red|blue")]);
+        let inputs = (0..100)
+            .map(|n| format!("{{int}} {{color}} {}", n))
+            .chain(["{unknown}".into()])
+            .collect::<Vec<_>>();
+        let inputs = inputs.iter().map(String::as_str).collect::<Vec<_>>();
+
+        let res = Expression::regex_batch(&inputs, &pars);
+
+        assert_eq!(res.len(), inputs.len());
+        for (n, re) in res[..100].iter().enumerate() {
+            let re = re.as_ref().unwrap_or_else(|e| panic!("failed: {}", e));
+            assert_eq!(
+                re.as_str(),
+                format!("^((?:-?\\d+)|(?:\\d+)) (red|blue) {}$", n),
+            );
+        }
+        assert!(matches!(res[100], Err(Error::Expansion(_))));
+        assert!(Expression::regex_batch::<str, _>(&[], &pars).is_empty());
+    }
+
+    #[cfg(feature = "into-fancy-regex")]
+    #[test]
+    fn fancy_regex() {
+        let pars = HashMap::from([("not_b", "(?!b)\\w")]);
+        let re = Expression::fancy_regex_with_parameters_and_options(
+            "{not_b} {int}",
+            &pars,
+            Options {
+                anchor_end: false,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^((?!b)\\w) ((?:-?\\d+)|(?:\\d+))");
+        assert!(re.is_match("a 1 and more").unwrap());
+        assert!(!re.is_match("b 1").unwrap());
+
+        assert!(matches!(
+            Expression::fancy_regex_with_parameters(
+                "{not_b}",
+                &HashMap::from([("not_b", "(")])
+            ),
+            Err(Error::InvalidParameter(_)),
+        ));
+
+        let atomic = Expression::fancy_regex_with_parameters_and_options(
+            "a(b)b",
+            &HashMap::<&str, &str>::new(),
+            Options {
+                atomic_groups: true,
+                dialect: Dialect::Pcre,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert!(atomic.is_match("abb").unwrap());
+        assert!(!atomic.is_match("ab").unwrap());
+    }
+
+    #[test]
+    fn capture_map() {
+        let (re, map) =
+            Expression::regex_with_capture_map("a {int} b {} (c) {string}")
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.captures_len(), map.len() + 1);
+        assert_eq!(
+            map.iter()
+                .map(|p| (p.name, p.span.clone()))
+                .collect::<Vec<_>>(),
+            [("int", 2..7), ("", 10..12), ("string", 17..25)],
+        );
+
+        let pars = HashMap::from([("pair", "(a)(?:b)(c)")]);
+        let (custom_re, custom_map) =
+            Expression::regex_with_parameters_and_capture_map(
+                "{int} {pair} {word}",
+                &pars,
+            )
             .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(custom_re.captures_len(), custom_map.len() + 1);
+        assert_eq!(
+            custom_map.iter().map(|p| p.name).collect::<Vec<_>>(),
+            ["int", "pair", "pair", "pair", "word"],
+        );
+        assert_eq!(custom_map[1].span, 6..12);
+    }
 
-        assert_eq!(expr.as_str(), "^(?:a|b) (?:c|d|e)$");
+    #[test]
+    fn regex_with_builder() {
+        let pars = HashMap::from([("color", "[[:alpha:]]+")]);
+        let re = Expression::regex_with_builder(
+            "{color} {int}",
+            &pars,
+            Options {
+                case_insensitive: true,
+                ..Options::default()
+            },
+            |b| b.unicode(false),
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert!(re.is_match("RED 1"));
+        assert!(!re.is_match("rød 1"));
+        assert!(!re.is_match("red ١"));
+
+        let big = HashMap::from([("big", "a{100}")]);
+
+        assert!(matches!(
+            Expression::regex_with_builder(
+                "{string}",
+                &big,
+                Options::default(),
+                |b| b.size_limit(100),
+            )
+            .unwrap_err(),
+            Error::Regex(_),
+        ));
+        assert!(matches!(
+            Expression::regex_with_builder(
+                "{big}",
+                &big,
+                Options::default(),
+                |b| b.size_limit(100),
+            )
+            .unwrap_err(),
+            Error::InvalidParameter(_),
+        ));
     }
 
     #[test]
-    fn empty() {
-        let expr =
-            Expression::regex("").unwrap_or_else(|e| panic!("failed: {}", e));
+    fn capture_count() {
+        let pars = HashMap::from([
+            ("pair", r"(a)(?:b)(c)"),
+            ("named", r"(?P<x>a)(?<y>b)(?i:c)"),
+            ("escaped", r"\(a\)[(]x[^]()](y)"),
+        ]);
 
-        assert_eq!(expr.as_str(), "^$");
+        for expr in [
+            "a {int} b {} (c) {string}",
+            "{ordinal} {pair} or {named} and/or {escaped}",
+            "{pair}{pair}",
+        ] {
+            let re = Expression::regex_with_parameters(expr, &pars)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let parsed = Expression::parse(expr).unwrap();
+            let map = parsed
+                .capture_map_with_parameters_and_options(
+                    &pars,
+                    Options::default(),
+                )
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(map.len() + 1, re.captures_len(), "on: {}", expr);
+        }
+
+        let expr = Expression::parse("{pair} {int:2}").unwrap();
+        let opts = Options {
+            non_capturing_parameters: true,
+            quantified_parameters: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            expr.capture_map_with_parameters_and_options(&pars, opts)
+                .unwrap()
+                .len(),
+            2,
+        );
+        assert!(matches!(
+            expr.capture_count().unwrap_err(),
+            Error::Expansion(e) if *e.not_found == "pair",
+        ));
     }
 
     #[test]
-    fn escape_regex_characters() {
-        let expr = Expression::regex(r"^$[]\(\){}\\.|?*+")
+    fn bytes_regex() {
+        let re = Expression::bytes_regex("{word} {int} café {}")
             .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures(b"na\xefve 42 caf\xc3\xa9 \xff\xfe").unwrap();
+        assert_eq!(&caps[1], b"na\xefve");
+        assert_eq!(&caps[2], b"42");
+        assert_eq!(&caps[3], b"\xff\xfe");
 
-        assert_eq!(expr.as_str(), r"^\^\$\[\]\(\)(.*)\\\.\|\?\*\+$");
+        let pars = HashMap::from([("color", "red|blue")]);
+        let insensitive = Expression::bytes_regex_with_parameters_and_options(
+            "{color}",
+            &pars,
+            Options {
+                case_insensitive: true,
+                ..Options::default()
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert!(insensitive.is_match(b"BLUE"));
     }
 
     #[test]
@@ -618,6 +5365,68 @@ mod spec {
         assert_eq!(expr.as_str(), "^(?:a)?$");
     }
 
+    #[test]
+    fn optional_parameter() {
+        let options = Options {
+            optional_parameters: true,
+            ..Options::default()
+        };
+        let expected = r"^a(?: b((?:-?\d+)|(?:\d+))\.)?$";
+
+        let re = Expression::regex_with_options("a( b{int}.)", options)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), expected);
+        assert_eq!(re.captures("a b3.").unwrap().get(1).unwrap().as_str(), "3");
+        assert!(re.captures("a").unwrap().get(1).is_none());
+
+        let expr =
+            Expression::parse_with_syntax("a( b{int}.)", options.syntax())
+                .unwrap();
+        assert_eq!(
+            expr.clone()
+                .into_regex_char_iter()
+                .collect::<Result<String, _>>()
+                .unwrap(),
+            expected,
+        );
+        assert_eq!(
+            expr.clone()
+                .into_regex_char_iter_boxed()
+                .collect::<Result<String, _>>()
+                .unwrap(),
+            expected,
+        );
+
+        let pars = HashMap::from([("num", r"\d+")]);
+        let custom = Expression::regex_with_parameters_and_options(
+            "a( b{num}) c",
+            &pars,
+            options,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(custom.as_str(), r"^a(?: b(\d+))? c$");
+
+        let optimized = Expression::regex_with_options(
+            "a(s)( b{int})",
+            Options {
+                optimize: true,
+                ..options
+            },
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        for text in ["a", "as b3", "a b"] {
+            assert_eq!(
+                optimized.is_match(text),
+                text != "a b",
+                "on text: {}",
+                text,
+            );
+        }
+
+        let err = Expression::regex("a( b{int})").unwrap_err();
+        assert!(matches!(err, Error::Parsing(_)), "wrong err: {}", err);
+    }
+
     #[test]
     fn parameter() {
         let expr = Expression::regex("{int}")
@@ -626,6 +5435,53 @@ mod spec {
         assert_eq!(expr.as_str(), "^((?:-?\\d+)|(?:\\d+))$");
     }
 
+    #[test]
+    fn regex_string() {
+        let re = Expression::regex_string("a/b (c) {int}\\(")
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(re, r"^(?:a|b) (?:c)? ((?:-?\d+)|(?:\d+))\($");
+    }
+
+    #[test]
+    fn regex_string_parsing_error() {
+        let err = Expression::regex_string("{} (a").unwrap_err();
+
+        assert!(matches!(err, Error::Parsing(_)), "wrong err: {}", err);
+    }
+
+    #[test]
+    fn size_hint() {
+        for input in [
+            "",
+            "a/b(c) {int} {float}",
+            "(a) b/c/d\\/ {} {word}",
+            "\\(a\\) [b]. ^$ \\\\ \\{",
+            "/^a(b)$/",
+        ] {
+            let expr = AnyExpression::parse(input)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let len = (&expr)
+                .into_regex_string()
+                .unwrap_or_else(|e| panic!("failed: {}", e))
+                .chars()
+                .count();
+
+            let mut iter = expr.into_regex_char_iter();
+            assert!(iter.size_hint().0 > 0, "on input: {}", input);
+            for remaining in (0..=len).rev() {
+                let (lower, upper) = iter.size_hint();
+                assert!(lower <= remaining, "on input: {}", input);
+                assert!(
+                    upper.map_or(true, |u| u >= remaining),
+                    "on input: {}",
+                    input,
+                );
+                let _ = iter.next();
+            }
+        }
+    }
+
     #[test]
     fn text() {
         let expr =
@@ -646,12 +5502,66 @@ mod spec {
     #[test]
     fn unknown_parameter() {
         match Expression::regex("{custom}").unwrap_err() {
-            Error::Expansion(UnknownParameterError { not_found }) => {
+            Error::Expansion(UnknownParameterError { not_found, .. }) => {
                 assert_eq!(*not_found, "custom");
             }
-            e @ (Error::Parsing(_) | Error::Regex(_)) => {
-                panic!("wrong err: {}", e);
-            }
+            e @ (Error::Parsing(_)
+            | Error::Regex(_)
+            | Error::Shadowing(_)
+            | Error::InvalidParameter(_)
+            | Error::Formatting(_)) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-regex-lite")]
+            e @ Error::RegexLite(_) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-fancy-regex")]
+            e @ Error::FancyRegex(_) => panic!("wrong err: {}", e),
         }
     }
+
+    #[test]
+    fn validate_with_parameters() {
+        let pars = HashMap::from([("color", "red|blue")]);
+
+        assert_eq!(
+            Expression::validate_with_parameters("{color} {int} {}", &pars),
+            Ok(()),
+        );
+
+        let unknown = Expression::validate_with_parameters(
+            "{colour} {word} {size} {colour}",
+            &pars,
+        )
+        .unwrap_err()
+        .into_iter()
+        .map(|d| match d {
+            Diagnostic::UnknownParameter(p) => (*p, p.location_offset()),
+            d @ (Diagnostic::Parsing(_)
+            | Diagnostic::UnmatchedReservedCharacter(_)
+            | Diagnostic::AdjacentAnonymousParameters(_)) => {
+                panic!("wrong diagnostic: {}", d)
+            }
+        })
+        .collect::<Vec<_>>();
+        assert_eq!(unknown, [("colour", 1), ("size", 17), ("colour", 24)]);
+
+        let parsing =
+            Expression::validate_with_parameters("{unknown} (a", &pars)
+                .unwrap_err();
+        assert_eq!(parsing.len(), 1);
+        assert!(matches!(parsing[0], Diagnostic::Parsing(_)));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "supported by `ExpandInto` only")]
+    fn char_iter_asserts_expand_into_only_options() {
+        let options = Options {
+            quantified_parameters: true,
+            ..Options::default()
+        };
+
+        let _ = Expression::parse("{int:2}")
+            .unwrap()
+            .into_regex_char_iter_with(options)
+            .collect::<Result<String, _>>();
+    }
 }
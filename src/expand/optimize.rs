@@ -0,0 +1,150 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Optimizations applied when expanding with [`Options::optimize`].
+//!
+//! [`Options::optimize`]: super::Options::optimize
+
+use std::fmt;
+
+use nom::{AsChar, InputIter};
+
+use crate::{Alternation, Alternative, Optional};
+
+use super::{Dialect, Options};
+
+/// Returns the only [`char`] matched by the given [`Optional`], if it matches
+/// exactly one, so it can be expanded into `c?` instead of `(?:c)?`.
+pub(super) fn single_char<Input>(opt: &Optional<Input>) -> Option<char>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let cooked = cooked(&opt.0);
+    let mut chars = cooked.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then(|| c)
+}
+
+/// Returns literal texts matched by every [`SingleAlternation`] of the given
+/// [`Alternation`], merging adjacent textual [`Alternative`]s into one.
+///
+/// [`None`] is returned if any [`SingleAlternation`] contains an
+/// [`Optional`].
+///
+/// [`SingleAlternation`]: crate::SingleAlternation
+pub(super) fn literals<Input>(alt: &Alternation<Input>) -> Option<Vec<String>>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    alt.0
+        .iter()
+        .map(|single| {
+            single.iter().try_fold(String::new(), |mut lit, a| match a {
+                Alternative::Text(t) => {
+                    lit.push_str(&cooked(t));
+                    Some(lit)
+                }
+                Alternative::Optional(_) => None,
+            })
+        })
+        .collect()
+}
+
+/// Writes an alternation of the given `literals` into the `w`riter, hoisting
+/// their common prefix out of it.
+///
+/// Duplicated `literals` are written once, and no group is written at all if
+/// only one remains.
+pub(super) fn write_hoisted<W>(
+    w: &mut W,
+    literals: &[String],
+    options: Options,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    let prefix_len = literals.split_first().map_or(0, |(first, rest)| {
+        rest.iter().fold(first.len(), |len, lit| {
+            first[..len]
+                .char_indices()
+                .zip(lit.chars())
+                .find_map(|((i, a), b)| (a != b).then(|| i))
+                .unwrap_or_else(|| len.min(lit.len()))
+        })
+    });
+
+    let mut rests = Vec::<&str>::with_capacity(literals.len());
+    for lit in literals {
+        let rest = &lit[prefix_len..];
+        if !rests.contains(&rest) {
+            rests.push(rest);
+        }
+    }
+
+    if let Some(first) = literals.first() {
+        write_escaped(w, &first[..prefix_len], options.dialect)?;
+    }
+    if let [single] = rests.as_slice() {
+        return write_escaped(w, single, options.dialect);
+    }
+    w.write_str(options.alternation_start())?;
+    for (n, rest) in rests.iter().enumerate() {
+        if n > 0 {
+            w.write_char('|')?;
+        }
+        write_escaped(w, rest, options.dialect)?;
+    }
+    w.write_char(')')
+}
+
+/// Writes the given cooked `text` into the `w`riter, escaping [`char`]s
+/// special for the given [`Dialect`], with runs of other [`char`]s written at
+/// once.
+pub(super) fn write_escaped<W>(
+    w: &mut W,
+    text: &str,
+    dialect: Dialect,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    let mut rest = text;
+    while let Some(i) = rest.find(|c| dialect.escaped_chars().contains(c)) {
+        w.write_str(&rest[..i])?;
+        let c = rest[i..].chars().next().unwrap_or_default();
+        w.write_char('\\')?;
+        w.write_char(c)?;
+        rest = &rest[(i + c.len_utf8())..];
+    }
+    w.write_str(rest)
+}
+
+/// Returns a cooked view of the given `text`, with `\` escapes resolved the
+/// same way as [`EscapeForRegex`] does.
+///
+/// [`EscapeForRegex`]: super::EscapeForRegex
+fn cooked<Input>(text: &Input) -> String
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let mut chars = text.iter_elements().map(AsChar::as_char);
+    let mut cooked = String::new();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            cooked.extend(chars.next());
+        } else {
+            cooked.push(c);
+        }
+    }
+    cooked
+}
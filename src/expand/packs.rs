@@ -0,0 +1,185 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Ready-made packs of custom [`Parameter`]s for common domains.
+//!
+//! Every pack is a slice of [`Parameter`] names and their [`Regex`] patterns,
+//! so it may be used as a [`ParametersProvider`] directly, combined with
+//! other ones via [`ProviderExt::or()`], or layered into a
+//! [`ParameterTypeRegistry`] via [`ParameterTypeRegistry::define_pack()`][1].
+//!
+//! # Example
+//!
+//! ```rust
+//! # use cucumber_expressions::{
+//! #     expand::{packs, ParameterTypeRegistry},
+//! #     Expression,
+//! # };
+//! #
+//! let mut registry = ParameterTypeRegistry::new();
+//! registry.define_pack(packs::WEEKDAYS).unwrap();
+//! registry.define_pack(packs::COUNTRIES).unwrap();
+//!
+//! let re = Expression::regex_with_parameters(
+//!     "I fly to {country} on {weekday}",
+//!     &registry,
+//! )
+//! .unwrap();
+//! assert!(re.is_match("I fly to NZ on Friday"));
+//! ```
+//!
+//! [`Parameter`]: crate::Parameter
+//! [`ParameterTypeRegistry`]: super::ParameterTypeRegistry
+//! [`ParametersProvider`]: super::ParametersProvider
+//! [`ProviderExt::or()`]: super::parameters::ProviderExt::or()
+//! [`Regex`]: regex::Regex
+//! [1]: super::ParameterTypeRegistry::define_pack()
+
+/// Pack of custom [`Parameter`] names and their [`Regex`] patterns.
+///
+/// [`Parameter`]: crate::Parameter
+/// [`Regex`]: regex::Regex
+pub type Pack = &'static [(&'static str, &'static str)];
+
+/// `{color}` [`Parameter`] matching basic color names in lowercase (like
+/// `red` or `light blue`).
+///
+/// [`Parameter`]: crate::Parameter
+pub const COLORS: Pack = &[(
+    "color",
+    "(?:(?:light|dark) )?(?:red|orange|yellow|green|cyan|blue|purple|violet\
+     |magenta|pink|brown)|black|white|gr[ae]y",
+)];
+
+/// `{yesno}` [`Parameter`] matching affirmative and negative answers (`yes`,
+/// `no`, `y`, `n`, `on`, `off`, `true` and `false`), in lowercase or
+/// capitalized.
+///
+/// [`Parameter`]: crate::Parameter
+pub const BOOLEANS: Pack =
+    &[("yesno", "[Yy]es|[Nn]o|[Oo]n|[Oo]ff|[Tt]rue|[Ff]alse|[YyNn]")];
+
+/// `{weekday}` [`Parameter`] matching capitalized English names of weekdays,
+/// either full (`Monday`) or abbreviated (`Mon`).
+///
+/// [`Parameter`]: crate::Parameter
+pub const WEEKDAYS: Pack = &[(
+    "weekday",
+    "Mon(?:day)?|Tue(?:sday)?|Wed(?:nesday)?|Thu(?:rsday)?|Fri(?:day)?\
+     |Sat(?:urday)?|Sun(?:day)?",
+)];
+
+/// `{month}` [`Parameter`] matching capitalized English names of months,
+/// either full (`January`) or abbreviated (`Jan`).
+///
+/// [`Parameter`]: crate::Parameter
+pub const MONTHS: Pack = &[(
+    "month",
+    "Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?\
+     |Aug(?:ust)?|Sep(?:tember)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?",
+)];
+
+/// `{country}` [`Parameter`] matching officially assigned [ISO 3166-1
+/// alpha-2][1] country codes (like `US` or `NZ`).
+///
+/// [`Parameter`]: crate::Parameter
+/// [1]: https://en.wikipedia.org/wiki/ISO_3166-1_alpha-2
+pub const COUNTRIES: Pack = &[(
+    "country",
+    "A[DEFGILMOQRSTUWXZ]|B[ABDEFGHIJLMNOQRSTVWYZ]|C[ACDFGHIKLMNORUVWXYZ]\
+     |D[EJKMOZ]|E[CEGHRST]|F[IJKMOR]|G[ABDEFGHILMNPQRSTUWY]|H[KMNRTU]\
+     |I[DELMNOQRST]|J[EMOP]|K[EGHIMNPRWYZ]|L[ABCIKRSTUVY]\
+     |M[ACDEFGHKLMNOPQRSTUVWXYZ]|N[ACEFGILOPRUZ]|OM|P[AEFGHKLMNRSTWY]|QA\
+     |R[EOSUW]|S[ABCDEGHIJKLMNORSTVXYZ]|T[CDFGHJKLMNORTVWZ]|U[AGMSYZ]\
+     |V[ACEGINU]|W[FS]|Y[ET]|Z[AMW]",
+)];
+
+/// All the packs of this module.
+pub const ALL: &[Pack] = &[COLORS, BOOLEANS, WEEKDAYS, MONTHS, COUNTRIES];
+
+#[cfg(all(test, feature = "into-regex"))]
+mod spec {
+    use crate::{
+        expand::{builtin_parameter_names, ParameterTypeRegistry},
+        Expression,
+    };
+
+    use super::{Pack, ALL, BOOLEANS, COLORS, COUNTRIES, MONTHS, WEEKDAYS};
+
+    /// Asserts that the only [`Parameter`] of the given `pack` matches all
+    /// the `matching` texts and none of the `other` ones.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    fn assert_pack(pack: Pack, matching: &[&str], other: &[&str]) {
+        let expr = format!("{{{}}}", pack[0].0);
+        let re = Expression::regex_with_parameters(&expr, pack)
+            .unwrap_or_else(|e| panic!("{}: {}", expr, e));
+        for text in matching {
+            assert!(re.is_match(text), "{} doesn't match {}", expr, text);
+        }
+        for text in other {
+            assert!(!re.is_match(text), "{} matches {}", expr, text);
+        }
+    }
+
+    #[test]
+    fn matches() {
+        assert_pack(
+            COLORS,
+            &["red", "light blue", "dark green", "grey", "gray", "black"],
+            &["Red", "light black", "dark"],
+        );
+        assert_pack(
+            BOOLEANS,
+            &["yes", "No", "y", "N", "on", "Off", "true", "False"],
+            &["yep", "nope", "YES"],
+        );
+        assert_pack(
+            WEEKDAYS,
+            &["Monday", "Mon", "Wednesday", "Thu", "Sunday"],
+            &["monday", "Mond", "Thurs"],
+        );
+        assert_pack(
+            MONTHS,
+            &["January", "Jan", "May", "Jun", "June", "Sep", "September"],
+            &["january", "Sept", "Ma"],
+        );
+        assert_pack(
+            COUNTRIES,
+            &["US", "NZ", "UA", "DE", "OM", "QA", "ZW"],
+            &["us", "UK", "XX", "USA", "QQ"],
+        );
+    }
+
+    #[test]
+    fn layers_into_registry() {
+        let mut registry = ParameterTypeRegistry::new();
+        for pack in ALL {
+            registry.define_pack(pack).unwrap();
+        }
+        for (name, _) in ALL.iter().copied().flatten() {
+            assert!(
+                !builtin_parameter_names().any(|n| n == *name),
+                "{} shadows a built-in parameter",
+                name,
+            );
+            assert!(registry.lookup_by_name(name).is_some());
+        }
+
+        let re = Expression::regex_with_parameters(
+            "{color} car on {weekday}, {int} {month}",
+            &registry,
+        )
+        .unwrap();
+        assert!(re.is_match("light blue car on Friday, 13 Oct"));
+
+        assert!(registry.define_pack(COLORS).is_err());
+    }
+}
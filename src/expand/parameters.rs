@@ -12,16 +12,37 @@
 //!
 //! [1]: https://github.com/cucumber/cucumber-expressions#custom-parameter-types
 
-use std::{collections::HashMap, fmt::Display, iter, vec};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    fmt::{self, Display},
+    hash::{BuildHasher, Hash, Hasher as _},
+    iter, slice, str,
+    sync::Arc,
+    vec,
+};
 
+use derive_more::{AsRef, Deref, Display, From, Into};
 use either::Either;
-use nom::{AsChar, InputIter};
+use nom::{AsChar, InputIter, Needed};
+#[cfg(feature = "into-regex")]
+use regex_syntax::ast::{parse::Parser, Ast};
+
+use crate::{AnyExpression, Parameter, SingleExpression};
 
-use crate::{Parameter, SingleExpression};
+#[cfg(feature = "derive")]
+pub use cucumber_expressions_derive::Parameters;
 
 use super::{
-    Expression, IntoRegexCharIter, ParameterIter, SingleExpressionIter,
-    UnknownParameterError,
+    builtin_or_quantified_parameter, builtin_parameter,
+    expand_builtin_parameter_into, expand_optional_parameter_into,
+    expression_affixes_len, expression_prefix_iter, expression_suffix_iter,
+    group_names, optional_parameter_iter, regular_expression_iter,
+    write_group_start, Error, ExpandInto, Expression, ExpressionPrefixIter,
+    ExpressionSuffixIter, IntoRegexCharIter, InvalidParameterError,
+    MinRegexLen as _, OptionalParameterIter, Options, ParameterIter,
+    RegularExpressionIter, ShadowedParameterError, SingleExpressionIter,
+    SingleExpressionRefIter, SizeHinted, UnknownParameterError,
 };
 
 /// Parser of a [Cucumber Expressions][0] [AST] `Element` with [custom][1]
@@ -30,9 +51,18 @@ use super::{
 /// Every [`Parameter`] should be represented by a single [`Regex`] capturing
 /// group.
 ///
+/// Custom `Parameters` take precedence over the [default ones][2], so a custom
+/// `{int}` [`Parameter`] shadows the built-in one.
+///
+/// `Parameters` are never cloned during expansion: [`ExpandInto`] borrows
+/// them, while [`IntoRegexCharIter`] shares them between the iterators of
+/// [`SingleExpression`]s via an [`Arc`]. So a [`Provider`] doesn't need to be
+/// [`Clone`] (holding a database handle, for example).
+///
 /// [`Regex`]: regex::Regex
 /// [0]: https://github.com/cucumber/cucumber-expressions#readme
 /// [1]: https://github.com/cucumber/cucumber-expressions#custom-parameter-types
+/// [2]: https://github.com/cucumber/cucumber-expressions#parameter-types
 /// [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
 #[derive(Clone, Copy, Debug)]
 pub struct WithCustom<Element, Parameters> {
@@ -51,6 +81,64 @@ pub struct WithCustom<Element, Parameters> {
 }
 
 /// Provider of custom [`Parameter`]s.
+///
+/// This trait is object safe, so [`Provider`]s assembled at runtime may be
+/// passed as `&dyn Provider` or [`Box`]`<dyn Provider>` (with [`Item`] and
+/// [`Value`] specified).
+///
+/// For implementing a custom [`Provider`], consider the [`SimpleProvider`]
+/// instead, not involving any [`InputIter`] machinery.
+///
+/// With `derive` Cargo feature enabled, [`Provider`]s may be declared as
+/// structs via `#[derive(Parameters)]` macro, validating the [`Parameter`]s at
+/// compile time.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// # use cucumber_expressions::{expand::parameters::Parameters, Expression};
+/// #
+/// #[derive(Default, Parameters)]
+/// struct Params {
+///     #[param(regex = "red|blue")]
+///     color: (),
+///     #[param(name = "animal", regex = "cat|dog")]
+///     pet: (),
+/// }
+///
+/// let re = Expression::regex_with_parameters(
+///     "{color} {animal}",
+///     &Params::default(),
+/// )
+/// .unwrap();
+/// assert!(re.is_match("red cat"));
+/// assert_eq!(Params::PARAMETERS[1], ("animal", "cat|dog"));
+/// # }
+/// ```
+///
+/// # Resolution order
+///
+/// Expanding a [`Parameter`], its name is resolved deterministically, never
+/// depending on the iteration order of a container (like a [`HashMap`]), so
+/// the same [`Parameter`]s always produce the same [`Regex`] pattern,
+/// reproducible across runs and platforms for caching purposes:
+/// 1. The [`Provider`] is consulted via [`get()`] by the exact name. Ordered
+///    containers having several entries with the same name (like a slice)
+///    resolve to the first one, while combined [`Provider`]s (see
+///    [`ProviderExt::or()`]) consult the first [`Provider`] before the
+///    fallback one.
+/// 2. The [built-in][1] [`Parameter`] with the same name is used.
+/// 3. The hook of [`ProviderExt::on_unknown_parameter()`] is called, if any.
+///
+/// Unordered containers return their [`names()`] sorted.
+///
+/// [`get()`]: Provider::get
+/// [`names()`]: Provider::names
+/// [`Item`]: Self::Item
+/// [`Regex`]: regex::Regex
+/// [`Value`]: Self::Value
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
 pub trait Provider<Input> {
     /// `<`[`Value`]` as `[`InputIter`]`>::`[`Item`].
     ///
@@ -67,199 +155,2920 @@ pub trait Provider<Input> {
 
     /// Returns a [`Value`] matcher corresponding to the given `input`, if any.
     ///
+    /// Consulted before the [default ones][1], so returning a [`Value`] for a
+    /// built-in [`Parameter`] name overrides it.
+    ///
     /// [`Value`]: Self::Value
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
     fn get(&self, input: &Input) -> Option<Self::Value>;
+
+    /// Returns names of all the provided [`Parameter`]s, used to suggest a
+    /// similar one in an [`UnknownParameterError`].
+    ///
+    /// Returns nothing by default.
+    fn names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns a fingerprint of all the provided [`Parameter`]s, changing
+    /// whenever any of them changes, to distinguish [`Regex`]es expanded with
+    /// different [`Parameter`]s in the [`cache`].
+    ///
+    /// Returns [`None`] by default, so [`Regex`]es expanded with this
+    /// [`Provider`] are never cached.
+    ///
+    /// [`cache`]: super::cache
+    /// [`Regex`]: regex::Regex
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl<Input, P> Provider<Input> for Arc<P>
+where
+    P: Provider<Input> + ?Sized,
+{
+    type Item = P::Item;
+    type Value = P::Value;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        (**self).get(input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+}
+
+impl<Input, P> Provider<Input> for Box<P>
+where
+    P: Provider<Input> + ?Sized,
+{
+    type Item = P::Item;
+    type Value = P::Value;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        (**self).get(input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+}
+
+impl<'p, Input, Item, Value> Provider<Input>
+    for &'p (dyn Provider<Input, Item = Item, Value = Value> + 'p)
+where
+    Item: AsChar,
+    Value: InputIter<Item = Item>,
+{
+    type Item = Item;
+    type Value = Value;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        (**self).get(input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+}
+
+impl<'p, Input, Item, Value> Provider<Input>
+    for &'p (dyn Provider<Input, Item = Item, Value = Value> + Send + Sync + 'p)
+where
+    Item: AsChar,
+    Value: InputIter<Item = Item>,
+{
+    type Item = Item;
+    type Value = Value;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        (**self).get(input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        (**self).names()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
 }
 
+/// Looks up [`Parameter`]s in O(1) time, without any allocations for names
+/// shorter than 64 bytes.
 impl<'p, Input, Key, Value, S> Provider<Input> for &'p HashMap<Key, Value, S>
 where
     Input: InputIter,
     <Input as InputIter>::Item: AsChar,
-    Key: AsRef<str>,
+    Key: Borrow<str> + Eq + Hash,
     Value: AsRef<str>,
+    S: BuildHasher,
 {
     type Item = char;
     type Value = &'p str;
 
     fn get(&self, input: &Input) -> Option<Self::Value> {
-        self.iter().find_map(|(k, v)| {
-            k.as_ref()
-                .chars()
-                .eq(input.iter_elements().map(AsChar::as_char))
-                .then(|| v.as_ref())
-        })
+        let map: &'p HashMap<_, _, _> = *self;
+        with_name(input, |name| map.get(name)).map(AsRef::as_ref)
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self
+            .keys()
+            .map(|k| k.borrow().to_owned())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(
+            self.iter().map(|(k, v)| (k.borrow(), v.as_ref())),
+        ))
     }
 }
 
-impl<Input, Pars> IntoRegexCharIter<Input>
-    for WithCustom<Expression<Input>, Pars>
+/// Unlike a [`HashMap`], reports [`Provider::names()`] in their insertion
+/// order.
+#[cfg(feature = "indexmap")]
+impl<'p, Input, Key, Value, S> Provider<Input>
+    for &'p indexmap::IndexMap<Key, Value, S>
 where
-    Input: Clone + Display + InputIter,
+    Input: InputIter,
     <Input as InputIter>::Item: AsChar,
-    Pars: Clone + Provider<Input>,
-    <Pars as Provider<Input>>::Value: InputIter,
+    Key: AsRef<str>,
+    Value: AsRef<str>,
 {
-    type Iter = ExpressionWithParsIter<Input, Pars>;
+    type Item = char;
+    type Value = &'p str;
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        let add_pars: fn(_) -> _ = |(item, parameters)| WithCustom {
-            element: item,
-            parameters,
-        };
-        let into_regex_char_iter: fn(_) -> _ =
-            IntoRegexCharIter::into_regex_char_iter;
-        iter::once(Ok('^'))
-            .chain(
-                self.element
-                    .0
-                    .into_iter()
-                    .zip(iter::repeat(self.parameters))
-                    .map(add_pars)
-                    .flat_map(into_regex_char_iter),
-            )
-            .chain(iter::once(Ok('$')))
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        find_pair(self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())), input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.keys().map(|k| k.as_ref().to_owned()).collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(
+            self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())),
+        ))
     }
 }
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<`[`Expression`]`>`.
-type ExpressionWithParsIter<I, P> = iter::Chain<
-    iter::Chain<
-        iter::Once<Result<char, UnknownParameterError<I>>>,
-        iter::FlatMap<
-            iter::Map<
-                iter::Zip<vec::IntoIter<SingleExpression<I>>, iter::Repeat<P>>,
-                fn(
-                    (SingleExpression<I>, P),
-                ) -> WithCustom<SingleExpression<I>, P>,
-            >,
-            SingleExprWithParsIter<I, P>,
-            fn(
-                WithCustom<SingleExpression<I>, P>,
-            ) -> SingleExprWithParsIter<I, P>,
-        >,
-    >,
-    iter::Once<Result<char, UnknownParameterError<I>>>,
->;
+/// Looks up [`Parameter`]s in O(1) time, without any allocations for names
+/// shorter than 64 bytes.
+#[cfg(feature = "phf")]
+impl<'p, Input, Value> Provider<Input> for &'p phf::Map<&'static str, Value>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Value: AsRef<str>,
+{
+    type Item = char;
+    type Value = &'p str;
 
-impl<Input, Pars> IntoRegexCharIter<Input>
-    for WithCustom<SingleExpression<Input>, Pars>
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let map: &'p phf::Map<_, _> = *self;
+        with_name(input, |name| map.get(name)).map(AsRef::as_ref)
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names =
+            self.keys().map(|k| (*k).to_owned()).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(
+            self.entries().map(|(k, v)| (*k, v.as_ref())),
+        ))
+    }
+}
+
+impl<'p, Input, Key, Value> Provider<Input> for &'p BTreeMap<Key, Value>
 where
-    Input: Clone + Display + InputIter,
+    Input: InputIter,
     <Input as InputIter>::Item: AsChar,
-    Pars: Provider<Input>,
-    <Pars as Provider<Input>>::Value: InputIter,
+    Key: AsRef<str>,
+    Value: AsRef<str>,
 {
-    type Iter = SingleExprWithParsIter<Input, Pars>;
+    type Item = char;
+    type Value = &'p str;
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        use Either::{Left, Right};
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        find_pair(self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())), input)
+    }
 
-        if let SingleExpression::Parameter(item) = self.element {
-            Left(
-                WithCustom {
-                    element: item,
-                    parameters: self.parameters,
-                }
-                .into_regex_char_iter(),
-            )
-        } else {
-            Right(self.element.into_regex_char_iter())
-        }
+    fn names(&self) -> Vec<String> {
+        self.keys().map(|k| k.as_ref().to_owned()).collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(
+            self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())),
+        ))
     }
 }
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for
-/// [`WithCustom`]`<`[`SingleExpression`]`>`.
-type SingleExprWithParsIter<I, P> = Either<
-    <WithCustom<Parameter<I>, P> as IntoRegexCharIter<I>>::Iter,
-    SingleExpressionIter<I>,
->;
+impl<'p, Input, Key, Value> Provider<Input> for &'p [(Key, Value)]
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Key: AsRef<str>,
+    Value: AsRef<str>,
+{
+    type Item = char;
+    type Value = &'p str;
 
-impl<Input, P> IntoRegexCharIter<Input> for WithCustom<Parameter<Input>, P>
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        find_pair(self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())), input)
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|(k, _)| k.as_ref().to_owned()).collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(
+            self.iter().map(|(k, v)| (k.as_ref(), v.as_ref())),
+        ))
+    }
+}
+
+impl<'p, Input, Key, Value, const N: usize> Provider<Input>
+    for &'p [(Key, Value); N]
 where
-    Input: Clone + Display + InputIter,
+    Input: InputIter,
     <Input as InputIter>::Item: AsChar,
-    P: Provider<Input>,
-    <P as Provider<Input>>::Value: InputIter,
+    Key: AsRef<str>,
+    Value: AsRef<str>,
 {
-    type Iter = WithParsIter<Input, P>;
+    type Item = char;
+    type Value = &'p str;
 
-    fn into_regex_char_iter(self) -> Self::Iter {
-        use Either::{Left, Right};
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let pars: &'p [(Key, Value)] = *self;
+        Provider::get(&pars, input)
+    }
 
-        let ok: fn(_) -> _ = |c: <P::Value as InputIter>::Item| Ok(c.as_char());
-        self.parameters.get(&self.element).map_or_else(
-            || Right(self.element.into_regex_char_iter()),
-            |v| {
-                Left(
-                    iter::once(Ok('('))
-                        .chain(v.iter_elements().map(ok))
-                        .chain(iter::once(Ok(')'))),
-                )
-            },
-        )
+    fn names(&self) -> Vec<String> {
+        let pars: &[(Key, Value)] = *self;
+        Provider::<Input>::names(&pars)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        let pars: &[(Key, Value)] = *self;
+        Provider::<Input>::fingerprint(&pars)
     }
 }
 
-// TODO: Replace with TAIT, once stabilized:
-//       https://github.com/rust-lang/rust/issues/63063
-/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<`[`Parameter`]`>`.
-type WithParsIter<I, P> = Either<
-    iter::Chain<
-        iter::Chain<
-            iter::Once<Result<char, UnknownParameterError<I>>>,
-            iter::Map<
-                <<P as Provider<I>>::Value as InputIter>::IterElem,
-                fn(
-                    <<P as Provider<I>>::Value as InputIter>::Item,
-                ) -> Result<char, UnknownParameterError<I>>,
-            >,
-        >,
-        iter::Once<Result<char, UnknownParameterError<I>>>,
-    >,
-    ParameterIter<I>,
->;
+impl<'p, Input, const N: usize> Provider<Input> for [(&'p str, &'p str); N]
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Item = char;
+    type Value = &'p str;
 
-#[cfg(test)]
-mod spec {
-    use crate::expand::Error;
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        find_pair(self.iter().copied(), input)
+    }
 
-    use super::{Expression, HashMap, UnknownParameterError};
+    fn names(&self) -> Vec<String> {
+        self.iter().map(|(k, _)| (*k).to_owned()).collect()
+    }
 
-    #[test]
-    fn custom_parameter() {
-        let pars = HashMap::from([("custom", "custom")]);
-        let expr = Expression::regex_with_parameters("{custom}", &pars)
-            .unwrap_or_else(|e| panic!("failed: {}", e));
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_pairs(self.iter().copied()))
+    }
+}
 
-        assert_eq!(expr.as_str(), "^(custom)$");
+impl<'p, Input, Key, Value> Provider<Input> for &'p Vec<(Key, Value)>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Key: AsRef<str>,
+    Value: AsRef<str>,
+{
+    type Item = char;
+    type Value = &'p str;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        Provider::get(&self.as_slice(), input)
     }
 
-    #[test]
-    fn default_parameter() {
-        let pars = HashMap::from([("custom", "custom")]);
-        let expr = Expression::regex_with_parameters("{}", &pars)
-            .unwrap_or_else(|e| panic!("failed: {}", e));
+    fn names(&self) -> Vec<String> {
+        Provider::<Input>::names(&self.as_slice())
+    }
 
-        assert_eq!(expr.as_str(), "^(.*)$");
+    fn fingerprint(&self) -> Option<u64> {
+        Provider::<Input>::fingerprint(&self.as_slice())
     }
+}
 
-    #[test]
-    fn unknown_parameter() {
-        let pars = HashMap::<String, String>::new();
+/// Simplified [`Provider`] of custom [`Parameter`]s, looking them up by their
+/// `&str` names.
+///
+/// Every [`SimpleProvider`] is a [`Provider`] by reference, providing
+/// [`CowValue`]s.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "into-regex")] {
+/// # use std::borrow::Cow;
+/// #
+/// # use cucumber_expressions::{
+/// #     expand::parameters::SimpleProvider, Expression,
+/// # };
+/// #
+/// struct Colors;
+///
+/// impl SimpleProvider for Colors {
+///     fn get(&self, name: &str) -> Option<Cow<'_, str>> {
+///         (name == "color").then(|| "red|blue".into())
+///     }
+/// }
+///
+/// let re = Expression::regex_with_parameters("{color}", &Colors).unwrap();
+/// assert_eq!(re.as_str(), "^(red|blue)$");
+/// # }
+/// ```
+pub trait SimpleProvider {
+    /// Returns a [`Regex`] pattern of the [`Parameter`] with the given `name`,
+    /// if any.
+    ///
+    /// See [`Provider::get()`] for details.
+    ///
+    /// [`Regex`]: regex::Regex
+    fn get(&self, name: &str) -> Option<Cow<'_, str>>;
 
-        match Expression::regex_with_parameters("{custom}", &pars).unwrap_err()
-        {
-            Error::Expansion(UnknownParameterError { not_found }) => {
-                assert_eq!(*not_found, "custom");
-            }
-            e @ (Error::Regex(_) | Error::Parsing(_)) => {
-                panic!("wrong err: {}", e)
+    /// Returns names of all the provided [`Parameter`]s.
+    ///
+    /// See [`Provider::names()`] for details.
+    fn names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns a fingerprint of all the provided [`Parameter`]s.
+    ///
+    /// See [`Provider::fingerprint()`] for details.
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl<'p, Input, P> Provider<Input> for &'p P
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    P: SimpleProvider + ?Sized,
+{
+    type Item = char;
+    type Value = CowValue<'p>;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let provider: &'p P = self;
+        with_name(input, |name| SimpleProvider::get(provider, name))
+            .map(CowValue)
+    }
+
+    fn names(&self) -> Vec<String> {
+        SimpleProvider::names(*self)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        SimpleProvider::fingerprint(*self)
+    }
+}
+
+/// Extension of [`Provider`]s allowing to combine them.
+pub trait ProviderExt {
+    /// Combines this [`Provider`] with the `fallback` one, which is consulted
+    /// only for the [`Parameter`]s this [`Provider`] doesn't provide.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::{
+    /// #     expand::parameters::ProviderExt as _, Expression,
+    /// # };
+    /// #
+    /// let project = HashMap::from([("color", "red|blue"), ("size", "\\d+")]);
+    /// let module = HashMap::from([("color", "green")]);
+    /// let re = Expression::regex_with_parameters(
+    ///     "{color} {size}",
+    ///     (&module).or(&project),
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(re.as_str(), "^(green) (\\d+)$");
+    /// # }
+    /// ```
+    #[must_use]
+    fn or<Fallback>(self, fallback: Fallback) -> Or<Self, Fallback>
+    where
+        Self: Sized,
+    {
+        Or {
+            first: self,
+            fallback,
+        }
+    }
+
+    /// Combines this [`Provider`] with the `hook` resolving [`Parameter`]s
+    /// which are neither provided by this [`Provider`], nor [built-in][1]
+    /// ones, into their [`Regex`] patterns.
+    ///
+    /// The `hook` is called lazily, on expanding an unknown [`Parameter`]
+    /// only (consulting a database of domain enums, for example), and may
+    /// decline it by returning [`None`], so an [`UnknownParameterError`] is
+    /// raised as usual. As the `hook` can't list the [`Parameter`]s it
+    /// resolves, [`Regex`]es expanded with [`OnUnknown`] are never
+    /// [cached][2].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::expand::{
+    /// #     parameters::ProviderExt as _, ExpressionCompiler,
+    /// #     ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let registry = ParameterTypeRegistry::new();
+    /// let pars = (&registry).on_unknown_parameter(|name: &str| {
+    ///     (name == "fruit").then(|| "apple|pear")
+    /// });
+    /// let compiler = ExpressionCompiler::new().parameters(pars);
+    ///
+    /// let compiled = compiler.compile("{int} {fruit}").unwrap();
+    /// assert!(compiled.regex.is_match("2 pear"));
+    /// assert!(compiler.compile("{color}").is_err());
+    /// # }
+    /// ```
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: super::cache
+    #[must_use]
+    fn on_unknown_parameter<F>(self, hook: F) -> OnUnknown<Self, F>
+    where
+        Self: Sized,
+    {
+        OnUnknown {
+            parameters: self,
+            hook,
+        }
+    }
+
+    /// Makes this [`Provider`] compare [`Parameter`] names after [Unicode NFC
+    /// normalization][1], so the names differing in their normalization form
+    /// only (like `{café}` typed with a precomposed `é` or with `e` followed
+    /// by a combining acute accent) match each other.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// #
+    /// # use cucumber_expressions::{
+    /// #     expand::parameters::ProviderExt as _, Expression,
+    /// # };
+    /// #
+    /// # #[cfg(feature = "unicode-normalization")] {
+    /// let pars = HashMap::from([("cafe\u{301}", "open|closed")]);
+    ///
+    /// let strict = Expression::regex_with_parameters("{caf\u{e9}}", &pars);
+    /// assert!(strict.is_err());
+    ///
+    /// let re = Expression::regex_with_parameters(
+    ///     "{caf\u{e9}}",
+    ///     (&pars).normalized_names(),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(re.as_str(), "^(open|closed)$");
+    /// # }
+    /// ```
+    ///
+    /// [1]: https://unicode.org/reports/tr15
+    #[cfg(feature = "unicode-normalization")]
+    #[must_use]
+    fn normalized_names(self) -> NormalizedNames<Self>
+    where
+        Self: Sized,
+    {
+        NormalizedNames(self)
+    }
+}
+
+impl<P> ProviderExt for P {}
+
+/// [`Provider`] consulting the `First` [`Provider`] and falling back to the
+/// `Fallback` one, created via [`ProviderExt::or()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Or<First, Fallback> {
+    /// [`Provider`] consulted first.
+    pub first: First,
+
+    /// [`Provider`] consulted for the [`Parameter`]s not provided by the
+    /// [`first`] one.
+    ///
+    /// [`first`]: Or::first
+    pub fallback: Fallback,
+}
+
+impl<Input, First, Fallback> Provider<Input> for Or<First, Fallback>
+where
+    First: Provider<Input>,
+    Fallback: Provider<Input, Item = First::Item, Value = First::Value>,
+{
+    type Item = First::Item;
+    type Value = First::Value;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        self.first.get(input).or_else(|| self.fallback.get(input))
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names = self.first.names();
+        for name in self.fallback.names() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        let first = self.first.fingerprint()?;
+        let fallback = self.fallback.fingerprint()?;
+
+        let mut hasher = DefaultHasher::new();
+        (first, fallback).hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+/// [`Provider`] resolving unknown [`Parameter`]s with a `hook`, created via
+/// [`ProviderExt::on_unknown_parameter()`].
+#[derive(Clone, Copy, Debug)]
+pub struct OnUnknown<P, F> {
+    /// [`Provider`] consulted first.
+    pub parameters: P,
+
+    /// Hook resolving [`Parameter`]s which are neither provided by the
+    /// [`parameters`], nor [built-in][1] ones.
+    ///
+    /// [`parameters`]: OnUnknown::parameters
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub hook: F,
+}
+
+impl<Input, P, F, V> Provider<Input> for OnUnknown<P, F>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    P: Provider<Input>,
+    F: Fn(&str) -> Option<V>,
+    V: Into<String>,
+{
+    type Item = char;
+    type Value = OwnedValue;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        if let Some(v) = self.parameters.get(input) {
+            return Some(OwnedValue(
+                v.iter_elements().map(AsChar::as_char).collect(),
+            ));
+        }
+
+        let name = input
+            .iter_elements()
+            .map(AsChar::as_char)
+            .collect::<String>();
+        // Quantified `Parameter`s are reserved regardless of the `Options`, as
+        // they aren't known here.
+        let reserved = Options {
+            quantified_parameters: true,
+            ..Options::default()
+        };
+        if builtin_or_quantified_parameter(&name.as_str(), reserved).is_some() {
+            return None;
+        }
+        (self.hook)(&name).map(|v| OwnedValue(v.into()))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.parameters.names()
+    }
+}
+
+/// [`Provider`] comparing [`Parameter`] names after [Unicode NFC
+/// normalization][1], created via [`ProviderExt::normalized_names()`].
+///
+/// [1]: https://unicode.org/reports/tr15
+#[cfg(feature = "unicode-normalization")]
+#[derive(Clone, Copy, Debug)]
+pub struct NormalizedNames<P>(pub P);
+
+#[cfg(feature = "unicode-normalization")]
+impl<Input, P> Provider<Input> for NormalizedNames<P>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    P: Provider<Input> + for<'n> Provider<&'n str>,
+{
+    type Item = char;
+    type Value = OwnedValue;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        use unicode_normalization::UnicodeNormalization as _;
+
+        if let Some(v) = Provider::<Input>::get(&self.0, input) {
+            return Some(OwnedValue(
+                v.iter_elements().map(AsChar::as_char).collect(),
+            ));
+        }
+
+        let name = with_name(input, |name| name.nfc().collect::<String>());
+        Provider::<Input>::names(&self.0)
+            .into_iter()
+            .find(|n| n.nfc().eq(name.chars()))
+            .and_then(|n| {
+                Provider::<&str>::get(&self.0, &n.as_str()).map(|v| {
+                    OwnedValue(v.iter_elements().map(AsChar::as_char).collect())
+                })
+            })
+    }
+
+    fn names(&self) -> Vec<String> {
+        Provider::<Input>::names(&self.0)
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        // Differs from the inner `Provider`'s one, as more `Parameter` names
+        // are resolved.
+        let mut hasher = DefaultHasher::new();
+        (Provider::<Input>::fingerprint(&self.0)?, "nfc").hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+/// [`Provider`] of custom [`Parameter`]s computed dynamically by a closure
+/// mapping a [`Parameter`] name into its [`OwnedValue`] matcher.
+///
+/// Allows to consult an in-memory registry or to generate [`Parameter`]s on
+/// the fly, without materializing them into a map. As the closure can't list
+/// the [`Parameter`]s it provides, [`Regex`]es expanded with [`FromFn`] are
+/// never [cached][1] and no similar [`Parameter`]s are suggested in an
+/// [`UnknownParameterError`].
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "into-regex")] {
+/// # use cucumber_expressions::{expand::parameters::FromFn, Expression};
+/// #
+/// let pars = FromFn(|name: &str| {
+///     name.strip_prefix("one_of:").map(|v| v.replace(',', "|"))
+/// });
+/// let re =
+///     Expression::regex_with_parameters("{one_of:red,blue}", pars).unwrap();
+///
+/// assert_eq!(re.as_str(), "^(red|blue)$");
+/// # }
+/// ```
+///
+/// [`Regex`]: regex::Regex
+/// [1]: super::cache
+#[derive(Clone, Copy, Debug)]
+pub struct FromFn<F>(pub F);
+
+impl<Input, F, V> Provider<Input> for FromFn<F>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+    F: Fn(&str) -> Option<V>,
+    V: Into<String>,
+{
+    type Item = char;
+    type Value = OwnedValue;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let name = input
+            .iter_elements()
+            .map(AsChar::as_char)
+            .collect::<String>();
+        (self.0)(&name).map(|v| OwnedValue(v.into()))
+    }
+}
+
+/// Owned [`Provider::Value`] matcher of a [`Parameter`] computed on the fly by
+/// a [`FromFn`] [`Provider`].
+#[derive(AsRef, Clone, Debug, Deref, Display, Eq, From, Into, PartialEq)]
+pub struct OwnedValue(pub String);
+
+impl InputIter for OwnedValue {
+    type Item = char;
+    type Iter = vec::IntoIter<(usize, char)>;
+    type IterElem = vec::IntoIter<char>;
+
+    fn iter_indices(&self) -> Self::Iter {
+        self.0.char_indices().collect::<Vec<_>>().into_iter()
+    }
+
+    fn iter_elements(&self) -> Self::IterElem {
+        self.0.chars().collect::<Vec<_>>().into_iter()
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.0.as_str().position(predicate)
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+        self.0.as_str().slice_index(count)
+    }
+}
+
+/// [`Cow`] [`Provider::Value`] matcher of a [`Parameter`], either borrowed
+/// from a [`Provider`] or computed on the fly for a single lookup.
+///
+/// Unlike an [`OwnedValue`], doesn't allocate when iterated over, if
+/// borrowed.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "into-regex")] {
+/// # use std::{borrow::Cow, collections::HashMap};
+/// #
+/// # use cucumber_expressions::{
+/// #     expand::{parameters::CowValue, ParametersProvider},
+/// #     Expression, Spanned,
+/// # };
+/// #
+/// /// Provides enums stored as lists of their values.
+/// struct Enums(HashMap<&'static str, Vec<&'static str>>);
+///
+/// impl<'p> ParametersProvider<Spanned<'_>> for &'p Enums {
+///     type Item = char;
+///     type Value = CowValue<'p>;
+///
+///     fn get(&self, input: &Spanned<'_>) -> Option<Self::Value> {
+///         let values = self.0.get(input.fragment())?;
+///         Some(CowValue(match values.as_slice() {
+///             [single] => Cow::Borrowed(*single),
+///             _ => Cow::Owned(values.join("|")),
+///         }))
+///     }
+/// }
+///
+/// let enums = Enums(HashMap::from([
+///     ("color", vec!["red", "blue"]),
+///     ("size", vec!["XL"]),
+/// ]));
+/// let re =
+///     Expression::regex_with_parameters("{color} {size}", &enums).unwrap();
+/// assert_eq!(re.as_str(), "^(red|blue) (XL)$");
+/// # }
+/// ```
+///
+/// [`Cow`]: std::borrow::Cow
+#[derive(AsRef, Clone, Debug, Deref, Display, Eq, From, Into, PartialEq)]
+pub struct CowValue<'v>(pub Cow<'v, str>);
+
+impl<'v> From<&'v str> for CowValue<'v> {
+    fn from(s: &'v str) -> Self {
+        Self(Cow::Borrowed(s))
+    }
+}
+
+impl From<String> for CowValue<'_> {
+    fn from(s: String) -> Self {
+        Self(Cow::Owned(s))
+    }
+}
+
+impl<'v> InputIter for CowValue<'v> {
+    type Item = char;
+    type Iter = Either<str::CharIndices<'v>, vec::IntoIter<(usize, char)>>;
+    type IterElem = Either<str::Chars<'v>, vec::IntoIter<char>>;
+
+    fn iter_indices(&self) -> Self::Iter {
+        match &self.0 {
+            Cow::Borrowed(s) => Either::Left(s.char_indices()),
+            Cow::Owned(s) => {
+                Either::Right(s.char_indices().collect::<Vec<_>>().into_iter())
+            }
+        }
+    }
+
+    fn iter_elements(&self) -> Self::IterElem {
+        match &self.0 {
+            Cow::Borrowed(s) => Either::Left(s.chars()),
+            Cow::Owned(s) => {
+                Either::Right(s.chars().collect::<Vec<_>>().into_iter())
+            }
+        }
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.0.as_ref().position(predicate)
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, Needed> {
+        self.0.as_ref().slice_index(count)
+    }
+}
+
+/// Precompiled [`Regex`] value of a custom [`Parameter`], spliced into
+/// expansions as is.
+///
+/// Usable as a value of any [`Provider`] container (like a [`HashMap`]), so
+/// already maintained [`Regex`]es (or [`Hir`]s) don't need to be kept as
+/// strings.
+///
+/// Only the pattern of a [`Regex`] is spliced, so the flags set via
+/// [`RegexBuilder`] are lost, and should be specified inline instead (like
+/// `(?i)`). [`Hir`]s have their flags applied already.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// #
+/// # use cucumber_expressions::{expand::parameters::Precompiled, Expression};
+/// # use regex::Regex;
+/// #
+/// let color = Regex::new("(?i)red|blue").unwrap();
+/// let pars = HashMap::from([("color", Precompiled::new(color).unwrap())]);
+///
+/// let re = Expression::regex_with_parameters("{color} car", &pars).unwrap();
+/// assert!(re.is_match("RED car"));
+///
+/// assert!(Precompiled::new(Regex::new("^red$").unwrap()).is_err());
+/// ```
+///
+/// [`Hir`]: regex_syntax::hir::Hir
+/// [`Regex`]: regex::Regex
+/// [`RegexBuilder`]: regex::RegexBuilder
+#[cfg(feature = "into-regex")]
+#[derive(Clone, Debug)]
+pub struct Precompiled(regex::Regex);
+
+#[cfg(feature = "into-regex")]
+impl Precompiled {
+    /// Wraps the given [`Regex`], validating it to be spliceable into an
+    /// expansion.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Regex`] is anchored to the start or the end of a text.
+    ///
+    /// [`Regex`]: regex::Regex
+    pub fn new(regex: regex::Regex) -> Result<Self, PrecompiledError> {
+        let hir = regex_syntax::Parser::new()
+            .parse(regex.as_str())
+            .map_err(|e| PrecompiledError::Invalid(e.to_string()))?;
+        if Self::is_anchored(&hir) {
+            return Err(PrecompiledError::Anchored(regex.as_str().to_owned()));
+        }
+        Ok(Self(regex))
+    }
+
+    /// Checks whether the given [`Hir`] contains an anchor to the start or the
+    /// end of a text anywhere.
+    ///
+    /// [`Hir`]: regex_syntax::hir::Hir
+    fn is_anchored(hir: &regex_syntax::hir::Hir) -> bool {
+        use regex_syntax::hir::{Anchor, HirKind};
+
+        match hir.kind() {
+            HirKind::Anchor(Anchor::StartText | Anchor::EndText) => true,
+            HirKind::Repetition(rep) => Self::is_anchored(&rep.hir),
+            HirKind::Group(group) => Self::is_anchored(&group.hir),
+            HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+                hirs.iter().any(Self::is_anchored)
+            }
+            HirKind::Empty
+            | HirKind::Literal(_)
+            | HirKind::Class(_)
+            | HirKind::Anchor(_)
+            | HirKind::WordBoundary(_) => false,
+        }
+    }
+
+    /// Compiles the given [`Hir`] into a [`Regex`], validating it to be
+    /// spliceable into an expansion.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Hir`] is anchored to the start or the end of a text, or its
+    /// [`Regex`] exceeds the size limits.
+    ///
+    /// [`Hir`]: regex_syntax::hir::Hir
+    /// [`Regex`]: regex::Regex
+    pub fn from_hir(
+        hir: &regex_syntax::hir::Hir,
+    ) -> Result<Self, PrecompiledError> {
+        let regex = regex::Regex::new(&hir.to_string())
+            .map_err(|e| PrecompiledError::Invalid(e.to_string()))?;
+        Self::new(regex)
+    }
+
+    /// Returns the wrapped [`Regex`].
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub const fn regex(&self) -> &regex::Regex {
+        &self.0
+    }
+
+    /// Unwraps the [`Regex`].
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub fn into_regex(self) -> regex::Regex {
+        self.0
+    }
+}
+
+#[cfg(feature = "into-regex")]
+impl AsRef<str> for Precompiled {
+    fn as_ref(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+#[cfg(feature = "into-regex")]
+impl TryFrom<regex::Regex> for Precompiled {
+    type Error = PrecompiledError;
+
+    fn try_from(regex: regex::Regex) -> Result<Self, Self::Error> {
+        Self::new(regex)
+    }
+}
+
+#[cfg(feature = "into-regex")]
+impl TryFrom<&regex_syntax::hir::Hir> for Precompiled {
+    type Error = PrecompiledError;
+
+    fn try_from(hir: &regex_syntax::hir::Hir) -> Result<Self, Self::Error> {
+        Self::from_hir(hir)
+    }
+}
+
+/// Error of creating a [`Precompiled`] value.
+#[cfg(feature = "into-regex")]
+#[derive(Clone, Debug, Display, derive_more::Error, Eq, PartialEq)]
+pub enum PrecompiledError {
+    /// Pattern is anchored to the start or the end of a text, so can't be
+    /// spliced into an expansion.
+    #[display(
+        fmt = "Precompiled regex /{}/ is anchored, so can't be spliced into \
+               an expansion",
+        _0
+    )]
+    Anchored(#[error(not(source))] String),
+
+    /// Pattern fails to be parsed or compiled.
+    #[display(fmt = "Precompiled regex is invalid: {}", _0)]
+    Invalid(#[error(not(source))] String),
+}
+
+/// Length (in bytes) of a stack buffer [`Parameter`] names are collected into
+/// by [`with_name()`].
+pub(super) const NAME_BUFFER_LEN: usize = 64;
+
+/// Calls the given function `f` with the given `input` [`Parameter`] name as
+/// a [`str`].
+///
+/// Collects the name into a stack buffer, allocating a [`String`] only for
+/// names longer than [`NAME_BUFFER_LEN`] bytes.
+pub(super) fn with_name<Input, R>(input: &Input, f: impl FnOnce(&str) -> R) -> R
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    let mut buf = [0_u8; NAME_BUFFER_LEN];
+    let mut len = 0;
+    let fits = input.iter_elements().map(AsChar::as_char).all(|c| {
+        buf.get_mut(len..)
+            .filter(|rest| rest.len() >= c.len_utf8())
+            .map(|rest| len += c.encode_utf8(rest).len())
+            .is_some()
+    });
+
+    match buf
+        .get(..len)
+        .filter(|_| fits)
+        .and_then(|name| str::from_utf8(name).ok())
+    {
+        Some(name) => f(name),
+        None => f(&input
+            .iter_elements()
+            .map(AsChar::as_char)
+            .collect::<String>()),
+    }
+}
+
+/// Looks up the value of the custom [`Parameter`] with the given `name` among
+/// the given `pairs` of names and values, in O(n) time.
+fn find_pair<'p, Input>(
+    mut pairs: impl Iterator<Item = (&'p str, &'p str)>,
+    name: &Input,
+) -> Option<&'p str>
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    pairs.find_map(|(k, v)| {
+        k.chars()
+            .eq(name.iter_elements().map(AsChar::as_char))
+            .then(|| v)
+    })
+}
+
+/// Hashes the given custom [`Parameter`]s `pairs` of names and values
+/// regardless of their order, so the same [`Parameter`]s have the same
+/// [`Provider::fingerprint()`] in any container.
+pub(super) fn fingerprint_pairs<'p>(
+    pairs: impl Iterator<Item = (&'p str, &'p str)>,
+) -> u64 {
+    let mut pairs = pairs.collect::<Vec<_>>();
+    pairs.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    pairs.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<Expression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = ExpressionWithParsIter<Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let add_pars: fn(_) -> _ =
+            |(element, (parameters, opts)): (SingleExpression<Input>, _)| {
+                (
+                    WithCustom {
+                        element,
+                        parameters,
+                    },
+                    opts,
+                )
+            };
+        let into_regex_char_iter: fn(_) -> _ =
+            |(item, opts): (
+                WithCustom<SingleExpression<Input>, Arc<Pars>>,
+                _,
+            )| { item.into_regex_char_iter_with(opts) };
+        let lower =
+            expression_affixes_len(options) + self.element.min_regex_len();
+        SizeHinted::new(
+            expression_prefix_iter(options)
+                .chain(
+                    self.element
+                        .0
+                        .into_iter()
+                        .zip(iter::repeat((Arc::new(self.parameters), options)))
+                        .map(add_pars)
+                        .flat_map(into_regex_char_iter),
+                )
+                .chain(expression_suffix_iter(options)),
+            lower,
+        )
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<`[`Expression`]`>`.
+type ExpressionWithParsIter<I, P> = SizeHinted<
+    iter::Chain<
+        iter::Chain<
+            ExpressionPrefixIter<I>,
+            iter::FlatMap<
+                iter::Map<
+                    iter::Zip<
+                        vec::IntoIter<SingleExpression<I>>,
+                        iter::Repeat<(Arc<P>, Options)>,
+                    >,
+                    fn(
+                        (SingleExpression<I>, (Arc<P>, Options)),
+                    )
+                        -> (WithCustom<SingleExpression<I>, Arc<P>>, Options),
+                >,
+                SingleExprWithParsIter<I, Arc<P>>,
+                fn(
+                    (WithCustom<SingleExpression<I>, Arc<P>>, Options),
+                ) -> SingleExprWithParsIter<I, Arc<P>>,
+            >,
+        >,
+        ExpressionSuffixIter<I>,
+    >,
+>;
+
+impl<'e, Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<&'e Expression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = ExpressionRefWithParsIter<'e, Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        options.debug_assert_iterable();
+
+        let add_pars: fn(_) -> _ = |(element, (parameters, opts)): (
+            &'e SingleExpression<Input>,
+            _,
+        )| {
+            (
+                WithCustom {
+                    element,
+                    parameters,
+                },
+                opts,
+            )
+        };
+        let into_regex_char_iter: fn(_) -> _ =
+            |(item, opts): (
+                WithCustom<&'e SingleExpression<Input>, Arc<Pars>>,
+                _,
+            )| { item.into_regex_char_iter_with(opts) };
+        let lower =
+            expression_affixes_len(options) + self.element.min_regex_len();
+        SizeHinted::new(
+            expression_prefix_iter(options)
+                .chain(
+                    self.element
+                        .0
+                        .iter()
+                        .zip(iter::repeat((Arc::new(self.parameters), options)))
+                        .map(add_pars)
+                        .flat_map(into_regex_char_iter),
+                )
+                .chain(expression_suffix_iter(options)),
+            lower,
+        )
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<&`[`Expression`]`>`.
+type ExpressionRefWithParsIter<'e, I, P> = SizeHinted<
+    iter::Chain<
+        iter::Chain<
+            ExpressionPrefixIter<I>,
+            iter::FlatMap<
+                iter::Map<
+                    iter::Zip<
+                        slice::Iter<'e, SingleExpression<I>>,
+                        iter::Repeat<(Arc<P>, Options)>,
+                    >,
+                    fn(
+                        (&'e SingleExpression<I>, (Arc<P>, Options)),
+                    ) -> (
+                        WithCustom<&'e SingleExpression<I>, Arc<P>>,
+                        Options,
+                    ),
+                >,
+                SingleExprRefWithParsIter<'e, I, Arc<P>>,
+                fn(
+                    (WithCustom<&'e SingleExpression<I>, Arc<P>>, Options),
+                ) -> SingleExprRefWithParsIter<'e, I, Arc<P>>,
+            >,
+        >,
+        ExpressionSuffixIter<I>,
+    >,
+>;
+
+impl<Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<AnyExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = AnyExpressionWithParsIter<Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self.element {
+            AnyExpression::Cucumber(element) => Left(
+                WithCustom {
+                    element,
+                    parameters: self.parameters,
+                }
+                .into_regex_char_iter_with(options),
+            ),
+            AnyExpression::Regular(re) => {
+                Right(regular_expression_iter(&re, options))
+            }
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<`[`AnyExpression`]`>`.
+type AnyExpressionWithParsIter<I, P> =
+    Either<ExpressionWithParsIter<I, P>, RegularExpressionIter<I>>;
+
+impl<'e, Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<&'e AnyExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = AnyExpressionRefWithParsIter<'e, Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self.element {
+            AnyExpression::Cucumber(element) => Left(
+                WithCustom {
+                    element,
+                    parameters: self.parameters,
+                }
+                .into_regex_char_iter_with(options),
+            ),
+            AnyExpression::Regular(re) => {
+                Right(regular_expression_iter(re, options))
+            }
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<&`[`AnyExpression`]`>`.
+type AnyExpressionRefWithParsIter<'e, I, P> =
+    Either<ExpressionRefWithParsIter<'e, I, P>, RegularExpressionIter<I>>;
+
+impl<Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<SingleExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = SingleExprWithParsIter<Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self.element {
+            SingleExpression::Parameter(item) => Left(Left(
+                WithCustom {
+                    element: item,
+                    parameters: self.parameters,
+                }
+                .into_regex_char_iter(),
+            )),
+            SingleExpression::OptionalParameter(opt) => {
+                Left(Right(optional_parameter_iter(
+                    &opt.prefix,
+                    WithCustom {
+                        element: opt.parameter,
+                        parameters: self.parameters,
+                    }
+                    .into_regex_char_iter(),
+                    &opt.suffix,
+                    options,
+                )))
+            }
+            e @ (SingleExpression::Alternation(_)
+            | SingleExpression::Optional(_)
+            | SingleExpression::Text(_)
+            | SingleExpression::Whitespaces(_)) => {
+                Right(e.into_regex_char_iter_with(options))
+            }
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for
+/// [`WithCustom`]`<`[`SingleExpression`]`>`.
+type SingleExprWithParsIter<I, P> = Either<
+    Either<
+        <WithCustom<Parameter<I>, P> as IntoRegexCharIter<I>>::Iter,
+        OptionalParameterIter<
+            I,
+            <WithCustom<Parameter<I>, P> as IntoRegexCharIter<I>>::Iter,
+        >,
+    >,
+    SingleExpressionIter<I>,
+>;
+
+impl<'e, Input, Pars> IntoRegexCharIter<Input>
+    for WithCustom<&'e SingleExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    <Pars as Provider<Input>>::Value: InputIter,
+{
+    type Iter = SingleExprRefWithParsIter<'e, Input, Pars>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        match self.element {
+            SingleExpression::Parameter(item) => Left(Left(
+                WithCustom {
+                    element: item.clone(),
+                    parameters: self.parameters,
+                }
+                .into_regex_char_iter(),
+            )),
+            SingleExpression::OptionalParameter(opt) => {
+                Left(Right(optional_parameter_iter(
+                    &opt.prefix,
+                    WithCustom {
+                        element: opt.parameter.clone(),
+                        parameters: self.parameters,
+                    }
+                    .into_regex_char_iter(),
+                    &opt.suffix,
+                    options,
+                )))
+            }
+            SingleExpression::Alternation(_)
+            | SingleExpression::Optional(_)
+            | SingleExpression::Text(_)
+            | SingleExpression::Whitespaces(_) => {
+                Right(self.element.into_regex_char_iter_with(options))
+            }
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for
+/// [`WithCustom`]`<&`[`SingleExpression`]`>`.
+type SingleExprRefWithParsIter<'e, I, P> = Either<
+    Either<
+        <WithCustom<Parameter<I>, P> as IntoRegexCharIter<I>>::Iter,
+        OptionalParameterIter<
+            I,
+            <WithCustom<Parameter<I>, P> as IntoRegexCharIter<I>>::Iter,
+        >,
+    >,
+    SingleExpressionRefIter<'e, I>,
+>;
+
+impl<Input, P> IntoRegexCharIter<Input> for WithCustom<Parameter<Input>, P>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    P: Provider<Input>,
+    <P as Provider<Input>>::Value: InputIter,
+{
+    type Iter = WithParsIter<Input, P>;
+
+    fn into_regex_char_iter_with(self, options: Options) -> Self::Iter {
+        use Either::{Left, Right};
+
+        options.debug_assert_iterable();
+
+        let ok: fn(_) -> _ = |c: <P::Value as InputIter>::Item| Ok(c.as_char());
+        match self.parameters.get(&self.element) {
+            Some(v) => Left(
+                iter::once(Ok('('))
+                    .chain(v.iter_elements().map(ok))
+                    .chain(iter::once(Ok(')'))),
+            ),
+            None if builtin_parameter(&self.element.0, options).is_none() => {
+                Right(Right(iter::once(Err(UnknownParameterError::new(
+                    self.element.0,
+                    self.parameters.names(),
+                )))))
+            }
+            None => Right(self.element.into_regex_char_iter_with(options)),
+        }
+    }
+}
+
+// TODO: Replace with TAIT, once stabilized:
+//       https://github.com/rust-lang/rust/issues/63063
+/// [`IntoRegexCharIter::Iter`] for [`WithCustom`]`<`[`Parameter`]`>`.
+type WithParsIter<I, P> = Either<
+    iter::Chain<
+        iter::Chain<
+            iter::Once<Result<char, UnknownParameterError<I>>>,
+            iter::Map<
+                <<P as Provider<I>>::Value as InputIter>::IterElem,
+                fn(
+                    <<P as Provider<I>>::Value as InputIter>::Item,
+                ) -> Result<char, UnknownParameterError<I>>,
+            >,
+        >,
+        iter::Once<Result<char, UnknownParameterError<I>>>,
+    >,
+    ParameterIter<I>,
+>;
+
+impl<Input, Pars> ExpandInto<Input> for WithCustom<Expression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_expression_into(&self.element, &self.parameters, w, options)
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input> for WithCustom<&Expression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_expression_into(self.element, &self.parameters, w, options)
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input> for WithCustom<AnyExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match &self.element {
+            AnyExpression::Cucumber(expr) => {
+                expand_expression_into(expr, &self.parameters, w, options)
+            }
+            AnyExpression::Regular(_) => {
+                self.element.expand_into_with(w, options)
+            }
+        }
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input> for WithCustom<&AnyExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        match self.element {
+            AnyExpression::Cucumber(expr) => {
+                expand_expression_into(expr, &self.parameters, w, options)
+            }
+            AnyExpression::Regular(_) => {
+                self.element.expand_into_with(w, options)
+            }
+        }
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input>
+    for WithCustom<SingleExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_single_expression_into(
+            &self.element,
+            &self.parameters,
+            w,
+            options,
+        )
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input>
+    for WithCustom<&SingleExpression<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_single_expression_into(
+            self.element,
+            &self.parameters,
+            w,
+            options,
+        )
+    }
+}
+
+impl<Input, Pars> ExpandInto<Input> for WithCustom<Parameter<Input>, Pars>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+{
+    fn expand_into_with<W>(
+        &self,
+        w: &mut W,
+        options: Options,
+    ) -> Result<(), Error<Input>>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        expand_parameter_into(&self.element, &self.parameters, options, None, w)
+    }
+}
+
+/// Writes the given [`Expression`] expanded with the custom `parameters` and
+/// [`Options`] into the `w`riter.
+pub(super) fn expand_expression_into<Input, Pars, W>(
+    expr: &Expression<Input>,
+    parameters: &Pars,
+    w: &mut W,
+    options: Options,
+) -> Result<(), Error<Input>>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    W: fmt::Write + ?Sized,
+{
+    let mut names = group_names(expr, options).into_iter();
+
+    w.write_str(options.prefix())?;
+    w.write_str(options.padding())?;
+    for e in &expr.0 {
+        match e {
+            SingleExpression::Parameter(p) => {
+                let name = names.next();
+                expand_parameter_into(
+                    p,
+                    parameters,
+                    options,
+                    name.as_deref(),
+                    w,
+                )?;
+            }
+            SingleExpression::OptionalParameter(opt) => {
+                let name = names.next();
+                expand_optional_parameter_into(opt, options, w, |p, w| {
+                    expand_parameter_into(
+                        p,
+                        parameters,
+                        options,
+                        name.as_deref(),
+                        w,
+                    )
+                })?;
+            }
+            SingleExpression::Alternation(_)
+            | SingleExpression::Optional(_)
+            | SingleExpression::Text(_)
+            | SingleExpression::Whitespaces(_) => {
+                e.expand_into_with(w, options)?;
+            }
+        }
+    }
+    w.write_str(options.padding())?;
+    Ok(w.write_str(options.suffix())?)
+}
+
+/// Writes the given [`SingleExpression`] expanded with the custom `parameters`
+/// and [`Options`] into the `w`riter.
+fn expand_single_expression_into<Input, Pars, W>(
+    expr: &SingleExpression<Input>,
+    parameters: &Pars,
+    w: &mut W,
+    options: Options,
+) -> Result<(), Error<Input>>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    W: fmt::Write + ?Sized,
+{
+    match expr {
+        SingleExpression::Parameter(p) => {
+            expand_parameter_into(p, parameters, options, None, w)
+        }
+        SingleExpression::OptionalParameter(opt) => {
+            expand_optional_parameter_into(opt, options, w, |p, w| {
+                expand_parameter_into(p, parameters, options, None, w)
+            })
+        }
+        SingleExpression::Alternation(_)
+        | SingleExpression::Optional(_)
+        | SingleExpression::Text(_)
+        | SingleExpression::Whitespaces(_) => expr.expand_into_with(w, options),
+    }
+}
+
+/// Writes the given [`Parameter`] expanded with the custom `parameters` into
+/// the `w`riter as a capturing group with the given [`Options`], named with
+/// the given `name`, if any.
+fn expand_parameter_into<Input, Pars, W>(
+    parameter: &Parameter<Input>,
+    parameters: &Pars,
+    options: Options,
+    name: Option<&str>,
+    w: &mut W,
+) -> Result<(), Error<Input>>
+where
+    Input: Clone + Display + InputIter,
+    <Input as InputIter>::Item: AsChar,
+    Pars: Provider<Input>,
+    W: fmt::Write + ?Sized,
+{
+    let v = match parameters.get(parameter) {
+        Some(v) => v,
+        None if builtin_or_quantified_parameter(&parameter.0, options)
+            .is_none() =>
+        {
+            return Err(UnknownParameterError::new(
+                parameter.0.clone(),
+                parameters.names(),
+            )
+            .into());
+        }
+        None => {
+            return expand_builtin_parameter_into(parameter, options, name, w)
+        }
+    };
+    if options.strict_parameters
+        && builtin_parameter(&parameter.0, options).is_some()
+    {
+        return Err(ShadowedParameterError {
+            shadowed: parameter.0.clone(),
+        }
+        .into());
+    }
+    write_group_start(w, name, options.dialect)?;
+    let chars = v.iter_elements().map(AsChar::as_char);
+    if options.non_capturing_parameters {
+        write_non_capturing(w, chars)?;
+    } else {
+        for c in chars {
+            w.write_char(c)?;
+        }
+    }
+    Ok(w.write_char(')')?)
+}
+
+/// Writes the given custom [`Parameter`] value into the `w`riter, converting
+/// all its capturing groups (including named ones) into non-capturing ones.
+///
+/// Escaped [`char`]s and character classes are written as is.
+pub(super) fn write_non_capturing<W>(
+    w: &mut W,
+    value: impl Iterator<Item = char>,
+) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    let mut chars = value.peekable();
+    let mut class_depth = 0_usize;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                w.write_char(c)?;
+                if let Some(escaped) = chars.next() {
+                    w.write_char(escaped)?;
+                }
+            }
+            '[' => {
+                class_depth += 1;
+                w.write_char(c)?;
+                // `]` right after `[` or `[^` is matched literally.
+                if let Some(negation) = chars.next_if_eq(&'^') {
+                    w.write_char(negation)?;
+                }
+                if let Some(bracket) = chars.next_if_eq(&']') {
+                    w.write_char(bracket)?;
+                }
+            }
+            ']' if class_depth > 0 => {
+                class_depth -= 1;
+                w.write_char(c)?;
+            }
+            '(' if class_depth == 0 => {
+                if chars.next_if_eq(&'?').is_none() {
+                    w.write_str("(?:")?;
+                    continue;
+                }
+                let named = if chars.next_if_eq(&'P').is_some() {
+                    if chars.next_if_eq(&'<').is_none() {
+                        w.write_str("(?P")?;
+                        continue;
+                    }
+                    true
+                } else if chars.next_if_eq(&'<').is_some() {
+                    if let Some(look_behind) =
+                        chars.next_if(|n| *n == '=' || *n == '!')
+                    {
+                        w.write_str("(?<")?;
+                        w.write_char(look_behind)?;
+                        continue;
+                    }
+                    true
+                } else {
+                    false
+                };
+                if named {
+                    // Skip the group name along with its closing `>`.
+                    chars.by_ref().take_while(|n| *n != '>').for_each(drop);
+                    w.write_str("(?:")?;
+                } else {
+                    w.write_str("(?")?;
+                }
+            }
+            _ => w.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the number of capturing groups (including named ones) in the given
+/// custom [`Parameter`] value, without compiling it.
+///
+/// Escaped [`char`]s and character classes are skipped the same way
+/// [`write_non_capturing()`] does.
+pub(super) fn capture_groups_len(value: impl Iterator<Item = char>) -> usize {
+    let mut chars = value.peekable();
+    let mut class_depth = 0_usize;
+    let mut len = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let _ = chars.next();
+            }
+            '[' => {
+                class_depth += 1;
+                // `]` right after `[` or `[^` is matched literally.
+                let _ = chars.next_if_eq(&'^');
+                let _ = chars.next_if_eq(&']');
+            }
+            ']' if class_depth > 0 => class_depth -= 1,
+            '(' if class_depth == 0 => {
+                let capturing = if chars.next_if_eq(&'?').is_none() {
+                    true
+                } else if chars.next_if_eq(&'P').is_some() {
+                    chars.peek() == Some(&'<')
+                } else {
+                    chars.next_if_eq(&'<').is_some()
+                        && !matches!(chars.peek(), Some('=' | '!'))
+                };
+                len += usize::from(capturing);
+            }
+            _ => {}
+        }
+    }
+    len
+}
+
+/// Returns the index of the enclosing capturing group (if any) of every
+/// capturing group (including named ones) in the given [`Regex`] pattern, in
+/// order, according to its parsed [`Ast`].
+///
+/// Returns nothing if the `pattern` can't be parsed by [`regex_syntax`] (like
+/// the `(?<name>…)` groups supported by newer [`regex`] versions only), so all
+/// its capturing groups are considered top-level ones.
+///
+/// [`Regex`]: regex::Regex
+#[cfg(feature = "into-regex")]
+pub(super) fn capture_groups_parents(pattern: &str) -> Vec<Option<usize>> {
+    Parser::new().parse(pattern).map_or_else(
+        |_| Vec::new(),
+        |ast| {
+            let mut parents = Vec::new();
+            collect_capture_groups_parents(&ast, None, &mut parents);
+            parents
+        },
+    )
+}
+
+/// Pushes the index of the enclosing capturing group of every capturing group
+/// in the given [`Ast`] into the given `parents`, considering the given
+/// `parent` as the enclosing one of the [`Ast`] itself.
+#[cfg(feature = "into-regex")]
+fn collect_capture_groups_parents(
+    ast: &Ast,
+    parent: Option<usize>,
+    parents: &mut Vec<Option<usize>>,
+) {
+    match ast {
+        Ast::Group(group) => {
+            let enclosing = if group.capture_index().is_some() {
+                parents.push(parent);
+                Some(parents.len() - 1)
+            } else {
+                parent
+            };
+            collect_capture_groups_parents(&group.ast, enclosing, parents);
+        }
+        Ast::Repetition(rep) => {
+            collect_capture_groups_parents(&rep.ast, parent, parents);
+        }
+        Ast::Concat(concat) => {
+            for inner in &concat.asts {
+                collect_capture_groups_parents(inner, parent, parents);
+            }
+        }
+        Ast::Alternation(alt) => {
+            for inner in &alt.asts {
+                collect_capture_groups_parents(inner, parent, parents);
+            }
+        }
+        Ast::Empty(_)
+        | Ast::Flags(_)
+        | Ast::Literal(_)
+        | Ast::Dot(_)
+        | Ast::Assertion(_)
+        | Ast::Class(_) => {}
+    }
+}
+
+/// Checks every custom [`Parameter`] used in the given [`Expression`] with the
+/// provided `compile` function, so an invalid [`Regex`] fragment is reported
+/// along with the [`Parameter`] it's provided for.
+///
+/// Fragments are checked on their own, as a valid expanded [`Regex`] may still
+/// contain an invalid fragment (like `a)(b` expanded into `(a)(b)`).
+///
+/// # Errors
+///
+/// With the first [`Parameter`] whose fragment fails to `compile`.
+///
+/// [`Regex`]: regex::Regex
+pub(super) fn validate_parameters<Input, Pars, E>(
+    expr: &Expression<Input>,
+    parameters: &Pars,
+    mut compile: impl FnMut(&str) -> Result<(), E>,
+) -> Result<(), InvalidParameterError<Input>>
+where
+    Input: Clone + Display,
+    Pars: Provider<Input>,
+    E: Display,
+{
+    for e in &expr.0 {
+        if let Some(Parameter(p)) = e.parameter() {
+            if let Some(v) = parameters.get(p) {
+                let fragment =
+                    v.iter_elements().map(AsChar::as_char).collect::<String>();
+                if let Err(err) = compile(&fragment) {
+                    return Err(InvalidParameterError {
+                        parameter: p.clone(),
+                        fragment,
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "into-regex"))]
+mod spec {
+    use std::{cell::RefCell, collections::BTreeSet};
+
+    use crate::{expand::Error, Spanned};
+
+    use crate::expand::{
+        ExpandInto as _, IntoBoxedRegexCharIter as _, IntoRegexCharIter as _,
+        Options, Whitespaces,
+    };
+
+    use super::{
+        AnyExpression, AsChar, BTreeMap, Expression, FromFn, HashMap,
+        InputIter, InvalidParameterError, Provider, ProviderExt as _,
+        ShadowedParameterError, UnknownParameterError, WithCustom,
+    };
+
+    #[test]
+    fn custom_parameter() {
+        let pars = HashMap::from([("custom", "custom")]);
+        let expr = Expression::regex_with_parameters("{custom}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(custom)$");
+    }
+
+    #[test]
+    fn custom_parameter_shadows_builtin() {
+        let pars = HashMap::from([("int", "\\d{3}"), ("word", "[a-z]+")]);
+        let re =
+            Expression::regex_with_parameters("{int} {word} {word}", &pars)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^(\\d{3}) ([a-z]+) ([a-z]+)$");
+        assert!(!re.is_match("-1 a b"));
+
+        let expr = Expression::parse("{int} {word} {string}").unwrap();
+        let with_int = HashMap::from([("int", "\\d{3}"), ("string", "\\w+")]);
+        let expected = "^(\\d{3}) ([^\\s]+) (\\w+)$";
+        let mut written = String::new();
+        WithCustom {
+            element: &expr,
+            parameters: &with_int,
+        }
+        .expand_into(&mut written)
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(written, expected);
+
+        let iterated = expr
+            .clone()
+            .with_parameters(&with_int)
+            .into_regex_string()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(iterated, expected);
+
+        let boxed = expr
+            .with_parameters(&with_int)
+            .into_regex_char_iter_boxed()
+            .collect::<Result<String, _>>()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(boxed, expected);
+    }
+
+    #[test]
+    fn strict_parameters() {
+        let strict = Options {
+            strict_parameters: true,
+            ..Options::default()
+        };
+        let pars = HashMap::from([("color", "red|blue"), ("word", "[a-z]+")]);
+
+        let re = Expression::regex_with_parameters_and_options(
+            "{color} {int}",
+            &pars,
+            strict,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^(red|blue) ((?:-?\\d+)|(?:\\d+))$");
+
+        match Expression::regex_with_parameters_and_options(
+            "{color} {word}",
+            &pars,
+            strict,
+        )
+        .unwrap_err()
+        {
+            Error::Shadowing(ShadowedParameterError { shadowed }) => {
+                assert_eq!(*shadowed, "word");
+                assert_eq!(shadowed.location_offset(), 9);
+            }
+            e @ (Error::Regex(_)
+            | Error::Parsing(_)
+            | Error::Expansion(_)
+            | Error::InvalidParameter(_)
+            | Error::Formatting(_)) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-regex-lite")]
+            e @ Error::RegexLite(_) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-fancy-regex")]
+            e @ Error::FancyRegex(_) => panic!("wrong err: {}", e),
+        }
+    }
+
+    #[test]
+    fn non_capturing_parameters() {
+        let non_capturing = Options {
+            non_capturing_parameters: true,
+            ..Options::default()
+        };
+        let pars = HashMap::from([
+            ("pair", r"(\d+),(\d+)"),
+            ("color", "red|blue"),
+            ("misc", r"(?P<x>a)(?<y>b)(?<=b)(?i:c)(?P=x)[^](]\("),
+        ]);
+
+        let mut written = String::new();
+        Expression::parse("{pair} {color} {misc}")
+            .unwrap()
+            .with_parameters(&pars)
+            .expand_into_with(&mut written, non_capturing)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(
+            written,
+            concat!(
+                r"^((?:\d+),(?:\d+)) (red|blue) ",
+                r"((?:a)(?:b)(?<=b)(?i:c)(?P=x)[^](]\()$",
+            ),
+        );
+
+        let re = Expression::regex_with_parameters_and_options(
+            "{pair} {color} {int}",
+            &pars,
+            non_capturing,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = re.captures("1,2 red 3").unwrap();
+        assert_eq!(caps.len(), 4);
+        assert_eq!(&caps[1], "1,2");
+        assert_eq!(&caps[2], "red");
+        assert_eq!(&caps[3], "3");
+    }
+
+    #[test]
+    fn capture_groups_parents() {
+        for (pattern, expected) in [
+            ("a", vec![]),
+            ("(a)(b)", vec![None, None]),
+            ("((a)(?:(b)|(c)))", vec![None, Some(0), Some(0), Some(0)]),
+            (
+                r"(?P<x>(a)\((b))[(](?P<y>(?i)c(d))",
+                vec![None, Some(0), Some(0), None, Some(3)],
+            ),
+            (r"\((b[)]c)", vec![None]),
+        ] {
+            assert_eq!(
+                super::capture_groups_parents(pattern),
+                expected,
+                "on pattern: {}",
+                pattern,
+            );
+            assert_eq!(
+                super::capture_groups_len(pattern.chars()),
+                expected.len(),
+                "on pattern: {}",
+                pattern,
+            );
+        }
+
+        for (pattern, expected) in [
+            ("(?x)(a # (b\n)(c)", vec![None, None]),
+            ("(?x)(a) \\# (b) # )\n(c)", vec![None, None, None]),
+        ] {
+            assert_eq!(
+                super::capture_groups_parents(pattern),
+                expected,
+                "on pattern: {}",
+                pattern,
+            );
+        }
+        assert!(super::capture_groups_parents("(?<x>a)(b)").is_empty());
+    }
+
+    #[test]
+    fn invalid_parameter() {
+        let pars = HashMap::from([("ok", "[a-z]+"), ("bad", "a)(b")]);
+
+        match Expression::regex_with_parameters("{ok} {bad}", &pars)
+            .unwrap_err()
+        {
+            Error::InvalidParameter(InvalidParameterError {
+                parameter,
+                fragment,
+                reason,
+            }) => {
+                assert_eq!(*parameter, "bad");
+                assert_eq!(parameter.location_offset(), 6);
+                assert_eq!(fragment, "a)(b");
+                assert!(!reason.is_empty());
+            }
+            e @ (Error::Regex(_)
+            | Error::Parsing(_)
+            | Error::Expansion(_)
+            | Error::Shadowing(_)
+            | Error::Formatting(_)) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-regex-lite")]
+            e @ Error::RegexLite(_) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-fancy-regex")]
+            e @ Error::FancyRegex(_) => panic!("wrong err: {}", e),
+        }
+
+        for res in [
+            Expression::regex_with_parameters_and_options(
+                "{bad}",
+                &pars,
+                Options::default(),
+            ),
+            AnyExpression::regex_with_parameters("{bad}", &pars),
+            Expression::regex_with_parameters_and_capture_map("{bad}", &pars)
+                .map(|(re, _)| re),
+        ] {
+            assert!(
+                matches!(res, Err(Error::InvalidParameter(_))),
+                "wrong result: {:?}",
+                res,
+            );
+        }
+        assert!(matches!(
+            Expression::bytes_regex_with_parameters_and_options(
+                "{bad}",
+                &pars,
+                Options::default(),
+            ),
+            Err(Error::InvalidParameter(_)),
+        ));
+
+        assert!(Expression::regex_with_parameters("{ok}", &pars).is_ok());
+    }
+
+    #[test]
+    fn fingerprint() {
+        let pars = HashMap::from([("color", "red|blue"), ("size", "\\d+")]);
+        let same = HashMap::from([("size", "\\d+"), ("color", "red|blue")]);
+        let other = HashMap::from([("color", "red"), ("size", "\\d+")]);
+
+        let fp = |p| Provider::<&str>::fingerprint(&p);
+        assert!(fp(&pars).is_some());
+        assert_eq!(fp(&pars), fp(&same));
+        assert_ne!(fp(&pars), fp(&other));
+    }
+
+    #[test]
+    fn pairs() {
+        let array = Expression::regex_with_parameters(
+            "{color} {size}",
+            &[("color", "red|blue"), ("size", "\\d+")],
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(array.as_str(), "^(red|blue) (\\d+)$");
+
+        let pars = vec![("color".to_owned(), "red|blue".to_owned())];
+        let vec = Expression::regex_with_parameters("{color}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(vec.as_str(), "^(red|blue)$");
+
+        let slice =
+            Expression::regex_with_parameters("{color}", pars.as_slice())
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(slice.as_str(), "^(red|blue)$");
+
+        let hash_map = pars.iter().cloned().collect::<HashMap<_, _>>();
+        assert_eq!(Provider::<&str>::names(&&pars), ["color"]);
+        assert_eq!(
+            Provider::<&str>::fingerprint(&&pars),
+            Provider::<&str>::fingerprint(&&hash_map),
+        );
+    }
+
+    #[test]
+    fn owned_array() {
+        let pars = [("color", "red|blue"), ("size", "\\d+")];
+        let expr = Expression::regex_with_parameters("{color} {size}", pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(red|blue) (\\d+)$");
+        assert_eq!(Provider::<&str>::names(&pars), ["color", "size"]);
+        assert_eq!(
+            Provider::<&str>::fingerprint(&pars),
+            Provider::<&str>::fingerprint(&&pars),
+        );
+    }
+
+    #[test]
+    fn from_fn() {
+        let registry = HashMap::from([("color", "red|blue")]);
+        let pars = FromFn(|name: &str| {
+            registry.get(name).map(|v| format!("{}|green", v))
+        });
+
+        let expr = Expression::regex_with_parameters("{color} {int}", pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(expr.as_str(), "^(red|blue|green) ((?:-?\\d+)|(?:\\d+))$",);
+
+        let iter = Expression::parse("{color}")
+            .unwrap()
+            .with_parameters(pars)
+            .into_regex_char_iter()
+            .collect::<Result<String, _>>()
+            .unwrap();
+        assert_eq!(iter, "^(red|blue|green)$");
+
+        assert!(matches!(
+            Expression::regex_with_parameters("{size}", pars),
+            Err(Error::Expansion(_)),
+        ));
+        assert_eq!(Provider::<&str>::fingerprint(&pars), None);
+    }
+
+    #[test]
+    fn on_unknown_parameter() {
+        let pars = HashMap::from([("color", "red|blue")]);
+        let seen = RefCell::new(BTreeSet::new());
+        let resolving = (&pars).on_unknown_parameter(|name: &str| {
+            let _ = seen.borrow_mut().insert(name.to_owned());
+            name.strip_prefix("enum:").map(|v| v.replace(',', "|"))
+        });
+
+        let expr = Expression::regex_with_parameters(
+            "{color} {enum:S,M} {int} {}",
+            resolving,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(
+            expr.as_str(),
+            "^(red|blue) (S|M) ((?:-?\\d+)|(?:\\d+)) (.*)$",
+        );
+        assert_eq!(*seen.borrow(), BTreeSet::from(["enum:S,M".to_owned()]));
+
+        let quantified = Options {
+            quantified_parameters: true,
+            ..Options::default()
+        };
+        assert!(Expression::parse("{int+}")
+            .unwrap()
+            .with_parameters(resolving)
+            .expand_into_pattern_with(quantified)
+            .is_ok());
+        assert_eq!(*seen.borrow(), BTreeSet::from(["enum:S,M".to_owned()]));
+
+        assert!(matches!(
+            Expression::regex_with_parameters("{size}", resolving),
+            Err(Error::Expansion(_)),
+        ));
+        assert_eq!(
+            *seen.borrow(),
+            BTreeSet::from(["enum:S,M".to_owned(), "size".to_owned()]),
+        );
+        assert_eq!(Provider::<&str>::names(&resolving), ["color"]);
+        assert_eq!(Provider::<&str>::fingerprint(&resolving), None);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalized_names() {
+        use crate::expand::{ParameterType, ParameterTypeRegistry};
+
+        // Decomposed `é` in the custom `Parameter` name.
+        let pars = HashMap::from([("cafe\u{301}", "open|closed")]);
+        let normalized = (&pars).normalized_names();
+
+        assert!(
+            Expression::regex_with_parameters("{caf\u{e9}}", &pars).is_err()
+        );
+        for expr in ["{caf\u{e9}}", "{cafe\u{301}}"] {
+            let re = Expression::regex_with_parameters(expr, normalized)
+                .unwrap_or_else(|e| panic!("{}: {}", expr, e));
+            assert_eq!(re.as_str(), "^(open|closed)$");
+        }
+        assert!(matches!(
+            Expression::regex_with_parameters("{cafe}", normalized),
+            Err(Error::Expansion(_)),
+        ));
+        assert_eq!(
+            Provider::<&str>::names(&normalized),
+            ["cafe\u{301}".to_owned()],
+        );
+        assert_ne!(
+            Provider::<&str>::fingerprint(&normalized),
+            Provider::<&str>::fingerprint(&&pars),
+        );
+
+        let mut registry = ParameterTypeRegistry::new();
+        registry
+            .define(ParameterType::untyped("n\u{e3}o", "nope"))
+            .unwrap();
+        let re = Expression::regex_with_parameters(
+            "{int} {na\u{303}o}",
+            (&registry).normalized_names(),
+        )
+        .unwrap();
+        assert!(re.is_match("1 nope"));
+    }
+
+    #[test]
+    fn precompiled() {
+        use regex::Regex;
+        use regex_syntax::Parser;
+
+        use super::{Precompiled, PrecompiledError};
+
+        let hir = Parser::new().parse("(?i)(\\d+)-(\\d+)").unwrap();
+        let pars = BTreeMap::from([
+            ("color", Precompiled::new(Regex::new("red|blue").unwrap())),
+            ("range", Precompiled::try_from(&hir)),
+        ])
+        .into_iter()
+        .map(|(k, v)| v.map(|v| (k, v)))
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .unwrap();
+
+        let expr = Expression::regex_with_parameters("{color} {range}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = expr.captures("red 1-2").unwrap();
+        assert_eq!(caps.len(), 5);
+        assert_eq!(&caps[4], "2");
+        assert_eq!(pars["color"].regex().as_str(), "red|blue");
+        assert_eq!(pars["color"].clone().into_regex().as_str(), "red|blue");
+
+        for anchored in ["^red", "red$", "\\Ared\\z"] {
+            assert_eq!(
+                Precompiled::try_from(Regex::new(anchored).unwrap())
+                    .unwrap_err(),
+                PrecompiledError::Anchored(anchored.to_owned()),
+            );
+        }
+        assert!(Precompiled::new(Regex::new("(?m)^red$").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn resolves_deterministically() {
+        use std::collections::hash_map::RandomState;
+
+        let pairs = (0..32).map(|i| (format!("p{}", i), format!("v{}", i)));
+        let first = pairs.clone().collect::<HashMap<_, _>>();
+        let mut second = HashMap::with_hasher(RandomState::new());
+        second.extend(pairs.rev());
+
+        let names = Provider::<&str>::names(&&first);
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(names, Provider::<&str>::names(&&second));
+        assert_eq!(
+            Provider::<&str>::fingerprint(&&first),
+            Provider::<&str>::fingerprint(&&second),
+        );
+        assert_eq!(
+            Expression::regex_with_parameters("{p1} {p30}", &first)
+                .unwrap()
+                .as_str(),
+            Expression::regex_with_parameters("{p1} {p30}", &second)
+                .unwrap()
+                .as_str(),
+        );
+
+        let duplicated = [("color", "red"), ("color", "blue")];
+        let re = Expression::regex_with_parameters("{color}", &duplicated[..])
+            .unwrap();
+        assert_eq!(re.as_str(), "^(red)$");
+    }
+
+    #[test]
+    fn cow_value() {
+        use std::borrow::Cow;
+
+        use super::CowValue;
+
+        let borrowed = CowValue::from("a|b");
+        let owned = CowValue::from(String::from("a|b"));
+        assert_eq!(borrowed, owned);
+        assert!(matches!(borrowed.0, Cow::Borrowed(_)));
+        assert!(matches!(owned.0, Cow::Owned(_)));
+        for v in [&borrowed, &owned] {
+            assert_eq!(v.iter_elements().collect::<String>(), "a|b");
+            assert_eq!(
+                v.iter_indices().collect::<Vec<_>>(),
+                [(0, 'a'), (1, '|'), (2, 'b')],
+            );
+            assert_eq!(v.position(|c| c == '|'), Some(1));
+            assert_eq!(v.slice_index(2), Ok(2));
+        }
+    }
+
+    #[test]
+    fn simple_provider() {
+        use std::borrow::Cow;
+
+        use super::SimpleProvider;
+
+        struct Enums(Vec<(&'static str, Vec<&'static str>)>);
+
+        impl SimpleProvider for Enums {
+            fn get(&self, name: &str) -> Option<Cow<'_, str>> {
+                self.0.iter().find(|(n, _)| *n == name).map(|(_, v)| {
+                    match v.as_slice() {
+                        [single] => Cow::Borrowed(*single),
+                        _ => Cow::Owned(v.join("|")),
+                    }
+                })
+            }
+
+            fn names(&self) -> Vec<String> {
+                self.0.iter().map(|(n, _)| (*n).to_owned()).collect()
+            }
+        }
+
+        let enums =
+            Enums(vec![("color", vec!["red", "blue"]), ("size", vec!["XL"])]);
+        let expr = Expression::regex_with_parameters("{color} {size}", &enums)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(expr.as_str(), "^(red|blue) (XL)$");
+
+        let dyn_enums: &dyn SimpleProvider = &enums;
+        let iter = Expression::parse("{size}")
+            .unwrap()
+            .with_parameters(dyn_enums)
+            .into_regex_char_iter()
+            .collect::<Result<String, _>>()
+            .unwrap();
+        assert_eq!(iter, "^(XL)$");
+
+        assert_eq!(
+            Expression::regex_with_parameters("{colour}", &enums)
+                .unwrap_err()
+                .to_string(),
+            "Regex expansion failed: Parameter 'colour' not found, did you \
+             mean 'color'?",
+        );
+        assert_eq!(Provider::<&str>::fingerprint(&&enums), None);
+    }
+
+    #[test]
+    fn dyn_provider() {
+        type DynProvider<'p> =
+            dyn Provider<Spanned<'p>, Item = char, Value = &'p str> + 'p;
+
+        let pars = HashMap::from([("color", "red|blue")]);
+        let boxed: Box<DynProvider<'_>> = Box::new(&pars);
+        let borrowed: &DynProvider<'_> = &*boxed;
+
+        let from_ref = Expression::regex_with_parameters("{color}", borrowed)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(from_ref.as_str(), "^(red|blue)$");
+
+        let from_box = Expression::regex_with_parameters("{color}", boxed)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(from_box.as_str(), "^(red|blue)$");
+    }
+
+    #[test]
+    fn or() {
+        let project = HashMap::from([("color", "red|blue"), ("size", "\\d+")]);
+        let module = BTreeMap::from([("color", "green"), ("shape", "round")]);
+        let pars = (&module).or(&project);
+
+        let expr =
+            Expression::regex_with_parameters("{color} {size} {shape}", pars)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(expr.as_str(), "^(green) (\\d+) (round)$");
+
+        let mut names = Provider::<&str>::names(&pars);
+        names.sort();
+        assert_eq!(names, ["color", "shape", "size"]);
+
+        let reversed = (&project).or(&module);
+        assert!(Provider::<&str>::fingerprint(&pars).is_some());
+        assert_ne!(
+            Provider::<&str>::fingerprint(&pars),
+            Provider::<&str>::fingerprint(&reversed),
+        );
+    }
+
+    #[cfg(feature = "indexmap")]
+    #[test]
+    fn index_map() {
+        let pars = indexmap::IndexMap::<_, _>::from_iter([
+            ("size", "\\d+"),
+            ("color", "red|blue"),
+        ]);
+        let expr = Expression::regex_with_parameters("{color} {size}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(red|blue) (\\d+)$");
+        assert_eq!(Provider::<&str>::names(&&pars), ["size", "color"]);
+
+        let hash_map = pars.clone().into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(
+            Provider::<&str>::fingerprint(&&pars),
+            Provider::<&str>::fingerprint(&&hash_map),
+        );
+    }
+
+    #[cfg(feature = "phf")]
+    #[test]
+    fn phf_map() {
+        static PARS: phf::Map<&str, &str> = phf::phf_map! {
+            "color" => "red|blue",
+            "size" => "\\d+",
+        };
+
+        let expr = Expression::regex_with_parameters("{color} {size}", &PARS)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(expr.as_str(), "^(red|blue) (\\d+)$");
+
+        let long = "long".repeat(super::NAME_BUFFER_LEN);
+        assert!(Provider::<&str>::get(&&PARS, &long.as_str()).is_none());
+
+        let hash_map = PARS
+            .entries()
+            .map(|(k, v)| (*k, *v))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(
+            Provider::<&str>::fingerprint(&&PARS),
+            Provider::<&str>::fingerprint(&&hash_map),
+        );
+    }
+
+    #[test]
+    fn hash_map_lookup() {
+        let long = "long".repeat(super::NAME_BUFFER_LEN);
+        let pars = HashMap::from([
+            ("ünïcödé".to_owned(), "u".to_owned()),
+            (long.clone(), "l".to_owned()),
+        ]);
+
+        let expr = Expression::regex_with_parameters(
+            &format!("{{ünïcödé}} {{{}}}", long),
+            &pars,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(expr.as_str(), "^(u) (l)$");
+
+        assert!(matches!(
+            Expression::regex_with_parameters(&format!("{{{}s}}", long), &pars),
+            Err(Error::Expansion(_)),
+        ));
+    }
+
+    #[test]
+    fn btree_map() {
+        let pars = BTreeMap::from([("color", "red|blue"), ("size", "\\d+")]);
+        let expr = Expression::regex_with_parameters("{color} {size}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(red|blue) (\\d+)$");
+        assert_eq!(Provider::<&str>::names(&&pars), ["color", "size"]);
+
+        let hash_map = pars.clone().into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(
+            Provider::<&str>::fingerprint(&&pars),
+            Provider::<&str>::fingerprint(&&hash_map),
+        );
+    }
+
+    #[test]
+    fn non_clone_provider() {
+        /// [`Provider`] which cannot be cloned, like one holding a database
+        /// handle.
+        struct Database(Vec<(&'static str, &'static str)>);
+
+        impl<Input> Provider<Input> for Database
+        where
+            Input: InputIter,
+            <Input as InputIter>::Item: AsChar,
+        {
+            type Item = char;
+            type Value = &'static str;
+
+            fn get(&self, input: &Input) -> Option<Self::Value> {
+                self.0.iter().find_map(|(k, v)| {
+                    k.chars()
+                        .eq(input.iter_elements().map(AsChar::as_char))
+                        .then(|| *v)
+                })
+            }
+        }
+
+        let db = || Database(vec![("color", "red|blue")]);
+        let expr = Expression::parse("{color} {int}").unwrap();
+
+        let chars = WithCustom {
+            element: &expr,
+            parameters: db(),
+        }
+        .into_regex_string()
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        let mut written = String::new();
+        WithCustom {
+            element: &expr,
+            parameters: db(),
+        }
+        .expand_into(&mut written)
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(chars, r"^(red|blue) ((?:-?\d+)|(?:\d+))$");
+        assert_eq!(written, chars);
+
+        let res = Expression::regex_batch(&["{color}", "{int} {color}"], db());
+        assert!(res[0].as_ref().unwrap().is_match("red"));
+        assert!(res[1].as_ref().unwrap().is_match("1 blue"));
+    }
+
+    #[test]
+    fn default_parameter() {
+        let pars = HashMap::from([("custom", "custom")]);
+        let expr = Expression::regex_with_parameters("{}", &pars)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert_eq!(expr.as_str(), "^(.*)$");
+    }
+
+    #[test]
+    fn any_expression() {
+        let pars = HashMap::from([("custom", "custom")]);
+
+        for (input, expected) in [
+            ("{custom}", "^(custom)$"),
+            (r"/(\w+)/", r"^(?:(\w+))$"),
+            (r"^(\w+)$", r"^(?:^(\w+)$)$"),
+        ] {
+            let expr = AnyExpression::regex_with_parameters(input, &pars)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(expr.as_str(), expected, "on input: {}", input);
+        }
+    }
+
+    #[test]
+    fn borrowed_expression() {
+        let expr = Expression::parse("a/b {custom} (c)").unwrap();
+
+        for pars in [
+            HashMap::from([("custom", "custom")]),
+            HashMap::from([("custom", "other")]),
+        ] {
+            let re = WithCustom {
+                element: &expr,
+                parameters: &pars,
+            }
+            .into_regex_string()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(re, format!("^(?:a|b) ({}) (?:c)?$", pars["custom"]));
+        }
+
+        let err = (&expr).into_regex_string().unwrap_err();
+        assert_eq!(*err.not_found, "custom");
+    }
+
+    #[test]
+    fn expand_into() {
+        let pars = HashMap::from([("custom", "custom")]);
+
+        for input in ["{custom} a/b {int}", r"/(\w+)/"] {
+            let expr = AnyExpression::parse(input).unwrap();
+
+            let mut written = String::new();
+            WithCustom {
+                element: &expr,
+                parameters: &pars,
+            }
+            .expand_into(&mut written)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            assert_eq!(
+                written,
+                expr.with_parameters(&pars)
+                    .into_regex_string()
+                    .unwrap_or_else(|e| panic!("failed: {}", e)),
+                "on input: {}",
+                input,
+            );
+        }
+
+        let expr = Expression::parse("{unknown}").unwrap();
+        match expr.with_parameters(&pars).expand_into(&mut String::new()) {
+            Err(Error::Expansion(UnknownParameterError {
+                not_found, ..
+            })) => {
+                assert_eq!(*not_found, "unknown");
+            }
+            res @ (Ok(())
+            | Err(
+                Error::Regex(_)
+                | Error::Parsing(_)
+                | Error::Shadowing(_)
+                | Error::InvalidParameter(_)
+                | Error::Formatting(_),
+            )) => {
+                panic!("wrong result: {:?}", res)
+            }
+            #[cfg(feature = "into-regex-lite")]
+            res @ Err(Error::RegexLite(_)) => {
+                panic!("wrong result: {:?}", res)
+            }
+            #[cfg(feature = "into-fancy-regex")]
+            res @ Err(Error::FancyRegex(_)) => {
+                panic!("wrong result: {:?}", res)
+            }
+        }
+    }
+
+    #[test]
+    fn options() {
+        let pars = HashMap::from([("custom", "custom")]);
+        let options = Options {
+            anchor_start: false,
+            ..Options::default()
+        };
+
+        let re = Expression::regex_with_parameters_and_options(
+            "{custom} {int}",
+            &pars,
+            options,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "(custom) ((?:-?\\d+)|(?:\\d+))$");
+
+        let iterated = Expression::parse("{custom}")
+            .unwrap()
+            .with_parameters(&pars)
+            .into_regex_char_iter_with(options)
+            .collect::<Result<String, _>>()
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(iterated, "(custom)$");
+
+        let padded = Options {
+            whitespaces: Whitespaces::FlexiblePadded,
+            ..Options::default()
+        };
+        let expected = "^\\s*(custom)\\s+(custom)\\s*$";
+        let expr = Expression::parse("{custom} {custom}").unwrap();
+        let mut written = String::new();
+        expr.clone()
+            .with_parameters(&pars)
+            .expand_into_with(&mut written, padded)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(written, expected);
+        assert_eq!(
+            expr.with_parameters(&pars)
+                .into_regex_char_iter_with(padded)
+                .collect::<Result<String, _>>()
+                .unwrap_or_else(|e| panic!("failed: {}", e)),
+            expected,
+        );
+
+        let named = Options {
+            named_groups: true,
+            ..Options::default()
+        };
+        let numbered = Expression::regex_with_parameters_and_options(
+            "{custom} {custom} {word}",
+            &pars,
+            named,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(
+            numbered.as_str(),
+            "^(?P<custom_1>custom) (?P<custom_2>custom) (?P<word>[^\\s]+)$",
+        );
+
+        let sanitized = Expression::regex_with_parameters_and_options(
+            "{1st custom}",
+            &HashMap::from([("1st custom", "custom")]),
+            named,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(sanitized.as_str(), "^(?P<_1st_custom>custom)$");
+
+        let clashing = Expression::regex_with_parameters_and_options(
+            "{int} {int_1} {int}",
+            &HashMap::from([("int_1", "x")]),
+            named,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+        let caps = clashing.captures("1 x 3").unwrap();
+        assert_eq!(&caps["int_2"], "1");
+        assert_eq!(&caps["int_1"], "x");
+        assert_eq!(&caps["int_3"], "3");
+    }
+
+    #[test]
+    fn unknown_parameter_suggestion() {
+        let pars = HashMap::from([("color", "red|blue"), ("size", "\\d+")]);
+
+        let err = Expression::parse("{colour}")
+            .unwrap()
+            .with_parameters(&pars)
+            .expand_into(&mut String::new())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Regex expansion failed: \
+             Parameter 'colour' not found, did you mean 'color'?",
+        );
+
+        let iterated = Expression::parse("{flaot}")
+            .unwrap()
+            .with_parameters(&pars)
+            .into_regex_string()
+            .unwrap_err();
+        assert_eq!(iterated.suggestion.as_deref(), Some("float"));
+        assert!(iterated.known.contains(&"int".to_owned()));
+        assert!(iterated.known.ends_with(&["color".into(), "size".into()]));
+
+        let boxed = Expression::parse("{sise}")
+            .unwrap()
+            .with_parameters(&pars)
+            .into_regex_char_iter_boxed()
+            .collect::<Result<String, _>>()
+            .unwrap_err();
+        assert_eq!(boxed.suggestion.as_deref(), Some("size"));
+
+        let unrelated =
+            Expression::regex_with_parameters("{xyz}", &pars).unwrap_err();
+        assert_eq!(
+            unrelated.to_string(),
+            "Regex expansion failed: Parameter 'xyz' not found.",
+        );
+    }
+
+    #[test]
+    fn unknown_parameter() {
+        let pars = HashMap::<String, String>::new();
+
+        match Expression::regex_with_parameters("{custom}", &pars).unwrap_err()
+        {
+            Error::Expansion(UnknownParameterError { not_found, .. }) => {
+                assert_eq!(*not_found, "custom");
             }
+            e @ (Error::Regex(_)
+            | Error::Parsing(_)
+            | Error::Shadowing(_)
+            | Error::InvalidParameter(_)
+            | Error::Formatting(_)) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-regex-lite")]
+            e @ Error::RegexLite(_) => panic!("wrong err: {}", e),
+            #[cfg(feature = "into-fancy-regex")]
+            e @ Error::FancyRegex(_) => panic!("wrong err: {}", e),
         }
     }
 }
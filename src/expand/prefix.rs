@@ -0,0 +1,405 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Expansion into [`Regex`]es matching prefixes of texts, to check whether a
+//! partially typed text may still match an [`Expression`].
+
+use std::collections::HashMap;
+
+use nom::{AsChar, InputIter};
+use regex::Regex;
+
+use crate::{
+    Alternative, Expression, Parameter, SingleExpression, Spanned, TextChars,
+};
+
+use super::{
+    builtin_parameter, Dialect, Error, Options, ParametersProvider,
+    UnknownParameterError,
+};
+
+#[allow(clippy::multiple_inherent_impl)] // because of `into-regex` feature
+impl<'s> Expression<Spanned<'s>> {
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into a [`Regex`] matching any prefix of the texts matched by the
+    /// [`Expression::regex()`], so autocompletion may check whether a text
+    /// typed so far is still a viable prefix of the [`Expression`].
+    ///
+    /// Being anchored at both ends, the [`Regex`] should be used with
+    /// [`Regex::is_match()`] only, as its capturing groups don't correspond to
+    /// [`Parameter`]s anymore. A prefix of a built-in [`Parameter`] is checked
+    /// precisely, while a partially typed anonymous `{}` one, or the ones
+    /// enabled by `extra-params` feature, match anything on a single line.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let re = Expression::prefix_regex("I have {int} cucumber(s)").unwrap();
+    ///
+    /// assert!(re.is_match(""));
+    /// assert!(re.is_match("I ha"));
+    /// assert!(re.is_match("I have -4"));
+    /// assert!(re.is_match("I have 42 cucumbers"));
+    /// assert!(!re.is_match("I have four"));
+    /// assert!(!re.is_match("I have 42 cucumbers!"));
+    /// ```
+    ///
+    /// [`Error`]: enum@Error
+    /// [`Parameter`]: crate::Parameter
+    pub fn prefix_regex<Input: AsRef<str> + ?Sized>(
+        input: &'s Input,
+    ) -> Result<Regex, Error<Spanned<'s>>> {
+        Expression::prefix_regex_with_parameters(
+            input,
+            &HashMap::<&str, &str>::new(),
+        )
+    }
+
+    /// Parses the given `input` as an [`Expression`], and immediately expands
+    /// it into a [`Regex`] matching any prefix of the texts matched by the
+    /// [`Expression::regex_with_parameters()`], considering the custom defined
+    /// `parameters` in addition to [default ones][1].
+    ///
+    /// See [`Expression::prefix_regex()`] for more details. Prefixes of custom
+    /// [`Parameter`]s can't be derived from their patterns, so a partially
+    /// typed one matches anything on a single line.
+    ///
+    /// # Errors
+    ///
+    /// See [`Error`] for more details.
+    ///
+    /// [`Error`]: enum@Error
+    /// [`Parameter`]: crate::Parameter
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[allow(clippy::needless_pass_by_value)] // consistency with other methods
+    pub fn prefix_regex_with_parameters<Input, Parameters>(
+        input: &'s Input,
+        parameters: Parameters,
+    ) -> Result<Regex, Error<Spanned<'s>>>
+    where
+        Input: AsRef<str> + ?Sized,
+        Parameters: ParametersProvider<Spanned<'s>>,
+    {
+        let expr = Expression::parse(input)?;
+        let elements = expr
+            .iter()
+            .map(|e| Element::new(e, &parameters))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut re = String::from("^");
+        write_prefix_of(&mut re, &elements);
+        re.push('$');
+        Ok(Regex::new(&re)?)
+    }
+}
+
+/// Element of an [`Expression`] prepared for expanding its prefixes.
+#[derive(Debug)]
+enum Element {
+    /// Cooked text.
+    Text(String),
+
+    /// Cooked optional text.
+    Optional(String),
+
+    /// Branches, consisting of [`Element::Text`]s and [`Element::Optional`]s
+    /// only.
+    Alternation(Vec<Vec<Self>>),
+
+    /// Optional sequence of an [`Element::Text`], [`Element::Parameter`] and
+    /// another [`Element::Text`].
+    OptionalParameter(Vec<Self>),
+
+    /// Parameter.
+    Parameter {
+        /// [`Regex`] pattern matching the whole [`Element::Parameter`].
+        whole: String,
+
+        /// [`Regex`] pattern matching any prefix of the
+        /// [`Element::Parameter`].
+        prefix: &'static str,
+    },
+}
+
+impl Element {
+    /// Prepares the given [`SingleExpression`] for expanding its prefixes,
+    /// considering the custom defined `parameters`.
+    ///
+    /// # Errors
+    ///
+    /// If the [`SingleExpression`] is an unknown [`Parameter`].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    fn new<'s, P>(
+        expr: &SingleExpression<Spanned<'s>>,
+        parameters: &P,
+    ) -> Result<Self, Error<Spanned<'s>>>
+    where
+        P: ParametersProvider<Spanned<'s>>,
+    {
+        Ok(match expr {
+            SingleExpression::Text(t) | SingleExpression::Whitespaces(t) => {
+                Self::Text(TextChars::new(t).cooked())
+            }
+            SingleExpression::Optional(opt) => {
+                Self::Optional(opt.chars().cooked())
+            }
+            SingleExpression::Alternation(alt) => Self::Alternation(
+                alt.iter()
+                    .map(|single| {
+                        single
+                            .iter()
+                            .map(|a| match a {
+                                Alternative::Text(t) => {
+                                    Self::Text(TextChars::new(t).cooked())
+                                }
+                                Alternative::Optional(opt) => {
+                                    Self::Optional(opt.chars().cooked())
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            SingleExpression::OptionalParameter(opt) => {
+                Self::OptionalParameter(vec![
+                    Self::Text(opt.prefix_chars().cooked()),
+                    Self::parameter(&opt.parameter, parameters)?,
+                    Self::Text(opt.suffix_chars().cooked()),
+                ])
+            }
+            SingleExpression::Parameter(p) => Self::parameter(p, parameters)?,
+        })
+    }
+
+    /// Prepares the given [`Parameter`] for expanding its prefixes,
+    /// considering the custom defined `parameters`.
+    ///
+    /// # Errors
+    ///
+    /// If the [`Parameter`] is unknown.
+    fn parameter<'s, P>(
+        p: &Parameter<Spanned<'s>>,
+        parameters: &P,
+    ) -> Result<Self, Error<Spanned<'s>>>
+    where
+        P: ParametersProvider<Spanned<'s>>,
+    {
+        if let Some(v) = parameters.get(&p.0) {
+            let value =
+                v.iter_elements().map(AsChar::as_char).collect::<String>();
+            return Ok(Self::Parameter {
+                whole: format!("({})", value),
+                prefix: ".*",
+            });
+        }
+        let whole =
+            builtin_parameter(&p.0, Options::default()).ok_or_else(|| {
+                UnknownParameterError::new(p.0, parameters.names())
+            })?;
+        Ok(Self::Parameter {
+            whole: whole.into(),
+            prefix: builtin_prefix(p.0.fragment()),
+        })
+    }
+}
+
+/// Returns a [`Regex`] pattern matching any prefix of texts matched by the
+/// built-in [`Parameter`] with the given `name`.
+///
+/// [`Parameter`]: crate::Parameter
+fn builtin_prefix(name: &str) -> &'static str {
+    match name {
+        "int" | "byte" | "short" | "long" | "biginteger" => r"-?\d*",
+        "float" | "double" | "bigdecimal" => {
+            r"[-+]?(?:\d*\.?\d*|\d*\.\d+[Ee][+-]?\d*|\d+[Ee][+-]?\d*)"
+        }
+        "boolean" => r"(?:t(?:r(?:ue?)?)?|f(?:a(?:l(?:se?)?)?)?)?",
+        "ordinal" => r"(?:\d+(?:st?|nd?|rd?|th?)|\d*)",
+        "word" => r"[^\s]*",
+        "string" => concat!(
+            r#"(?:"[^"\\]*(?:\\.[^"\\]*)*(?:\\|")?"#,
+            r#"|'[^'\\]*(?:\\.[^'\\]*)*(?:\\|')?)?"#,
+        ),
+        _ => ".*",
+    }
+}
+
+/// Writes a [`Regex`] pattern matching any prefix of texts matched by the
+/// given sequence of [`Element`]s into the `w`riter.
+fn write_prefix_of(w: &mut String, elements: &[Element]) {
+    let (first, rest) = match elements.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    if rest.is_empty() {
+        return write_element(w, first, true);
+    }
+
+    // Either the first `Element` is typed partially, or it's typed as a whole
+    // and followed by a prefix of the rest ones.
+    w.push_str("(?:");
+    write_element(w, first, true);
+    w.push('|');
+    write_element(w, first, false);
+    w.push_str("(?:");
+    write_prefix_of(w, rest);
+    w.push_str("))");
+}
+
+/// Writes a [`Regex`] pattern matching the given [`Element`] as a whole, or
+/// any of its prefixes, into the `w`riter.
+fn write_element(w: &mut String, element: &Element, prefix: bool) {
+    match element {
+        Element::Text(t) | Element::Optional(t) if prefix => {
+            w.push_str("(?:");
+            for (i, c) in t.char_indices() {
+                if i > 0 {
+                    w.push('|');
+                }
+                write_escaped(w, &t[..i + c.len_utf8()]);
+            }
+            w.push_str(")?");
+        }
+        Element::Text(t) => write_escaped(w, t),
+        Element::Optional(t) => {
+            w.push_str("(?:");
+            write_escaped(w, t);
+            w.push_str(")?");
+        }
+        Element::Alternation(branches) => {
+            w.push_str("(?:");
+            for (i, branch) in branches.iter().enumerate() {
+                if i > 0 {
+                    w.push('|');
+                }
+                if prefix {
+                    write_prefix_of(w, branch);
+                } else {
+                    for e in branch {
+                        write_element(w, e, false);
+                    }
+                }
+            }
+            w.push(')');
+        }
+        Element::OptionalParameter(elements) => {
+            w.push_str("(?:");
+            if prefix {
+                write_prefix_of(w, elements);
+            } else {
+                for e in elements {
+                    write_element(w, e, false);
+                }
+            }
+            w.push_str(")?");
+        }
+        Element::Parameter { whole, prefix: p } => {
+            w.push_str(if prefix { p } else { whole });
+        }
+    }
+}
+
+/// Writes the given cooked `text` escaped for a [`Regex`] into the `w`riter.
+fn write_escaped(w: &mut String, text: &str) {
+    for c in text.chars() {
+        if Dialect::Rust.escaped_chars().contains(c) {
+            w.push('\\');
+        }
+        w.push(c);
+    }
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use crate::{expand::Error, Expression};
+
+    #[test]
+    fn viable_prefixes() {
+        let re = Expression::prefix_regex(
+            "I have {float} cucumber(s) in my belly/stomach {string}",
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        for viable in [
+            "",
+            "I",
+            "I have",
+            "I have ",
+            "I have -",
+            "I have 1.",
+            "I have 1.5e",
+            "I have 1.5e-3 cucumbers in my bel",
+            "I have .5 cucumber in my stom",
+            "I have 1 cucumbers in my belly \"a \\",
+            "I have 1 cucumbers in my belly 'a b'",
+        ] {
+            assert!(re.is_match(viable), "on input: {}", viable);
+        }
+        for not_viable in [
+            "I hav ",
+            "I have a",
+            "I have 1..",
+            "I have 1 cucumberz",
+            "I have 1 cucumbers in my big belly",
+            "I have 1 cucumbers in my bellystomach",
+            "I have 1 cucumbers in my belly x",
+            "I have 1 cucumbers in my belly 'a' ",
+        ] {
+            assert!(!re.is_match(not_viable), "on input: {}", not_viable);
+        }
+    }
+
+    #[test]
+    fn builtin_parameters() {
+        for (expr, viable, not_viable) in [
+            ("{int}", &["", "-", "-1", "42"][..], &["+", "1.", "a"][..]),
+            ("{boolean}", &["", "t", "tru", "false"], &["x", "truex"]),
+            ("{ordinal}", &["", "1", "1s", "22nd"], &["1x", "1stx"]),
+            ("{word}!", &["", "word", "word!"], &[" ", "a b"]),
+            ("{} end", &["", "any thing", "a end"], &["a\nb"]),
+        ] {
+            let re = Expression::prefix_regex(expr)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+            for text in viable {
+                assert!(re.is_match(text), "`{}` on: {}", expr, text);
+            }
+            for text in not_viable {
+                assert!(!re.is_match(text), "`{}` on: {}", expr, text);
+            }
+        }
+    }
+
+    #[test]
+    fn custom_parameters() {
+        let pars = HashMap::from([("color", "red|blue")]);
+        let re =
+            Expression::prefix_regex_with_parameters("the {color} car", &pars)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        assert!(re.is_match("the gr"));
+        assert!(re.is_match("the red ca"));
+        assert!(!re.is_match("a red"));
+
+        assert!(matches!(
+            Expression::prefix_regex("{color}").unwrap_err(),
+            Error::Expansion(_),
+        ));
+    }
+}
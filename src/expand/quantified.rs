@@ -0,0 +1,241 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Parameter`]s repeated several times, enabled with
+//! [`Options::quantified_parameters`].
+//!
+//! [`Parameter`]: crate::Parameter
+
+use std::{borrow::Cow, fmt, str};
+
+use super::{builtin_parameter, parameters::write_non_capturing, Options};
+
+/// [`Parameter`] repeated several times with a separator, parsed from its name
+/// when [`Options::quantified_parameters`] are enabled.
+///
+/// # Grammar
+///
+/// ```ebnf
+/// quantified = name, ( ':', count | '+' ), [ ',', '"', separator, '"' ]
+/// ```
+///
+/// Where `count` is a positive number of repetitions, while `+` means one or
+/// more of them. [`QuantifiedParameter::DEFAULT_SEPARATOR`] is used, if the
+/// `separator` is omitted. The repeated [`Parameter`] should be a built-in
+/// one.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::expand::{QuantifiedParameter, Repetition};
+/// #
+/// let quantified = QuantifiedParameter::parse(r#"int:3,",""#).unwrap();
+///
+/// assert_eq!(quantified.name, "int");
+/// assert_eq!(quantified.repetition, Repetition::Exactly(3));
+/// assert_eq!(quantified.separator, ",");
+/// assert_eq!(
+///     quantified.split("1,2,3").collect::<Vec<_>>(),
+///     ["1", "2", "3"],
+/// );
+///
+/// let quantified = QuantifiedParameter::parse("word+").unwrap();
+///
+/// assert_eq!(quantified.repetition, Repetition::OneOrMore);
+/// assert_eq!(quantified.separator, " ");
+/// ```
+///
+/// [`Parameter`]: crate::Parameter
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct QuantifiedParameter<'n> {
+    /// Name of the repeated [`Parameter`].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub name: &'n str,
+
+    /// [`Repetition`] of the [`Parameter`].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    pub repetition: Repetition,
+
+    /// Text separating the repeated values, with escape sequences resolved.
+    pub separator: Cow<'n, str>,
+}
+
+/// Number of times a [`QuantifiedParameter`] is repeated.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Repetition {
+    /// Exactly the given positive number of times (`{int:3}`).
+    Exactly(usize),
+
+    /// One or more times (`{int+}`).
+    OneOrMore,
+}
+
+impl<'n> QuantifiedParameter<'n> {
+    /// Separator of the repeated values, used if none is specified.
+    pub const DEFAULT_SEPARATOR: &'static str = " ";
+
+    /// Parses the given raw [`Parameter`] `name` (as written between its curly
+    /// braces) as a [`QuantifiedParameter`].
+    ///
+    /// Returns [`None`] if the `name` isn't a quantified one.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[must_use]
+    pub fn parse(name: &'n str) -> Option<Self> {
+        let (quantified, separator) = match name.split_once(",\"") {
+            Some((q, sep)) => {
+                let sep = sep.strip_suffix('"').filter(|s| !s.is_empty())?;
+                (q, Cow::Owned(cooked(sep)))
+            }
+            None => (name, Cow::Borrowed(Self::DEFAULT_SEPARATOR)),
+        };
+
+        let (repeated, repetition) =
+            if let Some(n) = quantified.strip_suffix('+') {
+                (n, Repetition::OneOrMore)
+            } else {
+                let (n, count) = quantified.rsplit_once(':')?;
+                let count = count.parse().ok().filter(|c| {
+                    *c > 0 && count.bytes().all(|b| b.is_ascii_digit())
+                })?;
+                (n, Repetition::Exactly(count))
+            };
+
+        Some(Self {
+            name: repeated,
+            repetition,
+            separator,
+        })
+    }
+
+    /// Splits the text `captured` by this [`QuantifiedParameter`] into the
+    /// repeated values.
+    ///
+    /// Note, that values containing the [`QuantifiedParameter::separator`]
+    /// themselves (like `{string}`s) are split too.
+    #[must_use]
+    pub fn split<'t>(&'t self, captured: &'t str) -> str::Split<'t, &'t str> {
+        captured.split(self.separator.as_ref())
+    }
+
+    /// Returns a [`Regex`] pattern of this [`QuantifiedParameter`], as a single
+    /// capturing group, according to the given [`Options`].
+    ///
+    /// Returns [`None`] if the repeated [`Parameter`] isn't a built-in one.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [`Regex`]: regex::Regex
+    pub(super) fn pattern(&self, options: Options) -> Option<String> {
+        let value = builtin_parameter(&self.name, options)?;
+        let mut re = String::new();
+        self.write_pattern(&mut re, value, options).ok()?;
+        Some(re)
+    }
+
+    /// Writes a [`Regex`] pattern of this [`QuantifiedParameter`] repeating
+    /// the given `value` pattern into the `w`riter.
+    ///
+    /// [`Regex`]: regex::Regex
+    fn write_pattern<W>(
+        &self,
+        w: &mut W,
+        value: &str,
+        options: Options,
+    ) -> fmt::Result
+    where
+        W: fmt::Write + ?Sized,
+    {
+        w.write_char('(')?;
+        write_non_capturing(w, value.chars())?;
+        let count = match self.repetition {
+            Repetition::Exactly(1) => return w.write_char(')'),
+            Repetition::Exactly(count) => Some(count - 1),
+            Repetition::OneOrMore => None,
+        };
+        w.write_str("(?:")?;
+        for c in self.separator.chars() {
+            if options.dialect.escaped_chars().contains(c) {
+                w.write_char('\\')?;
+            }
+            w.write_char(c)?;
+        }
+        write_non_capturing(w, value.chars())?;
+        match count {
+            Some(c) => write!(w, "){{{}}})", c),
+            None => w.write_str(")*)"),
+        }
+    }
+}
+
+/// Resolves escape sequences in the given raw `text`.
+fn cooked(text: &str) -> String {
+    let mut chars = text.chars();
+    let mut cooked = String::with_capacity(text.len());
+    while let Some(c) = chars.next() {
+        cooked.extend(if c == '\\' { chars.next() } else { Some(c) });
+    }
+    cooked
+}
+
+#[cfg(all(test, feature = "into-regex"))]
+mod spec {
+    use super::{Options, QuantifiedParameter, Repetition};
+
+    #[test]
+    fn parses() {
+        for (name, expected) in [
+            ("int:3", Some(("int", Repetition::Exactly(3), " "))),
+            ("word+", Some(("word", Repetition::OneOrMore, " "))),
+            (r#"int:2,", ""#, Some(("int", Repetition::Exactly(2), ", "))),
+            (r#"float+,";""#, Some(("float", Repetition::OneOrMore, ";"))),
+            (r#"+,"\ ""#, Some(("", Repetition::OneOrMore, " "))),
+            ("int", None),
+            ("int:0", None),
+            ("int:+1", None),
+            ("int:x", None),
+            (r#"int+,"""#, None),
+            (r#"int+,"a"#, None),
+        ] {
+            let parsed = QuantifiedParameter::parse(name);
+
+            assert_eq!(
+                parsed
+                    .as_ref()
+                    .map(|q| { (q.name, q.repetition, q.separator.as_ref()) }),
+                expected,
+                "on input: {}",
+                name,
+            );
+        }
+    }
+
+    #[test]
+    fn pattern() {
+        let pattern = |name| {
+            QuantifiedParameter::parse(name)
+                .unwrap()
+                .pattern(Options::default())
+        };
+
+        assert_eq!(
+            pattern(r#"int:3,",""#).unwrap(),
+            r"((?:(?:-?\d+)|(?:\d+))(?:,(?:(?:-?\d+)|(?:\d+))){2})",
+        );
+        assert_eq!(pattern("word+").unwrap(), r"((?:[^\s]+)(?: (?:[^\s]+))*)");
+        assert_eq!(pattern("int:1").unwrap(), r"((?:(?:-?\d+)|(?:\d+)))");
+        assert_eq!(
+            pattern(r#"word+,".""#).unwrap(),
+            r"((?:[^\s]+)(?:\.(?:[^\s]+))*)",
+        );
+        assert_eq!(pattern("color+"), None);
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Analysis of custom [`Parameter`]s' [`Regex`] fragments for [ReDoS] risks.
+//!
+//! [`Regex`] crate itself never backtracks, so it's immune to [ReDoS], while
+//! backtracking engines (like [`Dialect::Pcre`] or [`Dialect::EcmaScript`]
+//! targets, or [`fancy_regex`]) may take exponential time matching fragments
+//! with nested unbounded quantifiers (like `(a+)+`) or overlapping
+//! alternatives repeated without a bound (like `(a|ab)*`).
+//!
+//! The analysis is a heuristic, so it may report fragments being safe in
+//! practice (like `(\d+,)*`), and miss some of the dangerous ones.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::collections::HashMap;
+//! #
+//! # use cucumber_expressions::{expand::redos, Expression};
+//! #
+//! let parameters = HashMap::from([("list", "(\\w+\\s?)*"), ("id", "\\d+")]);
+//! let expr = Expression::parse("{list} by {id}").unwrap();
+//! let warnings = redos::analyze_parameters(&expr, &parameters);
+//!
+//! assert_eq!(warnings.len(), 1);
+//! assert_eq!(*warnings[0].parameter, "list");
+//! assert_eq!(warnings[0].risk.kind, redos::Kind::NestedQuantifiers);
+//! assert_eq!(warnings[0].risk.span, 0..9);
+//! ```
+//!
+//! [`Dialect::EcmaScript`]: super::Dialect::EcmaScript
+//! [`Dialect::Pcre`]: super::Dialect::Pcre
+//! [`Parameter`]: crate::Parameter
+//! [`Regex`]: regex::Regex
+//! [ReDoS]: https://en.wikipedia.org/wiki/ReDoS
+
+use std::{fmt, ops::Range};
+
+use derive_more::{Display, Error};
+use nom::{AsChar, InputIter};
+use regex_syntax::{
+    ast::{self, Ast, RepetitionKind, RepetitionRange},
+    hir::{self, translate::Translator, HirKind},
+};
+
+use crate::{Expression, Parameter};
+
+use super::ParametersProvider;
+
+/// Kind of a [ReDoS] [`Risk`].
+///
+/// [ReDoS]: https://en.wikipedia.org/wiki/ReDoS
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+pub enum Kind {
+    /// Unbounded quantifier applied to an expression containing another
+    /// unbounded quantifier (like `(a+)+` or `(a*b?)*`).
+    #[display(fmt = "nested unbounded quantifiers")]
+    NestedQuantifiers,
+
+    /// Alternation repeated without a bound, whose alternatives may start
+    /// with the same character (like `(a|ab)*` or `(\d|1)+`).
+    #[display(fmt = "overlapping alternatives repeated without a bound")]
+    OverlappingAlternation,
+}
+
+/// [ReDoS] risk found in a [`Regex`] fragment.
+///
+/// [`Regex`]: regex::Regex
+/// [ReDoS]: https://en.wikipedia.org/wiki/ReDoS
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Risk {
+    /// [`Kind`] of this [`Risk`].
+    pub kind: Kind,
+
+    /// Byte range of the risky part in the analyzed [`Regex`] fragment.
+    ///
+    /// [`Regex`]: regex::Regex
+    pub span: Range<usize>,
+}
+
+/// [`Risk`] found in a [`Regex`] fragment of a custom [`Parameter`].
+///
+/// [`Regex`]: regex::Regex
+#[derive(Clone, Debug, Display, Error)]
+#[display(
+    fmt = "Parameter '{}' regex '{}' may backtrack catastrophically: {} at \
+           '{}'",
+    parameter,
+    fragment,
+    "risk.kind",
+    "&fragment[risk.span.clone()]"
+)]
+pub struct Warning<Input>
+where
+    Input: fmt::Display,
+{
+    /// Custom [`Parameter`] provided with a risky [`Regex`] fragment.
+    ///
+    /// [`Regex`]: regex::Regex
+    pub parameter: Input,
+
+    /// Risky [`Regex`] fragment.
+    ///
+    /// [`Regex`]: regex::Regex
+    pub fragment: String,
+
+    /// [`Risk`] found in the [`Warning::fragment`].
+    #[error(not(source))]
+    pub risk: Risk,
+}
+
+/// Analyzes [`Regex`] fragments of all the custom `parameters` used in the
+/// given [`Expression`] for [ReDoS] risks.
+///
+/// Fragments failing to parse are skipped, as they're reported by an
+/// [`InvalidParameterError`] on expansion.
+///
+/// [`InvalidParameterError`]: super::InvalidParameterError
+/// [`Regex`]: regex::Regex
+/// [ReDoS]: https://en.wikipedia.org/wiki/ReDoS
+#[allow(clippy::needless_pass_by_value)] // consistency with other methods
+pub fn analyze_parameters<Input, Parameters>(
+    expr: &Expression<Input>,
+    parameters: Parameters,
+) -> Vec<Warning<Input>>
+where
+    Input: Clone + fmt::Display,
+    Parameters: ParametersProvider<Input>,
+{
+    let mut warnings = Vec::new();
+    for e in &expr.0 {
+        if let Some(Parameter(p)) = e.parameter() {
+            if let Some(v) = parameters.get(p) {
+                let fragment =
+                    v.iter_elements().map(AsChar::as_char).collect::<String>();
+                warnings.extend(analyze(&fragment).into_iter().map(|risk| {
+                    Warning {
+                        parameter: p.clone(),
+                        fragment: fragment.clone(),
+                        risk,
+                    }
+                }));
+            }
+        }
+    }
+    warnings
+}
+
+/// Analyzes the given [`Regex`] `fragment` for [ReDoS] risks.
+///
+/// Returns nothing if the `fragment` fails to parse.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::expand::redos::{analyze, Kind};
+/// #
+/// assert_eq!(analyze("(a|ab)*c")[0].kind, Kind::OverlappingAlternation);
+/// assert_eq!(analyze("(a|ab)*c")[0].span, 1..5);
+/// assert!(analyze("(a|b)*c").is_empty());
+/// assert!(analyze("\\d+").is_empty());
+/// ```
+///
+/// [`Regex`]: regex::Regex
+/// [ReDoS]: https://en.wikipedia.org/wiki/ReDoS
+#[must_use]
+pub fn analyze(fragment: &str) -> Vec<Risk> {
+    let parsed = match ast::parse::Parser::new().parse(fragment) {
+        Ok(parsed) => parsed,
+        Err(_) => return Vec::new(),
+    };
+    let mut analysis = Analysis {
+        fragment,
+        risks: Vec::new(),
+    };
+    analysis.check(&parsed, false);
+    analysis.risks
+}
+
+/// State of analyzing a single [`Regex`] fragment.
+///
+/// [`Regex`]: regex::Regex
+#[derive(Debug)]
+struct Analysis<'f> {
+    /// Analyzed [`Regex`] fragment.
+    ///
+    /// [`Regex`]: regex::Regex
+    fragment: &'f str,
+
+    /// [`Risk`]s found so far.
+    risks: Vec<Risk>,
+}
+
+impl Analysis<'_> {
+    /// Checks the given [`Ast`] for [`Risk`]s, considering whether it's
+    /// repeated without a bound.
+    fn check(&mut self, ast: &Ast, repeated: bool) {
+        match ast {
+            Ast::Repetition(rep) => {
+                let unbounded = is_unbounded(&rep.op.kind);
+                if unbounded && contains_unbounded(&rep.ast) {
+                    self.risks.push(Risk {
+                        kind: Kind::NestedQuantifiers,
+                        span: span(&rep.span),
+                    });
+                }
+                self.check(&rep.ast, repeated || unbounded);
+            }
+            Ast::Group(group) => self.check(&group.ast, repeated),
+            Ast::Concat(concat) => {
+                for a in &concat.asts {
+                    self.check(a, repeated);
+                }
+            }
+            Ast::Alternation(alt) => {
+                if repeated && self.overlaps(&alt.asts) {
+                    self.risks.push(Risk {
+                        kind: Kind::OverlappingAlternation,
+                        span: span(&alt.span),
+                    });
+                }
+                for a in &alt.asts {
+                    self.check(a, repeated);
+                }
+            }
+            Ast::Empty(_)
+            | Ast::Flags(_)
+            | Ast::Literal(_)
+            | Ast::Dot(_)
+            | Ast::Assertion(_)
+            | Ast::Class(_) => {}
+        }
+    }
+
+    /// Checks whether any two of the given alternatives may start with the
+    /// same character.
+    fn overlaps(&self, alternatives: &[Ast]) -> bool {
+        let firsts = alternatives
+            .iter()
+            .map(|a| self.first(a).0)
+            .collect::<Vec<_>>();
+        firsts.iter().enumerate().any(|(i, a)| {
+            firsts[i + 1..].iter().any(|b| {
+                let mut common = a.clone();
+                common.intersect(b);
+                !common.ranges().is_empty()
+            })
+        })
+    }
+
+    /// Returns the set of characters the given [`Ast`] may start with, along
+    /// with whether it may match an empty string.
+    fn first(&self, ast: &Ast) -> (hir::ClassUnicode, bool) {
+        match ast {
+            Ast::Empty(_) | Ast::Flags(_) | Ast::Assertion(_) => {
+                (hir::ClassUnicode::empty(), true)
+            }
+            Ast::Literal(lit) => (class_of(lit.c), false),
+            Ast::Dot(_) | Ast::Class(_) => (self.class(ast), false),
+            Ast::Repetition(rep) => {
+                let (first, empty) = self.first(&rep.ast);
+                (first, empty || min_repetitions(&rep.op.kind) == 0)
+            }
+            Ast::Group(group) => self.first(&group.ast),
+            Ast::Alternation(alt) => alt.asts.iter().fold(
+                (hir::ClassUnicode::empty(), false),
+                |(mut first, empty), a| {
+                    let (f, e) = self.first(a);
+                    first.union(&f);
+                    (first, empty || e)
+                },
+            ),
+            Ast::Concat(concat) => {
+                let mut first = hir::ClassUnicode::empty();
+                for a in &concat.asts {
+                    let (f, empty) = self.first(a);
+                    first.union(&f);
+                    if !empty {
+                        return (first, false);
+                    }
+                }
+                (first, true)
+            }
+        }
+    }
+
+    /// Returns the set of characters matched by the given character class
+    /// [`Ast`].
+    ///
+    /// Returns all the characters, if the [`Ast`] can't be translated.
+    fn class(&self, ast: &Ast) -> hir::ClassUnicode {
+        let translated = Translator::new().translate(self.fragment, ast);
+        match translated.as_ref().map(hir::Hir::kind) {
+            Ok(HirKind::Class(hir::Class::Unicode(class))) => class.clone(),
+            Ok(HirKind::Literal(hir::Literal::Unicode(c))) => class_of(*c),
+            _ => any_char(),
+        }
+    }
+}
+
+/// Checks whether the given [`RepetitionKind`] has no upper bound.
+const fn is_unbounded(kind: &RepetitionKind) -> bool {
+    matches!(
+        kind,
+        RepetitionKind::ZeroOrMore
+            | RepetitionKind::OneOrMore
+            | RepetitionKind::Range(RepetitionRange::AtLeast(_)),
+    )
+}
+
+/// Returns the minimal number of repetitions of the given [`RepetitionKind`].
+const fn min_repetitions(kind: &RepetitionKind) -> u32 {
+    match kind {
+        RepetitionKind::ZeroOrOne | RepetitionKind::ZeroOrMore => 0,
+        RepetitionKind::OneOrMore => 1,
+        RepetitionKind::Range(
+            RepetitionRange::Exactly(min)
+            | RepetitionRange::AtLeast(min)
+            | RepetitionRange::Bounded(min, _),
+        ) => *min,
+    }
+}
+
+/// Checks whether the given [`Ast`] contains a repetition without an upper
+/// bound.
+fn contains_unbounded(ast: &Ast) -> bool {
+    match ast {
+        Ast::Repetition(rep) => {
+            is_unbounded(&rep.op.kind) || contains_unbounded(&rep.ast)
+        }
+        Ast::Group(group) => contains_unbounded(&group.ast),
+        Ast::Concat(concat) => concat.asts.iter().any(contains_unbounded),
+        Ast::Alternation(alt) => alt.asts.iter().any(contains_unbounded),
+        Ast::Empty(_)
+        | Ast::Flags(_)
+        | Ast::Literal(_)
+        | Ast::Dot(_)
+        | Ast::Assertion(_)
+        | Ast::Class(_) => false,
+    }
+}
+
+/// Converts the given [`ast::Span`] into a byte range.
+const fn span(span: &ast::Span) -> Range<usize> {
+    span.start.offset..span.end.offset
+}
+
+/// Returns a set of the single given [`char`].
+fn class_of(c: char) -> hir::ClassUnicode {
+    hir::ClassUnicode::new([hir::ClassUnicodeRange::new(c, c)])
+}
+
+/// Returns a set of all the [`char`]s.
+fn any_char() -> hir::ClassUnicode {
+    hir::ClassUnicode::new([hir::ClassUnicodeRange::new('\0', char::MAX)])
+}
+
+#[cfg(test)]
+mod spec {
+    use std::collections::HashMap;
+
+    use crate::Expression;
+
+    use super::{analyze, analyze_parameters, Kind};
+
+    #[test]
+    fn nested_quantifiers() {
+        for (fragment, span) in [
+            ("(a+)+", 0..5),
+            ("x(a*b?)*", 1..8),
+            ("(?:\\w+\\s?){2,}", 0..14),
+            ("((a+)b)*c", 0..8),
+        ] {
+            let risks = analyze(fragment);
+
+            assert_eq!(risks.len(), 1, "on input: {}", fragment);
+            assert_eq!(risks[0].kind, Kind::NestedQuantifiers);
+            assert_eq!(risks[0].span, span, "on input: {}", fragment);
+        }
+    }
+
+    #[test]
+    fn overlapping_alternation() {
+        for (fragment, span) in [
+            ("(a|ab)*", 1..5),
+            ("(?:\\d|1)+", 3..7),
+            ("(?:x|[a-z])*", 3..10),
+            ("(?:b?a|a)+", 3..8),
+            ("(?:\\w|_)*", 3..7),
+        ] {
+            let risks = analyze(fragment);
+
+            assert_eq!(risks.len(), 1, "on input: {}", fragment);
+            assert_eq!(risks[0].kind, Kind::OverlappingAlternation);
+            assert_eq!(risks[0].span, span, "on input: {}", fragment);
+        }
+    }
+
+    #[test]
+    fn safe() {
+        for fragment in [
+            "\\d+",
+            "(a|b)*",
+            "(?:red|green|blue)",
+            "(a|ab)",
+            "(?:a|ab){2}",
+            "[a-z]+(?:-[a-z]+)?",
+            "(a+",
+        ] {
+            assert!(analyze(fragment).is_empty(), "on input: {}", fragment);
+        }
+    }
+
+    #[test]
+    fn parameters() {
+        let pars = HashMap::from([
+            ("bad", "(a+)+"),
+            ("worse", "(a|a)*b+"),
+            ("good", "a+"),
+        ]);
+        let expr = Expression::parse("{bad} {good} {int} {worse}").unwrap();
+        let warnings = analyze_parameters(&expr, &pars);
+
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|w| (*w.parameter, w.risk.kind))
+                .collect::<Vec<_>>(),
+            [
+                ("bad", Kind::NestedQuantifiers),
+                ("worse", Kind::OverlappingAlternation),
+            ],
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "Parameter 'bad' regex '(a+)+' may backtrack catastrophically: \
+             nested unbounded quantifiers at '(a+)+'",
+        );
+    }
+}
@@ -0,0 +1,3250 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Registry`] of [`ParameterType`]s, mirroring the `ParameterTypeRegistry`
+//! of the [reference implementations][1].
+//!
+//! # Example
+//!
+//! ```rust
+//! # #[cfg(feature = "into-regex")] {
+//! # use cucumber_expressions::{
+//! #     expand::{ParameterType, ParameterTypeRegistry},
+//! #     Expression,
+//! # };
+//! #
+//! let mut registry = ParameterTypeRegistry::new();
+//! registry
+//!     .define(ParameterType::untyped("color", "red|blue"))
+//!     .unwrap();
+//!
+//! assert!(registry.lookup_by_name("int").unwrap().is_builtin());
+//! assert_eq!(
+//!     registry.lookup_by_name("color").unwrap().regexp(),
+//!     "red|blue"
+//! );
+//! assert!(registry
+//!     .define(ParameterType::untyped("color", "green"))
+//!     .is_err());
+//!
+//! let re =
+//!     Expression::regex_with_parameters("{color} {int}", &registry).unwrap();
+//! assert!(re.is_match("red 42"));
+//! # }
+//! ```
+//!
+//! # Global registry
+//!
+//! With `global-registry` Cargo feature enabled, besides the explicitly
+//! constructed [`Registry`]s, there is a process-wide one, shared by all
+//! threads. [`ParameterType`]s are [`register()`]ed into it
+//! once (usually at startup), and a [`snapshot()`] of it is taken whenever a
+//! [`Registry`] is needed.
+//!
+//! ```rust
+//! # #[cfg(feature = "global-registry")] {
+//! # use cucumber_expressions::{
+//! #     expand::{registry, ParameterType},
+//! #     Expression,
+//! # };
+//! #
+//! registry::register(ParameterType::untyped("fruit", "apple|pear")).unwrap();
+//!
+//! let registry = registry::snapshot();
+//! let re = Expression::regex_with_parameters("{fruit}", &registry).unwrap();
+//! assert!(re.is_match("pear"));
+//! # }
+//! ```
+//!
+//! [`register()`]: crate::expand::registry::register
+//! [`snapshot()`]: crate::expand::registry::snapshot
+//! [1]: https://github.com/cucumber/cucumber-expressions#readme
+
+#[cfg(feature = "global-registry")]
+use std::sync::{PoisonError, RwLock};
+use std::{
+    any::{self, Any},
+    borrow::Cow,
+    collections::HashMap,
+    error::Error as StdError,
+    fmt, iter, mem, slice,
+    str::FromStr,
+    sync::Arc,
+};
+
+use derive_more::{AsRef, Deref, Display, Error};
+use nom::{AsChar, InputIter};
+
+#[cfg(feature = "derive")]
+pub use cucumber_expressions_derive::Parameter;
+
+use super::{
+    builtin_parameter_names, builtin_parameter_pattern,
+    parameters::{capture_groups_len, fingerprint_pairs, with_name, CowValue},
+    unquote_string, NumberFormat, Options, ParameterCaptures,
+    ParametersProvider,
+};
+
+/// Type-erased error returned by a [`ParameterType`] transformer.
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// Transformer of capturing groups matched by a [`ParameterType`] (the whole
+/// one first, followed by the nested ones) into a `T` value.
+type Transformer<T> =
+    Arc<dyn Fn(&[Option<&str>]) -> Result<T, BoxError> + Send + Sync>;
+
+/// [`ParameterType`] with its transformer's result being type-erased, as
+/// stored in a [`Registry`].
+pub type AnyParameterType = ParameterType<Box<dyn Any + Send>>;
+
+/// Definition of a [`Parameter`] type, transforming the matched text into a
+/// `T` value.
+///
+/// [`Parameter`]: crate::ast::Parameter
+///
+/// # Example
+///
+/// ```rust
+/// # use std::str::FromStr;
+/// #
+/// # use cucumber_expressions::expand::ParameterType;
+/// #
+/// #[derive(Debug, PartialEq)]
+/// enum Color {
+///     Red,
+///     Blue,
+/// }
+///
+/// impl FromStr for Color {
+///     type Err = String;
+///
+///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+///         match s {
+///             "red" => Ok(Self::Red),
+///             "blue" => Ok(Self::Blue),
+///             _ => Err(format!("unknown color: {}", s)),
+///         }
+///     }
+/// }
+///
+/// let color = ParameterType::new("color", "red|blue", Color::from_str);
+///
+/// assert_eq!(color.transform("blue").unwrap(), Color::Blue);
+/// assert!(color.transform("green").is_err());
+/// assert!(color.type_name().unwrap().ends_with("Color"));
+/// ```
+#[allow(clippy::struct_excessive_bools)] // independent flags
+pub struct ParameterType<T = String> {
+    /// Name of this [`ParameterType`], used in [`Parameter`]s.
+    name: String,
+
+    /// [`Regex`] pattern matching values of this [`ParameterType`].
+    ///
+    /// [`Regex`]: regex::Regex
+    regexp: String,
+
+    /// Name of the type values of this [`ParameterType`] are transformed into,
+    /// if any.
+    type_name: Option<String>,
+
+    /// Human-readable description of this [`ParameterType`], if any.
+    description: Option<String>,
+
+    /// Example texts matched by this [`ParameterType`].
+    examples: Vec<String>,
+
+    /// Indicator whether this [`ParameterType`] is a [built-in][1] one.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    builtin: bool,
+
+    /// Indicator whether this [`ParameterType`] is preferred when several
+    /// [`ParameterType`]s match the same [`Regex`] pattern.
+    ///
+    /// [`Regex`]: regex::Regex
+    preferential: bool,
+
+    /// Indicator whether this [`ParameterType`] should be used in generated
+    /// step definition snippets.
+    use_for_snippets: bool,
+
+    /// Indicator whether this [`ParameterType`] matches its values
+    /// case-insensitively.
+    case_insensitive: bool,
+
+    /// [`Transformer`] of the matched texts into `T` values.
+    transformer: Transformer<T>,
+}
+
+impl ParameterType {
+    /// Creates a new custom [`ParameterType`] with the given `name`, matching
+    /// the given `regexp` (without a capturing group around it), and
+    /// transforming nothing, so its values are the matched texts as is.
+    #[must_use]
+    pub fn untyped(name: impl Into<String>, regexp: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            regexp: regexp.into(),
+            type_name: None,
+            description: None,
+            examples: Vec::new(),
+            builtin: false,
+            preferential: false,
+            use_for_snippets: true,
+            case_insensitive: false,
+            transformer: Arc::new(|groups| Ok(whole(groups).to_owned())),
+        }
+    }
+}
+
+impl<T> ParameterType<T> {
+    /// Creates a new custom [`ParameterType`] with the given `name`, matching
+    /// the given `regexp` (without a capturing group around it), and
+    /// transforming the matched texts with the given `transformer`.
+    ///
+    /// The [type name][1] defaults to the name of `T`.
+    ///
+    /// [1]: ParameterType::type_name()
+    #[must_use]
+    pub fn new<F, E>(
+        name: impl Into<String>,
+        regexp: impl Into<String>,
+        transformer: F,
+    ) -> Self
+    where
+        F: Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+        E: Into<BoxError>,
+    {
+        Self {
+            name: name.into(),
+            regexp: regexp.into(),
+            type_name: Some(any::type_name::<T>().to_owned()),
+            description: None,
+            examples: Vec::new(),
+            builtin: false,
+            preferential: false,
+            use_for_snippets: true,
+            case_insensitive: false,
+            transformer: Arc::new(move |groups| {
+                transformer(whole(groups)).map_err(Into::into)
+            }),
+        }
+    }
+
+    /// Creates a new custom [`ParameterType`] with the given `name`, matching
+    /// the given `regexp` (without a capturing group around it), and
+    /// transforming the values of its nested capturing groups with the given
+    /// `transformer`, like a money type matching its amount and currency
+    /// separately.
+    ///
+    /// Groups not participating in a match are passed as [`None`]. If the
+    /// `regexp` has no nested capturing groups, or only the whole matched
+    /// text is [transformed][1], it's passed alone.
+    ///
+    /// The [type name][2] defaults to the name of `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::expand::{
+    /// #     registry::BoxError, ExpressionCompiler, ParameterType,
+    /// #     ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let pattern = r"(\d+) (EUR|USD)";
+    /// let money =
+    ///     ParameterType::grouped("money", pattern, |groups| match groups {
+    ///         [Some(amount), Some(currency)] => {
+    ///             Ok((amount.parse::<u32>()?, (*currency).to_owned()))
+    ///         }
+    ///         _ => Err(BoxError::from("expected amount and currency")),
+    ///     });
+    /// assert_eq!(money.group_count(), 2);
+    ///
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry.define(money.clone()).unwrap();
+    /// let compiled = ExpressionCompiler::new()
+    ///     .parameters(&registry)
+    ///     .compile("I pay {money}")
+    ///     .unwrap();
+    ///
+    /// let captures = compiled.captures("I pay 42 EUR").unwrap();
+    /// assert_eq!(
+    ///     captures[0].groups,
+    ///     [Some("42 EUR"), Some("42"), Some("EUR")],
+    /// );
+    /// assert_eq!(
+    ///     money.transform_groups(&captures[0].groups).unwrap(),
+    ///     (42, "EUR".to_owned()),
+    /// );
+    /// # }
+    /// ```
+    ///
+    /// [1]: ParameterType::transform()
+    /// [2]: ParameterType::type_name()
+    #[must_use]
+    pub fn grouped<F, E>(
+        name: impl Into<String>,
+        regexp: impl Into<String>,
+        transformer: F,
+    ) -> Self
+    where
+        F: Fn(&[Option<&str>]) -> Result<T, E> + Send + Sync + 'static,
+        E: Into<BoxError>,
+    {
+        Self {
+            name: name.into(),
+            regexp: regexp.into(),
+            type_name: Some(any::type_name::<T>().to_owned()),
+            description: None,
+            examples: Vec::new(),
+            builtin: false,
+            preferential: false,
+            use_for_snippets: true,
+            case_insensitive: false,
+            transformer: Arc::new(move |groups| {
+                let nested = groups.get(1..).filter(|g| !g.is_empty());
+                transformer(nested.unwrap_or(groups)).map_err(Into::into)
+            }),
+        }
+    }
+
+    /// Creates a new custom [`ParameterType`] with the given `name`, matching
+    /// the given `regexp` (without a capturing group around it), and
+    /// transforming the matched texts via [`FromStr`].
+    #[must_use]
+    pub fn from_str(name: impl Into<String>, regexp: impl Into<String>) -> Self
+    where
+        T: FromStr + 'static,
+        T::Err: Into<BoxError>,
+    {
+        Self::new(name, regexp, T::from_str)
+    }
+
+    /// Sets the name of the type values of this [`ParameterType`] are
+    /// transformed into.
+    #[must_use]
+    pub fn with_type_name(mut self, type_name: impl Into<String>) -> Self {
+        self.type_name = Some(type_name.into());
+        self
+    }
+
+    /// Sets the human-readable description of this [`ParameterType`], used in
+    /// its [documentation][1].
+    ///
+    /// [1]: Registry::document()
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds the given example text matched by this [`ParameterType`], used in
+    /// its [documentation][1].
+    ///
+    /// [1]: Registry::document()
+    #[must_use]
+    pub fn with_example(mut self, example: impl Into<String>) -> Self {
+        self.examples.push(example.into());
+        self
+    }
+
+    /// Sets whether this [`ParameterType`] is preferred when several
+    /// [`ParameterType`]s match the same [`Regex`] pattern.
+    ///
+    /// Not preferential by default.
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub const fn preferential(mut self, preferential: bool) -> Self {
+        self.preferential = preferential;
+        self
+    }
+
+    /// Sets whether this [`ParameterType`] should be used in generated step
+    /// definition snippets.
+    ///
+    /// Used for snippets by default.
+    #[must_use]
+    pub const fn use_for_snippets(mut self, use_for_snippets: bool) -> Self {
+        self.use_for_snippets = use_for_snippets;
+        self
+    }
+
+    /// Sets whether this [`ParameterType`] matches its values
+    /// case-insensitively, by wrapping its [`Regex`] pattern into a `(?i:…)`
+    /// group, without making the whole expression case-insensitive.
+    ///
+    /// Case-sensitive by default.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::{
+    /// #     expand::{ParameterType, ParameterTypeRegistry},
+    /// #     Expression,
+    /// # };
+    /// #
+    /// let color = ParameterType::untyped("color", "red|blue");
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry.define(color.case_insensitive(true)).unwrap();
+    ///
+    /// let re = Expression::regex_with_parameters("the {color} car", &registry)
+    ///     .unwrap();
+    /// assert_eq!(re.as_str(), "^the ((?i:red|blue)) car$");
+    /// assert!(re.is_match("the Red car"));
+    /// assert!(!re.is_match("the red Car"));
+    /// # }
+    /// ```
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub const fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Returns the name of this [`ParameterType`].
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the [`Regex`] pattern matching values of this
+    /// [`ParameterType`].
+    ///
+    /// For [built-in][1] [`ParameterType`]s, this is the pattern returned by
+    /// [`builtin_parameter_pattern()`] with the default [`Options`], without
+    /// its capturing group, if the one spans the whole pattern.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub fn regexp(&self) -> &str {
+        &self.regexp
+    }
+
+    /// Returns the number of capturing groups nested into the [`Regex`]
+    /// pattern of this [`ParameterType`].
+    ///
+    /// Being expanded, this [`ParameterType`] owns `1 + group_count()`
+    /// capturing groups: the one around its pattern, and the nested ones.
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub fn group_count(&self) -> usize {
+        capture_groups_len(self.regexp.chars())
+    }
+
+    /// Returns the name of the type values of this [`ParameterType`] are
+    /// transformed into, if any.
+    #[must_use]
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// Returns the human-readable description of this [`ParameterType`], if
+    /// any.
+    #[must_use]
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the example texts matched by this [`ParameterType`].
+    #[must_use]
+    pub fn examples(&self) -> &[String] {
+        &self.examples
+    }
+
+    /// Indicates whether this [`ParameterType`] is a [built-in][1] one.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub const fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+
+    /// Indicates whether this [`ParameterType`] is preferred when several
+    /// [`ParameterType`]s match the same [`Regex`] pattern.
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub const fn is_preferential(&self) -> bool {
+        self.preferential
+    }
+
+    /// Indicates whether this [`ParameterType`] should be used in generated
+    /// step definition snippets.
+    #[must_use]
+    pub const fn is_used_for_snippets(&self) -> bool {
+        self.use_for_snippets
+    }
+
+    /// Indicates whether this [`ParameterType`] matches its values
+    /// [case-insensitively][1].
+    ///
+    /// [1]: ParameterType::case_insensitive()
+    #[must_use]
+    pub const fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// Returns the [`Regex`] pattern this [`ParameterType`] is expanded into:
+    /// its [`regexp()`][1], wrapped into a `(?i:…)` group, if it's
+    /// [case-insensitive][2].
+    ///
+    /// Inline flags aren't supported by [`Dialect::EcmaScript`], so the
+    /// patterns of [case-insensitive][2] [`ParameterType`]s shouldn't be used
+    /// with it.
+    ///
+    /// [`Dialect::EcmaScript`]: super::Dialect::EcmaScript
+    /// [`Regex`]: regex::Regex
+    /// [1]: ParameterType::regexp()
+    /// [2]: ParameterType::case_insensitive()
+    #[must_use]
+    pub fn pattern(&self) -> Cow<'_, str> {
+        if self.case_insensitive {
+            Cow::Owned(format!("(?i:{})", self.regexp))
+        } else {
+            Cow::Borrowed(&self.regexp)
+        }
+    }
+
+    /// Transforms the given text, matched by this [`ParameterType`], into a
+    /// `T` value.
+    ///
+    /// # Errors
+    ///
+    /// If the transformer of this [`ParameterType`] fails.
+    pub fn transform(&self, matched: &str) -> Result<T, TransformError> {
+        self.transform_groups(&[Some(matched)])
+    }
+
+    /// Transforms the given capturing groups, matched by this
+    /// [`ParameterType`] (the whole one first, followed by the nested ones, as
+    /// in [`ParameterCaptures::groups`]), into a `T` value.
+    ///
+    /// # Errors
+    ///
+    /// If the transformer of this [`ParameterType`] fails.
+    pub fn transform_groups(
+        &self,
+        groups: &[Option<&str>],
+    ) -> Result<T, TransformError> {
+        (self.transformer)(groups).map_err(|e| TransformError {
+            parameter: self.name.clone(),
+            matched: whole(groups).to_owned(),
+            reason: e.into(),
+        })
+    }
+
+    /// Erases the type of this [`ParameterType`]'s transformer result, so it
+    /// may be stored in a [`Registry`] along with other [`ParameterType`]s.
+    #[must_use]
+    pub fn erase(self) -> AnyParameterType
+    where
+        T: Send + 'static,
+    {
+        let transformer = self.transformer;
+        ParameterType {
+            name: self.name,
+            regexp: self.regexp,
+            type_name: self.type_name,
+            description: self.description,
+            examples: self.examples,
+            builtin: self.builtin,
+            preferential: self.preferential,
+            use_for_snippets: self.use_for_snippets,
+            case_insensitive: self.case_insensitive,
+            transformer: Arc::new(move |groups| {
+                transformer(groups)
+                    .map(|v| -> Box<dyn Any + Send> { Box::new(v) })
+            }),
+        }
+    }
+
+    /// Indicates whether this [`ParameterType`] is provided by a [`Registry`]
+    /// as a [`ParametersProvider`], being either a custom one, or a
+    /// [built-in][1] one with a [`Regex`] pattern different from the one of
+    /// the default [`Options`].
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    fn is_provided(&self) -> bool {
+        !self.builtin
+            || self.case_insensitive
+            || builtin_parameter_pattern(&self.name, Options::default())
+                .map(strip_group)
+                != Some(self.regexp.as_str())
+    }
+}
+
+impl<T> Clone for ParameterType<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            regexp: self.regexp.clone(),
+            type_name: self.type_name.clone(),
+            description: self.description.clone(),
+            examples: self.examples.clone(),
+            builtin: self.builtin,
+            preferential: self.preferential,
+            use_for_snippets: self.use_for_snippets,
+            case_insensitive: self.case_insensitive,
+            transformer: Arc::clone(&self.transformer),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ParameterType<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParameterType")
+            .field("name", &self.name)
+            .field("regexp", &self.regexp)
+            .field("type_name", &self.type_name)
+            .field("description", &self.description)
+            .field("examples", &self.examples)
+            .field("builtin", &self.builtin)
+            .field("preferential", &self.preferential)
+            .field("use_for_snippets", &self.use_for_snippets)
+            .field("case_insensitive", &self.case_insensitive)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Plain definition of an untyped [`ParameterType`], deserializable from
+/// configuration files with `serde` feature enabled.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "serde")]
+/// # {
+/// # use cucumber_expressions::expand::{
+/// #     registry::Definition, ParameterTypeRegistry,
+/// # };
+/// #
+/// let registry: ParameterTypeRegistry = serde_json::from_str(
+///     r#"[
+///         {"name": "color", "regexp": "red|blue", "type_name": "Color"},
+///         {"name": "size", "regexp": "\\d+", "use_for_snippets": false}
+///     ]"#,
+/// )
+/// .unwrap();
+///
+/// let color = registry.lookup_by_name("color").unwrap();
+/// assert_eq!(color.regexp(), "red|blue");
+/// assert_eq!(color.type_name(), Some("Color"));
+/// assert!(!registry
+///     .lookup_by_name("size")
+///     .unwrap()
+///     .is_used_for_snippets());
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct Definition {
+    /// [Name][1] of the defined [`ParameterType`].
+    ///
+    /// [1]: ParameterType::name()
+    pub name: String,
+
+    /// [`Regex`] pattern of the defined [`ParameterType`].
+    ///
+    /// [`Regex`]: regex::Regex
+    #[cfg_attr(feature = "serde", serde(alias = "regex"))]
+    pub regexp: String,
+
+    /// [Type name][1] of the defined [`ParameterType`], if any.
+    ///
+    /// [1]: ParameterType::type_name()
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub type_name: Option<String>,
+
+    /// [Description][1] of the defined [`ParameterType`], if any.
+    ///
+    /// [1]: ParameterType::description()
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: Option<String>,
+
+    /// [Examples][1] of the defined [`ParameterType`].
+    ///
+    /// [1]: ParameterType::examples()
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub examples: Vec<String>,
+
+    /// Indicator whether the defined [`ParameterType`] is
+    /// [preferential][1].
+    ///
+    /// [1]: ParameterType::preferential()
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub preferential: bool,
+
+    /// Indicator whether the defined [`ParameterType`] should be
+    /// [used for snippets][1].
+    ///
+    /// [1]: ParameterType::use_for_snippets()
+    #[cfg_attr(feature = "serde", serde(default = "default_use_for_snippets"))]
+    pub use_for_snippets: bool,
+
+    /// Indicator whether the defined [`ParameterType`] matches its values
+    /// [case-insensitively][1].
+    ///
+    /// [1]: ParameterType::case_insensitive()
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub case_insensitive: bool,
+}
+
+/// Default value of a [`Definition::use_for_snippets`] flag.
+#[cfg(feature = "serde")]
+const fn default_use_for_snippets() -> bool {
+    true
+}
+
+impl From<Definition> for ParameterType {
+    fn from(def: Definition) -> Self {
+        let mut ty = Self::untyped(def.name, def.regexp)
+            .preferential(def.preferential)
+            .use_for_snippets(def.use_for_snippets)
+            .case_insensitive(def.case_insensitive);
+        ty.type_name = def.type_name;
+        ty.description = def.description;
+        ty.examples = def.examples;
+        ty
+    }
+}
+
+/// Creates an [`AnyParameterType`] of the [built-in][1] [`Parameter`] with the
+/// given `name`, if any, transforming its matched texts the same way the
+/// [reference implementations][2] do.
+///
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+/// [2]: https://github.com/cucumber/cucumber-expressions#readme
+fn builtin(
+    name: &str,
+    number_format: NumberFormat,
+) -> Option<AnyParameterType> {
+    /// Creates a [`ParameterType`] transforming its matched texts via
+    /// [`FromStr`].
+    fn parsed<T>(name: &str, regexp: &str) -> AnyParameterType
+    where
+        T: FromStr + Send + 'static,
+        T::Err: Into<BoxError>,
+    {
+        ParameterType::<T>::from_str(name, regexp).erase()
+    }
+
+    /// Creates a [`ParameterType`] transforming its matched numbers of the
+    /// given [`NumberFormat`] via [`FromStr`].
+    fn number<T>(
+        name: &str,
+        regexp: &str,
+        format: NumberFormat,
+    ) -> AnyParameterType
+    where
+        T: FromStr + Send + 'static,
+        T::Err: Into<BoxError>,
+    {
+        ParameterType::new(name, regexp, move |s| {
+            format.normalize(s).parse::<T>()
+        })
+        .erase()
+    }
+
+    let options = Options {
+        number_format,
+        ..Options::default()
+    };
+    let re = strip_group(builtin_parameter_pattern(name, options)?);
+    let mut ty = match name {
+        "byte" => number::<i8>(name, re, number_format),
+        "short" => number::<i16>(name, re, number_format),
+        "int" => number::<i32>(name, re, number_format),
+        "long" => number::<i64>(name, re, number_format),
+        "float" => number::<f32>(name, re, number_format),
+        "double" => number::<f64>(name, re, number_format),
+        "boolean" => parsed::<bool>(name, re),
+        "ordinal" => parsed::<u64>(name, re),
+        "string" => ParameterType::new(name, re, |s| {
+            unquote_string(s)
+                .map(Cow::into_owned)
+                .ok_or("not a quoted string")
+        })
+        .erase(),
+        _ => parsed::<String>(name, re),
+    };
+    let (description, examples) = builtin_docs(name, number_format);
+    ty.description = Some(description.to_owned());
+    ty.examples = examples.iter().map(|&e| e.to_owned()).collect();
+    ty.builtin = true;
+    ty.preferential = matches!(name, "int" | "float");
+    ty.use_for_snippets = matches!(name, "int" | "float" | "string");
+    Some(ty)
+}
+
+/// Returns the description and examples of the [built-in][1] [`Parameter`]
+/// with the given `name`, matching numbers of the given [`NumberFormat`].
+///
+/// [`Parameter`]: crate::ast::Parameter
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+fn builtin_docs(
+    name: &str,
+    number_format: NumberFormat,
+) -> (&'static str, &'static [&'static str]) {
+    let decimal_comma = matches!(
+        number_format,
+        NumberFormat::DotGroupingDecimalComma
+            | NumberFormat::SpaceGroupingDecimalComma,
+    );
+    let decimals: &[_] = if decimal_comma {
+        &["3,6", "-9,2"]
+    } else {
+        &["3.6", "-9.2"]
+    };
+    match name {
+        "int" => ("Matches integers.", &["71", "-19"]),
+        "byte" => ("Matches 8-bit integers.", &["7", "-19"]),
+        "short" => ("Matches 16-bit integers.", &["71", "-19"]),
+        "long" => ("Matches 64-bit integers.", &["71", "-19"]),
+        "biginteger" => ("Matches arbitrary-precision integers.", &["71"]),
+        "float" => ("Matches 32-bit floating point numbers.", decimals),
+        "double" => ("Matches 64-bit floating point numbers.", decimals),
+        "bigdecimal" => ("Matches arbitrary-precision decimals.", decimals),
+        "boolean" => ("Matches `true` or `false`.", &["true", "false"]),
+        "ordinal" => ("Matches ordinal numbers.", &["1st", "22nd", "3rd"]),
+        "word" => ("Matches words without whitespace.", &["banana"]),
+        "string" => (
+            "Matches single- or double-quoted strings, transformed \
+             without the quotes.",
+            &["\"banana split\"", "'blue'"],
+        ),
+        "uuid" => ("Matches UUIDs.", &["67e55044-10b1-426f-9247-bb680e5fe0c8"]),
+        "date" => ("Matches ISO 8601 dates.", &["2021-12-31"]),
+        "datetime" => (
+            "Matches ISO 8601 date-times.",
+            &["2021-12-31T23:59:59Z", "2021-12-31 23:59"],
+        ),
+        "email" => ("Matches email addresses.", &["user@example.com"]),
+        "url" => ("Matches URLs.", &["https://example.com/path"]),
+        _ => ("", &[]),
+    }
+}
+
+/// Returns the whole matched text from the given capturing groups of a
+/// [`ParameterType`].
+fn whole<'t>(groups: &[Option<&'t str>]) -> &'t str {
+    groups.first().copied().flatten().unwrap_or_default()
+}
+
+/// Strips the capturing group spanning the whole given `regexp`, if any.
+fn strip_group(regexp: &str) -> &str {
+    let inner =
+        match regexp.strip_prefix('(').and_then(|re| re.strip_suffix(')')) {
+            Some(inner) if !inner.starts_with('?') => inner,
+            _ => return regexp,
+        };
+
+    let (mut depth, mut escaped, mut class) = (0_usize, false, false);
+    for c in inner.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => class = true,
+            ']' => class = false,
+            _ if class => {}
+            '(' => depth += 1,
+            ')' if depth == 0 => return regexp,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    inner
+}
+
+/// Type matched and transformed by its own [`ParameterType`].
+///
+/// Usually implemented for enums via `#[derive(Parameter)]` macro, enabled by
+/// `derive` Cargo feature.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "derive")] {
+/// # use cucumber_expressions::{
+/// #     expand::{registry::Parameter, ParameterTypeRegistry, TypedParameter},
+/// #     Expression,
+/// # };
+/// #
+/// #[derive(Debug, Parameter, PartialEq)]
+/// #[param(name = "color")]
+/// enum Color {
+///     Red,
+///     DarkBlue,
+///     #[param(value = "green")]
+///     Lime,
+/// }
+///
+/// assert_eq!(Color::REGEXP, "dark blue|green|red");
+/// assert_eq!("dark blue".parse(), Ok(Color::DarkBlue));
+///
+/// let mut registry = ParameterTypeRegistry::new();
+/// registry.define(Color::parameter_type()).unwrap();
+///
+/// let re = Expression::regex_with_parameters("{color} car", &registry)
+///     .unwrap();
+/// assert!(re.is_match("green car"));
+/// # }
+/// ```
+pub trait TypedParameter: FromStr + Send + 'static
+where
+    Self::Err: Into<BoxError>,
+{
+    /// Name of the [`ParameterType`].
+    const NAME: &'static str;
+
+    /// [`Regex`] pattern of the [`ParameterType`].
+    ///
+    /// [`Regex`]: regex::Regex
+    const REGEXP: &'static str;
+
+    /// Returns the [`ParameterType`] transforming its matched texts into this
+    /// type via [`FromStr`].
+    #[must_use]
+    fn parameter_type() -> ParameterType<Self> {
+        ParameterType::from_str(Self::NAME, Self::REGEXP)
+    }
+}
+
+/// Type-erased value [transformed][1] by a [`ParameterType`], along with its
+/// declared [type name][2].
+///
+/// [1]: Registry::transform()
+/// [2]: ParameterType::type_name()
+pub struct AnyValue {
+    /// Transformed value itself.
+    value: Box<dyn Any + Send>,
+
+    /// [Type name][1] declared by the [`ParameterType`] transformed the value.
+    ///
+    /// [1]: ParameterType::type_name()
+    type_name: Option<String>,
+}
+
+impl AnyValue {
+    /// Returns the [type name][1] declared by the [`ParameterType`]
+    /// transformed this [`AnyValue`], if any.
+    ///
+    /// [1]: ParameterType::type_name()
+    #[must_use]
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// Indicates whether this [`AnyValue`] is of type `T`.
+    #[must_use]
+    pub fn is<T: Any>(&self) -> bool {
+        self.value.is::<T>()
+    }
+
+    /// Returns a reference to this [`AnyValue`], if it's of type `T`.
+    #[must_use]
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.downcast_ref()
+    }
+
+    /// Returns a mutable reference to this [`AnyValue`], if it's of type `T`.
+    #[must_use]
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.value.downcast_mut()
+    }
+
+    /// Converts this [`AnyValue`] into a `T` value.
+    ///
+    /// # Errors
+    ///
+    /// If this [`AnyValue`] isn't of type `T`, returning it back.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self.value.downcast() {
+            Ok(v) => Ok(*v),
+            Err(value) => Err(Self {
+                value,
+                type_name: self.type_name,
+            }),
+        }
+    }
+
+    /// Returns the type-erased value of this [`AnyValue`].
+    #[must_use]
+    pub fn into_inner(self) -> Box<dyn Any + Send> {
+        self.value
+    }
+}
+
+impl fmt::Debug for AnyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyValue")
+            .field("type_name", &self.type_name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Error of transforming a text matched by a [`ParameterType`].
+#[derive(Clone, Debug, Display, Error)]
+#[display(
+    fmt = "Parameter '{}' failed to transform '{}': {}",
+    parameter,
+    matched,
+    reason
+)]
+pub struct TransformError {
+    /// Name of the [`ParameterType`] failed to transform.
+    pub parameter: String,
+
+    /// Text matched by the [`ParameterType`].
+    pub matched: String,
+
+    /// Error returned by the [`ParameterType`] transformer.
+    #[error(not(source))]
+    pub reason: Arc<dyn StdError + Send + Sync>,
+}
+
+/// Error of parsing a text not matching any variant of a [`TypedParameter`]
+/// enum.
+#[derive(Clone, Debug, Display, Eq, Error, PartialEq)]
+#[display(fmt = "Unknown '{}' parameter value: '{}'", parameter, value)]
+pub struct UnknownVariantError {
+    /// Name of the [`ParameterType`] of the enum.
+    pub parameter: &'static str,
+
+    /// Text not matching any variant.
+    #[error(not(source))]
+    pub value: String,
+}
+
+/// Registry of [`ParameterType`]s, with the [built-in][1] ones pre-registered.
+///
+/// Used as a [`ParametersProvider`], provides its custom [`ParameterType`]s
+/// and the [built-in][1] ones [localized][2] differently from the default
+/// [`Options`] only, so the rest of the [built-in][1] ones are still expanded
+/// according to the [`Options`].
+///
+/// [2]: Registry::with_locale()
+/// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+#[derive(Clone, Debug)]
+pub struct Registry {
+    /// Registered [`ParameterType`]s in their definition order.
+    types: Vec<AnyParameterType>,
+
+    /// Indices of the registered [`ParameterType`]s by their names.
+    by_name: HashMap<String, usize>,
+
+    /// Indices of the registered [`ParameterType`]s by their [`Regex`]
+    /// patterns, pointing to the preferential one if there are several.
+    ///
+    /// [`Regex`]: regex::Regex
+    by_regexp: HashMap<String, usize>,
+
+    /// Locale this [`Registry`] is [constructed with][1], if any.
+    ///
+    /// [1]: Registry::with_locale()
+    locale: Option<String>,
+
+    /// [`NumberFormat`] of the [built-in][1] numeric [`ParameterType`]s.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    number_format: NumberFormat,
+
+    /// Parent [`Registry`] this one is [scoped][1] in, if any.
+    ///
+    /// [1]: Registry::scoped()
+    parent: Option<Arc<Self>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::localized(None, NumberFormat::Plain)
+    }
+}
+
+impl Registry {
+    /// Creates a new [`Registry`] with the [built-in][1] [`ParameterType`]s
+    /// pre-registered.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new [`Registry`] with the [built-in][1] [`ParameterType`]s
+    /// pre-registered according to the given [BCP 47] `locale` (like `en-US`),
+    /// mirroring `ParameterTypeRegistry(Locale)` of [`cucumber-jvm`][2].
+    ///
+    /// Numeric [built-in][1] [`ParameterType`]s match and transform numbers of
+    /// the locale's [`NumberFormat`] (see [`NumberFormat::from_locale()`]),
+    /// falling back to the [`NumberFormat::Plain`] one for unknown locales.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::{
+    /// #     expand::{NumberFormat, ParameterTypeRegistry},
+    /// #     Expression,
+    /// # };
+    /// #
+    /// let registry = ParameterTypeRegistry::with_locale("de-DE");
+    /// assert_eq!(
+    ///     registry.number_format(),
+    ///     NumberFormat::DotGroupingDecimalComma,
+    /// );
+    ///
+    /// let re = Expression::regex_with_parameters("I weigh {float}", &registry)
+    ///     .unwrap();
+    /// assert!(re.is_match("I weigh 1.000,5"));
+    ///
+    /// let float = registry.lookup_by_name("float").unwrap();
+    /// let value = float.transform("1.000,5").unwrap();
+    /// assert_eq!(value.downcast_ref::<f32>(), Some(&1000.5));
+    /// # }
+    /// ```
+    ///
+    /// [BCP 47]: https://www.rfc-editor.org/info/bcp47
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: https://github.com/cucumber/cucumber-jvm
+    #[must_use]
+    pub fn with_locale(locale: &str) -> Self {
+        Self::localized(
+            Some(locale.to_owned()),
+            NumberFormat::from_locale(locale).unwrap_or(NumberFormat::Plain),
+        )
+    }
+
+    /// Creates a new empty [`Registry`] scoped in the given `parent` one, so
+    /// all the [`ParameterType`]s of the `parent` are available in it, unless
+    /// overridden by the ones with the same name defined in this [`Registry`].
+    ///
+    /// Allows layering [`Registry`]s (like global → crate → module), adding or
+    /// overriding [`ParameterType`]s locally without mutating the shared
+    /// `parent`. The locale of the `parent` is inherited.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use std::sync::Arc;
+    /// #
+    /// # use cucumber_expressions::{
+    /// #     expand::{ParameterType, ParameterTypeRegistry},
+    /// #     Expression,
+    /// # };
+    /// #
+    /// let mut global = ParameterTypeRegistry::new();
+    /// global
+    ///     .define(ParameterType::untyped("color", "red|blue"))
+    ///     .unwrap();
+    /// let global = Arc::new(global);
+    ///
+    /// let mut local = ParameterTypeRegistry::scoped(Arc::clone(&global));
+    /// local
+    ///     .define(ParameterType::untyped("color", "green"))
+    ///     .unwrap();
+    /// local
+    ///     .define(ParameterType::untyped("size", "S|M|L"))
+    ///     .unwrap();
+    ///
+    /// assert_eq!(local.lookup_by_name("color").unwrap().regexp(), "green");
+    /// let color = global.lookup_by_name("color").unwrap();
+    /// assert_eq!(color.regexp(), "red|blue");
+    /// assert!(local.lookup_by_name("int").unwrap().is_builtin());
+    /// assert!(global.lookup_by_name("size").is_none());
+    ///
+    /// let re = Expression::regex_with_parameters("{color} hat {size}", &local)
+    ///     .unwrap();
+    /// assert!(re.is_match("green hat M"));
+    /// assert!(!re.is_match("red hat M"));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn scoped(parent: Arc<Self>) -> Self {
+        Self {
+            types: Vec::new(),
+            by_name: HashMap::new(),
+            by_regexp: HashMap::new(),
+            locale: parent.locale.clone(),
+            number_format: parent.number_format,
+            parent: Some(parent),
+        }
+    }
+
+    /// Returns the parent [`Registry`] this one is [scoped][1] in, if any.
+    ///
+    /// [1]: Registry::scoped()
+    #[must_use]
+    pub const fn parent(&self) -> Option<&Arc<Self>> {
+        self.parent.as_ref()
+    }
+
+    /// Returns the locale this [`Registry`] is [constructed with][1], if any.
+    ///
+    /// [1]: Registry::with_locale()
+    #[must_use]
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Returns the [`NumberFormat`] of the [built-in][1] numeric
+    /// [`ParameterType`]s of this [`Registry`].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub const fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    /// Defines the given [`ParameterType`] in this [`Registry`].
+    ///
+    /// Several [`ParameterType`]s may have the same [`Regex`] pattern only if
+    /// exactly one of them is [preferential][1].
+    ///
+    /// Conflicts are checked within this [`Registry`] only, so the
+    /// [`ParameterType`]s of its [parent][2] may be overridden.
+    ///
+    /// # Errors
+    ///
+    /// If the name of the [`ParameterType`] can't be referenced from an
+    /// expression, a [`ParameterType`] with the same name is defined already,
+    /// or the defined [`ParameterType`] conflicts with the ones having the
+    /// same [`Regex`] pattern.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: ParameterType::preferential()
+    /// [2]: Registry::scoped()
+    pub fn define<T: Send + 'static>(
+        &mut self,
+        parameter_type: ParameterType<T>,
+    ) -> Result<(), DefineError> {
+        self.define_erased(parameter_type.erase())
+    }
+
+    /// [Defines][1] all the custom [`Parameter`]s of the given `pack` (like
+    /// the ones of [`packs`][2]) in this [`Registry`] as
+    /// [untyped][3] [`ParameterType`]s.
+    ///
+    /// Either all the [`Parameter`]s of the `pack` are defined, or none.
+    ///
+    /// # Errors
+    ///
+    /// If any of the [`Parameter`]s fails to be [defined][1].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [1]: Registry::define()
+    /// [2]: super::packs
+    /// [3]: ParameterType::untyped()
+    pub fn define_pack(
+        &mut self,
+        pack: &[(&str, &str)],
+    ) -> Result<(), DefineError> {
+        let mut defined = self.clone();
+        for (name, regexp) in pack {
+            defined.define(ParameterType::untyped(*name, *regexp))?;
+        }
+        *self = defined;
+        Ok(())
+    }
+
+    /// Merges all the custom [`ParameterType`]s of the `other` [`Registry`]
+    /// (including the ones of its [parent][1]) into this one, resolving the
+    /// ones with the same name according to the given [`ConflictStrategy`].
+    ///
+    /// Either all the [`ParameterType`]s of the `other` [`Registry`] are
+    /// merged, or none. Like in [`Registry::define()`], conflicts are checked
+    /// within this [`Registry`] only, and the [`Regex`] pattern conflicts are
+    /// never resolved.
+    ///
+    /// # Errors
+    ///
+    /// If any of the [`ParameterType`]s fails to be [defined][2], or has the
+    /// same name as an existing one with [`ConflictStrategy::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::{
+    /// #     registry::ConflictStrategy, ParameterType, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let mut app = ParameterTypeRegistry::new();
+    /// app.define(ParameterType::untyped("color", "red|blue")).unwrap();
+    ///
+    /// let mut plugin = ParameterTypeRegistry::new();
+    /// plugin.define(ParameterType::untyped("color", "green")).unwrap();
+    /// plugin.define(ParameterType::untyped("size", "S|M|L")).unwrap();
+    ///
+    /// assert!(app.merge(&plugin, ConflictStrategy::Error).is_err());
+    /// assert!(app.lookup_by_name("size").is_none());
+    ///
+    /// let report = app.merge(&plugin, ConflictStrategy::Overwrite).unwrap();
+    /// assert_eq!(report.added, ["size"]);
+    /// assert_eq!(report.overwritten, ["color"]);
+    /// assert_eq!(app.lookup_by_name("color").unwrap().regexp(), "green");
+    /// ```
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: Registry::scoped()
+    /// [2]: Registry::define()
+    pub fn merge(
+        &mut self,
+        other: &Self,
+        strategy: ConflictStrategy,
+    ) -> Result<MergeReport, DefineError> {
+        let mut merged = self.clone();
+        let mut report = MergeReport::default();
+        for ty in other.iter().filter(|t| !t.builtin) {
+            match (merged.by_name.get(&ty.name).copied(), strategy) {
+                (None, _) => {
+                    merged.define_erased(ty.clone())?;
+                    report.added.push(ty.name.clone());
+                }
+                (Some(_), ConflictStrategy::Error) => {
+                    return Err(DefineError::DuplicateName(ty.name.clone()));
+                }
+                (Some(_), ConflictStrategy::KeepExisting) => {
+                    report.kept.push(ty.name.clone());
+                }
+                (Some(i), ConflictStrategy::Overwrite) => {
+                    merged.replace(i, ty.clone())?;
+                    report.overwritten.push(ty.name.clone());
+                }
+            }
+        }
+        *self = merged;
+        Ok(report)
+    }
+
+    /// Defines the given type-erased [`ParameterType`] in this [`Registry`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Registry::define()`] for details.
+    fn define_erased(
+        &mut self,
+        parameter_type: AnyParameterType,
+    ) -> Result<(), DefineError> {
+        validate_name(parameter_type.name())?;
+        if self.by_name.contains_key(parameter_type.name()) {
+            return Err(DefineError::DuplicateName(parameter_type.name));
+        }
+
+        let mut same_regexp = self
+            .types
+            .iter()
+            .filter(|t| t.regexp == parameter_type.regexp)
+            .peekable();
+        if let Some(first) = same_regexp.peek().copied() {
+            let preferential = same_regexp.find(|t| t.preferential);
+            match (preferential, parameter_type.preferential) {
+                (Some(existing), true) => {
+                    return Err(DefineError::DuplicatePreferential {
+                        regexp: parameter_type.regexp,
+                        existing: existing.name.clone(),
+                        defined: parameter_type.name,
+                    });
+                }
+                (None, false) => {
+                    return Err(DefineError::AmbiguousRegexp {
+                        regexp: parameter_type.regexp,
+                        existing: first.name.clone(),
+                        defined: parameter_type.name,
+                    });
+                }
+                (Some(_), false) | (None, true) => {}
+            }
+        }
+
+        self.insert(parameter_type);
+        Ok(())
+    }
+
+    /// Replaces the [`ParameterType`] at the given index with the given one,
+    /// [defining][1] it in the same place.
+    ///
+    /// # Errors
+    ///
+    /// See [`Registry::define()`] for details.
+    ///
+    /// [1]: Registry::define()
+    fn replace(
+        &mut self,
+        index: usize,
+        parameter_type: AnyParameterType,
+    ) -> Result<(), DefineError> {
+        let _ = self.types.remove(index);
+        self.reindex();
+        self.define_erased(parameter_type)?;
+        if let Some(defined) = self.types.pop() {
+            self.types.insert(index, defined);
+        }
+        self.reindex();
+        Ok(())
+    }
+
+    /// Returns the [`ParameterType`] with the given `name`, if any, looking it
+    /// up in the [parent][1] [`Registry`], if not defined in this one.
+    ///
+    /// [1]: Registry::scoped()
+    #[must_use]
+    pub fn lookup_by_name(&self, name: &str) -> Option<&AnyParameterType> {
+        self.by_name
+            .get(name)
+            .and_then(|&i| self.types.get(i))
+            .or_else(|| self.parent.as_ref()?.lookup_by_name(name))
+    }
+
+    /// Returns the [`ParameterType`] producing a capturing group with the given
+    /// [`Regex`] pattern (with or without the group itself), if any.
+    ///
+    /// If several [`ParameterType`]s have the same [`Regex`] pattern, returns
+    /// the [preferential][1] one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::{
+    /// #     ParameterType, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry
+    ///     .define(ParameterType::untyped("color", "red|blue"))
+    ///     .unwrap();
+    ///
+    /// let lookup = |re| registry.lookup_by_regexp(re).map(|t| t.name());
+    /// assert_eq!(lookup("(red|blue)"), Some("color"));
+    /// assert_eq!(lookup("red|blue"), Some("color"));
+    /// assert_eq!(lookup("(?:-?\\d+)|(?:\\d+)"), Some("int"));
+    /// assert_eq!(lookup("green"), None);
+    /// ```
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: ParameterType::preferential()
+    #[must_use]
+    pub fn lookup_by_regexp(&self, regexp: &str) -> Option<&AnyParameterType> {
+        self.by_regexp
+            .get(regexp)
+            .or_else(|| self.by_regexp.get(strip_group(regexp)))
+            .and_then(|&i| self.types.get(i))
+            .or_else(|| {
+                self.parent
+                    .as_ref()?
+                    .lookup_by_regexp(regexp)
+                    .filter(|t| !self.by_name.contains_key(&t.name))
+            })
+    }
+
+    /// Transforms the given [`ParameterCaptures`] with the registered
+    /// [`ParameterType`] of the captured [`Parameter`], into a type-erased
+    /// [`AnyValue`].
+    ///
+    /// Values of anonymous [`Parameter`]s (`{}`) are the matched texts as is.
+    ///
+    /// # Errors
+    ///
+    /// If there is no registered [`ParameterType`] for the captured
+    /// [`Parameter`], or its transformer fails.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use cucumber_expressions::expand::{
+    /// #     ExpressionCompiler, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let registry = ParameterTypeRegistry::new();
+    /// let compiled = ExpressionCompiler::new()
+    ///     .parameters(&registry)
+    ///     .compile("{int} cucumbers in {string}")
+    ///     .unwrap();
+    ///
+    /// let captures = compiled.captures("5 cucumbers in 'belly'").unwrap();
+    /// let values = registry.transform_all(&captures).unwrap();
+    ///
+    /// assert_eq!(values[0].type_name(), Some("i32"));
+    /// assert_eq!(values[0].downcast_ref::<i32>(), Some(&5));
+    /// assert_eq!(values[1].downcast_ref::<String>().unwrap(), "belly");
+    /// assert!(values[1].downcast_ref::<i32>().is_none());
+    /// # }
+    /// ```
+    ///
+    /// [`Parameter`]: crate::ast::Parameter
+    pub fn transform(
+        &self,
+        captures: &ParameterCaptures<'_, '_>,
+    ) -> Result<AnyValue, TransformError> {
+        transform(captures, |name| self.lookup_by_name(name))
+    }
+
+    /// [Transforms][1] all the given [`ParameterCaptures`] in order.
+    ///
+    /// # Errors
+    ///
+    /// On the first [`ParameterCaptures`] failed to be [transformed][1].
+    ///
+    /// [1]: Registry::transform()
+    pub fn transform_all(
+        &self,
+        captures: &[ParameterCaptures<'_, '_>],
+    ) -> Result<Vec<AnyValue>, TransformError> {
+        captures.iter().map(|c| self.transform(c)).collect()
+    }
+
+    /// Returns an [`Iterator`] over all the registered [`ParameterType`]s in
+    /// their definition order, [built-in][1] ones first.
+    ///
+    /// The [`ParameterType`]s of the [parent][2] [`Registry`] go before the
+    /// ones of this [`Registry`], unless overridden by them.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: Registry::scoped()
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            parent: self.parent.as_ref().map(|p| Box::new(p.iter())),
+            registry: self,
+            own: self.types.iter(),
+        }
+    }
+
+    /// Returns an [`Iterator`] over the registered custom [`ParameterType`]s
+    /// in their [iteration][1] order, including the ones overriding the
+    /// [built-in][2] ones.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::{
+    /// #     ParameterType, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry
+    ///     .define(ParameterType::untyped("color", "red|blue"))
+    ///     .unwrap();
+    ///
+    /// let custom = registry.types().map(|t| t.name()).collect::<Vec<_>>();
+    /// assert_eq!(custom, ["color"]);
+    /// assert!(registry.builtin_types().any(|t| t.name() == "int"));
+    /// ```
+    ///
+    /// [1]: Registry::iter()
+    /// [2]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn types(&self) -> impl Iterator<Item = &AnyParameterType> {
+        self.iter().filter(|t| !t.builtin)
+    }
+
+    /// Returns an [`Iterator`] over the [built-in][1] [`ParameterType`]s not
+    /// overridden by custom ones, in their [iteration][2] order.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: Registry::iter()
+    pub fn builtin_types(&self) -> impl Iterator<Item = &AnyParameterType> {
+        self.iter().filter(|t| t.builtin)
+    }
+
+    /// Returns an [`Iterator`] over the registered [`ParameterType`]s with
+    /// names starting with the given `prefix`, in their [iteration][1]
+    /// order, to complete a [`Parameter`] name being typed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::{
+    /// #     ParameterType, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry
+    ///     .define(
+    ///         ParameterType::untyped("fruit", "apple|pear")
+    ///             .with_description("Fruit in a basket."),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let found = registry
+    ///     .find_by_prefix("f")
+    ///     .map(|t| (t.name(), t.is_builtin()))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(found, [("float", true), ("fruit", false)]);
+    ///
+    /// let fruit = registry.find_by_prefix("fr").next().unwrap();
+    /// assert_eq!(fruit.description(), Some("Fruit in a basket."));
+    /// ```
+    ///
+    /// [`Parameter`]: crate::Parameter
+    /// [1]: Registry::iter()
+    pub fn find_by_prefix<'r>(
+        &'r self,
+        prefix: &'r str,
+    ) -> impl Iterator<Item = &'r AnyParameterType> {
+        self.iter().filter(move |t| t.name.starts_with(prefix))
+    }
+
+    /// Returns an [`Iterator`] over the registered [`ParameterType`]s to be
+    /// used in generated step definition snippets, preferential ones first.
+    pub fn snippet_types(&self) -> impl Iterator<Item = &AnyParameterType> {
+        let snippets = || self.iter().filter(|t| t.use_for_snippets);
+        snippets()
+            .filter(|t| t.preferential)
+            .chain(snippets().filter(|t| !t.preferential))
+    }
+
+    /// Generates [Cucumber Expressions][0] matching the given step `text`, to
+    /// be suggested in step definition snippets, the same way the
+    /// [reference implementations][0] do.
+    ///
+    /// Only the [`snippet_types()`][1] are used, and the expressions with
+    /// [preferential][2] ones go first.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::ParameterTypeRegistry;
+    /// #
+    /// let registry = ParameterTypeRegistry::new();
+    /// let generated = registry.generate_expressions("I have 3 cukes");
+    ///
+    /// assert_eq!(generated[0].source, "I have {int} cukes");
+    /// assert_eq!(generated[1].source, "I have {float} cukes");
+    /// ```
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    /// [1]: Registry::snippet_types()
+    /// [2]: ParameterType::preferential()
+    #[cfg(feature = "into-regex")]
+    #[must_use]
+    pub fn generate_expressions(
+        &self,
+        text: &str,
+    ) -> Vec<super::GeneratedExpression> {
+        super::generator::generate(self.snippet_types(), text)
+    }
+
+    /// Documents all the registered [`ParameterType`]s in their
+    /// [iteration][1] order, to be published as JSON (with `serde` feature
+    /// enabled) or [Markdown][2].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::expand::{
+    /// #     ParameterType, ParameterTypeRegistry,
+    /// # };
+    /// #
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry
+    ///     .define(
+    ///         ParameterType::untyped("color", "red|blue")
+    ///             .with_description("Color of a cucumber.")
+    ///             .with_example("red"),
+    ///     )
+    ///     .unwrap();
+    ///
+    /// let docs = registry.document();
+    /// let color = docs.iter().find(|d| d.name == "color").unwrap();
+    /// assert_eq!(color.description.as_deref(), Some("Color of a cucumber."));
+    /// assert_eq!(color.examples, ["red"]);
+    ///
+    /// let markdown = docs.to_markdown();
+    /// assert!(markdown.contains("| `{color}` | `red\\|blue` |"));
+    /// ```
+    ///
+    /// [1]: Registry::iter()
+    /// [2]: Documentation::to_markdown()
+    #[must_use]
+    pub fn document(&self) -> Documentation {
+        Documentation(self.iter().map(ParameterDoc::from).collect())
+    }
+
+    /// Returns the number of the registered [`ParameterType`]s, including the
+    /// [built-in][1] ones.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Indicates whether this [`Registry`] has no [`ParameterType`]s.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Freezes this [`Registry`] into an immutable [`FrozenRegistry`]
+    /// snapshot, to be shared across threads without any locking.
+    ///
+    /// The [scoped][1] [`Registry`]s are flattened, so lookups don't walk the
+    /// [parent][1] chain anymore.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "into-regex")] {
+    /// # use std::{sync::Arc, thread};
+    /// #
+    /// # use cucumber_expressions::{
+    /// #     expand::{ParameterType, ParameterTypeRegistry},
+    /// #     Expression,
+    /// # };
+    /// #
+    /// let mut registry = ParameterTypeRegistry::new();
+    /// registry
+    ///     .define(ParameterType::untyped("color", "red|blue"))
+    ///     .unwrap();
+    /// let frozen = registry.freeze();
+    ///
+    /// let handles = (0..2)
+    ///     .map(|_| {
+    ///         let frozen = Arc::clone(&frozen);
+    ///         thread::spawn(move || {
+    ///             Expression::regex_with_parameters("{color}", &*frozen)
+    ///                 .unwrap()
+    ///                 .is_match("red")
+    ///         })
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// for handle in handles {
+    ///     assert!(handle.join().unwrap());
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [1]: Registry::scoped()
+    #[must_use]
+    pub fn freeze(&self) -> Arc<FrozenRegistry> {
+        let types = self.iter().cloned().collect::<Vec<_>>();
+        let by_name = types
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.name.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let by_regexp = types
+            .iter()
+            .filter_map(|t| {
+                let found = self.lookup_by_regexp(&t.regexp)?;
+                Some((t.regexp.clone(), *by_name.get(&found.name)?))
+            })
+            .collect();
+        let provided = types
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_provided())
+            .map(|(i, t)| (t.name.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let fingerprint =
+            fingerprint_types(provided.values().filter_map(|&i| types.get(i)));
+
+        Arc::new(FrozenRegistry {
+            types,
+            by_name,
+            by_regexp,
+            provided,
+            fingerprint,
+            locale: self.locale.clone(),
+            number_format: self.number_format,
+        })
+    }
+
+    /// Inserts the given [`ParameterType`] without any checks.
+    fn insert(&mut self, parameter_type: AnyParameterType) {
+        let i = self.types.len();
+        let _ = self.by_name.insert(parameter_type.name.clone(), i);
+        if parameter_type.preferential {
+            let _ = self.by_regexp.insert(parameter_type.regexp.clone(), i);
+        } else {
+            let _ = self
+                .by_regexp
+                .entry(parameter_type.regexp.clone())
+                .or_insert(i);
+        }
+        self.types.push(parameter_type);
+    }
+
+    /// Rebuilds the indices of the registered [`ParameterType`]s.
+    fn reindex(&mut self) {
+        self.by_name.clear();
+        self.by_regexp.clear();
+        for ty in mem::take(&mut self.types) {
+            self.insert(ty);
+        }
+    }
+
+    /// Creates a new [`Registry`] with the [built-in][1] [`ParameterType`]s
+    /// pre-registered according to the given [`NumberFormat`].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    fn localized(locale: Option<String>, number_format: NumberFormat) -> Self {
+        let mut registry = Self {
+            types: Vec::new(),
+            by_name: HashMap::new(),
+            by_regexp: HashMap::new(),
+            locale,
+            number_format,
+            parent: None,
+        };
+        for ty in builtin_parameter_names()
+            .filter_map(|name| builtin(name, number_format))
+        {
+            registry.insert(ty);
+        }
+        registry
+    }
+
+    /// Returns an [`Iterator`] over the [`ParameterType`]s provided by this
+    /// [`Registry`] as a [`ParametersProvider`].
+    fn provided(&self) -> impl Iterator<Item = &AnyParameterType> {
+        self.iter().filter(|t| t.is_provided())
+    }
+}
+
+/// [`Iterator`] over the [`ParameterType`]s of a [`Registry`], returned by
+/// [`Registry::iter()`].
+#[derive(Clone, Debug)]
+pub struct Iter<'r> {
+    /// [`Iterator`] over the [`ParameterType`]s of the [parent][1]
+    /// [`Registry`], if not exhausted yet.
+    ///
+    /// [1]: Registry::scoped()
+    parent: Option<Box<Self>>,
+
+    /// [`Registry`] being iterated over.
+    registry: &'r Registry,
+
+    /// [`Iterator`] over the [`ParameterType`]s defined in the [`Registry`]
+    /// itself.
+    own: slice::Iter<'r, AnyParameterType>,
+}
+
+impl<'r> Iterator for Iter<'r> {
+    type Item = &'r AnyParameterType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(parent) = &mut self.parent {
+            let overridden = &self.registry.by_name;
+            if let Some(ty) = parent.find(|t| !overridden.contains_key(&t.name))
+            {
+                return Some(ty);
+            }
+            self.parent = None;
+        }
+        self.own.next()
+    }
+}
+
+/// Documentation of the [`ParameterType`]s of a [`Registry`], returned by
+/// [`Registry::document()`].
+///
+/// Serializes as a sequence of [`ParameterDoc`]s with `serde` feature enabled.
+#[derive(AsRef, Clone, Debug, Deref, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct Documentation(pub Vec<ParameterDoc>);
+
+impl Documentation {
+    /// Renders this [`Documentation`] as a [Markdown] table.
+    ///
+    /// [Markdown]: https://github.github.com/gfm
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let rows = self.0.iter().map(|doc| {
+            let examples = doc
+                .examples
+                .iter()
+                .map(|e| markdown_code(e))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "| {} | {} | {} | {} |\n",
+                markdown_code(&format!("{{{}}}", doc.name)),
+                markdown_code(&doc.regexp),
+                doc.description
+                    .as_deref()
+                    .unwrap_or_default()
+                    .replace('|', "\\|")
+                    .replace('\n', " "),
+                examples,
+            )
+        });
+        iter::once(String::from(
+            "| Parameter | Pattern | Description | Examples |\n\
+             |-----------|---------|-------------|----------|\n",
+        ))
+        .chain(rows)
+        .collect()
+    }
+}
+
+/// Documentation of a single [`ParameterType`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParameterDoc {
+    /// [Name][1] of the documented [`ParameterType`].
+    ///
+    /// [1]: ParameterType::name()
+    pub name: String,
+
+    /// [`Regex`] pattern of the documented [`ParameterType`].
+    ///
+    /// [`Regex`]: regex::Regex
+    pub regexp: String,
+
+    /// [Type name][1] of the documented [`ParameterType`], if any.
+    ///
+    /// [1]: ParameterType::type_name()
+    pub type_name: Option<String>,
+
+    /// [Description][1] of the documented [`ParameterType`], if any.
+    ///
+    /// [1]: ParameterType::description()
+    pub description: Option<String>,
+
+    /// [Examples][1] of the documented [`ParameterType`].
+    ///
+    /// [1]: ParameterType::examples()
+    pub examples: Vec<String>,
+
+    /// Indicator whether the documented [`ParameterType`] is a [built-in][1]
+    /// one.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub builtin: bool,
+}
+
+impl<T> From<&ParameterType<T>> for ParameterDoc {
+    fn from(ty: &ParameterType<T>) -> Self {
+        Self {
+            name: ty.name.clone(),
+            regexp: ty.regexp.clone(),
+            type_name: ty.type_name.clone(),
+            description: ty.description.clone(),
+            examples: ty.examples.clone(),
+            builtin: ty.builtin,
+        }
+    }
+}
+
+/// Formats the given `text` as an inline [Markdown] code span, usable in a
+/// table cell.
+///
+/// [Markdown]: https://github.github.com/gfm
+fn markdown_code(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest + 1);
+    let pad = if longest > 0 { " " } else { "" };
+    format!(
+        "{}{}{}{}{}",
+        fence,
+        pad,
+        text.replace('|', "\\|"),
+        pad,
+        fence,
+    )
+}
+
+/// Immutable snapshot of a [`Registry`], [frozen][1] for sharing across
+/// threads without any locking.
+///
+/// Used as a [`ParametersProvider`], provides the same [`ParameterType`]s as
+/// the [`Registry`] it's [frozen][1] from.
+///
+/// [1]: Registry::freeze()
+#[derive(Clone, Debug)]
+pub struct FrozenRegistry {
+    /// [`ParameterType`]s visible in the [frozen][1] [`Registry`], in its
+    /// [iteration][2] order.
+    ///
+    /// [1]: Registry::freeze()
+    /// [2]: Registry::iter()
+    types: Vec<AnyParameterType>,
+
+    /// Indices of the [`ParameterType`]s by their names.
+    by_name: HashMap<String, usize>,
+
+    /// Indices of the [`ParameterType`]s by their [`Regex`] patterns,
+    /// resolved as [`Registry::lookup_by_regexp()`] does.
+    ///
+    /// [`Regex`]: regex::Regex
+    by_regexp: HashMap<String, usize>,
+
+    /// Indices of the [`ParameterType`]s provided as a [`ParametersProvider`]
+    /// by their names.
+    provided: HashMap<String, usize>,
+
+    /// Precomputed [`ParametersProvider::fingerprint()`].
+    fingerprint: u64,
+
+    /// Locale of the [frozen][1] [`Registry`], if any.
+    ///
+    /// [1]: Registry::freeze()
+    locale: Option<String>,
+
+    /// [`NumberFormat`] of the [built-in][1] numeric [`ParameterType`]s.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    number_format: NumberFormat,
+}
+
+impl FrozenRegistry {
+    /// Returns the locale of the [frozen][1] [`Registry`], if any.
+    ///
+    /// [1]: Registry::freeze()
+    #[must_use]
+    pub fn locale(&self) -> Option<&str> {
+        self.locale.as_deref()
+    }
+
+    /// Returns the [`NumberFormat`] of the [built-in][1] numeric
+    /// [`ParameterType`]s of this [`FrozenRegistry`].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub const fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    /// Returns the [`ParameterType`] with the given `name`, if any.
+    #[must_use]
+    pub fn lookup_by_name(&self, name: &str) -> Option<&AnyParameterType> {
+        self.by_name.get(name).and_then(|&i| self.types.get(i))
+    }
+
+    /// Returns the [`ParameterType`] producing a capturing group with the given
+    /// [`Regex`] pattern (with or without the group itself), if any.
+    ///
+    /// See [`Registry::lookup_by_regexp()`] for details.
+    ///
+    /// [`Regex`]: regex::Regex
+    #[must_use]
+    pub fn lookup_by_regexp(&self, regexp: &str) -> Option<&AnyParameterType> {
+        self.by_regexp
+            .get(regexp)
+            .or_else(|| self.by_regexp.get(strip_group(regexp)))
+            .and_then(|&i| self.types.get(i))
+    }
+
+    /// Transforms the given [`ParameterCaptures`] into a type-erased
+    /// [`AnyValue`].
+    ///
+    /// See [`Registry::transform()`] for details.
+    ///
+    /// # Errors
+    ///
+    /// If there is no [`ParameterType`] for the captured [`Parameter`], or its
+    /// transformer fails.
+    ///
+    /// [`Parameter`]: crate::ast::Parameter
+    pub fn transform(
+        &self,
+        captures: &ParameterCaptures<'_, '_>,
+    ) -> Result<AnyValue, TransformError> {
+        transform(captures, |name| self.lookup_by_name(name))
+    }
+
+    /// [Transforms][1] all the given [`ParameterCaptures`] in order.
+    ///
+    /// # Errors
+    ///
+    /// On the first [`ParameterCaptures`] failed to be [transformed][1].
+    ///
+    /// [1]: FrozenRegistry::transform()
+    pub fn transform_all(
+        &self,
+        captures: &[ParameterCaptures<'_, '_>],
+    ) -> Result<Vec<AnyValue>, TransformError> {
+        captures.iter().map(|c| self.transform(c)).collect()
+    }
+
+    /// Returns an [`Iterator`] over all the [`ParameterType`]s in the
+    /// [iteration][1] order of the [frozen][2] [`Registry`].
+    ///
+    /// [1]: Registry::iter()
+    /// [2]: Registry::freeze()
+    pub fn iter(&self) -> slice::Iter<'_, AnyParameterType> {
+        self.types.iter()
+    }
+
+    /// Returns an [`Iterator`] over the custom [`ParameterType`]s, like
+    /// [`Registry::types()`] does.
+    pub fn types(&self) -> impl Iterator<Item = &AnyParameterType> {
+        self.iter().filter(|t| !t.builtin)
+    }
+
+    /// Returns an [`Iterator`] over the [built-in][1] [`ParameterType`]s not
+    /// overridden by custom ones, like [`Registry::builtin_types()`] does.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn builtin_types(&self) -> impl Iterator<Item = &AnyParameterType> {
+        self.iter().filter(|t| t.builtin)
+    }
+
+    /// Returns an [`Iterator`] over the [`ParameterType`]s with names starting
+    /// with the given `prefix`, like [`Registry::find_by_prefix()`] does.
+    pub fn find_by_prefix<'r>(
+        &'r self,
+        prefix: &'r str,
+    ) -> impl Iterator<Item = &'r AnyParameterType> {
+        self.iter().filter(move |t| t.name.starts_with(prefix))
+    }
+
+    /// Returns the number of the [`ParameterType`]s, including the
+    /// [built-in][1] ones.
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Indicates whether this [`FrozenRegistry`] has no [`ParameterType`]s.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+impl<'r> IntoIterator for &'r FrozenRegistry {
+    type Item = &'r AnyParameterType;
+    type IntoIter = slice::Iter<'r, AnyParameterType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'p, Input> ParametersProvider<Input> for &'p FrozenRegistry
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Item = char;
+    type Value = CowValue<'p>;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let registry: &'p FrozenRegistry = self;
+        with_name(input, |name| registry.provided.get(name).copied())
+            .and_then(|i| registry.types.get(i))
+            .map(|t| CowValue(t.pattern()))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.types
+            .iter()
+            .filter(|t| self.provided.contains_key(&t.name))
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(self.fingerprint)
+    }
+}
+
+/// Transforms the given [`ParameterCaptures`] with the [`ParameterType`]
+/// returned by the `lookup` function for the captured [`Parameter`]'s name.
+///
+/// [`Parameter`]: crate::ast::Parameter
+fn transform<'r>(
+    captures: &ParameterCaptures<'_, '_>,
+    lookup: impl FnOnce(&str) -> Option<&'r AnyParameterType>,
+) -> Result<AnyValue, TransformError> {
+    let name = captures.parameter.name;
+    if name.is_empty() {
+        return Ok(AnyValue {
+            value: Box::new(whole(&captures.groups).to_owned()),
+            type_name: Some(any::type_name::<String>().to_owned()),
+        });
+    }
+
+    let ty = lookup(name).ok_or_else(|| TransformError {
+        parameter: name.to_owned(),
+        matched: whole(&captures.groups).to_owned(),
+        reason: Arc::from(BoxError::from("unknown parameter type")),
+    })?;
+    Ok(AnyValue {
+        value: ty.transform_groups(&captures.groups)?,
+        type_name: ty.type_name.clone(),
+    })
+}
+
+/// Process-wide [`Registry`], initialized on the first access.
+#[cfg(feature = "global-registry")]
+static GLOBAL: once_cell::sync::Lazy<RwLock<Registry>> =
+    once_cell::sync::Lazy::new(RwLock::default);
+
+/// [Defines][1] the given [`ParameterType`] in the process-wide [`Registry`].
+///
+/// # Errors
+///
+/// If the [`ParameterType`] conflicts with an already registered one.
+///
+/// [1]: Registry::define()
+#[cfg(feature = "global-registry")]
+pub fn register<T: Send + 'static>(
+    parameter_type: ParameterType<T>,
+) -> Result<(), DefineError> {
+    GLOBAL
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .define(parameter_type)
+}
+
+/// Returns a copy of the process-wide [`Registry`] with all the
+/// [`ParameterType`]s [`register()`]ed so far.
+///
+/// Transformers are shared with the process-wide [`Registry`], so taking a
+/// snapshot is cheap enough to be done once per a set of expansions.
+#[cfg(feature = "global-registry")]
+#[must_use]
+pub fn snapshot() -> Registry {
+    GLOBAL
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Deserializes a sequence of [`Definition`]s, [defining][1] them in a new
+/// [`Registry`].
+///
+/// [1]: Registry::define()
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Registry {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error as _;
+
+        let mut registry = Self::new();
+        for def in Vec::<Definition>::deserialize(deserializer)? {
+            registry
+                .define(ParameterType::from(def))
+                .map_err(D::Error::custom)?;
+        }
+        Ok(registry)
+    }
+}
+
+impl<'r> IntoIterator for &'r Registry {
+    type Item = &'r AnyParameterType;
+    type IntoIter = Iter<'r>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'p, Input> ParametersProvider<Input> for &'p Registry
+where
+    Input: InputIter,
+    <Input as InputIter>::Item: AsChar,
+{
+    type Item = char;
+    type Value = CowValue<'p>;
+
+    fn get(&self, input: &Input) -> Option<Self::Value> {
+        let registry: &'p Registry = self;
+        with_name(input, |name| registry.lookup_by_name(name))
+            .filter(|t| t.is_provided())
+            .map(|t| CowValue(t.pattern()))
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.provided().map(|t| t.name.clone()).collect()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        Some(fingerprint_types(self.provided()))
+    }
+}
+
+/// Hashes the names and [patterns][1] of the given [`ParameterType`]s the
+/// same way as [`fingerprint_pairs()`] does.
+///
+/// [1]: ParameterType::pattern()
+fn fingerprint_types<'t>(
+    types: impl Iterator<Item = &'t AnyParameterType>,
+) -> u64 {
+    let patterns = types.map(|t| (t.name(), t.pattern())).collect::<Vec<_>>();
+    fingerprint_pairs(patterns.iter().map(|(n, p)| (*n, p.as_ref())))
+}
+
+/// Checks whether the given [`ParameterType`] `name` can be referenced from
+/// an expression.
+fn validate_name(name: &str) -> Result<(), DefineError> {
+    if name.is_empty() {
+        return Err(DefineError::EmptyName);
+    }
+    name.chars()
+        .find(|&c| {
+            matches!(c, '{' | '}' | '(' | ')' | '\\' | '/')
+                || c.is_whitespace()
+                || c.is_control()
+        })
+        .map_or(Ok(()), |character| {
+            Err(DefineError::IllegalNameCharacter {
+                name: name.to_owned(),
+                character,
+            })
+        })
+}
+
+/// Strategy of resolving [`ParameterType`]s with the same name while
+/// [merging][1] [`Registry`]s.
+///
+/// [1]: Registry::merge()
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ConflictStrategy {
+    /// Fail with a [`DefineError::DuplicateName`].
+    Error,
+
+    /// Keep the existing [`ParameterType`], ignoring the merged one.
+    KeepExisting,
+
+    /// Overwrite the existing [`ParameterType`] with the merged one, keeping
+    /// its place in the definition order.
+    Overwrite,
+}
+
+/// Report of [merging][1] [`Registry`]s, listing names of the merged
+/// [`ParameterType`]s in their definition order.
+///
+/// [1]: Registry::merge()
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeReport {
+    /// Names of the [`ParameterType`]s added without any conflicts.
+    pub added: Vec<String>,
+
+    /// Names of the existing [`ParameterType`]s overwritten by the merged
+    /// ones.
+    pub overwritten: Vec<String>,
+
+    /// Names of the merged [`ParameterType`]s ignored in favor of the
+    /// existing ones.
+    pub kept: Vec<String>,
+}
+
+/// Error of defining a [`ParameterType`] in a [`Registry`].
+#[derive(Clone, Debug, Display, Eq, Error, PartialEq)]
+pub enum DefineError {
+    /// [`ParameterType`] name is empty, so it would be indistinguishable from
+    /// the anonymous [`Parameter`] `{}`.
+    ///
+    /// [`Parameter`]: crate::Parameter
+    #[display(fmt = "Parameter type name can't be empty.")]
+    EmptyName,
+
+    /// [`ParameterType`] name contains a `character` it can't be referenced
+    /// from an expression with: `{`, `}`, `(`, `)`, `\`, `/`, whitespace or
+    /// a control one.
+    #[display(
+        fmt = "Illegal character '{}' in parameter type name '{}'.",
+        "character.escape_default()",
+        name
+    )]
+    IllegalNameCharacter {
+        /// Name of the [`ParameterType`] being defined.
+        name: String,
+
+        /// Illegal character of the `name`.
+        character: char,
+    },
+
+    /// [`ParameterType`] with the same name is defined already.
+    #[display(fmt = "There is already a parameter type with name '{}'.", _0)]
+    DuplicateName(#[error(not(source))] String),
+
+    /// [`ParameterType`] has the same [`Regex`] pattern as the `existing` one,
+    /// while none of them is [preferential][1].
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: ParameterType::preferential()
+    #[display(
+        fmt = "Parameter type '{}' has the same regexp /{}/ as '{}', while \
+               none of them is preferential.",
+        defined,
+        regexp,
+        existing
+    )]
+    AmbiguousRegexp {
+        /// [`Regex`] pattern of both [`ParameterType`]s.
+        ///
+        /// [`Regex`]: regex::Regex
+        regexp: String,
+
+        /// Name of the already defined [`ParameterType`].
+        existing: String,
+
+        /// Name of the [`ParameterType`] being defined.
+        defined: String,
+    },
+
+    /// [`ParameterType`] is [preferential][1] for the same [`Regex`] pattern
+    /// as the `existing` one.
+    ///
+    /// [`Regex`]: regex::Regex
+    /// [1]: ParameterType::preferential()
+    #[display(
+        fmt = "There can only be one preferential parameter type per regexp. \
+               The regexp /{}/ is used for two preferential parameter types, \
+               '{}' and '{}'.",
+        regexp,
+        existing,
+        defined
+    )]
+    DuplicatePreferential {
+        /// [`Regex`] pattern of both [`ParameterType`]s.
+        ///
+        /// [`Regex`]: regex::Regex
+        regexp: String,
+
+        /// Name of the already defined preferential [`ParameterType`].
+        existing: String,
+
+        /// Name of the preferential [`ParameterType`] being defined.
+        defined: String,
+    },
+}
+
+#[cfg(all(test, feature = "into-regex"))]
+mod spec {
+    use std::{collections::HashMap, sync::Arc};
+
+    use crate::{
+        expand::{
+            builtin_parameter_names, ExpressionCompiler, NumberFormat,
+            ParametersProvider, FLOAT_PATTERN, INT_PATTERN, WORD_PATTERN,
+        },
+        Expression, Spanned,
+    };
+
+    use super::{
+        strip_group, AnyParameterType, BoxError, ConflictStrategy, DefineError,
+        MergeReport, ParameterType, Registry,
+    };
+
+    #[test]
+    fn builtins_are_registered() {
+        let registry = Registry::new();
+
+        assert_eq!(registry.len(), builtin_parameter_names().count());
+        assert!(registry.iter().all(ParameterType::is_builtin));
+        assert_eq!(
+            registry.lookup_by_name("word").map(ParameterType::regexp),
+            Some("[^\\s]+"),
+        );
+        assert!(
+            ParametersProvider::<Spanned<'_>>::names(&&registry).is_empty(),
+        );
+    }
+
+    #[test]
+    fn defines_and_expands() {
+        let mut registry = Registry::new();
+        registry
+            .define(
+                ParameterType::untyped("color", "red|blue").with_type_name("C"),
+            )
+            .unwrap();
+
+        let color = registry.lookup_by_name("color").unwrap();
+        assert_eq!(color.type_name(), Some("C"));
+        assert!(!color.is_builtin());
+        assert_eq!(
+            registry.iter().last().map(ParameterType::name),
+            Some("color")
+        );
+
+        let re = Expression::regex_with_parameters("{color} {word}", &registry)
+            .unwrap_or_else(|e| panic!("failed: {}", e));
+        assert_eq!(re.as_str(), "^(red|blue) ([^\\s]+)$");
+
+        let map = HashMap::from([("color", "red|blue")]);
+        assert_eq!(
+            ParametersProvider::<&str>::fingerprint(&&registry),
+            ParametersProvider::<&str>::fingerprint(&&map),
+        );
+    }
+
+    #[test]
+    fn transforms() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::new("pair", "\\d+,\\d+", |s: &str| {
+                s.split_once(',')
+                    .map(|(a, b)| (a.to_owned(), b.to_owned()))
+                    .ok_or("no comma")
+            }))
+            .unwrap();
+
+        let pair = registry.lookup_by_name("pair").unwrap();
+        assert_eq!(
+            pair.transform("1,2")
+                .unwrap()
+                .downcast::<(String, String)>()
+                .map(|p| *p)
+                .unwrap(),
+            ("1".to_owned(), "2".to_owned()),
+        );
+        assert_eq!(
+            pair.transform("12").unwrap_err().to_string(),
+            "Parameter 'pair' failed to transform '12': no comma",
+        );
+
+        let transform = |name, s| {
+            registry.lookup_by_name(name).unwrap().transform(s).unwrap()
+        };
+        assert_eq!(transform("int", "-42").downcast_ref(), Some(&-42_i32));
+        assert_eq!(transform("double", "1.5").downcast_ref(), Some(&1.5_f64));
+        assert_eq!(transform("boolean", "true").downcast_ref(), Some(&true));
+        assert_eq!(transform("ordinal", "3").downcast_ref(), Some(&3_u64));
+        assert_eq!(
+            transform("string", "'it\\'s'").downcast_ref::<String>(),
+            Some(&"it's".to_owned()),
+        );
+        assert_eq!(
+            transform("word", "cucumber").downcast_ref::<String>(),
+            Some(&"cucumber".to_owned()),
+        );
+        assert!(registry
+            .lookup_by_name("int")
+            .unwrap()
+            .transform("x")
+            .is_err());
+    }
+
+    #[test]
+    fn transforms_captures() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::grouped(
+                "pair",
+                "(\\d+),(\\d+)",
+                |g| match g {
+                    [Some(a), Some(b)] => {
+                        Ok((a.parse::<u8>()?, b.parse::<u8>()?))
+                    }
+                    _ => Err(BoxError::from("no pair")),
+                },
+            ))
+            .unwrap();
+        let compiled = ExpressionCompiler::new()
+            .parameters(&registry)
+            .compile("{pair} {} {float}")
+            .unwrap();
+
+        let captures = compiled.captures("1,2 any 0.5").unwrap();
+        let mut values = registry.transform_all(&captures).unwrap();
+        assert_eq!(values.len(), 3);
+        assert!(values[0].is::<(u8, u8)>());
+        assert_eq!(values[0].type_name(), Some("(u8, u8)"));
+        assert_eq!(values[1].downcast_ref::<String>().unwrap(), "any");
+        *values[2].downcast_mut::<f32>().unwrap() += 1.0;
+        let float = values.pop().unwrap().downcast::<u8>().unwrap_err();
+        assert_eq!(float.downcast::<f32>().ok(), Some(1.5));
+        assert_eq!(
+            values.swap_remove(0).into_inner().downcast_ref(),
+            Some(&(1_u8, 2_u8)),
+        );
+
+        let pars = HashMap::from([("color", "red")]);
+        let unknown = ExpressionCompiler::new()
+            .parameters(&pars)
+            .compile("{color}")
+            .unwrap();
+        assert_eq!(
+            registry
+                .transform_all(&unknown.captures("red").unwrap())
+                .unwrap_err()
+                .to_string(),
+            "Parameter 'color' failed to transform 'red': \
+             unknown parameter type",
+        );
+    }
+
+    #[test]
+    fn transforms_groups() {
+        let range = ParameterType::grouped(
+            "range",
+            "(\\d+)(?:-(\\d+))?",
+            |g| match g {
+                [Some(from), to] => Ok((
+                    from.parse::<u8>()?,
+                    to.map(str::parse::<u8>).transpose()?,
+                )),
+                _ => Err(BoxError::from("no range")),
+            },
+        );
+        assert_eq!(range.group_count(), 2);
+        assert_eq!(
+            range
+                .transform_groups(&[Some("1-2"), Some("1"), Some("2")])
+                .unwrap(),
+            (1, Some(2)),
+        );
+        assert_eq!(
+            range
+                .transform_groups(&[Some("1"), Some("1"), None])
+                .unwrap(),
+            (1, None),
+        );
+        assert_eq!(
+            range.transform("1-2").unwrap_err().to_string(),
+            "Parameter 'range' failed to transform '1-2': no range",
+        );
+
+        let untyped = ParameterType::untyped("pair", "(\\d+),(\\d+)");
+        assert_eq!(untyped.group_count(), 2);
+        assert_eq!(
+            untyped
+                .transform_groups(&[Some("1,2"), Some("1"), Some("2")])
+                .unwrap(),
+            "1,2",
+        );
+        assert_eq!(ParameterType::untyped("a", "a").group_count(), 0);
+    }
+
+    #[test]
+    fn localizes() {
+        let registry = Registry::with_locale("fr-FR");
+        assert_eq!(registry.locale(), Some("fr-FR"));
+        assert_eq!(
+            registry.number_format(),
+            NumberFormat::SpaceGroupingDecimalComma,
+        );
+        assert_eq!(
+            ParametersProvider::<Spanned<'_>>::names(&&registry),
+            ["int", "byte", "short", "long", "biginteger"]
+                .into_iter()
+                .chain(["float", "double", "bigdecimal"])
+                .map(str::to_owned)
+                .collect::<Vec<_>>(),
+        );
+
+        let re = Expression::regex_with_parameters("{int} {double}", &registry)
+            .unwrap();
+        let caps = re.captures("-1 000 2 000,25").unwrap();
+        assert_eq!(&caps[1], "-1 000");
+        assert_eq!(&caps[2], "2 000,25");
+
+        let int = registry.lookup_by_name("int").unwrap();
+        let parsed = int.transform("-1 000").unwrap();
+        assert_eq!(parsed.downcast_ref::<i32>(), Some(&-1000));
+        let double = registry.lookup_by_name("double").unwrap();
+        let float = double.transform("2 000,25").unwrap();
+        assert_eq!(float.downcast_ref::<f64>(), Some(&2000.25));
+
+        let unknown = Registry::with_locale("xx");
+        assert_eq!(unknown.locale(), Some("xx"));
+        assert_eq!(unknown.number_format(), NumberFormat::Plain);
+        assert!(ParametersProvider::<Spanned<'_>>::names(&&unknown).is_empty());
+        assert_eq!(Registry::new().locale(), None);
+    }
+
+    #[test]
+    fn scopes() {
+        let mut global = Registry::new();
+        global
+            .define(ParameterType::untyped("color", "red"))
+            .unwrap();
+        global
+            .define(ParameterType::untyped("size", "S|M"))
+            .unwrap();
+        let global = Arc::new(global);
+
+        let mut krate = Registry::scoped(Arc::clone(&global));
+        krate.define(ParameterType::untyped("size", "XL")).unwrap();
+        krate
+            .define(ParameterType::untyped("shape", "red"))
+            .unwrap();
+        let krate = Arc::new(krate);
+
+        let mut module = Registry::scoped(Arc::clone(&krate));
+        module
+            .define(ParameterType::untyped("color", "blue"))
+            .unwrap();
+        assert_eq!(
+            module.define(ParameterType::untyped("color", "green")),
+            Err(DefineError::DuplicateName("color".to_owned())),
+        );
+
+        assert!(Arc::ptr_eq(module.parent().unwrap(), &krate));
+        assert!(global.parent().is_none());
+        let localized = Registry::scoped(Arc::new(Registry::with_locale("en")));
+        assert_eq!(localized.locale(), Some("en"));
+        assert_eq!(localized.number_format(), NumberFormat::CommaGrouping);
+
+        let lookup = |name| module.lookup_by_name(name).unwrap().regexp();
+        assert_eq!(lookup("color"), "blue");
+        assert_eq!(lookup("size"), "XL");
+        assert_eq!(lookup("shape"), "red");
+        assert!(module.lookup_by_name("int").unwrap().is_builtin());
+        assert_eq!(module.lookup_by_regexp("red").unwrap().name(), "shape");
+        assert!(krate.lookup_by_regexp("S|M").is_none());
+
+        let builtins = builtin_parameter_names().count();
+        assert_eq!(module.len(), builtins + 3);
+        assert_eq!(
+            module
+                .iter()
+                .skip(builtins)
+                .map(ParameterType::name)
+                .collect::<Vec<_>>(),
+            ["size", "shape", "color"],
+        );
+        assert_eq!(
+            ParametersProvider::<Spanned<'_>>::names(&&module),
+            ["size", "shape", "color"],
+        );
+        assert!(!Registry::scoped(Arc::new(Registry::new())).is_empty());
+    }
+
+    #[test]
+    fn merges() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::untyped("color", "red|blue"))
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("size", "S|M"))
+            .unwrap();
+
+        let mut base = Registry::new();
+        base.define(ParameterType::<u8>::from_str("size", "\\d+"))
+            .unwrap();
+        let mut other = Registry::scoped(Arc::new(base));
+        other
+            .define(ParameterType::untyped("shape", "round|square"))
+            .unwrap();
+
+        let names = |r: &Registry| {
+            r.iter()
+                .filter(|t| !t.is_builtin())
+                .map(|t| t.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            registry.merge(&other, ConflictStrategy::Error).unwrap_err(),
+            DefineError::DuplicateName("size".to_owned()),
+        );
+        assert_eq!(names(&registry), ["color", "size"]);
+
+        let mut kept = registry.clone();
+        assert_eq!(
+            kept.merge(&other, ConflictStrategy::KeepExisting).unwrap(),
+            MergeReport {
+                added: vec!["shape".to_owned()],
+                overwritten: vec![],
+                kept: vec!["size".to_owned()],
+            },
+        );
+        assert_eq!(names(&kept), ["color", "size", "shape"]);
+        assert_eq!(kept.lookup_by_name("size").unwrap().regexp(), "S|M");
+
+        assert_eq!(
+            registry.merge(&other, ConflictStrategy::Overwrite).unwrap(),
+            MergeReport {
+                added: vec!["shape".to_owned()],
+                overwritten: vec!["size".to_owned()],
+                kept: vec![],
+            },
+        );
+        assert_eq!(names(&registry), ["color", "size", "shape"]);
+        let size = registry.lookup_by_name("size").unwrap();
+        assert_eq!(size.transform("3").unwrap().downcast_ref(), Some(&3_u8));
+        assert!(registry.lookup_by_regexp("S|M").is_none());
+        assert_eq!(
+            registry.lookup_by_regexp("red|blue").unwrap().name(),
+            "color",
+        );
+
+        let mut ambiguous = Registry::new();
+        ambiguous
+            .define(ParameterType::untyped("hue", "red|blue"))
+            .unwrap();
+        assert!(matches!(
+            registry.merge(&ambiguous, ConflictStrategy::Overwrite),
+            Err(DefineError::AmbiguousRegexp { .. }),
+        ));
+        assert_eq!(names(&registry), ["color", "size", "shape"]);
+    }
+
+    /// Collects names of the given [`ParameterType`]s.
+    fn names<'r>(
+        types: impl Iterator<Item = &'r AnyParameterType>,
+    ) -> Vec<&'r str> {
+        types.map(ParameterType::name).collect()
+    }
+
+    #[test]
+    fn introspects() {
+        let mut parent = Registry::new();
+        parent
+            .define(ParameterType::untyped("color", "red|blue"))
+            .unwrap();
+        let mut registry = Registry::scoped(Arc::new(parent));
+        registry
+            .define(ParameterType::untyped("int", "\\d+"))
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("size", "S|M"))
+            .unwrap();
+
+        assert_eq!(names(registry.types()), ["color", "int", "size"]);
+        let builtins = names(registry.builtin_types());
+        assert!(builtins.contains(&"float"));
+        assert!(!builtins.contains(&"int"));
+        assert_eq!(
+            builtins.len() + 3,
+            registry.len(),
+            "builtins: {:?}",
+            builtins,
+        );
+        assert_eq!(names(registry.find_by_prefix("si")), ["size"]);
+        assert_eq!(names(registry.find_by_prefix("in")), ["int"]);
+        assert!(names(registry.find_by_prefix("x")).is_empty());
+        assert_eq!(
+            registry.find_by_prefix("").count(),
+            registry.iter().count(),
+        );
+
+        let frozen = registry.freeze();
+        assert_eq!(names(frozen.types()), names(registry.types()),);
+        assert_eq!(names(frozen.builtin_types()), builtins);
+        assert_eq!(names(frozen.find_by_prefix("co")), ["color"]);
+    }
+
+    #[test]
+    fn freezes() {
+        let mut global = Registry::with_locale("de");
+        global
+            .define(ParameterType::untyped("color", "red|blue"))
+            .unwrap();
+        let mut local = Registry::scoped(Arc::new(global));
+        local
+            .define(ParameterType::new("color", "green", |s| {
+                Ok::<_, BoxError>(s.len())
+            }))
+            .unwrap();
+        local
+            .define(ParameterType::untyped("hue", "red|blue"))
+            .unwrap();
+
+        let frozen = local.freeze();
+        assert_eq!(frozen.len(), local.len());
+        assert!(!frozen.is_empty());
+        assert_eq!(frozen.locale(), Some("de"));
+        assert_eq!(frozen.number_format(), local.number_format());
+        assert!(frozen
+            .iter()
+            .map(ParameterType::name)
+            .eq(local.iter().map(ParameterType::name)));
+        assert_eq!(frozen.lookup_by_name("color").unwrap().regexp(), "green");
+        assert_eq!(
+            frozen.lookup_by_regexp("(red|blue)").unwrap().name(),
+            "hue"
+        );
+
+        let provided = |p: &dyn Fn(&str) -> Option<String>| {
+            ["color", "hue", "int", "float", "unknown"].map(p)
+        };
+        assert_eq!(
+            provided(&|n| ParametersProvider::get(&&*frozen, &n)
+                .map(|v| v.to_string())),
+            provided(&|n| ParametersProvider::get(&&local, &n)
+                .map(|v| v.to_string())),
+        );
+        assert_eq!(
+            ParametersProvider::<Spanned<'_>>::names(&&*frozen),
+            ParametersProvider::<Spanned<'_>>::names(&&local),
+        );
+        assert_eq!(
+            ParametersProvider::<Spanned<'_>>::fingerprint(&&*frozen),
+            ParametersProvider::<Spanned<'_>>::fingerprint(&&local),
+        );
+
+        let compiled = ExpressionCompiler::new()
+            .parameters(&*frozen)
+            .compile("{color} {float}")
+            .unwrap();
+        let captures = compiled.captures("green 1.000,5").unwrap();
+        let values = frozen.transform_all(&captures).unwrap();
+        assert_eq!(values[0].downcast_ref::<usize>(), Some(&5));
+        assert_eq!(values[1].downcast_ref::<f32>(), Some(&1000.5));
+    }
+
+    #[test]
+    fn documents() {
+        let mut registry = Registry::with_locale("fr");
+        registry
+            .define(
+                ParameterType::untyped("quote", "`[^`]*`|a|b")
+                    .with_description("Quoted\ncode | text.")
+                    .with_example("`a`")
+                    .with_example("b"),
+            )
+            .unwrap();
+
+        let docs = registry.document();
+        assert_eq!(docs.len(), registry.len());
+        assert!(docs.iter().all(|d| d.description.is_some()));
+        let float = docs.iter().find(|d| d.name == "float").unwrap();
+        assert!(float.builtin);
+        assert_eq!(float.type_name.as_deref(), Some("f32"));
+        assert_eq!(float.examples, ["3,6", "-9,2"]);
+
+        let quote = docs.last().unwrap();
+        assert!(!quote.builtin);
+        assert_eq!(quote.type_name, None);
+        assert_eq!(quote.regexp, "`[^`]*`|a|b");
+
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::to_value(&docs).unwrap()[docs.len() - 1],
+            serde_json::json!({
+                "name": "quote",
+                "regexp": "`[^`]*`|a|b",
+                "type_name": null,
+                "description": "Quoted\ncode | text.",
+                "examples": ["`a`", "b"],
+                "builtin": false,
+            }),
+        );
+
+        let markdown = docs.to_markdown();
+        assert!(markdown.starts_with(
+            "| Parameter | Pattern | Description | Examples |\n\
+             |-----------|---------|-------------|----------|\n\
+             | `{int}` |",
+        ));
+        assert!(markdown.ends_with(
+            "| `{quote}` | `` `[^`]*`\\|a\\|b `` | Quoted code \\| text. \
+             | `` `a` ``, `b` |\n",
+        ));
+    }
+
+    #[test]
+    fn snippet_types() {
+        let mut registry = Registry::new();
+        registry
+            .define(
+                ParameterType::untyped("color", "red|blue").preferential(true),
+            )
+            .unwrap();
+        registry
+            .define(
+                ParameterType::untyped("hidden", "x").use_for_snippets(false),
+            )
+            .unwrap();
+
+        let int = registry.lookup_by_name("int").unwrap();
+        assert!(int.is_preferential());
+        assert!(int.is_used_for_snippets());
+        assert!(!registry
+            .lookup_by_name("word")
+            .unwrap()
+            .is_used_for_snippets());
+
+        assert_eq!(
+            registry
+                .snippet_types()
+                .map(ParameterType::name)
+                .collect::<Vec<_>>(),
+            ["int", "float", "color", "string"],
+        );
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let mut registry = Registry::new();
+        registry
+            .define(
+                ParameterType::untyped("color", "red|blue")
+                    .case_insensitive(true),
+            )
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("size", "big|small"))
+            .unwrap();
+
+        let color = registry.lookup_by_name("color").unwrap();
+        assert!(color.is_case_insensitive());
+        assert_eq!(color.regexp(), "red|blue");
+        assert_eq!(color.pattern(), "(?i:red|blue)");
+        assert_eq!(
+            registry.lookup_by_name("size").unwrap().pattern(),
+            "big|small"
+        );
+
+        for re in [
+            Expression::regex_with_parameters("{color} {size}", &registry),
+            Expression::regex_with_parameters(
+                "{color} {size}",
+                &*registry.freeze(),
+            ),
+        ] {
+            let re = re.unwrap();
+            assert_eq!(re.as_str(), "^((?i:red|blue)) (big|small)$");
+            assert!(re.is_match("RED big"));
+            assert!(!re.is_match("red BIG"));
+        }
+
+        let mut sensitive = Registry::new();
+        sensitive
+            .define(ParameterType::untyped("color", "red|blue"))
+            .unwrap();
+        sensitive
+            .define(ParameterType::untyped("size", "big|small"))
+            .unwrap();
+        assert_ne!(
+            ParametersProvider::<&str>::fingerprint(&&registry),
+            ParametersProvider::<&str>::fingerprint(&&sensitive),
+        );
+
+        assert_eq!(
+            registry.generate_expressions("a Blue car")[0].source,
+            "a {color} car",
+        );
+    }
+
+    #[test]
+    fn detects_duplicates() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::untyped("color", "red"))
+            .unwrap();
+
+        assert_eq!(
+            registry.define(ParameterType::untyped("color", "blue")),
+            Err(DefineError::DuplicateName("color".to_owned())),
+        );
+        assert_eq!(
+            registry.define(ParameterType::untyped("int", "\\d+")),
+            Err(DefineError::DuplicateName("int".to_owned())),
+        );
+        assert_eq!(
+            registry.lookup_by_name("color").map(ParameterType::regexp),
+            Some("red"),
+        );
+    }
+
+    #[test]
+    fn detects_invalid_names() {
+        let mut registry = Registry::new();
+
+        assert_eq!(
+            registry.define(ParameterType::untyped("", "a")),
+            Err(DefineError::EmptyName),
+        );
+        for (name, character) in [
+            ("{color}", '{'),
+            ("col}or", '}'),
+            ("co(lor)", '('),
+            ("red/blue", '/'),
+            ("red\\blue", '\\'),
+            ("dark blue", ' '),
+            ("dark\tblue", '\t'),
+            ("dark\u{7f}", '\u{7f}'),
+        ] {
+            assert_eq!(
+                registry.define(ParameterType::untyped(name, "a")),
+                Err(DefineError::IllegalNameCharacter {
+                    name: name.to_owned(),
+                    character,
+                }),
+                "{}",
+                name,
+            );
+        }
+        assert_eq!(
+            DefineError::IllegalNameCharacter {
+                name: "dark blue".to_owned(),
+                character: ' ',
+            }
+            .to_string(),
+            "Illegal character ' ' in parameter type name 'dark blue'.",
+        );
+
+        registry
+            .define(ParameterType::untyped("dark-blue.ŭ", "a"))
+            .unwrap();
+        assert!(registry.lookup_by_name("dark-blue.ŭ").is_some());
+    }
+
+    #[test]
+    fn detects_regexp_conflicts() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::untyped("color", "red"))
+            .unwrap();
+
+        assert_eq!(
+            registry.define(ParameterType::untyped("colour", "red")),
+            Err(DefineError::AmbiguousRegexp {
+                regexp: "red".to_owned(),
+                existing: "color".to_owned(),
+                defined: "colour".to_owned(),
+            }),
+        );
+        assert!(matches!(
+            registry.define(ParameterType::untyped("bool", "true|false")),
+            Err(DefineError::AmbiguousRegexp { existing, .. })
+                if existing == "boolean",
+        ));
+
+        registry
+            .define(ParameterType::untyped("colour", "red").preferential(true))
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("rgb", "red"))
+            .unwrap();
+        assert_eq!(
+            registry
+                .define(
+                    ParameterType::untyped("rouge", "red").preferential(true)
+                )
+                .unwrap_err()
+                .to_string(),
+            "There can only be one preferential parameter type per regexp. \
+             The regexp /red/ is used for two preferential parameter types, \
+             'colour' and 'rouge'.",
+        );
+
+        registry
+            .define(ParameterType::untyped("num", "-?\\d+").preferential(true))
+            .unwrap();
+        assert!(matches!(
+            registry.define(
+                ParameterType::untyped("integer", "(?:-?\\d+)|(?:\\d+)")
+                    .preferential(true),
+            ),
+            Err(DefineError::DuplicatePreferential { existing, .. })
+                if existing == "int",
+        ));
+    }
+
+    #[test]
+    fn lookups_by_regexp() {
+        let mut registry = Registry::new();
+        registry
+            .define(ParameterType::untyped("color", "red"))
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("colour", "red").preferential(true))
+            .unwrap();
+        registry
+            .define(ParameterType::untyped("rgb", "red"))
+            .unwrap();
+
+        let lookup =
+            |re| registry.lookup_by_regexp(re).map(ParameterType::name);
+        assert_eq!(lookup("red"), Some("colour"));
+        assert_eq!(lookup("(red)"), Some("colour"));
+        assert_eq!(lookup(INT_PATTERN), Some("int"));
+        assert_eq!(lookup(FLOAT_PATTERN), Some("float"));
+        assert_eq!(lookup(WORD_PATTERN), Some("word"));
+        assert_eq!(lookup("\\d+"), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializes() {
+        use super::Definition;
+
+        let registry = serde_json::from_str::<Registry>(
+            r#"[
+                {"name": "color", "regex": "red|blue", "preferential": true},
+                {"name": "size", "regexp": "\\d+"}
+            ]"#,
+        )
+        .unwrap_or_else(|e| panic!("failed: {}", e));
+
+        let color = registry.lookup_by_name("color").unwrap();
+        assert!(color.is_preferential());
+        assert!(color.is_used_for_snippets());
+        assert_eq!(color.type_name(), None);
+        assert_eq!(
+            registry.lookup_by_regexp("\\d+").map(ParameterType::name),
+            Some("size"),
+        );
+
+        assert_eq!(
+            serde_json::from_str::<Registry>(
+                r#"[{"name": "int", "regexp": "\\d+"}]"#,
+            )
+            .unwrap_err()
+            .to_string(),
+            "There is already a parameter type with name 'int'.",
+        );
+
+        let def = Definition {
+            name: "color".to_owned(),
+            regexp: "red".to_owned(),
+            type_name: Some("Color".to_owned()),
+            description: Some("Color of a cucumber.".to_owned()),
+            examples: vec!["red".to_owned()],
+            preferential: false,
+            use_for_snippets: false,
+            case_insensitive: true,
+        };
+        let json = serde_json::to_string(&def).unwrap();
+        assert_eq!(serde_json::from_str::<Definition>(&json).unwrap(), def);
+    }
+
+    #[cfg(feature = "global-registry")]
+    #[test]
+    fn registers_globally() {
+        use super::{register, snapshot};
+
+        let threads =
+            [("global_a", "x|y"), ("global_b", "z")].map(|(n, re)| {
+                std::thread::spawn(move || {
+                    register(ParameterType::untyped(n, re)).unwrap();
+                })
+            });
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let registry = snapshot();
+        assert!(registry.lookup_by_name("int").unwrap().is_builtin());
+        assert_eq!(
+            registry.lookup_by_name("global_a").unwrap().regexp(),
+            "x|y",
+        );
+        assert!(registry.lookup_by_name("global_b").is_some());
+        assert_eq!(
+            register(ParameterType::untyped("global_a", "z")),
+            Err(DefineError::DuplicateName("global_a".to_owned())),
+        );
+    }
+
+    #[test]
+    fn strips_group() {
+        assert_eq!(strip_group("(a|b)"), "a|b");
+        assert_eq!(strip_group("(\\d+)(?:st|nd)"), "(\\d+)(?:st|nd)");
+        assert_eq!(strip_group("(a)|(b)"), "(a)|(b)");
+        assert_eq!(strip_group("(?:a)"), "(?:a)");
+        assert_eq!(strip_group("([)]+)"), "[)]+");
+        assert_eq!(strip_group("(\\))"), "\\)");
+    }
+}
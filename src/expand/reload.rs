@@ -0,0 +1,601 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`ReloadableRegistry`] of [`ParameterType`]s [defined][1] in a JSON or
+//! YAML file, reloaded once the file changes.
+//!
+//! Every reload [freezes][2] a new [`FrozenRegistry`] snapshot and atomically
+//! swaps it with the previous one, so the snapshots taken already are never
+//! mutated, while the failed reloads keep the previous snapshot in place.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use std::{fs, sync::Arc, time::Duration};
+//! #
+//! # use cucumber_expressions::{
+//! #     expand::reload::ReloadableRegistry,
+//! #     Expression,
+//! # };
+//! #
+//! let path = std::env::temp_dir()
+//!     .join(format!("cucumber-expressions-{}.yaml", std::process::id()));
+//! fs::write(&path, "- name: color\n  regexp: red|blue\n").unwrap();
+//!
+//! let registry = Arc::new(ReloadableRegistry::load(&path).unwrap());
+//! let _watcher = Arc::clone(&registry)
+//!     .watch(Duration::from_millis(100), |e| eprintln!("{}", e));
+//!
+//! let snapshot = registry.snapshot();
+//! let re = Expression::regex_with_parameters("{color}", &*snapshot).unwrap();
+//! assert!(re.is_match("red"));
+//!
+//! fs::write(&path, "- name: color\n  regexp: green|yellow\n").unwrap();
+//! registry.reload().unwrap();
+//!
+//! let snapshot = registry.snapshot();
+//! let re = Expression::regex_with_parameters("{color}", &*snapshot).unwrap();
+//! assert!(re.is_match("green"));
+//! # fs::remove_file(&path).unwrap();
+//! ```
+//!
+//! [`ParameterType`]: super::ParameterType
+//! [1]: super::registry::Definition
+//! [2]: Registry::freeze()
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, PoisonError, RwLock},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use derive_more::{Display, Error};
+
+use super::registry::{
+    DefineError, Definition, FrozenRegistry, ParameterType, Registry,
+};
+
+/// Stamp of a file version: its modification time (if supported by the
+/// platform) and length.
+type Stamp = (Option<SystemTime>, u64);
+
+/// [`Registry`] of [`ParameterType`]s [defined][1] in a JSON or YAML file,
+/// reloaded on demand or by a [`Watcher`].
+///
+/// The format of the file is detected by its extension: `.json`, `.yaml` or
+/// `.yml`. The file contains a sequence of [`Definition`]s, the same as the
+/// deserialized [`Registry`] does.
+///
+/// [1]: Definition
+#[derive(Debug)]
+pub struct ReloadableRegistry {
+    /// Path of the file to load the [`Definition`]s from.
+    path: PathBuf,
+
+    /// Base [`Registry`] the loaded [`Definition`]s are [scoped][1] in.
+    ///
+    /// [1]: Registry::scoped()
+    base: Arc<Registry>,
+
+    /// Currently loaded [`FrozenRegistry`] snapshot.
+    current: RwLock<Current>,
+}
+
+/// Currently loaded [`FrozenRegistry`] snapshot of a [`ReloadableRegistry`].
+#[derive(Debug)]
+struct Current {
+    /// Loaded [`FrozenRegistry`] snapshot.
+    snapshot: Arc<FrozenRegistry>,
+
+    /// [`Stamp`] of the file the [`Current::snapshot`] has been loaded from.
+    stamp: Stamp,
+
+    /// Version of the file the last reload has failed on, if any.
+    failed: Option<Failed>,
+}
+
+/// Version of the file a reload of a [`ReloadableRegistry`] has failed on.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Failed {
+    /// File's metadata couldn't be read.
+    Metadata,
+
+    /// File of the [`Stamp`] couldn't be loaded.
+    Load(Stamp),
+}
+
+impl Failed {
+    /// Returns the [`Failed`] version of the file, once its [`Stamp`] is read
+    /// as the given one.
+    fn of(stamp: &Result<Stamp, ReloadError>) -> Self {
+        stamp.as_ref().map_or(Self::Metadata, |s| Self::Load(*s))
+    }
+}
+
+impl ReloadableRegistry {
+    /// Loads a new [`ReloadableRegistry`] from the file at the given `path`,
+    /// with the [built-in][1] [`ParameterType`]s pre-registered.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, parsed or its [`Definition`]s can't be
+    /// [defined][2].
+    ///
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    /// [2]: Registry::define()
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ReloadError> {
+        Self::scoped(Arc::new(Registry::new()), path)
+    }
+
+    /// Loads a new [`ReloadableRegistry`] from the file at the given `path`,
+    /// [scoped][1] in the given `base` [`Registry`].
+    ///
+    /// Allows keeping the [`ParameterType`]s with typed transformers defined
+    /// in code, while the file adds new ones or overrides them.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, parsed or its [`Definition`]s can't be
+    /// [defined][2].
+    ///
+    /// [1]: Registry::scoped()
+    /// [2]: Registry::define()
+    pub fn scoped(
+        base: Arc<Registry>,
+        path: impl Into<PathBuf>,
+    ) -> Result<Self, ReloadError> {
+        let path = path.into();
+        let stamp = stamp(&path)?;
+        let snapshot = load(&base, &path)?;
+        Ok(Self {
+            path,
+            base,
+            current: RwLock::new(Current {
+                snapshot,
+                stamp,
+                failed: None,
+            }),
+        })
+    }
+
+    /// Returns the path of the file this [`ReloadableRegistry`] is loaded
+    /// from.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the currently loaded [`FrozenRegistry`] snapshot.
+    ///
+    /// The returned snapshot isn't affected by further reloads, so should be
+    /// taken again to observe them.
+    #[must_use]
+    pub fn snapshot(&self) -> Arc<FrozenRegistry> {
+        Arc::clone(
+            &self
+                .current
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .snapshot,
+        )
+    }
+
+    /// Reloads this [`ReloadableRegistry`] from its file unconditionally.
+    ///
+    /// The snapshot loaded from an older version of the file than the current
+    /// one (like by a concurrent [`Watcher`]) is discarded.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, parsed or its [`Definition`]s can't be
+    /// [defined][1]. The previous snapshot is kept in such case.
+    ///
+    /// [1]: Registry::define()
+    pub fn reload(&self) -> Result<(), ReloadError> {
+        self.swap(stamp(&self.path)).map(drop)
+    }
+
+    /// Reloads this [`ReloadableRegistry`] from its file, if it has been
+    /// modified since the last load, detected by its modification time and
+    /// length.
+    ///
+    /// Returns whether the snapshot has been swapped.
+    ///
+    /// # Errors
+    ///
+    /// See [`ReloadableRegistry::reload()`] for details. Every modification of
+    /// the file is reported only once, so the same version failing to reload
+    /// isn't retried until the file is modified again.
+    pub fn reload_if_modified(&self) -> Result<bool, ReloadError> {
+        let stamp = stamp(&self.path);
+        {
+            let current =
+                self.current.read().unwrap_or_else(PoisonError::into_inner);
+            if stamp.as_ref().ok() == Some(&current.stamp)
+                || current.failed == Some(Failed::of(&stamp))
+            {
+                return Ok(false);
+            }
+        }
+        self.swap(stamp)
+    }
+
+    /// Loads a new snapshot from the file of the given [`Stamp`], and swaps the
+    /// current one with it, unless the current one is loaded from a newer
+    /// version of the file already.
+    ///
+    /// Returns whether the snapshot has been swapped.
+    ///
+    /// # Errors
+    ///
+    /// See [`ReloadableRegistry::reload()`] for details. The [`Stamp`] of the
+    /// failed reload is remembered in such case.
+    fn swap(
+        &self,
+        stamp: Result<Stamp, ReloadError>,
+    ) -> Result<bool, ReloadError> {
+        let failed = Failed::of(&stamp);
+        let loaded = stamp.and_then(|version| {
+            load(&self.base, &self.path).map(|snapshot| (snapshot, version))
+        });
+
+        let mut current =
+            self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let (snapshot, version) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                current.failed = Some(failed);
+                return Err(e);
+            }
+        };
+        current.failed = None;
+        if version.0 < current.stamp.0 {
+            return Ok(false);
+        }
+        current.snapshot = snapshot;
+        current.stamp = version;
+        Ok(true)
+    }
+
+    /// Spawns a [`Watcher`] thread [reloading][1] this [`ReloadableRegistry`]
+    /// every `interval`, once its file is modified, reporting failed reloads
+    /// to the given `on_error` callback.
+    ///
+    /// The thread stops once the returned [`Watcher`] is dropped.
+    ///
+    /// [1]: ReloadableRegistry::reload_if_modified()
+    #[must_use]
+    pub fn watch(
+        self: Arc<Self>,
+        interval: Duration,
+        mut on_error: impl FnMut(ReloadError) + Send + 'static,
+    ) -> Watcher {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || {
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                stopped.recv_timeout(interval)
+            {
+                if let Err(e) = self.reload_if_modified() {
+                    on_error(e);
+                }
+            }
+        });
+        Watcher {
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Handle of a thread watching a [`ReloadableRegistry`], returned by
+/// [`ReloadableRegistry::watch()`].
+///
+/// Stops the thread once dropped.
+#[derive(Debug)]
+pub struct Watcher {
+    /// Sender stopping the thread once dropped.
+    stop: Option<mpsc::Sender<()>>,
+
+    /// Handle of the thread.
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop = None;
+        if let Some(thread) = self.thread.take() {
+            // Panics of `on_error` callback are not propagated.
+            drop(thread.join());
+        }
+    }
+}
+
+/// Error of loading a [`ReloadableRegistry`].
+#[derive(Debug, Display, Error)]
+pub enum ReloadError {
+    /// File can't be read.
+    #[display(
+        fmt = "Failed to read parameter types from '{}': {}",
+        "path.display()",
+        source
+    )]
+    Io {
+        /// Path of the file.
+        path: PathBuf,
+
+        /// Underlying [`io::Error`].
+        source: io::Error,
+    },
+
+    /// File has an extension other than `.json`, `.yaml` or `.yml`.
+    #[display(
+        fmt = "Unsupported format of parameter types file '{}', expected a \
+               `.json`, `.yaml` or `.yml` one.",
+        "_0.display()"
+    )]
+    UnsupportedFormat(#[error(not(source))] PathBuf),
+
+    /// File contains an invalid JSON.
+    #[display(fmt = "Failed to parse parameter types: {}", _0)]
+    Json(serde_json::Error),
+
+    /// File contains an invalid YAML.
+    #[display(fmt = "Failed to parse parameter types: {}", _0)]
+    Yaml(serde_yaml::Error),
+
+    /// [`Definition`] can't be [defined][1].
+    ///
+    /// [1]: Registry::define()
+    #[display(fmt = "Failed to define parameter type: {}", _0)]
+    Define(DefineError),
+}
+
+/// Returns the current [`Stamp`] of the file at the given `path`.
+///
+/// # Errors
+///
+/// If the file's metadata can't be read.
+fn stamp(path: &Path) -> Result<Stamp, ReloadError> {
+    let meta = fs::metadata(path).map_err(|source| ReloadError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+    Ok((meta.modified().ok(), meta.len()))
+}
+
+/// Loads the [`Definition`]s from the file at the given `path`, scoped in the
+/// `base` [`Registry`], and freezes them.
+///
+/// The [`Stamp`] of the file should be taken before loading, so the
+/// modifications made during reading are reloaded next time.
+///
+/// # Errors
+///
+/// If the file can't be read, parsed or its [`Definition`]s can't be
+/// [defined][1].
+///
+/// [1]: Registry::define()
+fn load(
+    base: &Arc<Registry>,
+    path: &Path,
+) -> Result<Arc<FrozenRegistry>, ReloadError> {
+    let io_err = |source| ReloadError::Io {
+        path: path.to_owned(),
+        source,
+    };
+
+    let definitions = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let file = fs::File::open(path).map_err(io_err)?;
+            serde_json::from_reader::<_, Vec<Definition>>(io::BufReader::new(
+                file,
+            ))
+            .map_err(ReloadError::Json)?
+        }
+        Some("yaml" | "yml") => {
+            let file = fs::File::open(path).map_err(io_err)?;
+            serde_yaml::from_reader::<_, Vec<Definition>>(file)
+                .map_err(ReloadError::Yaml)?
+        }
+        Some(_) | None => {
+            return Err(ReloadError::UnsupportedFormat(path.to_owned()));
+        }
+    };
+
+    let mut registry = Registry::scoped(Arc::clone(base));
+    for def in definitions {
+        registry
+            .define(ParameterType::from(def))
+            .map_err(ReloadError::Define)?;
+    }
+    Ok(registry.freeze())
+}
+
+#[cfg(test)]
+mod spec {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process,
+        sync::{mpsc, Arc},
+        time::{Duration, SystemTime},
+    };
+
+    use crate::expand::{ParameterType, ParameterTypeRegistry};
+
+    use super::{ReloadError, ReloadableRegistry};
+
+    /// Temporary file, unique for the current process, removed once dropped.
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        /// Creates a new [`TempFile`] with the given `name` (containing the
+        /// test name to not clash with other tests) and `contents`.
+        fn new(name: &str, contents: &str) -> Self {
+            let file = Self(std::env::temp_dir().join(format!(
+                "cucumber-expressions-spec-{}-{}",
+                process::id(),
+                name,
+            )));
+            file.write(contents);
+            file
+        }
+
+        /// Overwrites this [`TempFile`] with the given `contents`.
+        fn write(&self, contents: &str) {
+            fs::write(&self.0, contents).unwrap();
+        }
+
+        /// Returns the path of this [`TempFile`].
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            // May be removed already by the test itself.
+            drop(fs::remove_file(&self.0));
+        }
+    }
+
+    /// Returns the [`Regex`] pattern of the `color` [`ParameterType`] of the
+    /// current snapshot of the given [`ReloadableRegistry`].
+    ///
+    /// [`Regex`]: regex::Regex
+    fn color(registry: &ReloadableRegistry) -> String {
+        registry
+            .snapshot()
+            .lookup_by_name("color")
+            .unwrap()
+            .regexp()
+            .to_owned()
+    }
+
+    #[test]
+    fn reloads_if_modified() {
+        let file = TempFile::new(
+            "reloads_if_modified.json",
+            r#"[{"name":"color","regexp":"r"}]"#,
+        );
+        let registry = ReloadableRegistry::load(file.path()).unwrap();
+        let old = registry.snapshot();
+
+        assert_eq!(color(&registry), "r");
+        assert!(registry.snapshot().lookup_by_name("int").is_some());
+        assert!(!registry.reload_if_modified().unwrap());
+
+        file.write(r#"[{"name":"color","regexp":"gb"}]"#);
+        assert!(registry.reload_if_modified().unwrap());
+        assert_eq!(color(&registry), "gb");
+        assert_eq!(old.lookup_by_name("color").unwrap().regexp(), "r");
+
+        file.write(r#"[{"name":"a","regexp":"a"},{"name":"a","regexp":"b"}]"#);
+        assert!(matches!(
+            registry.reload_if_modified(),
+            Err(ReloadError::Define(_)),
+        ));
+        assert!(!registry.reload_if_modified().unwrap());
+        file.write("[{");
+        assert!(matches!(registry.reload(), Err(ReloadError::Json(_))));
+        assert_eq!(color(&registry), "gb");
+
+        fs::remove_file(file.path()).unwrap();
+        assert!(matches!(registry.reload(), Err(ReloadError::Io { .. })));
+        assert_eq!(color(&registry), "gb");
+    }
+
+    #[test]
+    fn keeps_newer_snapshot() {
+        let file = TempFile::new(
+            "keeps_newer_snapshot.json",
+            r#"[{"name":"color","regexp":"r"}]"#,
+        );
+        let registry = ReloadableRegistry::load(file.path()).unwrap();
+
+        file.write(r#"[{"name":"color","regexp":"gb"}]"#);
+        let older = Ok((Some(SystemTime::UNIX_EPOCH), 0));
+        assert!(!registry.swap(older).unwrap());
+        assert_eq!(color(&registry), "r");
+
+        registry.reload().unwrap();
+        assert_eq!(color(&registry), "gb");
+    }
+
+    #[test]
+    fn scopes_in_base() {
+        let mut base = ParameterTypeRegistry::new();
+        base.define(ParameterType::<u8>::from_str("size", "\\d+"))
+            .unwrap();
+        base.define(ParameterType::untyped("color", "red")).unwrap();
+        let file = TempFile::new(
+            "scopes_in_base.yml",
+            "- name: color\n  regexp: blue\n",
+        );
+
+        let registry =
+            ReloadableRegistry::scoped(Arc::new(base), file.path()).unwrap();
+        assert_eq!(registry.path(), file.path());
+        assert_eq!(color(&registry), "blue");
+
+        let size = registry.snapshot();
+        let size = size.lookup_by_name("size").unwrap();
+        assert_eq!(size.transform("3").unwrap().downcast_ref(), Some(&3_u8));
+
+        file.write("- name: color\n  regex: [");
+        assert!(matches!(registry.reload(), Err(ReloadError::Yaml(_))));
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let file = TempFile::new("rejects_unknown_format.toml", "");
+        assert_eq!(
+            ReloadableRegistry::load(file.path())
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Unsupported format of parameter types file '{}', expected \
+                 a `.json`, `.yaml` or `.yml` one.",
+                file.path().display(),
+            ),
+        );
+    }
+
+    #[test]
+    fn watches() {
+        let file =
+            TempFile::new("watches.yaml", "- name: color\n  regexp: r\n");
+        let registry = Arc::new(ReloadableRegistry::load(file.path()).unwrap());
+        let (errors, errored) = mpsc::channel();
+        let watcher =
+            Arc::clone(&registry).watch(Duration::from_millis(10), move |e| {
+                errors.send(e.to_string()).unwrap();
+            });
+
+        file.write("- name: color\n  regexp: gb\n");
+        for _ in 0..500 {
+            if color(&registry) == "gb" {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(color(&registry), "gb");
+
+        fs::remove_file(file.path()).unwrap();
+        let err = errored.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(err.starts_with("Failed to read"), "wrong err: {}", err);
+        assert!(errored.recv_timeout(Duration::from_millis(100)).is_err());
+
+        drop(watcher);
+        assert_eq!(Arc::strong_count(&registry), 1);
+    }
+}
@@ -98,16 +98,49 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod ast;
+pub mod catalog;
 mod combinator;
-#[cfg(feature = "into-regex")]
+pub mod diagnostic;
+pub mod event;
+#[cfg(any(feature = "into-regex", feature = "into-regex-lite"))]
 pub mod expand;
+pub mod matcher;
 pub mod parse;
 
 // TODO: Remove once `derive_more` 0.99.17 is released.
 use syn as _;
 
+#[cfg(all(
+    feature = "indexmap",
+    not(any(feature = "into-regex", feature = "into-regex-lite")),
+))]
+use indexmap as _;
+#[cfg(all(
+    feature = "phf",
+    not(any(feature = "into-regex", feature = "into-regex-lite")),
+))]
+use phf as _;
+#[cfg(all(
+    feature = "serde",
+    not(any(feature = "into-regex", feature = "into-regex-lite")),
+))]
+use serde as _;
+#[cfg(all(
+    feature = "unicode-normalization",
+    not(any(feature = "into-regex", feature = "into-regex-lite")),
+))]
+use unicode_normalization as _;
+
+#[cfg(all(test, not(feature = "phf")))]
+use phf as _;
+#[cfg(all(test, not(all(feature = "serde", feature = "into-regex"))))]
+use serde_json as _;
+
 #[doc(inline)]
 pub use self::ast::{
-    Alternation, Alternative, Expression, Optional, Parameter,
-    SingleAlternation, SingleExpression, Spanned,
+    Alternation, Alternative, AnyExpression, Expression, ExpressionKind,
+    Optional, OptionalParameter, Parameter, SingleAlternation,
+    SingleExpression, Span, Spanned, TextChar, TextChars,
 };
+#[doc(inline)]
+pub use self::diagnostic::Diagnostic;
@@ -0,0 +1,534 @@
+// Copyright (c) 2021  Brendan Molloy <brendan@bbqsrc.net>,
+//                     Ilya Solovyiov <ilya.solovyiov@gmail.com>,
+//                     Kai Ren <tyranron@gmail.com>
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Matching of texts against [`Expression`]s directly, by interpreting their
+//! [AST] without expanding it into a regex.
+//!
+//! Doesn't require any regex engine, so is available without any Cargo
+//! features enabled.
+//!
+//! [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
+
+use crate::{
+    Alternative, Diagnostic, Expression, SingleExpression, Span, TextChars,
+};
+
+#[allow(clippy::multiple_inherent_impl)] // because of `matcher` module
+impl<Input: Span> Expression<Input> {
+    /// Matches the whole given `text` against this [`Expression`] directly,
+    /// without expanding it into a regex, returning texts captured by its
+    /// [`Parameter`]s, in order.
+    ///
+    /// Matches the same texts as the regex expanded with the default options
+    /// does (backtracking into [`Optional`]s, [`Alternation`]s and
+    /// [`Parameter`]s when needed), except that digits are matched as ASCII
+    /// ones only. Only the [built-in][1] [`Parameter`]s are supported, except
+    /// the ones enabled by `extra-params` feature, and not inside
+    /// [`OptionalParameter`]s.
+    ///
+    /// Returns [`None`] if the `text` doesn't match.
+    ///
+    /// # Errors
+    ///
+    /// With [`Diagnostic::UnknownParameter`] if this [`Expression`] contains an
+    /// unsupported [`Parameter`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use cucumber_expressions::Expression;
+    /// #
+    /// let expr =
+    ///     Expression::parse("I have {int} cucumber(s) in my {word}").unwrap();
+    ///
+    /// assert_eq!(
+    ///     expr.match_str("I have 42 cucumbers in my belly").unwrap(),
+    ///     Some(vec!["42", "belly"]),
+    /// );
+    /// assert_eq!(expr.match_str("I have 1 cucumber").unwrap(), None);
+    /// ```
+    ///
+    /// [`Alternation`]: crate::Alternation
+    /// [`Optional`]: crate::Optional
+    /// [`OptionalParameter`]: crate::OptionalParameter
+    /// [`Parameter`]: crate::Parameter
+    /// [1]: https://github.com/cucumber/cucumber-expressions#parameter-types
+    pub fn match_str<'t>(
+        &self,
+        text: &'t str,
+    ) -> Result<Option<Vec<&'t str>>, Diagnostic<Input>> {
+        let nodes =
+            self.iter().map(Node::new).collect::<Result<Vec<_>, _>>()?;
+
+        let mut captures = Vec::new();
+        Ok(Matching { text }
+            .nodes(&nodes, None, 0, &mut captures)
+            .then(|| captures))
+    }
+}
+
+/// [`SingleExpression`] prepared for matching.
+#[derive(Debug)]
+enum Node {
+    /// Cooked text to be matched literally.
+    Text(String),
+
+    /// Cooked text to be matched optionally.
+    Optional(String),
+
+    /// Branches to be matched in order, consisting of [`Node::Text`]s and
+    /// [`Node::Optional`]s only.
+    Alternation(Vec<Vec<Self>>),
+
+    /// Built-in parameter.
+    Parameter(Builtin),
+}
+
+impl Node {
+    /// Prepares the given [`SingleExpression`] for matching.
+    ///
+    /// # Errors
+    ///
+    /// If the [`SingleExpression`] is an unsupported [`Parameter`].
+    ///
+    /// [`Parameter`]: crate::Parameter
+    fn new<Input: Span>(
+        expr: &SingleExpression<Input>,
+    ) -> Result<Self, Diagnostic<Input>> {
+        Ok(match expr {
+            SingleExpression::Text(t) | SingleExpression::Whitespaces(t) => {
+                Self::Text(TextChars::new(t).cooked())
+            }
+            SingleExpression::Optional(opt) => {
+                Self::Optional(opt.chars().cooked())
+            }
+            SingleExpression::Alternation(alt) => Self::Alternation(
+                alt.iter()
+                    .map(|single| {
+                        single
+                            .iter()
+                            .map(|a| match a {
+                                Alternative::Text(t) => {
+                                    Self::Text(TextChars::new(t).cooked())
+                                }
+                                Alternative::Optional(opt) => {
+                                    Self::Optional(opt.chars().cooked())
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            ),
+            // Missing captures can't be represented.
+            SingleExpression::OptionalParameter(opt) => {
+                return Err(Diagnostic::UnknownParameter(
+                    opt.parameter.0.clone(),
+                ));
+            }
+            SingleExpression::Parameter(p) => Self::Parameter(
+                Builtin::new(&p.chars().cooked())
+                    .ok_or_else(|| Diagnostic::UnknownParameter(p.0.clone()))?,
+            ),
+        })
+    }
+}
+
+/// Built-in parameter, scanned without a regex.
+#[derive(Clone, Copy, Debug)]
+enum Builtin {
+    /// `{int}` and its aliases, matching `-?\d+`.
+    Int,
+
+    /// `{float}` and its aliases, matching
+    /// `[-+]?(?:\d*\.\d+|\d+)(?:[Ee][+-]?\d+)?`.
+    Float,
+
+    /// `{boolean}`, matching `true|false`.
+    Boolean,
+
+    /// `{ordinal}`, matching `\d+(?:st|nd|rd|th)` and capturing the number.
+    Ordinal,
+
+    /// `{word}`, matching `[^\s]+`.
+    Word,
+
+    /// `{string}`, matching a single-quoted or double-quoted string.
+    String,
+
+    /// Anonymous `{}`, matching `.*`.
+    Anonymous,
+}
+
+impl Builtin {
+    /// Returns the [`Builtin`] parameter with the given `name`, if any.
+    fn new(name: &str) -> Option<Self> {
+        Some(match name {
+            "int" | "byte" | "short" | "long" | "biginteger" => Self::Int,
+            "float" | "double" | "bigdecimal" => Self::Float,
+            "boolean" => Self::Boolean,
+            "ordinal" => Self::Ordinal,
+            "word" => Self::Word,
+            "string" => Self::String,
+            "" => Self::Anonymous,
+            _ => return None,
+        })
+    }
+
+    /// Returns all the ways this [`Builtin`] parameter matches the start of
+    /// the given `text`, as `(matched, captured)` lengths in bytes, in order of
+    /// preference.
+    fn scan(self, text: &str) -> Vec<(usize, usize)> {
+        let same = |len| (len, len);
+        match self {
+            Self::Int => {
+                let sign = usize::from(text.starts_with('-'));
+                let digits = ascii_digits(&text[sign..]);
+                (1..=digits).rev().map(|d| same(sign + d)).collect()
+            }
+            Self::Float => {
+                let extent = text
+                    .find(|c: char| !"+-.0123456789Ee".contains(c))
+                    .unwrap_or(text.len());
+                (1..=extent)
+                    .rev()
+                    .filter(|len| is_float(&text[..*len]))
+                    .map(same)
+                    .collect()
+            }
+            Self::Boolean => ["true", "false"]
+                .iter()
+                .filter(|b| text.starts_with(*b))
+                .map(|b| same(b.len()))
+                .collect(),
+            Self::Ordinal => (1..=ascii_digits(text))
+                .rev()
+                .filter(|d| {
+                    ["st", "nd", "rd", "th"]
+                        .iter()
+                        .any(|s| text[*d..].starts_with(s))
+                })
+                .map(|d| (d + 2, d))
+                .collect(),
+            Self::Word => {
+                let extent =
+                    text.find(char::is_whitespace).unwrap_or(text.len());
+                char_ends(&text[..extent]).rev().map(same).collect()
+            }
+            Self::String => quoted_len(text).map(same).into_iter().collect(),
+            Self::Anonymous => {
+                let extent = text.find('\n').unwrap_or(text.len());
+                char_ends(&text[..extent])
+                    .rev()
+                    .chain(Some(0))
+                    .map(same)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// State of matching a single `text`.
+#[derive(Clone, Copy, Debug)]
+struct Matching<'t> {
+    /// Whole text being matched.
+    text: &'t str,
+}
+
+/// [`Node`]s to be matched after the current ones, forming a stack.
+#[derive(Clone, Copy, Debug)]
+struct Continuation<'n, 'c> {
+    /// [`Node`]s to be matched next.
+    nodes: &'n [Node],
+
+    /// [`Continuation`] to be matched after these [`Node`]s.
+    next: Option<&'c Self>,
+}
+
+impl<'t> Matching<'t> {
+    /// Indicates whether the given `nodes`, followed by the `next`
+    /// [`Continuation`], match the rest of the text starting at the `pos`
+    /// byte, pushing the captured texts into the given `captures`.
+    ///
+    /// `captures` are left unchanged on mismatch.
+    fn nodes(
+        self,
+        nodes: &[Node],
+        next: Option<&Continuation<'_, '_>>,
+        pos: usize,
+        captures: &mut Vec<&'t str>,
+    ) -> bool {
+        let (node, rest) = match nodes.split_first() {
+            Some(split) => split,
+            None => {
+                return next.map_or(pos == self.text.len(), |cont| {
+                    self.nodes(cont.nodes, cont.next, pos, captures)
+                })
+            }
+        };
+        let text = &self.text[pos..];
+
+        match node {
+            Node::Text(t) => {
+                text.starts_with(t.as_str())
+                    && self.nodes(rest, next, pos + t.len(), captures)
+            }
+            Node::Optional(t) => {
+                (text.starts_with(t.as_str())
+                    && self.nodes(rest, next, pos + t.len(), captures))
+                    || self.nodes(rest, next, pos, captures)
+            }
+            Node::Alternation(branches) => {
+                let cont = Continuation { nodes: rest, next };
+                branches
+                    .iter()
+                    .any(|b| self.nodes(b, Some(&cont), pos, captures))
+            }
+            Node::Parameter(p) => p.scan(text).into_iter().any(|(len, cap)| {
+                captures.push(&text[..cap]);
+                let matched = self.nodes(rest, next, pos + len, captures);
+                if !matched {
+                    let _ = captures.pop();
+                }
+                matched
+            }),
+        }
+    }
+}
+
+/// Returns the number of leading ASCII digits in the given `text`.
+fn ascii_digits(text: &str) -> usize {
+    text.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+/// Returns byte offsets of the ends of all the [`char`]s in the given `text`.
+fn char_ends(text: &str) -> impl DoubleEndedIterator<Item = usize> + '_ {
+    text.char_indices().map(|(i, c)| i + c.len_utf8())
+}
+
+/// Indicates whether the given `text` is a float matched by a `{float}`
+/// parameter as a whole.
+fn is_float(text: &str) -> bool {
+    let mut rest = text.strip_prefix(['-', '+'].as_ref()).unwrap_or(text);
+
+    let int = ascii_digits(rest);
+    rest = &rest[int..];
+    let fraction = rest.strip_prefix('.').map(ascii_digits);
+    match fraction {
+        Some(0) => return false,
+        Some(digits) => rest = &rest[1 + digits..],
+        None if int == 0 => return false,
+        None => {}
+    }
+
+    rest.strip_prefix(['E', 'e'].as_ref())
+        .map_or(rest.is_empty(), |exp| {
+            let exp = exp.strip_prefix(['-', '+'].as_ref()).unwrap_or(exp);
+            let digits = ascii_digits(exp);
+            digits > 0 && digits == exp.len()
+        })
+}
+
+/// Returns the length of a single-quoted or double-quoted string at the start
+/// of the given `text`, if any.
+///
+/// Quotes may be escaped with `\` inside the string.
+fn quoted_len(text: &str) -> Option<usize> {
+    let quote = text.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        if c == quote {
+            return Some(i + 1);
+        }
+        if c == '\\' {
+            // Like `.` in a regex, an escaped character can't be a newline.
+            let _ = chars.next().filter(|(_, e)| *e != '\n')?;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod spec {
+    use crate::{Diagnostic, Expression};
+
+    #[test]
+    fn text_and_optionals() {
+        let expr = Expression::parse("I have cucumber(s) \\(fresh\\)").unwrap();
+
+        for (text, expected) in [
+            ("I have cucumber (fresh)", true),
+            ("I have cucumbers (fresh)", true),
+            ("I have cucumberss (fresh)", false),
+            ("I have cucumbers fresh", false),
+            ("I have cucumbers (fresh) ", false),
+        ] {
+            assert_eq!(
+                expr.match_str(text).unwrap().is_some(),
+                expected,
+                "on input: {}",
+                text,
+            );
+        }
+    }
+
+    #[test]
+    fn alternation() {
+        let expr = Expression::parse("{int} cucumber(s)/apple(s)/a(n)/an(d)x")
+            .unwrap();
+
+        for (text, expected) in [
+            ("1 cucumber", true),
+            ("1 cucumbers", true),
+            ("1 apples", true),
+            ("1 a", true),
+            ("1 an", true),
+            ("1 andx", true),
+            ("1 anx", true),
+            ("1 ax", false),
+            ("1 pear", false),
+        ] {
+            assert_eq!(
+                expr.match_str(text).unwrap().is_some(),
+                expected,
+                "on input: {}",
+                text,
+            );
+        }
+    }
+
+    #[test]
+    fn parameters() {
+        let expr = Expression::parse(
+            "{int} {float} {boolean} {ordinal} {word} {string} {}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            expr.match_str("-1 +2.5E-3 true 42nd word 'it\\'s' any thing")
+                .unwrap(),
+            Some(vec![
+                "-1",
+                "+2.5E-3",
+                "true",
+                "42",
+                "word",
+                "'it\\'s'",
+                "any thing",
+            ]),
+        );
+        assert_eq!(expr.match_str("1 2 yes 1st w 's' x").unwrap(), None);
+    }
+
+    #[test]
+    fn backtracking() {
+        let expr = Expression::parse("{int}{int} {} and {}").unwrap();
+
+        assert_eq!(
+            expr.match_str("123 a and b and c").unwrap(),
+            Some(vec!["12", "3", "a and b", "c"]),
+        );
+        assert_eq!(
+            Expression::parse("{word}s")
+                .unwrap()
+                .match_str("wordss")
+                .unwrap(),
+            Some(vec!["words"]),
+        );
+        assert_eq!(
+            Expression::parse("{}").unwrap().match_str("").unwrap(),
+            Some(vec![""]),
+        );
+        assert_eq!(
+            Expression::parse("{}").unwrap().match_str("a\nb").unwrap(),
+            None,
+        );
+    }
+
+    #[test]
+    fn floats() {
+        let expr = Expression::parse("{float}").unwrap();
+
+        for matching in
+            ["1", "-1.5", ".5", "+10", "1E10", "1.5E-3", "1.5e3", "2e+1"]
+        {
+            assert!(
+                expr.match_str(matching).unwrap().is_some(),
+                "on input: {}",
+                matching,
+            );
+        }
+        for not_matching in ["", ".", "1.", "-", "E10", "e10", "1.5e", "1..5"] {
+            assert!(
+                expr.match_str(not_matching).unwrap().is_none(),
+                "on input: {}",
+                not_matching,
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_parameter() {
+        let expr = Expression::parse("{int} {color}").unwrap();
+
+        match expr.match_str("1 red").unwrap_err() {
+            Diagnostic::UnknownParameter(p) => assert_eq!(*p, "color"),
+            e @ (Diagnostic::Parsing(_)
+            | Diagnostic::UnmatchedReservedCharacter(_)
+            | Diagnostic::AdjacentAnonymousParameters(_)) => {
+                panic!("wrong err: {}", e)
+            }
+        }
+    }
+
+    #[cfg(feature = "into-regex")]
+    #[test]
+    fn same_as_regex() {
+        for (expr, texts) in [
+            (
+                "I have {int} cucumber(s) in my belly/stomach",
+                &[
+                    "I have 1 cucumber in my belly",
+                    "I have -5 cucumbers in my stomach",
+                    "I have 1 cucumbers in my",
+                ][..],
+            ),
+            (
+                "{float} {string}",
+                &["1.5 \"a b\"", ".5 'a'", "1. 'a'", "1 \"a"],
+            ),
+            ("{word}{int}", &["abc123", "123", "a-1"]),
+            (
+                "a(b)c/d(e) {ordinal}",
+                &["ac 1st", "abc 2nd", "de 3rd", "d 4", "d 11th"],
+            ),
+        ] {
+            let re = Expression::regex(expr)
+                .unwrap_or_else(|e| panic!("failed: {}", e));
+            let parsed = Expression::parse(expr).unwrap();
+
+            for text in texts {
+                let expected = re.captures(text).map(|caps| {
+                    caps.iter()
+                        .skip(1)
+                        .map(|m| m.map_or("", |m| m.as_str()))
+                        .collect::<Vec<_>>()
+                });
+
+                assert_eq!(
+                    parsed.match_str(text).unwrap(),
+                    expected,
+                    "on expression `{}` and input: {}",
+                    expr,
+                    text,
+                );
+            }
+        }
+    }
+}
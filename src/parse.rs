@@ -16,34 +16,141 @@
 //! [1]: https://github.com/cucumber/cucumber-expressions#readme
 //! [AST]: https://en.wikipedia.org/wiki/Abstract_syntax_tree
 
-use std::{fmt::Display, ops::RangeFrom};
+use std::fmt::Display;
 
 use derive_more::{Display, Error};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while, take_while1},
-    character::complete::one_of,
-    combinator::{map, peek, verify},
+    character::complete::satisfy,
+    combinator::{map, not, peek, verify},
     error::{ErrorKind, ParseError},
-    multi::{many0, many1, separated_list1},
+    multi::many0,
     sequence::tuple,
-    AsChar, Compare, Err, FindToken, IResult, InputIter, InputLength,
-    InputTake, InputTakeAtPosition, Needed, Offset, Parser, Slice,
+    AsChar, Err, IResult, Needed, Parser,
 };
 
 use crate::{
     ast::{
-        Alternation, Alternative, Expression, Optional, Parameter,
-        SingleExpression,
+        Alternation, Alternative, Expression, Optional, OptionalParameter,
+        Parameter, SingleAlternation, SingleExpression, Span,
     },
     combinator,
 };
 
 /// Reserved characters requiring a special handling.
-pub const RESERVED_CHARS: &str = r#"{}()\/ "#;
+pub const RESERVED_CHARS: &str = r"{}()\/ ";
+
+/// Set of [`RESERVED_CHARS`] having a special meaning while parsing.
+///
+/// Disabling a construct makes its characters a plain text, so they don't need
+/// to be escaped anymore, while the existing escapes of them are still
+/// accepted. Whitespace and `\` are always reserved.
+///
+/// # Example
+///
+/// ```rust
+/// # use cucumber_expressions::{parse::Syntax, Expression};
+/// #
+/// let syntax = Syntax {
+///     alternation: false,
+///     ..Syntax::default()
+/// };
+/// let expr =
+///     Expression::parse_with_syntax("GET /users/{int}", syntax).unwrap();
+///
+/// assert_eq!(expr.len(), 4);
+/// ```
+#[allow(clippy::struct_excessive_bools)] // independent flags
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Syntax {
+    /// Whether `/` denotes an [`Alternation`].
+    pub alternation: bool,
+
+    /// Whether `(` and `)` denote an [`Optional`].
+    pub optional: bool,
+
+    /// Whether `{` and `}` denote a [`Parameter`].
+    pub parameter: bool,
+
+    /// Whether an [`Optional`] may contain a single [`Parameter`], being
+    /// parsed as an [`OptionalParameter`] (like `( with {int} retries)`),
+    /// instead of failing with an [`Error::ParameterInOptional`].
+    ///
+    /// Takes effect only along with [`Syntax::optional`] and
+    /// [`Syntax::parameter`].
+    pub optional_parameters: bool,
+
+    /// Handling of newlines embedded into an [`Expression`].
+    pub newlines: Newlines,
+}
+
+/// Handling of newlines (`\n` and `\r`) embedded into an [`Expression`], for
+/// example, spanning multiple lines of a raw string.
+///
+/// Either way, spans of the parsed [`Expression`] keep pointing to the original
+/// lines and columns.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Newlines {
+    /// Newlines are treated as a plain text and are matched literally.
+    Literal,
+
+    /// Newlines are not allowed, resulting in an
+    /// [`Error::UnexpectedNewline`].
+    Forbidden,
+}
+
+impl Default for Syntax {
+    fn default() -> Self {
+        Self::CUCUMBER
+    }
+}
+
+impl Syntax {
+    /// Default [Cucumber Expressions][0] syntax, having all the
+    /// [`RESERVED_CHARS`] enabled.
+    ///
+    /// [0]: https://github.com/cucumber/cucumber-expressions#readme
+    pub const CUCUMBER: Self = Self {
+        alternation: true,
+        optional: true,
+        parameter: true,
+        optional_parameters: false,
+        newlines: Newlines::Literal,
+    };
+
+    /// Checks whether the given `c`haracter is reserved in this [`Syntax`].
+    #[must_use]
+    pub const fn is_reserved(&self, c: char) -> bool {
+        match c {
+            '/' => self.alternation,
+            '(' | ')' => self.optional,
+            '{' | '}' => self.parameter,
+            ' ' | '\\' => true,
+            _ => false,
+        }
+    }
+
+    /// Checks whether the given `c`haracter is a newline forbidden in this
+    /// [`Syntax`].
+    #[must_use]
+    pub const fn is_forbidden_newline(&self, c: char) -> bool {
+        matches!(c, '\n' | '\r') && matches!(self.newlines, Newlines::Forbidden)
+    }
+
+    /// Checks whether the given `c`haracter is one of the `special` ones and is
+    /// reserved in this [`Syntax`], or is a forbidden newline.
+    fn is_special(self, c: char, special: &str) -> bool {
+        special.contains(c) && self.is_reserved(c)
+            || self.is_forbidden_newline(c)
+    }
+}
 
 /// Matches `normal` and [`RESERVED_CHARS`] escaped with `\`.
 ///
+/// All the [`RESERVED_CHARS`] may be escaped regardless of the [`Syntax`], so
+/// disabling a construct doesn't break the existing escapes of its characters.
+///
 /// Uses [`combinator::escaped0`] under the hood.
 ///
 /// # Errors
@@ -62,25 +169,19 @@ pub const RESERVED_CHARS: &str = r#"{}()\/ "#;
 /// [`EscapedEndOfLine`]: Error::EscapedEndOfLine
 /// [`EscapedNonReservedCharacter`]: Error::EscapedNonReservedCharacter
 /// [`Failure`]: Err::Failure
-fn escaped_reserved_chars0<'a, Input: 'a, F, O1>(
+fn escaped_reserved_chars0<Input, F, O1>(
     normal: F,
 ) -> impl FnMut(Input) -> IResult<Input, Input, Error<Input>>
 where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition
-        + Slice<RangeFrom<usize>>
-        + InputIter,
-    <Input as InputIter>::Item: AsChar + Copy,
+    Input: Span,
     F: Parser<Input, O1, Error<Input>>,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
 {
     combinator::map_err(
-        combinator::escaped0(normal, '\\', one_of(RESERVED_CHARS)),
+        combinator::escaped0(
+            normal,
+            '\\',
+            satisfy(|c| RESERVED_CHARS.contains(c)),
+        ),
         |e| {
             if let Err::Error(Error::Other(span, ErrorKind::Escaped)) = e {
                 match span.input_len() {
@@ -141,30 +242,30 @@ where
 /// [`UnescapedReservedCharacter`]: Error::UnescapedReservedCharacter
 /// [`UnfinishedParameter`]: Error::UnfinishedParameter
 /// [0]: crate#grammar
-pub fn parameter<'a, Input: 'a>(
+pub fn parameter<Input: Span>(
     input: Input,
-) -> IResult<Input, Parameter<Input>, Error<Input>>
-where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
-{
-    let is_name = |c| !"{}(\\/".contains(c);
+) -> IResult<Input, Parameter<Input>, Error<Input>> {
+    parameter_with(Syntax::default())(input)
+}
 
-    let fail = |input: Input, opening_brace| {
+/// Creates a [`parameter()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`parameter()`] ones. Recoverable [`Error`] is returned in case
+/// [`Syntax::parameter`] is disabled.
+///
+/// [`Error`]: Err::Error
+pub fn parameter_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Parameter<Input>, Error<Input>> {
+    let is_name = move |c| !syntax.is_special(c, "{}(\\/");
+
+    let fail = move |input: Input, opening_brace| {
         match input.iter_elements().next().map(AsChar::as_char) {
-            Some('{') => {
+            Some('{') if syntax.parameter => {
                 if let Ok((_, (par, ..))) = peek(tuple((
-                    parameter,
+                    parameter_with(syntax),
                     escaped_reserved_chars0(take_while(is_name)),
                     tag("}"),
                 )))(input.clone())
@@ -177,8 +278,9 @@ where
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
-            Some('(') => {
-                if let Ok((_, opt)) = peek(optional)(input.clone()) {
+            Some('(') if syntax.optional => {
+                if let Ok((_, opt)) = peek(optional_with(syntax))(input.clone())
+                {
                     return Error::OptionalInParameter(
                         input.take(opt.0.input_len() + 2),
                     )
@@ -187,23 +289,32 @@ where
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
-            Some(c) if RESERVED_CHARS.contains(c) => {
+            Some(c) if syntax.is_reserved(c) => {
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
+            Some(c) if syntax.is_forbidden_newline(c) => {
+                return Error::UnexpectedNewline(input.take(1)).failure();
+            }
             _ => {}
         }
         Error::UnfinishedParameter(opening_brace).failure()
     };
 
-    let (input, opening_brace) = tag("{")(input)?;
-    let (input, par_name) =
-        escaped_reserved_chars0(take_while(is_name))(input)?;
-    let (input, _) = combinator::map_err(tag("}"), |_| {
-        fail(input.clone(), opening_brace.clone())
-    })(input.clone())?;
+    move |input: Input| {
+        if !syntax.parameter {
+            return Err(Err::Error(Error::Other(input, ErrorKind::Tag)));
+        }
+
+        let (input, opening_brace) = tag("{")(input)?;
+        let (input, par_name) =
+            escaped_reserved_chars0(take_while(is_name))(input)?;
+        let (input, _) = combinator::map_err(tag("}"), |_| {
+            fail(input.clone(), opening_brace.clone())
+        })(input.clone())?;
 
-    Ok((input, Parameter(par_name)))
+        Ok((input, Parameter(par_name)))
+    }
 }
 
 /// Parses an `optional` as defined in the [grammar spec][0].
@@ -254,30 +365,30 @@ where
 /// [`UnescapedReservedCharacter`]: Error::UnescapedReservedCharacter
 /// [`UnfinishedOptional`]: Error::UnfinishedOptional
 /// [0]: crate#grammar
-pub fn optional<'a, Input: 'a>(
+pub fn optional<Input: Span>(
     input: Input,
-) -> IResult<Input, Optional<Input>, Error<Input>>
-where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
-{
-    let is_in_optional = |c| !"(){\\/".contains(c);
+) -> IResult<Input, Optional<Input>, Error<Input>> {
+    optional_with(Syntax::default())(input)
+}
+
+/// Creates an [`optional()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`optional()`] ones. Recoverable [`Error`] is returned in case
+/// [`Syntax::optional`] is disabled.
+///
+/// [`Error`]: Err::Error
+pub fn optional_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Optional<Input>, Error<Input>> {
+    let is_in_optional = move |c| !syntax.is_special(c, "(){\\/");
 
-    let fail = |input: Input, opening_brace| {
+    let fail = move |input: Input, opening_brace| {
         match input.iter_elements().next().map(AsChar::as_char) {
-            Some('(') => {
+            Some('(') if syntax.optional => {
                 if let Ok((_, (opt, ..))) = peek(tuple((
-                    optional,
+                    optional_with(syntax),
                     escaped_reserved_chars0(take_while(is_in_optional)),
                     tag(")"),
                 )))(input.clone())
@@ -290,8 +401,10 @@ where
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
-            Some('{') => {
-                if let Ok((_, par)) = peek(parameter)(input.clone()) {
+            Some('{') if syntax.parameter => {
+                if let Ok((_, par)) =
+                    peek(parameter_with(syntax))(input.clone())
+                {
                     return Error::ParameterInOptional(
                         input.take(par.0.input_len() + 2),
                     )
@@ -300,31 +413,121 @@ where
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
-            Some('/') => {
+            Some('/') if syntax.alternation => {
                 return Error::AlternationInOptional(input.take(1)).failure();
             }
-            Some(c) if RESERVED_CHARS.contains(c) => {
+            Some(c) if syntax.is_reserved(c) => {
                 return Error::UnescapedReservedCharacter(input.take(1))
                     .failure();
             }
+            Some(c) if syntax.is_forbidden_newline(c) => {
+                return Error::UnexpectedNewline(input.take(1)).failure();
+            }
             _ => {}
         }
         Error::UnfinishedOptional(opening_brace).failure()
     };
 
-    let original_input = input.clone();
-    let (input, opening_paren) = tag("(")(input)?;
-    let (input, opt) =
-        escaped_reserved_chars0(take_while(is_in_optional))(input)?;
-    let (input, _) = combinator::map_err(tag(")"), |_| {
-        fail(input.clone(), opening_paren.clone())
-    })(input.clone())?;
+    move |input: Input| {
+        if !syntax.optional {
+            return Err(Err::Error(Error::Other(input, ErrorKind::Tag)));
+        }
+
+        let original_input = input.clone();
+        let (input, opening_paren) = tag("(")(input)?;
+        let (input, opt) =
+            escaped_reserved_chars0(take_while(is_in_optional))(input)?;
+        let (input, _) = combinator::map_err(tag(")"), |_| {
+            fail(input.clone(), opening_paren.clone())
+        })(input.clone())?;
 
-    if opt.input_len() == 0 {
-        return Err(Err::Failure(Error::EmptyOptional(original_input.take(2))));
+        if opt.input_len() == 0 {
+            return Err(Err::Failure(Error::EmptyOptional(
+                original_input.take(2),
+            )));
+        }
+
+        Ok((input, Optional(opt)))
     }
+}
 
-    Ok((input, Optional(opt)))
+/// Parses an [`OptionalParameter`]: an `optional` containing a single
+/// `parameter` surrounded by some text.
+///
+/// # Grammar
+///
+/// ```ebnf
+/// optional-parameter = '(' text-in-optional* parameter text-in-optional* ')'
+/// ```
+///
+/// # Example
+///
+/// ```text
+/// ( with {int} retries)
+/// ({word})
+/// ( at \({int}\))
+/// ```
+///
+/// # Errors
+///
+/// ## Recoverable [`Error`]
+///
+/// - If `input` isn't an [`OptionalParameter`], so it may be parsed as an
+///   [`optional()`] instead, reporting its errors.
+///
+/// [`Error`]: Err::Error
+pub fn optional_parameter<Input: Span>(
+    input: Input,
+) -> IResult<Input, OptionalParameter<Input>, Error<Input>> {
+    optional_parameter_with(Syntax {
+        optional_parameters: true,
+        ..Syntax::default()
+    })(input)
+}
+
+/// Creates an [`optional_parameter()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`optional_parameter()`] ones. Recoverable [`Error`] is returned
+/// in case [`Syntax::optional_parameters`], [`Syntax::optional`] or
+/// [`Syntax::parameter`] is disabled.
+///
+/// [`Error`]: Err::Error
+pub fn optional_parameter_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, OptionalParameter<Input>, Error<Input>>
+{
+    let is_in_optional = move |c| !syntax.is_special(c, "(){\\/");
+
+    move |input: Input| {
+        if !(syntax.optional_parameters && syntax.optional && syntax.parameter)
+        {
+            return Err(Err::Error(Error::Other(input, ErrorKind::Tag)));
+        }
+
+        let (rest, (_, prefix, parameter, suffix, _)) =
+            tuple((
+                tag("("),
+                escaped_reserved_chars0(take_while(is_in_optional)),
+                parameter_with(syntax),
+                escaped_reserved_chars0(take_while(is_in_optional)),
+                tag(")"),
+            ))(input.clone())
+            .map_err(|e| match e {
+                Err::Failure(e) => Err::Error(e),
+                e @ (Err::Error(_) | Err::Incomplete(_)) => e,
+            })?;
+
+        Ok((
+            rest,
+            OptionalParameter {
+                prefix,
+                parameter,
+                suffix,
+            },
+        ))
+    }
 }
 
 /// Parses an `alternative` as defined in the [grammar spec][0].
@@ -356,35 +559,36 @@ where
 ///
 /// [`Failure`]: Err::Failure
 /// [0]: crate#grammar
-pub fn alternative<'a, Input: 'a>(
+pub fn alternative<Input: Span>(
     input: Input,
-) -> IResult<Input, Alternative<Input>, Error<Input>>
-where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
-{
-    let is_without_whitespace = |c| !" ({\\/".contains(c);
+) -> IResult<Input, Alternative<Input>, Error<Input>> {
+    alternative_with(Syntax::default())(input)
+}
 
+/// Creates an [`alternative()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`alternative()`] ones.
+pub fn alternative_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Alternative<Input>, Error<Input>> {
     alt((
-        map(optional, Alternative::Optional),
         map(
-            verify(
-                escaped_reserved_chars0(take_while(is_without_whitespace)),
-                |p| p.input_len() > 0,
-            ),
+            // `OptionalParameter`s end an `Alternation`, as can't be its part.
+            tuple((
+                not(optional_parameter_with(syntax)),
+                optional_with(syntax),
+            )),
+            |((), opt)| Alternative::Optional(opt),
+        ),
+        map(
+            verify(text_without_whitespace(syntax), |p: &Input| {
+                p.input_len() > 0
+            }),
             Alternative::Text,
         ),
-    ))(input)
+    ))
 }
 
 /// Parses an `alternation` as defined in the [grammar spec][0].
@@ -424,53 +628,137 @@ where
 /// [`EmptyAlternation`]: Error::EmptyAlternation
 /// [`OnlyOptionalInAlternation`]: Error::OnlyOptionalInAlternation
 /// [0]: crate#grammar
-pub fn alternation<Input>(
+pub fn alternation<Input: Span>(
     input: Input,
-) -> IResult<Input, Alternation<Input>, Error<Input>>
+) -> IResult<Input, Alternation<Input>, Error<Input>> {
+    alternation_with(Syntax::default())(input)
+}
+
+/// Creates an [`alternation()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`alternation()`] ones. Recoverable [`Error`] is returned in case
+/// [`Syntax::alternation`] is disabled.
+///
+/// [`Error`]: Err::Error
+pub fn alternation_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Alternation<Input>, Error<Input>> {
+    map(
+        alternation_fold(
+            syntax,
+            Vec::new,
+            |mut alts: Vec<SingleAlternation<Input>>, n, alt| {
+                match alts.get_mut(n) {
+                    Some(single_alt) => single_alt.push(alt),
+                    None => alts.push(vec![alt]),
+                }
+                alts
+            },
+        ),
+        Alternation,
+    )
+}
+
+/// Creates an [`alternation()`] parser of the given [`Syntax`], folding the
+/// parsed [`Alternative`]s with the given `fold` function instead of
+/// collecting them.
+///
+/// `fold` receives an index of the `single-alternation` the [`Alternative`]
+/// belongs to.
+///
+/// # Errors
+///
+/// Same as [`alternation_with()`] ones.
+pub(crate) fn alternation_fold<Input, R, I, F>(
+    syntax: Syntax,
+    mut init: I,
+    mut fold: F,
+) -> impl FnMut(Input) -> IResult<Input, R, Error<Input>>
 where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
+    Input: Span,
+    I: FnMut() -> R,
+    F: FnMut(R, usize, Alternative<Input>) -> R,
 {
-    let original_input = input.clone();
-    let (rest, alt) = match separated_list1(tag("/"), many1(alternative))(input)
-    {
-        Ok((rest, alt)) => {
-            if let Ok((_, slash)) =
-                peek::<_, _, Error<Input>, _>(tag("/"))(rest.clone())
-            {
-                Err(Error::EmptyAlternation(slash).failure())
-            } else if alt.len() == 1 {
-                Err(Err::Error(Error::Other(rest, ErrorKind::Tag)))
-            } else {
-                Ok((rest, Alternation(alt)))
+    move |input: Input| {
+        if !syntax.alternation {
+            return Err(Err::Error(Error::Other(input, ErrorKind::Tag)));
+        }
+
+        let original_input = input.clone();
+        let mut acc = init();
+        let mut rest = input;
+        let mut slash = None;
+        let mut single_alts = 0;
+        let mut contains_only_optional = false;
+        loop {
+            let mut only_optional = true;
+            let mut single_alt_len = 0;
+            loop {
+                match alternative_with(syntax)(rest.clone()) {
+                    Ok((after, alt)) => {
+                        only_optional &=
+                            matches!(alt, Alternative::Optional(_));
+                        acc = fold(acc, single_alts, alt);
+                        single_alt_len += 1;
+                        rest = after;
+                    }
+                    Err(Err::Error(_)) => break,
+                    Err(e) => return Err(e),
+                }
+            }
+
+            if single_alt_len == 0 {
+                let empty = slash.or_else(|| {
+                    peek::<_, _, Error<Input>, _>(tag("/"))(rest.clone())
+                        .ok()
+                        .map(|(_, sl)| sl)
+                });
+                return Err(empty.map_or_else(
+                    || Err::Error(Error::Other(rest, ErrorKind::Many1)),
+                    |sl| Error::EmptyAlternation(sl).failure(),
+                ));
+            }
+            single_alts += 1;
+            contains_only_optional |= only_optional;
+
+            match tag::<_, _, Error<Input>>("/")(rest.clone()) {
+                Ok((after, sl)) => {
+                    slash = Some(sl);
+                    rest = after;
+                }
+                Err(_) => break,
             }
         }
-        Err(Err::Error(Error::Other(sp, ErrorKind::Many1)))
-            if peek::<_, _, Error<Input>, _>(tag("/"))(sp.clone()).is_ok() =>
-        {
-            Err(Error::EmptyAlternation(sp.take(1)).failure())
-        }
-        Err(e) => Err(e),
-    }?;
 
-    alt.contains_only_optional()
-        .then(|| {
+        if single_alts == 1 {
+            Err(Err::Error(Error::Other(rest, ErrorKind::Tag)))
+        } else if contains_only_optional {
             Err(Error::OnlyOptionalInAlternation(
-                original_input.take(alt.span_len()),
+                original_input.take(original_input.offset(&rest)),
             )
             .failure())
-        })
-        .unwrap_or(Ok((rest, alt)))
+        } else {
+            Ok((rest, acc))
+        }
+    }
+}
+
+/// Parses a possibly empty `text-without-whitespace+` as defined in the
+/// [grammar spec][0], using the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`escaped_reserved_chars0()`] ones.
+///
+/// [0]: crate#grammar
+pub(crate) fn text_without_whitespace<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Input, Error<Input>> {
+    let is_without_whitespace = move |c| !syntax.is_special(c, " ({\\/");
+
+    escaped_reserved_chars0(take_while(is_without_whitespace))
 }
 
 /// Parses a `single-expression` as defined in the [grammar spec][0].
@@ -505,39 +793,67 @@ where
 ///
 /// [`Failure`]: Err::Failure
 /// [0]: crate#grammar
-pub fn single_expression<'a, Input: 'a>(
+pub fn single_expression<Input: Span>(
     input: Input,
-) -> IResult<Input, SingleExpression<Input>, Error<Input>>
-where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
+) -> IResult<Input, SingleExpression<Input>, Error<Input>> {
+    single_expression_with(Syntax::default())(input)
+}
+
+/// Creates a [`single_expression()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`single_expression()`] ones.
+pub fn single_expression_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, SingleExpression<Input>, Error<Input>>
 {
-    let is_without_whitespace = |c| !" ({\\/".contains(c);
     let is_whitespace = |c| c == ' ';
 
     alt((
-        map(alternation, SingleExpression::Alternation),
-        map(optional, SingleExpression::Optional),
-        map(parameter, SingleExpression::Parameter),
         map(
-            verify(
-                escaped_reserved_chars0(take_while(is_without_whitespace)),
-                |s| s.input_len() > 0,
-            ),
+            optional_parameter_with(syntax),
+            SingleExpression::OptionalParameter,
+        ),
+        map(alternation_with(syntax), SingleExpression::Alternation),
+        map(optional_with(syntax), SingleExpression::Optional),
+        map(parameter_with(syntax), SingleExpression::Parameter),
+        map(
+            verify(text_without_whitespace(syntax), |s: &Input| {
+                s.input_len() > 0
+            }),
             SingleExpression::Text,
         ),
         map(take_while1(is_whitespace), SingleExpression::Whitespaces),
-    ))(input)
+        forbidden_newline(syntax),
+    ))
+}
+
+/// Fails with an [`Error::UnexpectedNewline`] if the `input` starts with a
+/// newline [`Newlines::Forbidden`] by the given [`Syntax`].
+///
+/// # Errors
+///
+/// ## Recoverable [`Error`]
+///
+/// - If `input` doesn't start with a forbidden newline.
+///
+/// ## Irrecoverable [`Failure`]
+///
+/// - [`UnexpectedNewline`].
+///
+/// [`Error`]: Err::Error
+/// [`Failure`]: Err::Failure
+/// [`UnexpectedNewline`]: Error::UnexpectedNewline
+pub(crate) fn forbidden_newline<Input: Span, O>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, O, Error<Input>> {
+    move |input: Input| match input.iter_elements().next() {
+        Some(c) if syntax.is_forbidden_newline(c) => {
+            Err(Error::UnexpectedNewline(input.take(1)).failure())
+        }
+        _ => Err(Err::Error(Error::Other(input, ErrorKind::Char))),
+    }
 }
 
 /// Parses an `expression` as defined in the [grammar spec][0].
@@ -567,24 +883,26 @@ where
 ///
 /// [`Failure`]: Err::Failure
 /// [0]: crate#grammar
-pub fn expression<'a, Input: 'a>(
+pub fn expression<Input: Span>(
     input: Input,
-) -> IResult<Input, Expression<Input>, Error<Input>>
-where
-    Input: Clone
-        + Display
-        + Offset
-        + InputLength
-        + InputTake
-        + InputTakeAtPosition<Item = char>
-        + Slice<RangeFrom<usize>>
-        + InputIter
-        + for<'s> Compare<&'s str>,
-    <Input as InputIter>::Item: AsChar + Copy,
-    Error<Input>: ParseError<Input>,
-    for<'s> &'s str: FindToken<<Input as InputIter>::Item>,
-{
-    map(many0(single_expression), Expression)(input)
+) -> IResult<Input, Expression<Input>, Error<Input>> {
+    expression_with(Syntax::default())(input)
+}
+
+/// Creates an [`expression()`] parser of the given [`Syntax`].
+///
+/// # Errors
+///
+/// Same as [`expression()`] ones.
+pub fn expression_with<Input: Span>(
+    syntax: Syntax,
+) -> impl FnMut(Input) -> IResult<Input, Expression<Input>, Error<Input>> {
+    move |input: Input| {
+        let (rest, nodes) =
+            many0(single_expression_with(syntax))(input.clone())?;
+        let source = input.take(input.offset(&rest));
+        Ok((rest, Expression(nodes, source)))
+    }
 }
 
 /// Possible parsing errors.
@@ -722,6 +1040,16 @@ where
     )]
     EscapedEndOfLine(#[error(not(source))] Input),
 
+    /// Newline is used while being [`Newlines::Forbidden`].
+    #[display(
+        fmt = "{}\n\
+               An expression may not contain a newline.\n\
+               Consider joining its lines, or allowing newlines with \
+               `Newlines::Literal`.",
+        _0
+    )]
+    UnexpectedNewline(#[error(not(source))] Input),
+
     /// Unknown error.
     #[display(
         fmt = "{}\n\
@@ -772,8 +1100,10 @@ mod spec {
     use nom::{error::ErrorKind, Err, IResult};
 
     use crate::{
+        ast::node,
         parse::{
-            alternation, alternative, expression, optional, parameter, Error,
+            alternation, alternative, expression, expression_with, optional,
+            parameter, Error, Newlines, Syntax,
         },
         Alternative, Spanned,
     };
@@ -1492,6 +1822,12 @@ mod spec {
                             },
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "\\({int})",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1565,6 +1901,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "a/i{int}n/y",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1632,6 +1974,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "{int}st/nd/rd/th",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1871,6 +2219,12 @@ mod spec {
                             },
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "mice/rats and rats\\/mice",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1892,6 +2246,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "{}",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1961,6 +2321,12 @@ mod spec {
                             },
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "three \\(exceptionally) \\{string} mice",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -1998,6 +2364,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "12\\\\/2020",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -2063,6 +2435,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "three (brown )mice/rats",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -2140,6 +2518,12 @@ mod spec {
                             ),
                         ),
                     ],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "{int} rat(s)/mouse/mice",
+                        extra: (),
+                    },
                 )"#,
             );
         }
@@ -2158,8 +2542,208 @@ mod spec {
         fn empty() {
             assert_ast_eq(
                 unwrap_parser(expression(Spanned::new(""))),
-                r#"Expression([],)"#,
+                r#"Expression(
+                    [],
+                    LocatedSpan {
+                        offset: 0,
+                        line: 1,
+                        fragment: "",
+                        extra: (),
+                    },
+                )"#,
+            );
+        }
+    }
+
+    mod syntax {
+        use super::{
+            expression_with, node, unwrap_parser, Err, Error, Newlines,
+            Spanned, Syntax,
+        };
+
+        /// Parses the given `input` with the given [`Syntax`], returning
+        /// textual representation of the parsed [`SingleExpression`]s.
+        fn parse(input: &str, syntax: Syntax) -> Vec<String> {
+            unwrap_parser(expression_with(syntax)(Spanned::new(input)))
+                .iter()
+                .map(node)
+                .collect()
+        }
+
+        #[test]
+        fn without_alternation() {
+            let syntax = Syntax {
+                alternation: false,
+                ..Syntax::default()
+            };
+
+            assert_eq!(
+                parse("GET /users/{int} (now)", syntax),
+                [
+                    "text:GET",
+                    "ws: ",
+                    "text:/users/",
+                    "par:int",
+                    "ws: ",
+                    "opt:now",
+                ],
+            );
+            assert_eq!(parse("(a/b)", syntax), ["opt:a/b"]);
+        }
+
+        #[test]
+        fn without_optional() {
+            let syntax = Syntax {
+                optional: false,
+                ..Syntax::default()
+            };
+
+            assert_eq!(
+                parse("(a) {int} b/c", syntax),
+                ["text:(a)", "ws: ", "par:int", "ws: ", "alt:2"],
             );
+            assert_eq!(parse("{a(b}", syntax), ["par:a(b"]);
+        }
+
+        #[test]
+        fn without_parameter() {
+            let syntax = Syntax {
+                parameter: false,
+                ..Syntax::default()
+            };
+
+            assert_eq!(parse("{a} (b)", syntax), ["text:{a}", "ws: ", "opt:b"]);
+            assert_eq!(parse("(a{b})", syntax), ["opt:a{b}"]);
+        }
+
+        #[test]
+        fn with_optional_parameters() {
+            let syntax = Syntax {
+                optional_parameters: true,
+                ..Syntax::default()
+            };
+
+            assert_eq!(
+                parse("I connect( with {int} retries) (now)", syntax),
+                [
+                    "text:I",
+                    "ws: ",
+                    "text:connect",
+                    "optpar: with |int| retries",
+                    "ws: ",
+                    "opt:now",
+                ],
+            );
+            assert_eq!(
+                parse(r"({}) a/b(c) (\({word}\))", syntax),
+                ["optpar:||", "ws: ", "alt:2", "ws: ", r"optpar:\(|word|\)",],
+            );
+            assert_eq!(
+                parse("a/b( {int})", syntax),
+                ["alt:2", "optpar: |int|"],
+            );
+
+            for input in ["({a} {b})", "(a {b)", "((a) {b})", "(a/{b})"] {
+                assert!(
+                    matches!(
+                        expression_with(syntax)(Spanned::new(input)),
+                        Err(Err::Failure(_)),
+                    ),
+                    "no failure on: {}",
+                    input,
+                );
+            }
+            assert!(matches!(
+                expression_with(Syntax::default())(Spanned::new("( {int})")),
+                Err(Err::Failure(Error::ParameterInOptional(_))),
+            ));
+        }
+
+        #[test]
+        fn allows_escaped_disabled() {
+            for (input, syntax) in [
+                (
+                    r"a\/b",
+                    Syntax {
+                        alternation: false,
+                        ..Syntax::default()
+                    },
+                ),
+                (
+                    r"a\(b\)",
+                    Syntax {
+                        optional: false,
+                        ..Syntax::default()
+                    },
+                ),
+                (
+                    r"a\{b\}",
+                    Syntax {
+                        parameter: false,
+                        ..Syntax::default()
+                    },
+                ),
+            ] {
+                assert_eq!(
+                    parse(input, syntax),
+                    [format!("text:{}", input)],
+                    "on input: {}",
+                    input,
+                );
+            }
+
+            match expression_with(Syntax::default())(Spanned::new(r"a\b"))
+                .unwrap_err()
+            {
+                Err::Failure(Error::EscapedNonReservedCharacter(e)) => {
+                    assert_eq!(*e, r"\b");
+                }
+                e @ (Err::Incomplete(_) | Err::Error(_) | Err::Failure(_)) => {
+                    panic!("wrong err: {}", e);
+                }
+            }
+        }
+
+        #[test]
+        fn newlines_literal() {
+            assert_eq!(
+                parse("a\nb {int}\n(c)", Syntax::default()),
+                ["text:a\nb", "ws: ", "par:int", "text:\n", "opt:c"],
+            );
+        }
+
+        #[test]
+        fn fails_on_forbidden_newlines() {
+            let syntax = Syntax {
+                newlines: Newlines::Forbidden,
+                ..Syntax::default()
+            };
+
+            for (input, line, column) in [
+                ("a\nb", 1, 2),
+                ("a\r\nb", 1, 2),
+                ("a {b\nc}", 1, 5),
+                ("a\n(b\nc)", 1, 2),
+                ("a (b\nc)", 1, 5),
+                ("a/b\nc", 1, 4),
+            ] {
+                match expression_with(syntax)(Spanned::new(input)).unwrap_err()
+                {
+                    Err::Failure(Error::UnexpectedNewline(e)) => {
+                        assert_eq!(
+                            (e.location_line(), e.get_utf8_column()),
+                            (line, column),
+                            "input: {:?}",
+                            input,
+                        );
+                    }
+                    e @ (Err::Incomplete(_)
+                    | Err::Error(_)
+                    | Err::Failure(_)) => {
+                        panic!("wrong err for {:?}: {}", input, e);
+                    }
+                }
+            }
         }
     }
 }